@@ -0,0 +1,17 @@
+use std::{env, process::Command};
+
+fn main() {
+    // the rustc version is sent in the Upstash-Telemetry-Runtime header
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| String::from("rustc"));
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .and_then(|v| v.split_whitespace().nth(1).map(str::to_string))
+        .unwrap_or_else(|| String::from("unknown"));
+
+    println!("cargo:rustc-env=QSTASH_RUSTC_VERSION={}", version);
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
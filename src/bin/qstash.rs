@@ -0,0 +1,257 @@
+//! # qstash
+//! A command line interface over the QStash client, built with the `cli` feature.
+//! The token is read from the `QSTASH_TOKEN` environment variable and every response
+//! is printed as JSON.
+
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use http::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method,
+};
+use qstash_rs::client::{
+    dead_letter_queue::DlqRequest, events::EventRequest, Client, PublishRequest, PublishRequestUrl,
+    QStashError,
+};
+use serde::Serialize;
+
+#[derive(Parser)]
+#[command(name = "qstash", version, about = "Interact with Upstash QStash")]
+struct Cli {
+    /// The QStash token.
+    #[arg(long, env = "QSTASH_TOKEN", hide_env_values = true)]
+    token: String,
+
+    /// The QStash base url, the default is `https://qstash.upstash.io`.
+    #[arg(long, env = "QSTASH_URL")]
+    url: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Publish a message to a url or a topic.
+    Publish {
+        /// The url or topic to send the message to.
+        destination: String,
+        /// The body of the message.
+        #[arg(long)]
+        body: Option<String>,
+        /// A header sent along with the message, as `Name: value`.
+        #[arg(long = "header", short = 'H')]
+        headers: Vec<String>,
+        /// Delay the delivery of the message, in seconds.
+        #[arg(long)]
+        delay: Option<u32>,
+        #[arg(long)]
+        retries: Option<u32>,
+        #[arg(long)]
+        callback: Option<String>,
+        #[arg(long)]
+        failure_callback: Option<String>,
+        /// The method used to deliver the message, the default is `POST`.
+        #[arg(long)]
+        method: Option<String>,
+    },
+    /// List the events of your messages.
+    Events {
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+    /// Interact with the dead letter queue.
+    Dlq {
+        #[command(subcommand)]
+        command: DlqCommand,
+    },
+    /// Interact with a message.
+    Message {
+        #[command(subcommand)]
+        command: MessageCommand,
+    },
+    /// Interact with the schedules.
+    Schedules {
+        #[command(subcommand)]
+        command: SchedulesCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum DlqCommand {
+    /// List the messages of the dead letter queue.
+    List {
+        #[arg(long)]
+        cursor: Option<String>,
+    },
+    /// Delete a message from the dead letter queue.
+    Delete { dlq_id: String },
+}
+
+#[derive(Subcommand)]
+enum MessageCommand {
+    /// Get a message by its id.
+    Get { message_id: String },
+    /// Cancel the delivery of a message.
+    Cancel { message_id: String },
+}
+
+#[derive(Subcommand)]
+enum SchedulesCommand {
+    /// List the schedules.
+    List,
+}
+
+/// The errors of the command line, printed before exiting.
+enum CliError {
+    Argument(String),
+    Request(QStashError),
+}
+
+impl From<QStashError> for CliError {
+    fn from(e: QStashError) -> Self {
+        CliError::Request(e)
+    }
+}
+
+/// print_json prints the value as pretty JSON.
+fn print_json<T: Serialize>(value: &T) -> Result<(), CliError> {
+    match serde_json::to_string_pretty(value) {
+        Ok(s) => {
+            println!("{}", s);
+            Ok(())
+        }
+        Err(e) => Err(CliError::Argument(e.to_string())),
+    }
+}
+
+/// parse_destination returns a url destination for http urls, and a topic otherwise.
+fn parse_destination(destination: &str) -> PublishRequestUrl {
+    match url::Url::parse(destination) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => PublishRequestUrl::Url(url),
+        _ => PublishRequestUrl::Topic(destination.to_string()),
+    }
+}
+
+/// parse_headers parses the `Name: value` headers.
+fn parse_headers(headers: &[String]) -> Result<HeaderMap, CliError> {
+    let mut map = HeaderMap::new();
+    for header in headers {
+        let (name, value) = match header.split_once(':') {
+            Some(h) => h,
+            None => {
+                return Err(CliError::Argument(format!(
+                    "Invalid header {}, expected `Name: value`",
+                    header
+                )))
+            }
+        };
+        let name = match HeaderName::from_bytes(name.trim().as_bytes()) {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(CliError::Argument(format!(
+                    "Invalid header {}: {}",
+                    header, e
+                )))
+            }
+        };
+        let value = match HeaderValue::from_str(value.trim()) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(CliError::Argument(format!(
+                    "Invalid header {}: {}",
+                    header, e
+                )))
+            }
+        };
+        map.append(name, value);
+    }
+    Ok(map)
+}
+
+async fn run(cli: Cli) -> Result<(), CliError> {
+    let client = Client::new(&cli.token, cli.url.as_deref(), None)?;
+
+    match cli.command {
+        Command::Publish {
+            destination,
+            body,
+            headers,
+            delay,
+            retries,
+            callback,
+            failure_callback,
+            method,
+        } => {
+            let mut request = PublishRequest::<String>::new(parse_destination(&destination));
+            request.body = body;
+            if !headers.is_empty() {
+                request.headers = Some(parse_headers(&headers)?);
+            }
+            request.delay = delay;
+            request.retries = retries;
+            request.callback = callback;
+            request.failure_callback = failure_callback;
+            request.method = match method {
+                Some(m) => match Method::from_bytes(m.to_uppercase().as_bytes()) {
+                    Ok(m) => Some(m),
+                    Err(e) => {
+                        return Err(CliError::Argument(format!("Invalid method {}: {}", m, e)))
+                    }
+                },
+                None => None,
+            };
+            print_json(&client.publish(request).await?)
+        }
+        Command::Events { cursor } => print_json(
+            &client
+                .get_events(Some(EventRequest {
+                    cursor,
+                    ..Default::default()
+                }))
+                .await?,
+        ),
+        Command::Dlq { command } => match command {
+            DlqCommand::List { cursor } => print_json(
+                &client
+                    .get_dead_letter_queue(Some(DlqRequest {
+                        cursor,
+                        ..Default::default()
+                    }))
+                    .await?,
+            ),
+            DlqCommand::Delete { dlq_id } => {
+                client.delete_dead_letter_message(&dlq_id).await?;
+                print_json(&serde_json::json!({ "deleted": dlq_id }))
+            }
+        },
+        Command::Message { command } => match command {
+            MessageCommand::Get { message_id } => {
+                print_json(&client.get_message(&message_id).await?)
+            }
+            MessageCommand::Cancel { message_id } => {
+                client.cancel_message(&message_id).await?;
+                print_json(&serde_json::json!({ "canceled": message_id }))
+            }
+        },
+        Command::Schedules { command } => match command {
+            SchedulesCommand::List => print_json(&client.list_schedules().await?),
+        },
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Argument(e)) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(2)
+        }
+        Err(CliError::Request(e)) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! # backend module
+//! This module contains the transport used by the client to talk to QStash.
+//! The endpoints only build [`RequestParts`] and read [`ResponseParts`], the [`HttpBackend`]
+//! sends them, so the client can run on another http client or on a test double.
+//! The `reqwest` feature, enabled by default, provides [`ReqwestBackend`].
+
+use std::{fmt, time::Duration};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::{
+    header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, RETRY_AFTER},
+    Method, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use url::Url;
+
+use super::error::QStashError;
+
+/// A request to QStash, with the whole body in memory so it can be sent again on retry.
+#[derive(Debug, Clone)]
+pub struct RequestParts {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Option<Bytes>,
+}
+
+impl RequestParts {
+    /// Creates a new [`RequestParts`] without headers and body.
+    pub fn new(method: Method, url: Url) -> Self {
+        Self {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+        }
+    }
+
+    /// header sets a header, replacing the previous values.
+    pub(crate) fn header(mut self, name: HeaderName, value: &'static str) -> Self {
+        self.headers.insert(name, HeaderValue::from_static(value));
+        self
+    }
+
+    /// headers adds the headers, appending the values of headers already set.
+    pub(crate) fn headers(mut self, headers: HeaderMap) -> Self {
+        for (name, value) in headers.iter() {
+            self.headers.append(name, value.clone());
+        }
+        self
+    }
+
+    pub(crate) fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// json sets the body to the serialized value, with the JSON content type.
+    pub(crate) fn json<T: Serialize>(self, value: &T) -> Result<Self, serde_json::Error> {
+        let body = serde_json::to_vec(value)?;
+        Ok(self.header(CONTENT_TYPE, "application/json").body(body))
+    }
+}
+
+/// A response of QStash, with the whole body read.
+#[derive(Debug, Clone)]
+pub struct ResponseParts {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl ResponseParts {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// json deserializes the body.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+
+    /// text returns the body, replacing invalid UTF-8.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// error_message returns the `error` field of a QStash error body, or the whole body.
+    pub(crate) fn error_message(&self) -> String {
+        #[derive(serde::Deserialize)]
+        struct ApiError {
+            error: String,
+        }
+        match self.json::<ApiError>() {
+            Ok(e) => e.error,
+            Err(_) => self.text(),
+        }
+    }
+
+    /// quota_error returns [`QStashError::QuotaExceeded`] if the queue or the account is out
+    /// of quota, a 412 or 429 status, with the `Retry-After` header in seconds if present.
+    pub(crate) fn quota_error(&self) -> Option<QStashError> {
+        if self.status != StatusCode::PRECONDITION_FAILED
+            && self.status != StatusCode::TOO_MANY_REQUESTS
+        {
+            return None;
+        }
+
+        let retry_after = self
+            .headers
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        Some(QStashError::QuotaExceeded {
+            status: self.status.as_u16(),
+            message: self.error_message(),
+            retry_after,
+        })
+    }
+}
+
+/// The ways a request can fail before a response is received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    /// The connection could not be established, the request was not sent.
+    Connect,
+    /// The request or the response took longer than allowed.
+    Timeout,
+    /// Any other failure, such as a connection closed in the middle of the response.
+    Other,
+}
+
+/// The error of a request that got no complete response.
+#[derive(Debug, Clone)]
+pub struct TransportError {
+    pub kind: TransportErrorKind,
+    pub message: String,
+}
+
+impl TransportError {
+    pub fn new(kind: TransportErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn is_connect(&self) -> bool {
+        self.kind == TransportErrorKind::Connect
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        self.kind == TransportErrorKind::Timeout
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} error: {}", self.kind, self.message)
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+/// The transport of the client.
+///
+/// A backend sends the request as is and reads the whole response.
+/// Retries, rate limiting and the authorization header are handled by the client.
+///
+/// # Example
+/// ```
+/// use async_trait::async_trait;
+/// use qstash_rs::client::{HttpBackend, RequestParts, ResponseParts, TransportError};
+///
+/// /// Answers every request with an empty list.
+/// #[derive(Debug)]
+/// struct Empty;
+///
+/// #[async_trait]
+/// impl HttpBackend for Empty {
+///     async fn execute(&self, _request: RequestParts) -> Result<ResponseParts, TransportError> {
+///         Ok(ResponseParts {
+///             status: http::StatusCode::OK,
+///             headers: http::HeaderMap::new(),
+///             body: "[]".into(),
+///         })
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait HttpBackend: fmt::Debug + Send + Sync {
+    /// execute sends the request and reads the response.
+    async fn execute(&self, request: RequestParts) -> Result<ResponseParts, TransportError>;
+}
+
+/// The [`HttpBackend`] on top of a [`reqwest::Client`].
+#[cfg(feature = "reqwest")]
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestBackend {
+    http: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest")]
+impl ReqwestBackend {
+    /// Creates a new [`ReqwestBackend`] sending the requests with the client.
+    pub fn new(http: reqwest::Client) -> Self {
+        Self { http }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for TransportError {
+    fn from(e: reqwest::Error) -> Self {
+        let kind = match (e.is_connect(), e.is_timeout()) {
+            (true, _) => TransportErrorKind::Connect,
+            (_, true) => TransportErrorKind::Timeout,
+            _ => TransportErrorKind::Other,
+        };
+        Self::new(kind, e.to_string())
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[async_trait]
+impl HttpBackend for ReqwestBackend {
+    async fn execute(&self, request: RequestParts) -> Result<ResponseParts, TransportError> {
+        let mut builder = self
+            .http
+            .request(request.method, request.url)
+            .headers(request.headers);
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        Ok(ResponseParts {
+            status,
+            headers,
+            body,
+        })
+    }
+}
@@ -0,0 +1,616 @@
+//! # batch module
+//! This module contains the batch functionality of the QStash client.
+//! The batch endpoint publishes many messages with a single request.
+
+use std::{collections::HashMap, time::Instant};
+
+use bytes::Bytes;
+use http::{
+    header::{self, HeaderMap, HeaderValue},
+    Method,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::QStashError, logging::log_response, Client, PublishOptions, PublishRequest,
+    PublishRequestUrl, QstashResponse,
+};
+use crate::log;
+
+/// The maximum amount of messages sent in a single call to the batch endpoint
+/// by [`Client::enqueue_batch`].
+pub const MAX_BATCH_ENTRIES: usize = 100;
+
+/// A single message of a batch.
+#[derive(Debug, Clone)]
+pub struct BatchEntry {
+    /// The url or topic to send the message to.
+    pub destination: PublishRequestUrl,
+
+    /// The message to send.
+    /// Please set the `Content-Type` header accordingly.
+    pub body: Option<String>,
+
+    /// Optionally send along headers with the message.
+    /// These headers will be sent to your destination.
+    pub headers: Option<HeaderMap>,
+
+    /// Optionally delay the delivery of this message.
+    /// In seconds.
+    pub delay: Option<u32>,
+
+    /// Optionally set the absolute delay of this message.
+    /// Unix timestamp in seconds.
+    pub not_before: Option<u32>,
+
+    /// Provide a unique id for deduplication.
+    pub deduplication_id: Option<String>,
+
+    /// If true, the message content will get hashed and used as deduplication id.
+    pub content_based_deduplication: Option<bool>,
+
+    /// Configure how many times you would like the delivery to be retried.
+    pub retries: Option<u32>,
+
+    /// Use a callback url to forward the response of your destination server to your callback url.
+    pub callback: Option<String>,
+
+    /// Use a failure callback url to be notified once the delivery failed and every retry was used.
+    pub failure_callback: Option<String>,
+
+    /// The method to use when sending a request to your API.
+    pub method: Option<Method>,
+
+    /// The queue to enqueue the message to, if any.
+    /// It takes precedence over the queue of a [`PublishRequestUrl::Queue`] destination.
+    pub queue: Option<String>,
+}
+
+impl BatchEntry {
+    /// Creates a new [`BatchEntry`] for the given destination.
+    pub fn new(destination: PublishRequestUrl) -> Self {
+        Self {
+            destination,
+            body: None,
+            headers: None,
+            delay: None,
+            not_before: None,
+            deduplication_id: None,
+            content_based_deduplication: None,
+            retries: None,
+            callback: None,
+            failure_callback: None,
+            method: None,
+            queue: None,
+        }
+    }
+
+    /// Creates a new [`BatchEntry`] for the given destination with the publish options.
+    pub fn with_options(destination: PublishRequestUrl, options: PublishOptions) -> Self {
+        Self {
+            headers: options.headers,
+            delay: options.delay,
+            not_before: options.not_before,
+            deduplication_id: options.deduplication_id,
+            content_based_deduplication: options.content_based_deduplication,
+            retries: options.retries,
+            callback: options.callback,
+            failure_callback: options.failure_callback,
+            method: options.method,
+            ..Self::new(destination)
+        }
+    }
+
+    /// from_request converts the publish request into an entry.
+    /// The body must be valid UTF-8, the batch endpoint takes the bodies as JSON strings.
+    pub(crate) fn from_request<T: Into<Bytes>>(
+        mut request: PublishRequest<T>,
+    ) -> Result<Self, QStashError> {
+        let body = request
+            .body
+            .take()
+            .map(|b| utf8_body(b.into()))
+            .transpose()?;
+
+        let mut entry = Self::from(request);
+        entry.body = body;
+        Ok(entry)
+    }
+
+    /// Creates a new [`BatchEntryBuilder`].
+    ///
+    /// # Example
+    /// ```
+    /// use qstash_rs::client::{batch::BatchEntry, PublishRequestUrl};
+    /// use std::collections::HashMap;
+    ///
+    /// let entry = BatchEntry::builder(PublishRequestUrl::Url(
+    ///     "https://example.com".parse().expect("Could not parse URL"),
+    /// ))
+    /// .retries(3)
+    /// .deduplication_id("order-42")
+    /// .body_json(&HashMap::from([("order", 42)]))
+    /// .build()
+    /// .expect("Invalid entry");
+    /// ```
+    pub fn builder(destination: PublishRequestUrl) -> BatchEntryBuilder {
+        BatchEntryBuilder {
+            entry: Self::new(destination),
+            error: None,
+        }
+    }
+
+    /// to_message converts the entry into the format the batch endpoint expects.
+    /// The options that are not set on the entry are filled from the defaults.
+    pub(crate) fn to_message(
+        &self,
+        defaults: Option<&PublishOptions>,
+    ) -> Result<BatchMessage, QStashError> {
+        let destination = self.destination.destination_url();
+
+        let headers = match Client::generate_headers(
+            PublishOptions {
+                headers: self.headers.clone(),
+                delay: self.delay,
+                not_before: self.not_before,
+                deduplication_id: self.deduplication_id.clone(),
+                content_based_deduplication: self.content_based_deduplication,
+                retries: self.retries,
+                callback: self.callback.clone(),
+                failure_callback: self.failure_callback.clone(),
+                method: self.method.clone(),
+            }
+            .with_defaults(defaults),
+        ) {
+            Ok(h) => h,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::BatchError);
+            }
+        };
+
+        let mut message_headers = HashMap::new();
+        for (name, value) in headers.iter() {
+            let value = match value.to_str() {
+                Ok(v) => v,
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    return Err(QStashError::BatchError);
+                }
+            };
+            message_headers.insert(name.to_string(), value.to_string());
+        }
+
+        Ok(BatchMessage {
+            destination,
+            queue: self
+                .queue
+                .clone()
+                .or_else(|| self.destination.queue_name().map(String::from)),
+            headers: message_headers,
+            body: self.body.clone(),
+        })
+    }
+}
+
+/// Converts the request into an entry, replacing invalid UTF-8 in the body.
+/// Use [`BatchEntryBuilder::body_raw`] to reject invalid bodies instead.
+impl<T: Into<Bytes>> From<PublishRequest<T>> for BatchEntry {
+    fn from(request: PublishRequest<T>) -> Self {
+        Self {
+            destination: request.url,
+            body: request
+                .body
+                .map(|b| String::from_utf8_lossy(&b.into()).into_owned()),
+            headers: request.headers,
+            delay: request.delay,
+            not_before: request.not_before,
+            deduplication_id: request.deduplication_id,
+            content_based_deduplication: request.content_based_deduplication,
+            retries: request.retries,
+            callback: request.callback,
+            failure_callback: request.failure_callback,
+            method: request.method,
+            queue: None,
+        }
+    }
+}
+
+/// utf8_body returns the body as a string, the batch endpoint takes the bodies as JSON strings.
+fn utf8_body(body: Bytes) -> Result<String, QStashError> {
+    match String::from_utf8(body.to_vec()) {
+        Ok(b) => Ok(b),
+        Err(e) => {
+            let formated_string = e.to_string();
+            log::error!(formated_string);
+            Err(QStashError::BatchError)
+        }
+    }
+}
+
+/// The builder of a [`BatchEntry`], see [`BatchEntry::builder`].
+#[derive(Debug, Clone)]
+pub struct BatchEntryBuilder {
+    entry: BatchEntry,
+    /// The first option that could not be set, returned by build.
+    error: Option<String>,
+}
+
+impl BatchEntryBuilder {
+    /// Set the message to send.
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.entry.body = Some(body.into());
+        self
+    }
+
+    /// Set the message to the serialized value, with the `Content-Type` header set to
+    /// `application/json`.
+    pub fn body_json<T: Serialize>(mut self, body: &T) -> Self {
+        match serde_json::to_string(body) {
+            Ok(b) => {
+                self.entry.body = Some(b);
+                self.header(header::CONTENT_TYPE.as_str(), "application/json")
+            }
+            Err(e) => {
+                self.error.get_or_insert_with(|| e.to_string());
+                self
+            }
+        }
+    }
+
+    /// Set the message with its content type.
+    /// The body must be valid UTF-8, the batch endpoint takes the bodies as JSON strings.
+    pub fn body_raw(mut self, body: impl Into<Bytes>, content_type: &str) -> Self {
+        match String::from_utf8(body.into().to_vec()) {
+            Ok(b) => {
+                self.entry.body = Some(b);
+                self.header(header::CONTENT_TYPE.as_str(), content_type)
+            }
+            Err(e) => {
+                self.error.get_or_insert_with(|| e.to_string());
+                self
+            }
+        }
+    }
+
+    /// Set a header sent along with the message, replacing the previous values.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        match (
+            header::HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                self.entry
+                    .headers
+                    .get_or_insert_with(HeaderMap::new)
+                    .insert(name, value);
+            }
+            _ => {
+                self.error
+                    .get_or_insert_with(|| format!("invalid header {}", name));
+            }
+        }
+        self
+    }
+
+    /// Delay the delivery of the message, in seconds.
+    pub fn delay(mut self, delay: u32) -> Self {
+        self.entry.delay = Some(delay);
+        self
+    }
+
+    /// Deliver the message at the unix timestamp in seconds.
+    pub fn not_before(mut self, not_before: u32) -> Self {
+        self.entry.not_before = Some(not_before);
+        self
+    }
+
+    /// Set the deduplication id of the message.
+    pub fn deduplication_id(mut self, deduplication_id: &str) -> Self {
+        self.entry.deduplication_id = Some(deduplication_id.to_string());
+        self
+    }
+
+    /// Deduplicate the message on a hash of its content.
+    pub fn content_based_deduplication(mut self, enabled: bool) -> Self {
+        self.entry.content_based_deduplication = Some(enabled);
+        self
+    }
+
+    /// Set how many times the delivery is retried.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.entry.retries = Some(retries);
+        self
+    }
+
+    /// Forward the response of the destination to the callback url.
+    pub fn callback(mut self, callback: &str) -> Self {
+        self.entry.callback = Some(callback.to_string());
+        self
+    }
+
+    /// Notify the failure callback url once the delivery failed and every retry was used.
+    pub fn failure_callback(mut self, failure_callback: &str) -> Self {
+        self.entry.failure_callback = Some(failure_callback.to_string());
+        self
+    }
+
+    /// Set the method used to deliver the message.
+    pub fn method(mut self, method: Method) -> Self {
+        self.entry.method = Some(method);
+        self
+    }
+
+    /// Enqueue the message to the queue.
+    pub fn queue(mut self, queue: &str) -> Self {
+        self.entry.queue = Some(queue.to_string());
+        self
+    }
+
+    /// build returns the entry, or an error if the body could not be serialized or is not
+    /// valid UTF-8, or a header could not be parsed.
+    pub fn build(self) -> Result<BatchEntry, QStashError> {
+        if let Some(error) = self.error {
+            log::error!("Invalid batch entry: {}", error);
+            return Err(QStashError::BatchError);
+        }
+        Ok(self.entry)
+    }
+}
+
+/// A message in the wire format of the batch endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct BatchMessage {
+    destination: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    queue: Option<String>,
+    headers: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+}
+
+impl BatchMessage {
+    /// body_size returns the size in bytes of the body delivered to the destination.
+    pub(crate) fn body_size(&self) -> usize {
+        self.body.as_ref().map_or(0, String::len)
+    }
+}
+
+/// The result of a single entry of a batch.
+/// An entry sent to a url returns a single response, an entry sent to a topic returns one
+/// response per endpoint of the topic.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum BatchResult {
+    /// The response of an entry sent to a url.
+    Single(QstashResponse),
+    /// The responses of an entry sent to a topic, one per endpoint.
+    Multi(Vec<QstashResponse>),
+}
+
+impl BatchResult {
+    /// responses returns the responses of the entry.
+    pub fn responses(&self) -> &[QstashResponse] {
+        match self {
+            BatchResult::Single(r) => std::slice::from_ref(r),
+            BatchResult::Multi(r) => r,
+        }
+    }
+
+    /// into_responses returns the responses of the entry.
+    pub fn into_responses(self) -> Vec<QstashResponse> {
+        match self {
+            BatchResult::Single(r) => vec![r],
+            BatchResult::Multi(r) => r,
+        }
+    }
+}
+
+impl Client {
+    /// Publish many messages with a single request.
+    ///
+    /// The results are returned in the same order as the entries, one per entry.
+    /// Entries sent to a topic return a [`BatchResult::Multi`] with one response per endpoint
+    /// of the topic.
+    pub async fn batch(&self, entries: Vec<BatchEntry>) -> Result<Vec<BatchResult>, QStashError> {
+        let mut messages = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            messages.push(self.batch_message(entry)?);
+        }
+
+        self.send_batch(&messages).await
+    }
+
+    /// batch_json publishes every body, serialized as JSON, to the same destination with a
+    /// single request.
+    /// The options are applied to every message and the `Content-Type` header is set to
+    /// `application/json`.
+    /// The results are returned in the same order as the bodies, see [`Client::batch`].
+    pub async fn batch_json<T: Serialize>(
+        &self,
+        destination: PublishRequestUrl,
+        bodies: Vec<T>,
+        options: Option<PublishOptions>,
+    ) -> Result<Vec<BatchResult>, QStashError> {
+        let mut options = options.unwrap_or_default();
+        options.headers.get_or_insert_with(HeaderMap::new).insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+
+        let mut entries = Vec::with_capacity(bodies.len());
+        for body in bodies {
+            let body = match serde_json::to_string(&body) {
+                Ok(b) => b,
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    return Err(QStashError::BatchError);
+                }
+            };
+            let mut entry = BatchEntry::with_options(destination.clone(), options.clone());
+            entry.body = Some(body);
+            entries.push(entry);
+        }
+
+        self.batch(entries).await
+    }
+
+    /// enqueue_batch enqueues the messages to the queue through the batch endpoint.
+    ///
+    /// The messages are sent in chunks of at most [`MAX_BATCH_ENTRIES`] messages and of the
+    /// maximum body size, one chunk after the other. The results are returned in the same
+    /// order as the requests, the first failed chunk is returned and the chunks after it
+    /// are not sent.
+    pub async fn enqueue_batch<T: Into<Bytes>>(
+        &self,
+        queue: &str,
+        requests: Vec<PublishRequest<T>>,
+    ) -> Result<Vec<BatchResult>, QStashError> {
+        let mut messages = Vec::with_capacity(requests.len());
+        for request in requests {
+            let mut entry = BatchEntry::from_request(request)?;
+            entry.queue = Some(queue.to_string());
+            messages.push(self.batch_message(&entry)?);
+        }
+
+        let mut responses = Vec::with_capacity(messages.len());
+        for chunk in chunk_messages(messages, MAX_BATCH_ENTRIES, self.max_body_size)? {
+            responses.extend(self.send_batch(&chunk).await?);
+        }
+        Ok(responses)
+    }
+
+    /// batch_message converts the entry with the defaults of the client, and signs its body
+    /// if payload signing is enabled.
+    pub(crate) fn batch_message(&self, entry: &BatchEntry) -> Result<BatchMessage, QStashError> {
+        let mut message = entry.to_message(self.publish_defaults.as_ref())?;
+        let body = message.body.as_deref().unwrap_or_default();
+        if let Some((name, signature)) = self.payload_signature(body.as_bytes()) {
+            message.headers.insert(name, signature);
+        }
+        #[cfg(feature = "opentelemetry")]
+        if self.propagate_trace_context {
+            for (name, value) in crate::trace_context::inject(HeaderMap::new()).iter() {
+                if let Ok(value) = value.to_str() {
+                    message.headers.insert(name.to_string(), value.to_string());
+                }
+            }
+        }
+        Ok(message)
+    }
+
+    /// send_batch sends the messages to the batch endpoint and returns one result per message.
+    /// The body of every message and the whole batch are checked against the maximum body size.
+    pub(crate) async fn send_batch(
+        &self,
+        messages: &[BatchMessage],
+    ) -> Result<Vec<BatchResult>, QStashError> {
+        for message in messages {
+            self.check_body_size(message.body_size())?;
+        }
+
+        let body = match serde_json::to_vec(messages) {
+            Ok(b) => b,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::BatchError);
+            }
+        };
+        self.check_body_size(body.len())?;
+
+        let path = match self.base_url.join(&format!("/{}/batch", self.version)) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::BatchError);
+            }
+        };
+
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        let request = self
+            .request(Method::POST, path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body);
+        let response = match self.execute(request).await {
+            Ok(r) => {
+                log_response("batch", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::BatchError);
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!("Batch failed with status {}", response.status());
+            return Err(response.quota_error().unwrap_or(QStashError::BatchError));
+        }
+
+        let response: Vec<BatchResult> = match response.json() {
+            Ok(r) => r,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::BatchError);
+            }
+        };
+
+        if response.len() != messages.len() {
+            log::error!(
+                "Expected {} responses but got {}",
+                messages.len(),
+                response.len()
+            );
+            return Err(QStashError::BatchError);
+        }
+
+        Ok(response)
+    }
+}
+
+/// chunk_messages splits the messages into chunks of at most `max_entries` messages,
+/// whose serialized JSON array is at most `max_bytes` long.
+/// A message larger than `max_bytes` gets a chunk of its own, the body size check rejects it.
+fn chunk_messages(
+    messages: Vec<BatchMessage>,
+    max_entries: usize,
+    max_bytes: usize,
+) -> Result<Vec<Vec<BatchMessage>>, QStashError> {
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    // the brackets of the array
+    let mut chunk_bytes = 2;
+
+    for message in messages {
+        // the message and its separator
+        let size = match serde_json::to_vec(&message) {
+            Ok(v) => v.len() + 1,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::BatchError);
+            }
+        };
+
+        if !chunk.is_empty() && (chunk.len() >= max_entries || chunk_bytes + size > max_bytes) {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_bytes = 2;
+        }
+        chunk.push(message);
+        chunk_bytes += size;
+    }
+
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    Ok(chunks)
+}
@@ -0,0 +1,269 @@
+//! # batching module
+//! This module contains the [`BatchingPublisher`], a background task that coalesces
+//! individual publishes into calls to the batch endpoint.
+//!
+//! Entries are collected until either `max_entries` or `max_bytes` is reached or
+//! `max_delay` has elapsed since the first pending entry, whichever comes first.
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{self, Instant},
+};
+
+use super::{
+    batch::{BatchEntry, BatchMessage},
+    error::QStashError,
+    sink::PublishSink,
+    Client, QstashResponse,
+};
+use crate::log;
+
+/// The configuration of a [`BatchingPublisher`].
+#[derive(Debug, Clone)]
+pub struct BatchingConfig {
+    /// The maximum amount of entries in a single batch.
+    pub max_entries: usize,
+    /// The maximum amount of time an entry waits before its batch is sent.
+    pub max_delay: Duration,
+    /// The maximum size in bytes of the serialized entries of a single batch.
+    pub max_bytes: usize,
+}
+
+impl Default for BatchingConfig {
+    /// 100 entries, 100 milliseconds and 1MB.
+    fn default() -> Self {
+        Self {
+            max_entries: 100,
+            max_delay: Duration::from_millis(100),
+            max_bytes: 1_000_000,
+        }
+    }
+}
+
+/// The channel the response of a single entry is sent through.
+type Responder = oneshot::Sender<Result<Vec<QstashResponse>, QStashError>>;
+
+/// The commands the handle sends to the background task.
+enum Command {
+    Send(BatchMessage, usize, Responder),
+    Flush(oneshot::Sender<()>),
+    Shutdown(oneshot::Sender<()>),
+}
+
+/// A cloneable handle to a background task that coalesces publishes into batches.
+///
+/// # Example
+/// ```no_run
+/// use qstash_rs::client::{
+///     batch::BatchEntry,
+///     batching::{BatchingConfig, BatchingPublisher},
+///     Client, PublishRequestUrl,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+///     let publisher = BatchingPublisher::spawn(qstash_client, BatchingConfig::default());
+///
+///     let mut entry = BatchEntry::new(PublishRequestUrl::Url(
+///         "https://example.com".parse().expect("Could not parse URL"),
+///     ));
+///     entry.body = Some(String::from("hello"));
+///
+///     match publisher.send(entry).await {
+///         Ok(r) => println!("{:?}", r),
+///         Err(err) => println!("{:?}", err),
+///     };
+///
+///     publisher.shutdown().await;
+/// }
+/// ```
+#[derive(Clone)]
+pub struct BatchingPublisher {
+    sender: mpsc::UnboundedSender<Command>,
+    max_entries: usize,
+    client: Arc<Client>,
+}
+
+impl BatchingPublisher {
+    /// Spawn the background task on the current tokio runtime and return a handle to it.
+    /// `max_bytes` is lowered to the maximum body size of the client if it is larger.
+    pub fn spawn(client: impl Into<Arc<Client>>, mut config: BatchingConfig) -> Self {
+        let client = client.into();
+        // the sizes include a byte per entry for the separators, one more for the brackets
+        config.max_bytes = config.max_bytes.min(client.max_body_size.saturating_sub(1));
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let max_entries = config.max_entries;
+        tokio::spawn(run(client.clone(), config, receiver));
+        Self {
+            sender,
+            max_entries,
+            client,
+        }
+    }
+
+    /// Create a [`PublishSink`] that publishes through this publisher.
+    /// At most `max_in_flight_batches` batches worth of entries are awaiting their
+    /// response at any time, sending more waits until some of them complete.
+    pub fn sink(&self, max_in_flight_batches: usize) -> PublishSink {
+        PublishSink::new(
+            self.clone(),
+            (max_in_flight_batches * self.max_entries).max(1),
+        )
+    }
+
+    /// Send an entry.
+    /// It resolves with the response of the entry once its batch has been published.
+    /// If the batch fails, every entry it contained fails with the same error.
+    pub async fn send(&self, entry: BatchEntry) -> Result<Vec<QstashResponse>, QStashError> {
+        let receiver = self.submit(entry)?;
+        match receiver.await {
+            Ok(r) => r,
+            Err(_) => {
+                log::error!("Batching publisher stopped before publishing the entry");
+                Err(QStashError::BatchError)
+            }
+        }
+    }
+
+    /// submit hands an entry to the background task and returns the channel its
+    /// response will be sent through.
+    pub(crate) fn submit(
+        &self,
+        entry: BatchEntry,
+    ) -> Result<oneshot::Receiver<Result<Vec<QstashResponse>, QStashError>>, QStashError> {
+        let message = self.client.batch_message(&entry)?;
+        self.client.check_body_size(message.body_size())?;
+
+        let size = match serde_json::to_vec(&message) {
+            Ok(v) => v.len() + 1,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::BatchError);
+            }
+        };
+
+        let (responder, receiver) = oneshot::channel();
+        if self
+            .sender
+            .send(Command::Send(message, size, responder))
+            .is_err()
+        {
+            log::error!("Batching publisher has been shut down");
+            return Err(QStashError::BatchError);
+        }
+
+        Ok(receiver)
+    }
+
+    /// request_flush asks the background task to publish the pending entries
+    /// without waiting for it.
+    pub(crate) fn request_flush(&self) {
+        let (done, _) = oneshot::channel();
+        let _ = self.sender.send(Command::Flush(done));
+    }
+
+    /// Publish the pending entries right away.
+    /// It resolves once the batch has been published.
+    pub async fn flush(&self) {
+        let (done, receiver) = oneshot::channel();
+        if self.sender.send(Command::Flush(done)).is_ok() {
+            let _ = receiver.await;
+        }
+    }
+
+    /// Stop accepting new entries and publish every pending one.
+    /// It resolves once all of them have been published.
+    pub async fn shutdown(&self) {
+        let (done, receiver) = oneshot::channel();
+        if self.sender.send(Command::Shutdown(done)).is_ok() {
+            let _ = receiver.await;
+        }
+    }
+}
+
+/// run is the background task that collects the entries and publishes the batches.
+async fn run(
+    client: Arc<Client>,
+    config: BatchingConfig,
+    mut receiver: mpsc::UnboundedReceiver<Command>,
+) {
+    let mut pending: Vec<(BatchMessage, Responder)> = Vec::new();
+    let mut pending_bytes = 0;
+    let mut shutdowns = Vec::new();
+
+    let deadline = time::sleep(config.max_delay);
+    tokio::pin!(deadline);
+
+    loop {
+        tokio::select! {
+            command = receiver.recv() => match command {
+                Some(Command::Send(message, size, responder)) => {
+                    if !pending.is_empty() && pending_bytes + size > config.max_bytes {
+                        flush(&client, &mut pending, &mut pending_bytes).await;
+                    }
+
+                    if pending.is_empty() {
+                        deadline.as_mut().reset(Instant::now() + config.max_delay);
+                    }
+                    pending.push((message, responder));
+                    pending_bytes += size;
+
+                    if pending.len() >= config.max_entries || pending_bytes >= config.max_bytes {
+                        flush(&client, &mut pending, &mut pending_bytes).await;
+                    }
+                }
+                Some(Command::Flush(done)) => {
+                    flush(&client, &mut pending, &mut pending_bytes).await;
+                    let _ = done.send(());
+                }
+                Some(Command::Shutdown(done)) => {
+                    // stop accepting new commands, the ones already queued are still processed
+                    receiver.close();
+                    shutdowns.push(done);
+                }
+                None => {
+                    flush(&client, &mut pending, &mut pending_bytes).await;
+                    for done in shutdowns {
+                        let _ = done.send(());
+                    }
+                    return;
+                }
+            },
+            _ = &mut deadline, if !pending.is_empty() => {
+                flush(&client, &mut pending, &mut pending_bytes).await;
+            }
+        }
+    }
+}
+
+/// flush publishes the pending entries and sends each of them its response.
+async fn flush(
+    client: &Client,
+    pending: &mut Vec<(BatchMessage, Responder)>,
+    pending_bytes: &mut usize,
+) {
+    if pending.is_empty() {
+        return;
+    }
+
+    let (messages, responders): (Vec<_>, Vec<_>) = std::mem::take(pending).into_iter().unzip();
+    *pending_bytes = 0;
+
+    match client.send_batch(&messages).await {
+        Ok(responses) => {
+            for (responder, response) in responders.into_iter().zip(responses) {
+                let _ = responder.send(Ok(response.into_responses()));
+            }
+        }
+        Err(e) => {
+            for responder in responders {
+                let _ = responder.send(Err(e.clone()));
+            }
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! # config module
+//! This module contains the configuration of a client, deserializable from any serde
+//! format such as TOML.
+//! Secrets can be read from the environment with `env:NAME` instead of being written
+//! in the configuration.
+
+use std::{collections::HashMap, time::Duration};
+
+use http::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method,
+};
+use serde::Deserialize;
+use url::Url;
+
+use super::{
+    error::QStashError, Client, HttpBackend, PublishOptions, RateLimit, RetryPolicy, Version,
+};
+use crate::log;
+
+/// The prefix of the values read from an environment variable.
+const ENV_PREFIX: &str = "env:";
+
+/// The configuration of a [`Client`].
+///
+/// # Example
+/// ```toml
+/// token = "env:QSTASH_TOKEN"
+/// timeout_ms = 10000
+///
+/// [retry]
+/// max_attempts = 3
+///
+/// [rate_limit]
+/// max_requests_per_second = 50
+/// burst = 100
+///
+/// [publish_defaults]
+/// retries = 3
+/// failure_callback = "https://example.com/failed"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    /// The token, or `env:NAME` to read it from an environment variable.
+    pub token: Option<String>,
+    /// The name of the environment variable containing the token.
+    pub token_env: Option<String>,
+    /// The default is `https://qstash.upstash.io`.
+    pub base_url: Option<String>,
+    /// `v1` or `v2`, the default is `v2`.
+    pub version: Option<String>,
+    /// The timeout of a request, from connecting until the response body is read.
+    pub timeout_ms: Option<u64>,
+    /// The timeout of connecting to QStash.
+    pub connect_timeout_ms: Option<u64>,
+    /// The maximum size in bytes of the body of a message.
+    pub max_body_size: Option<usize>,
+    pub retry: Option<RetryConfig>,
+    pub rate_limit: Option<RateLimit>,
+    pub publish_defaults: Option<PublishDefaults>,
+}
+
+/// The configuration of the [`RetryPolicy`].
+/// The backoffs default to the ones of [`RetryPolicy::default`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_backoff_ms: Option<u64>,
+    pub max_backoff_ms: Option<u64>,
+}
+
+/// The options applied to every published message, see [`Client::publish_defaults`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PublishDefaults {
+    /// The headers sent along with every message, the values accept `env:NAME`.
+    pub headers: Option<HashMap<String, String>>,
+    /// In seconds.
+    pub delay: Option<u32>,
+    pub retries: Option<u32>,
+    pub callback: Option<String>,
+    pub failure_callback: Option<String>,
+    pub method: Option<String>,
+}
+
+/// config_error logs and returns the error of an invalid field.
+fn config_error(field: &str, reason: impl Into<String>) -> QStashError {
+    let reason = reason.into();
+    log::error!("Invalid config field {}: {}", field, reason);
+    QStashError::ConfigError {
+        field: field.to_string(),
+        reason,
+    }
+}
+
+/// resolve_secret reads the value from the environment if it starts with `env:`.
+fn resolve_secret(field: &str, value: &str) -> Result<String, QStashError> {
+    let name = match value.strip_prefix(ENV_PREFIX) {
+        Some(name) => name,
+        None => return Ok(value.to_string()),
+    };
+    match std::env::var(name) {
+        Ok(v) => Ok(v),
+        Err(_) => Err(config_error(
+            field,
+            format!("environment variable {} is not set", name),
+        )),
+    }
+}
+
+impl PublishDefaults {
+    fn to_options(&self) -> Result<PublishOptions, QStashError> {
+        let headers = match &self.headers {
+            Some(headers) => {
+                let mut map = HeaderMap::new();
+                for (name, value) in headers {
+                    let field = format!("publish_defaults.headers.{}", name);
+                    let header_name = match HeaderName::from_bytes(name.as_bytes()) {
+                        Ok(n) => n,
+                        Err(e) => return Err(config_error(&field, e.to_string())),
+                    };
+                    let mut header_value =
+                        match HeaderValue::from_str(&resolve_secret(&field, value)?) {
+                            Ok(v) => v,
+                            Err(e) => return Err(config_error(&field, e.to_string())),
+                        };
+                    if value.starts_with(ENV_PREFIX) {
+                        header_value.set_sensitive(true);
+                    }
+                    map.insert(header_name, header_value);
+                }
+                Some(map)
+            }
+            None => None,
+        };
+
+        let method = match &self.method {
+            Some(method) => match Method::from_bytes(method.to_uppercase().as_bytes()) {
+                Ok(m) => Some(m),
+                Err(e) => return Err(config_error("publish_defaults.method", e.to_string())),
+            },
+            None => None,
+        };
+
+        Ok(PublishOptions {
+            headers,
+            delay: self.delay,
+            retries: self.retries,
+            callback: self.callback.clone(),
+            failure_callback: self.failure_callback.clone(),
+            method,
+            ..Default::default()
+        })
+    }
+}
+
+impl Client {
+    /// Initialize a new QStash client from its configuration.
+    /// An invalid configuration returns a [`QStashError::ConfigError`] naming the field.
+    #[cfg(feature = "reqwest")]
+    pub fn from_config(config: ClientConfig) -> Result<Client, QStashError> {
+        let mut http_builder = reqwest::Client::builder();
+        if let Some(timeout) = config.timeout_ms {
+            http_builder = http_builder.timeout(Duration::from_millis(timeout));
+        }
+        if let Some(timeout) = config.connect_timeout_ms {
+            http_builder = http_builder.connect_timeout(Duration::from_millis(timeout));
+        }
+        let http = match http_builder.build() {
+            Ok(c) => c,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ReqwestError);
+            }
+        };
+
+        Self::from_config_with_backend(config, super::ReqwestBackend::new(http))
+    }
+
+    /// Initialize a new QStash client from its configuration, sending the requests
+    /// through the backend.
+    /// The timeouts of the configuration are ignored, they are up to the backend.
+    pub fn from_config_with_backend(
+        config: ClientConfig,
+        backend: impl HttpBackend + 'static,
+    ) -> Result<Client, QStashError> {
+        let token = match (&config.token, &config.token_env) {
+            (Some(token), None) => resolve_secret("token", token)?,
+            (None, Some(name)) => resolve_secret("token_env", &format!("{}{}", ENV_PREFIX, name))?,
+            (Some(_), Some(_)) => {
+                return Err(config_error("token", "set either token or token_env"))
+            }
+            (None, None) => return Err(config_error("token", "missing token")),
+        };
+
+        let version = match config.version.as_deref().map(str::to_lowercase).as_deref() {
+            None | Some("v2") => Version::V2,
+            Some("v1") => Version::V1,
+            Some(v) => return Err(config_error("version", format!("unknown version {}", v))),
+        };
+
+        if let Some(base_url) = &config.base_url {
+            if let Err(e) = Url::parse(base_url) {
+                return Err(config_error("base_url", e.to_string()));
+            }
+        }
+
+        let mut client =
+            Client::with_backend(&token, config.base_url.as_deref(), Some(version), backend)?;
+
+        if let Some(limit) = config.max_body_size {
+            client = client.max_body_size(limit);
+        }
+
+        if let Some(retry) = config.retry {
+            if retry.max_attempts == 0 {
+                return Err(config_error("retry.max_attempts", "must be at least 1"));
+            }
+            let defaults = RetryPolicy::default();
+            client = client.retry_policy(RetryPolicy {
+                max_attempts: retry.max_attempts,
+                base_backoff: retry
+                    .base_backoff_ms
+                    .map_or(defaults.base_backoff, Duration::from_millis),
+                max_backoff: retry
+                    .max_backoff_ms
+                    .map_or(defaults.max_backoff, Duration::from_millis),
+            });
+        }
+
+        if let Some(rate_limit) = config.rate_limit {
+            client = match client.rate_limit(rate_limit) {
+                Ok(c) => c,
+                Err(QStashError::ConfigError { field, reason }) => {
+                    return Err(config_error(&format!("rate_limit.{}", field), reason));
+                }
+                Err(e) => return Err(e),
+            };
+        }
+
+        if let Some(defaults) = config.publish_defaults {
+            client = client.publish_defaults(defaults.to_options()?);
+        }
+
+        Ok(client)
+    }
+}
@@ -0,0 +1,230 @@
+//! # cron module
+//! This module contains a parser for the five field cron expressions of QStash schedules.
+//! It follows the usual cron interpretation, in UTC:
+//! - the fields are minute, hour, day of month, month and day of week;
+//! - a field is `*`, a value, a range `a-b` or a list of them, with an optional `/step`;
+//! - months and days of the week accept their three letter names, Sunday is 0 or 7;
+//! - if both the day of month and the day of week are restricted, a day matching either runs;
+//! - the `@yearly`, `@annually`, `@monthly`, `@weekly`, `@daily`, `@midnight` and `@hourly`
+//!   macros stand for their five field expression.
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The amount of days searched for the next run, 400 years cover every calendar.
+const MAX_SEARCH_DAYS: i64 = 146_097;
+
+/// The error returned for an invalid cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronError {
+    pub expression: String,
+    pub reason: String,
+}
+
+impl fmt::Display for CronError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid cron expression {:?}: {}",
+            self.expression, self.reason
+        )
+    }
+}
+
+impl std::error::Error for CronError {}
+
+/// The allowed values of a field, as a bit set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field {
+    values: u64,
+    /// False if the field starts with `*`.
+    restricted: bool,
+}
+
+impl Field {
+    fn contains(&self, value: u32) -> bool {
+        self.values & (1 << value) != 0
+    }
+}
+
+/// A parsed cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpression {
+    minutes: Field,
+    hours: Field,
+    days_of_month: Field,
+    months: Field,
+    days_of_week: Field,
+}
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const DAYS_OF_WEEK: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+impl CronExpression {
+    /// Parse a five field cron expression or a macro such as `@hourly`.
+    pub fn parse(expression: &str) -> Result<Self, CronError> {
+        let parse = || -> Result<Self, String> {
+            let expanded = match expression.trim() {
+                "@yearly" | "@annually" => "0 0 1 1 *",
+                "@monthly" => "0 0 1 * *",
+                "@weekly" => "0 0 * * 0",
+                "@daily" | "@midnight" => "0 0 * * *",
+                "@hourly" => "0 * * * *",
+                m if m.starts_with('@') => return Err(format!("unknown macro {}", m)),
+                _ => expression,
+            };
+            let fields: Vec<&str> = expanded.split_whitespace().collect();
+            if fields.len() != 5 {
+                return Err(format!("expected 5 fields, found {}", fields.len()));
+            }
+
+            let mut days_of_week = parse_field(fields[4], 0, 7, &DAYS_OF_WEEK)?;
+            // 7 is another name for sunday
+            if days_of_week.contains(7) {
+                days_of_week.values |= 1;
+            }
+
+            Ok(Self {
+                minutes: parse_field(fields[0], 0, 59, &[])?,
+                hours: parse_field(fields[1], 0, 23, &[])?,
+                days_of_month: parse_field(fields[2], 1, 31, &[])?,
+                months: parse_field(fields[3], 1, 12, &MONTHS)?,
+                days_of_week,
+            })
+        };
+
+        parse().map_err(|reason| CronError {
+            expression: expression.to_string(),
+            reason,
+        })
+    }
+
+    /// next_after returns the first run strictly after the time, or None if the expression
+    /// never runs again.
+    pub fn next_after(&self, time: SystemTime) -> Option<SystemTime> {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+        };
+        // runs happen at the start of a minute
+        let start = seconds.div_euclid(60) + 1;
+        let first_day = start.div_euclid(1440);
+        let mut minute_of_day = start.rem_euclid(1440) as u32;
+
+        for day in first_day..first_day + MAX_SEARCH_DAYS {
+            if self.matches_day(day) {
+                if let Some(minute) = self.first_minute_from(minute_of_day) {
+                    let minutes = day * 1440 + minute as i64;
+                    return from_unix_seconds(minutes * 60);
+                }
+            }
+            minute_of_day = 0;
+        }
+
+        None
+    }
+
+    /// matches_day returns true if the expression runs on the day since the unix epoch.
+    fn matches_day(&self, day: i64) -> bool {
+        let (_, month, day_of_month) = civil_from_days(day);
+        if !self.months.contains(month) {
+            return false;
+        }
+
+        let day_of_week = (day + 4).rem_euclid(7) as u32;
+        let dom = self.days_of_month.contains(day_of_month);
+        let dow = self.days_of_week.contains(day_of_week);
+        if self.days_of_month.restricted && self.days_of_week.restricted {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+
+    /// first_minute_from returns the first minute of the day at or after the one given
+    /// at which the expression runs.
+    fn first_minute_from(&self, minute_of_day: u32) -> Option<u32> {
+        (minute_of_day / 60..24)
+            .filter(|hour| self.hours.contains(*hour))
+            .flat_map(|hour| (0..60).map(move |minute| hour * 60 + minute))
+            .filter(|m| *m >= minute_of_day)
+            .find(|m| self.minutes.contains(m % 60))
+    }
+}
+
+/// parse_field parses a comma separated list of ranges within min and max.
+/// Names are matched case insensitively, the first name is the value min.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<Field, String> {
+    let value = |v: &str| -> Result<u32, String> {
+        if let Some(i) = names.iter().position(|n| n.eq_ignore_ascii_case(v)) {
+            return Ok(min + i as u32);
+        }
+        match v.parse::<u32>() {
+            Ok(n) if (min..=max).contains(&n) => Ok(n),
+            Ok(n) => Err(format!("{} is out of range {}-{}", n, min, max)),
+            Err(_) => Err(format!("invalid value {:?}", v)),
+        }
+    };
+
+    let mut values = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(s) if s > 0 => (range, s),
+                _ => return Err(format!("invalid step {:?}", step)),
+            },
+            None => (part, 1),
+        };
+
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // a single value with a step runs until the end of the range
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("invalid range {:?}", range));
+        }
+
+        for v in (start..=end).step_by(step as usize) {
+            values |= 1 << v;
+        }
+    }
+
+    Ok(Field {
+        values,
+        restricted: !field.starts_with('*'),
+    })
+}
+
+/// civil_from_days converts days since the unix epoch to a year, month and day.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn from_unix_seconds(seconds: i64) -> Option<SystemTime> {
+    if seconds >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+    }
+}
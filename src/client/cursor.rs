@@ -0,0 +1,193 @@
+//! # cursor module
+//! This module contains the streams over the paginated endpoints and the stores their
+//! cursors are checkpointed in, so an ingestion job can resume where it stopped.
+//!
+//! The cursor of a page is saved once every item of the previous page was consumed,
+//! never right after the fetch, so a job stopped in the middle of a page reads that
+//! page again when it resumes: items can be seen twice, never skipped.
+
+use std::{future::Future, io::ErrorKind, iter, path::PathBuf, sync::Arc};
+
+use async_trait::async_trait;
+use futures::{stream, Stream};
+
+use super::{error::QStashError, page::Page};
+use crate::log;
+
+/// A store of the pagination cursors, by name.
+#[async_trait]
+pub trait CursorStore: Send + Sync {
+    /// load returns the cursor saved under the name, if any.
+    async fn load(&self, name: &str) -> Result<Option<String>, QStashError>;
+    /// save replaces the cursor saved under the name.
+    async fn save(&self, name: &str, cursor: &str) -> Result<(), QStashError>;
+}
+
+/// A [`CursorStore`] keeping every cursor in a `<name>.cursor` file of a directory.
+/// Names can only contain ASCII letters, digits, `-`, `_` and `.`.
+#[derive(Debug, Clone)]
+pub struct FileCursorStore {
+    dir: PathBuf,
+}
+
+impl FileCursorStore {
+    /// Creates a new [`FileCursorStore`] in the directory, created on the first save.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// path returns the file of the cursor, or an error if the name is not a valid file name.
+    fn path(&self, name: &str) -> Result<PathBuf, QStashError> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            log::error!("Invalid cursor name {:?}", name);
+            return Err(QStashError::CursorStoreError);
+        }
+        Ok(self.dir.join(format!("{}.cursor", name)))
+    }
+}
+
+#[async_trait]
+impl CursorStore for FileCursorStore {
+    async fn load(&self, name: &str) -> Result<Option<String>, QStashError> {
+        match tokio::fs::read_to_string(self.path(name)?).await {
+            Ok(c) if c.trim().is_empty() => Ok(None),
+            Ok(c) => Ok(Some(c.trim().to_string())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::CursorStoreError)
+            }
+        }
+    }
+
+    /// save writes the cursor to a temporary file renamed over the previous one,
+    /// so a crash never leaves a partial cursor behind.
+    async fn save(&self, name: &str, cursor: &str) -> Result<(), QStashError> {
+        let path = self.path(name)?;
+        let temporary = self.dir.join(format!(".{}.cursor.tmp", name));
+        let result = async {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            tokio::fs::write(&temporary, cursor).await?;
+            tokio::fs::rename(&temporary, &path).await
+        }
+        .await;
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::CursorStoreError)
+            }
+        }
+    }
+}
+
+/// The store and name the cursor of a stream is checkpointed under.
+#[derive(Clone)]
+pub(crate) struct Checkpoint {
+    pub(crate) store: Arc<dyn CursorStore>,
+    pub(crate) name: String,
+}
+
+struct Pagination<T, F> {
+    fetch: F,
+    cursor: Option<String>,
+    items: Box<dyn Iterator<Item = Result<T, QStashError>> + Send>,
+    checkpoint: Option<Checkpoint>,
+    /// The cursor to save once the items of the current page are consumed.
+    unsaved: Option<String>,
+    started: bool,
+    finished: bool,
+}
+
+/// paginate returns the items of every page, starting at the cursor, or at the cursor
+/// saved in the checkpoint if there is one.
+/// Pages are fetched lazily, the stream ends after the last page or the first error
+/// fetching a page. An item that could not be deserialized is returned as an error
+/// and the stream goes on.
+pub(crate) fn paginate<T: 'static, F, Fut>(
+    cursor: Option<String>,
+    checkpoint: Option<Checkpoint>,
+    fetch: F,
+) -> impl Stream<Item = Result<T, QStashError>>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Page<T>, QStashError>>,
+{
+    let pagination = Pagination {
+        fetch,
+        cursor,
+        items: Box::new(iter::empty()),
+        checkpoint,
+        unsaved: None,
+        started: false,
+        finished: false,
+    };
+
+    stream::unfold(pagination, |mut p| async move {
+        if let Some(item) = p.items.next() {
+            return Some((item, p));
+        }
+
+        // every item of the page was consumed
+        if let (Some(checkpoint), Some(cursor)) = (&p.checkpoint, p.unsaved.take()) {
+            if let Err(e) = checkpoint.store.save(&checkpoint.name, &cursor).await {
+                p.finished = true;
+                return Some((Err(e), p));
+            }
+        }
+
+        loop {
+            if p.finished {
+                return None;
+            }
+
+            if !p.started {
+                p.started = true;
+                if let Some(checkpoint) = &p.checkpoint {
+                    match checkpoint.store.load(&checkpoint.name).await {
+                        Ok(Some(c)) => p.cursor = Some(c),
+                        Ok(None) => {}
+                        Err(e) => {
+                            p.finished = true;
+                            return Some((Err(e), p));
+                        }
+                    }
+                }
+            }
+
+            let page = match (p.fetch)(p.cursor.clone()).await {
+                Ok(page) => page,
+                Err(e) => {
+                    p.finished = true;
+                    return Some((Err(e), p));
+                }
+            };
+            match page.cursor.filter(|c| !c.is_empty()) {
+                Some(c) => {
+                    p.cursor = Some(c.clone());
+                    p.unsaved = Some(c);
+                }
+                None => p.finished = true,
+            }
+
+            p.items = page.items;
+            if let Some(item) = p.items.next() {
+                return Some((item, p));
+            }
+            // an empty page is consumed right away
+            if let (Some(checkpoint), Some(cursor)) = (&p.checkpoint, p.unsaved.take()) {
+                if let Err(e) = checkpoint.store.save(&checkpoint.name, &cursor).await {
+                    p.finished = true;
+                    return Some((Err(e), p));
+                }
+            }
+        }
+    })
+}
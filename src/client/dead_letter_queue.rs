@@ -1,87 +1,673 @@
 //! # dead_letter_queue module
 //! This module contains the methods implementation required to interact with the dead letter queue endpoint.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc, time::Instant};
 
+use base64::{engine::general_purpose::STANDARD, Engine};
+use futures::{Stream, TryStreamExt};
+use http::{
+    header::{HeaderMap, HeaderName, CONTENT_TYPE},
+    Method, StatusCode,
+};
 use serde::{Deserialize, Serialize};
 
-use crate::client::error::QStashError;
+use crate::{client::error::QStashError, log};
 
-use super::Client;
+use super::{
+    cursor::{paginate, Checkpoint, CursorStore},
+    logging::log_response,
+    messages::{deserialize_method, header_map, serialize_method, InvalidHeaders},
+    page::parse_page,
+    Client, PublishOptions, PublishRequest, PublishRequestUrl, QstashResponse, ResponseParts,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DlqMessage {
     pub message_id: String,
     pub url: String,
+    /// The url group of the message, also read from the `urlGroup` field.
+    #[serde(alias = "urlGroup")]
     pub topic_name: Option<String>,
     pub endpoint_name: Option<String>,
     pub key: Option<String>,
-    pub method: String,
+    /// The method the message was delivered with,
+    /// see [`parse_method`](super::messages::parse_method) for unknown methods.
+    #[serde(
+        default = "default_method",
+        deserialize_with = "deserialize_method",
+        serialize_with = "serialize_method"
+    )]
+    pub method: Method,
     pub header: Option<HashMap<String, Vec<String>>>,
     pub body: Option<String>,
+    /// The base64 encoded body, set instead of `body` when it is not valid UTF-8.
+    pub body_base64: Option<String>,
     pub max_retries: Option<u32>,
     pub not_before: Option<u64>,
     pub created_at: u64,
     pub callback: Option<String>,
     pub dlq_id: String,
+    /// The status code the destination answered the last failed delivery with.
+    pub response_status: Option<u16>,
+    /// The headers the destination answered the last failed delivery with.
+    pub response_header: Option<HashMap<String, Vec<String>>>,
+    /// The base64 encoded body the destination answered the last failed delivery with.
+    pub response_body: Option<String>,
+}
+
+/// default_method returns the method QStash delivers with by default.
+fn default_method() -> Method {
+    Method::POST
+}
+
+impl DlqMessage {
+    /// group_name returns the url group of the message, whichever spelling QStash used.
+    pub fn group_name(&self) -> Option<&str> {
+        self.topic_name.as_deref()
+    }
+
+    /// headers returns the headers of the message as a [`HeaderMap`].
+    /// The raw `header` map is kept as QStash returned it.
+    pub fn headers(&self) -> Result<HeaderMap, InvalidHeaders> {
+        header_map(self.header.as_ref())
+    }
+
+    /// decoded_body returns the original bytes of the body of the message.
+    pub fn decoded_body(&self) -> Result<Vec<u8>, QStashError> {
+        match (&self.body_base64, &self.body) {
+            (Some(encoded), _) => match STANDARD.decode(encoded) {
+                Ok(b) => Ok(b),
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    Err(QStashError::DeadLetterQueueError {
+                        status: None,
+                        message: formated_string,
+                    })
+                }
+            },
+            (None, Some(body)) => Ok(body.clone().into_bytes()),
+            (None, None) => Ok(Vec::new()),
+        }
+    }
+
+    /// response_headers returns the headers of the last failed delivery as a [`HeaderMap`].
+    pub fn response_headers(&self) -> Result<HeaderMap, InvalidHeaders> {
+        header_map(self.response_header.as_ref())
+    }
+
+    /// response_body_decoded returns the bytes of the body of the last failed delivery,
+    /// empty if QStash did not record it.
+    pub fn response_body_decoded(&self) -> Result<Vec<u8>, QStashError> {
+        match &self.response_body {
+            Some(encoded) => match STANDARD.decode(encoded) {
+                Ok(b) => Ok(b),
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    Err(QStashError::DeadLetterQueueError {
+                        status: None,
+                        message: formated_string,
+                    })
+                }
+            },
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// response_body_utf8 returns the body of the last failed delivery as text,
+    /// with the invalid UTF-8 sequences replaced.
+    pub fn response_body_utf8(&self) -> Result<String, QStashError> {
+        let body = self.response_body_decoded()?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// to_publish_request rebuilds the request that published the message, with its
+    /// original body, headers, method, retries and callback.
+    /// The destination is the url of the failed delivery, even for a message published to
+    /// a url group, so the endpoints that received it do not receive it again.
+    /// The headers are prefixed with `Upstash-Forward-` so QStash forwards them again,
+    /// except `Content-Type` and the `Upstash-` headers.
+    pub fn to_publish_request(&self) -> Result<PublishRequest<Vec<u8>>, QStashError> {
+        let url = match self.url.parse() {
+            Ok(u) => PublishRequestUrl::Url(u),
+            Err(e) => {
+                let formated_string = format!("Invalid url {}: {}", self.url, e);
+                log::error!(formated_string);
+                return Err(QStashError::InvalidUrl);
+            }
+        };
+
+        let headers = match self.headers() {
+            Ok(h) => forwarded_headers(h),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let body = self.decoded_body()?;
+
+        let mut request = PublishRequest::new(url);
+        request.body = match body.is_empty() {
+            true => None,
+            false => Some(body),
+        };
+        request.headers = Some(headers);
+        request.method = Some(self.method.clone());
+        request.retries = self.max_retries;
+        request.callback = self.callback.clone();
+        Ok(request)
+    }
+}
+
+/// forwarded_headers prefixes the headers delivered to the destination with
+/// `Upstash-Forward-`, keeping `Content-Type` and the `Upstash-` headers as they are.
+fn forwarded_headers(headers: HeaderMap) -> HeaderMap {
+    let mut forwarded = HeaderMap::with_capacity(headers.len());
+    for (name, value) in headers.iter() {
+        let name = match name == CONTENT_TYPE || name.as_str().starts_with("upstash-") {
+            true => name.clone(),
+            false => match HeaderName::from_bytes(format!("Upstash-Forward-{}", name).as_bytes()) {
+                Ok(n) => n,
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    continue;
+                }
+            },
+        };
+        forwarded.append(name, value.clone());
+    }
+    forwarded
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DlqResponse {
     pub messages: Vec<DlqMessage>,
+    /// The cursor of the next page, if there are more messages.
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
+impl DlqResponse {
+    /// next_request returns the request of the page after this one, with the filters of the
+    /// request of this page, or `None` if this is the last page.
+    pub fn next_request(&self, request: &DlqRequest) -> Option<DlqRequest> {
+        self.cursor
+            .as_ref()
+            .filter(|c| !c.is_empty())
+            .map(|c| DlqRequest {
+                cursor: Some(c.clone()),
+                ..request.clone()
+            })
+    }
+}
+
+/// The order of the messages of the dead letter queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DlqOrder {
+    /// The oldest messages first.
+    Asc,
+    /// The latest messages first.
+    Desc,
+}
+
+impl DlqOrder {
+    /// as_str returns the value of the `order` query parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DlqOrder::Asc => "earliestFirst",
+            DlqOrder::Desc => "latestFirst",
+        }
+    }
+}
+
+/// The maximum amount of messages of a page of the dead letter queue.
+pub const MAX_DLQ_COUNT: u32 = 100;
+
 /// The dead letter queue request.
-/// It contains the optional cursor.
-#[derive(Debug)]
+/// It contains the optional cursor, the opaque [`DlqResponse::cursor`] of the previous page,
+/// and the optional filters of the messages, every set filter must match.
+#[derive(Debug, Clone, Default)]
 pub struct DlqRequest {
-    pub cursor: Option<u32>,
+    pub cursor: Option<String>,
+    /// The maximum amount of messages of a page, between 1 and [`MAX_DLQ_COUNT`].
+    /// The default is the maximum.
+    pub count: Option<u32>,
+    /// The order of the messages, the default is the order of QStash.
+    pub order: Option<DlqOrder>,
+    pub message_id: Option<String>,
+    /// The destination url of the messages.
+    pub url: Option<String>,
+    pub topic_name: Option<String>,
+    pub schedule_id: Option<String>,
+    pub queue_name: Option<String>,
+    /// The earliest time the messages failed, in milliseconds since the epoch.
+    pub from_date: Option<u64>,
+    /// The latest time the messages failed, in milliseconds since the epoch.
+    pub to_date: Option<u64>,
+    /// The status code the destination answered the last delivery with.
+    pub response_status: Option<u16>,
+}
+
+impl DlqRequest {
+    /// query_pairs returns the query parameters of the request, in the order they are sent.
+    /// The values are not encoded yet.
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let strings = [
+            ("cursor", &self.cursor),
+            ("messageId", &self.message_id),
+            ("url", &self.url),
+            ("topicName", &self.topic_name),
+            ("scheduleId", &self.schedule_id),
+            ("queueName", &self.queue_name),
+            ("order", &self.order.map(|o| o.as_str().to_string())),
+        ];
+        let numbers = [
+            ("count", self.count.map(u64::from)),
+            ("fromDate", self.from_date),
+            ("toDate", self.to_date),
+            ("responseStatus", self.response_status.map(u64::from)),
+        ];
+
+        strings
+            .into_iter()
+            .filter_map(|(name, value)| value.clone().map(|v| (name, v)))
+            .chain(
+                numbers
+                    .into_iter()
+                    .filter_map(|(name, value)| value.map(|v| (name, v.to_string()))),
+            )
+            .collect()
+    }
+}
+
+/// The counts of the messages of the dead letter queue.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DlqSummary {
+    /// The amount of messages.
+    pub total: u64,
+    /// The amount of messages by destination url.
+    pub by_url: HashMap<String, u64>,
+    /// The amount of messages by the status of their last failed delivery.
+    /// Messages without a recorded status are not counted.
+    pub by_status: HashMap<u16, u64>,
+}
+
+impl DlqSummary {
+    /// add counts the message.
+    pub fn add(&mut self, message: &DlqMessage) {
+        self.total += 1;
+        *self.by_url.entry(message.url.clone()).or_default() += 1;
+        if let Some(status) = message.response_status {
+            *self.by_status.entry(status).or_default() += 1;
+        }
+    }
+}
+
+/// summarize counts the messages by destination url and by response status.
+pub fn summarize(messages: &[DlqMessage]) -> DlqSummary {
+    let mut summary = DlqSummary::default();
+    for message in messages {
+        summary.add(message);
+    }
+    summary
+}
+
+/// dlq_error returns the error of a dead letter queue request the API answered with an
+/// error status.
+fn dlq_error(response: &ResponseParts) -> QStashError {
+    QStashError::DeadLetterQueueError {
+        status: Some(response.status().as_u16()),
+        message: response.error_message(),
+    }
 }
 
 impl Client {
-    /// Retrieve your dead letter queue.
+    /// Retrieve a page of your dead letter queue, filtered by the request.
+    /// The request of the next page is returned by [`DlqResponse::next_request`].
     pub async fn get_dead_letter_queue(
         &self,
         request: Option<DlqRequest>,
     ) -> Result<DlqResponse, QStashError> {
-        let mut path = match self.base_url.join(&format!("/{}/dlq", self.version)) {
+        self.fetch_dead_letter_queue(&request.unwrap_or_default())
+            .await
+    }
+
+    /// Stream every message of your dead letter queue matching the filters of the request,
+    /// fetching the pages as they are consumed.
+    /// The stream ends after the last page or the first error.
+    /// It is cancel safe: dropping a pending `next()` keeps the page being fetched for the
+    /// next poll.
+    pub fn dead_letter_queue_stream(
+        &self,
+        request: Option<DlqRequest>,
+    ) -> impl Stream<Item = Result<DlqMessage, QStashError>> + Send + 'static {
+        self.paginate_dead_letter_queue(request.unwrap_or_default(), None)
+    }
+
+    /// dead_letter_summary counts the messages of your dead letter queue matching the filters
+    /// of the request, by destination url and by response status, see [`summarize`].
+    /// Every page is read, the first error is returned.
+    pub async fn dead_letter_summary(
+        &self,
+        request: Option<DlqRequest>,
+    ) -> Result<DlqSummary, QStashError> {
+        self.dead_letter_queue_stream(request)
+            .try_fold(DlqSummary::default(), |mut summary, message| async move {
+                summary.add(&message);
+                Ok(summary)
+            })
+            .await
+    }
+
+    /// Stream every message of your dead letter queue like [`Client::dead_letter_queue_stream`],
+    /// resuming from the cursor saved under the name in the store.
+    /// The cursor of a page is saved once every message of the previous page was consumed,
+    /// so messages are never skipped but a page interrupted midway is read again.
+    pub fn checkpointed_dead_letter_queue_stream(
+        &self,
+        store: Arc<dyn CursorStore>,
+        name: &str,
+    ) -> impl Stream<Item = Result<DlqMessage, QStashError>> + Send + 'static {
+        let checkpoint = Checkpoint {
+            store,
+            name: name.to_string(),
+        };
+        self.paginate_dead_letter_queue(DlqRequest::default(), Some(checkpoint))
+    }
+
+    fn paginate_dead_letter_queue(
+        &self,
+        request: DlqRequest,
+        checkpoint: Option<Checkpoint>,
+    ) -> impl Stream<Item = Result<DlqMessage, QStashError>> + Send + 'static {
+        let client = self.clone();
+        let cursor = request.cursor.clone();
+        paginate(cursor, checkpoint, move |cursor| {
+            let client = client.clone();
+            let request = DlqRequest {
+                cursor,
+                ..request.clone()
+            };
+            async move {
+                let response = client.fetch_dead_letter_queue_response(&request).await?;
+                let error = QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: "Invalid page of the dead letter queue".to_string(),
+                };
+                parse_page(response.body, "messages", error)
+            }
+        })
+    }
+
+    /// fetch_dead_letter_queue retrieves the page of the dead letter queue of the request.
+    pub(crate) async fn fetch_dead_letter_queue(
+        &self,
+        request: &DlqRequest,
+    ) -> Result<DlqResponse, QStashError> {
+        let response = self.fetch_dead_letter_queue_response(request).await?;
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+
+    /// fetch_dead_letter_queue_response sends the request of a page and returns the response unparsed.
+    async fn fetch_dead_letter_queue_response(
+        &self,
+        request: &DlqRequest,
+    ) -> Result<ResponseParts, QStashError> {
+        if let Some(count) = request.count.filter(|c| !(1..=MAX_DLQ_COUNT).contains(c)) {
+            log::error!("Invalid dead letter queue count {}", count);
+            return Err(QStashError::DeadLetterQueueError {
+                status: None,
+                message: format!("the count must be between 1 and {}", MAX_DLQ_COUNT),
+            });
+        }
+
+        let mut path = match self.api_url(&["dlq"]) {
             Ok(p) => p,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
-                return Err(QStashError::PublishError);
+                log::error!(formated_string);
+                return Err(QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: formated_string,
+                });
             }
         };
 
-        if let Some(request) = request {
-            if let Some(cursor) = request.cursor {
-                path.set_query(Some(&format!("cursor={}", cursor)));
+        let pairs = request.query_pairs();
+        if !pairs.is_empty() {
+            path.query_pairs_mut().extend_pairs(pairs);
+        }
+
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
+            Ok(r) => {
+                log_response("dlq", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!(
+                "Listing the dead letter queue failed with status {}",
+                response.status()
+            );
+            return Err(dlq_error(&response));
+        }
+
+        Ok(response)
+    }
+
+    /// get_dead_letter_message retrieves a single message of the dead letter queue by its dlq id.
+    /// It returns [`QStashError::DlqMessageNotFound`] if the message is not in the dead letter
+    /// queue, for example because it was already deleted or requeued.
+    pub async fn get_dead_letter_message(&self, dlq_id: &str) -> Result<DlqMessage, QStashError> {
+        let path = match self.api_url(&["dlq", dlq_id]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: formated_string,
+                });
             }
         };
 
-        let response = match self.http.get(path).send().await {
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
             Ok(r) => {
-                tracing::debug!("{:?}", r);
+                log_response("get_dlq_message", &r, started);
                 r
             }
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
-                return Err(QStashError::EventError);
+                log::error!(formated_string);
+                return Err(QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: formated_string,
+                });
             }
         };
 
-        let response = match response.json().await {
-            Ok(r) => r,
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(QStashError::DlqMessageNotFound(dlq_id.to_string()));
+        }
+        if !response.status().is_success() {
+            log::error!(
+                "Getting DLQ message {} failed with status {}",
+                dlq_id,
+                response.status()
+            );
+            return Err(dlq_error(&response));
+        }
+
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+
+    /// delete_dead_letter_message removes a message from the dead letter queue by its dlq id.
+    /// It returns [`QStashError::DlqMessageNotFound`] if the message is not in the dead letter
+    /// queue.
+    pub async fn delete_dead_letter_message(&self, dlq_id: &str) -> Result<(), QStashError> {
+        let path = match self.api_url(&["dlq", dlq_id]) {
+            Ok(p) => p,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
-                return Err(QStashError::EventError);
+                log::error!(formated_string);
+                return Err(QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: formated_string,
+                });
             }
         };
 
-        Ok(response)
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        match self.execute(self.request(Method::DELETE, path)).await {
+            Ok(r) => {
+                log_response("delete_dlq_message", &r, started);
+                match r.status() {
+                    s if s.is_success() => Ok(()),
+                    StatusCode::NOT_FOUND => {
+                        Err(QStashError::DlqMessageNotFound(dlq_id.to_string()))
+                    }
+                    _ => Err(dlq_error(&r)),
+                }
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::DeadLetterQueueError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+
+    /// requeue_dead_letter_message publishes a message of the dead letter queue again, to its
+    /// original destination with its original body, headers and method.
+    ///
+    /// The options replace the original ones, a header of the options replaces the original
+    /// header of the same name.
+    /// The message is removed from the dead letter queue only once it has been published,
+    /// if removing it fails the error is returned even though the message was published.
+    pub async fn requeue_dead_letter_message(
+        &self,
+        dlq_id: &str,
+        options: Option<PublishOptions>,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        self.requeue_dlq_message_with(dlq_id, |mut request| {
+            if let Some(options) = options {
+                let original = request.take_options();
+                request.set_options(options.with_defaults(Some(&original)));
+            }
+            request
+        })
+        .await
+    }
+
+    /// requeue_dlq_message_with publishes a message of the dead letter queue again after
+    /// letting the closure modify it.
+    ///
+    /// The closure receives the original request, see [`DlqMessage::to_publish_request`],
+    /// with the decoded body, and can change its body, headers, method or options.
+    /// Headers for the destination must be prefixed with `Upstash-Forward-`.
+    /// The message is removed from the dead letter queue only once it has been published.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use qstash_rs::client::Client;
+    /// use http::header::HeaderValue;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+    ///
+    ///     let result = qstash_client
+    ///         .requeue_dlq_message_with("<DLQ_ID>", |mut request| {
+    ///             request
+    ///                 .headers
+    ///                 .get_or_insert_with(Default::default)
+    ///                 .insert("Upstash-Forward-X-Requeued", HeaderValue::from_static("true"));
+    ///             request
+    ///         })
+    ///         .await;
+    ///
+    ///     match result {
+    ///         Ok(r) => println!("{:?}", r),
+    ///         Err(err) => println!("{:?}", err),
+    ///     };
+    /// }
+    /// ```
+    pub async fn requeue_dlq_message_with<F>(
+        &self,
+        dlq_id: &str,
+        modify: F,
+    ) -> Result<Vec<QstashResponse>, QStashError>
+    where
+        F: FnOnce(PublishRequest<Vec<u8>>) -> PublishRequest<Vec<u8>>,
+    {
+        let responses = self.republish_dead_letter_message(dlq_id, modify).await?;
+        self.delete_dead_letter_message(dlq_id).await?;
+        Ok(responses)
+    }
+
+    /// republish_dead_letter_message publishes a message of the dead letter queue again after
+    /// letting the closure modify it, without removing it from the dead letter queue.
+    pub(crate) async fn republish_dead_letter_message<F>(
+        &self,
+        dlq_id: &str,
+        modify: F,
+    ) -> Result<Vec<QstashResponse>, QStashError>
+    where
+        F: FnOnce(PublishRequest<Vec<u8>>) -> PublishRequest<Vec<u8>>,
+    {
+        let message = self.get_dead_letter_message(dlq_id).await?;
+        let request = modify(message.to_publish_request()?);
+
+        let responses = self.publish(request).await?;
+        if let Some(error) = responses.iter().find_map(|r| r.error.as_ref()) {
+            log::error!("Could not requeue {}: {}", dlq_id, error);
+            return Err(QStashError::PublishError);
+        }
+        Ok(responses)
     }
 }
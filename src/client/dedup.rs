@@ -0,0 +1,196 @@
+//! # dedup module
+//! This module contains the client side deduplication cache.
+//! It remembers the messages published recently so a duplicate publish returns the
+//! original response, marked as deduplicated, without sending a request.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use http::header::HeaderMap;
+use sha2::{Digest, Sha256};
+use tokio::time::Instant;
+
+use super::{Client, QstashResponse};
+use crate::log;
+
+/// The configuration of the client side deduplication cache.
+///
+/// A message is a duplicate if it has the deduplication id of a message published less
+/// than `ttl` ago or, without a deduplication id, the same destination, headers and body.
+/// Concurrent publishes of the same message are not deduplicated, only the ones sent
+/// after the first one succeeded.
+#[derive(Debug, Clone)]
+pub struct DedupCache {
+    /// The maximum amount of messages remembered, the least recently used are evicted first.
+    pub capacity: usize,
+    /// How long a message is remembered after it was published.
+    pub ttl: Duration,
+}
+
+impl DedupCache {
+    /// Creates a new [`DedupCache`].
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self { capacity, ttl }
+    }
+}
+
+struct Entry {
+    responses: Vec<QstashResponse>,
+    published_at: Instant,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    by_key: HashMap<String, Entry>,
+    /// The keys ordered from the least to the most recently used.
+    by_use: BTreeMap<u64, String>,
+    uses: u64,
+}
+
+impl Entries {
+    fn touch(&mut self, key: &str) {
+        self.uses += 1;
+        if let Some(entry) = self.by_key.get_mut(key) {
+            self.by_use.remove(&entry.last_used);
+            entry.last_used = self.uses;
+            self.by_use.insert(self.uses, key.to_string());
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.by_key.remove(key) {
+            self.by_use.remove(&entry.last_used);
+        }
+    }
+}
+
+/// The cache shared by every clone of a client.
+#[derive(Clone)]
+pub(crate) struct Deduplicator {
+    config: DedupCache,
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl Deduplicator {
+    pub(crate) fn new(config: DedupCache) -> Self {
+        Self {
+            config,
+            entries: Arc::new(Mutex::new(Entries::default())),
+        }
+    }
+
+    /// get returns the responses of the message if it was published within the ttl.
+    fn get(&self, key: &str) -> Option<Vec<QstashResponse>> {
+        let mut entries = match self.entries.lock() {
+            Ok(e) => e,
+            Err(e) => e.into_inner(),
+        };
+
+        let expired = match entries.by_key.get(key) {
+            Some(entry) => entry.published_at.elapsed() >= self.config.ttl,
+            None => return None,
+        };
+        if expired {
+            entries.remove(key);
+            return None;
+        }
+
+        entries.touch(key);
+        entries.by_key.get(key).map(|e| e.responses.clone())
+    }
+
+    /// insert remembers the responses of the message, evicting the least recently used ones.
+    fn insert(&self, key: String, responses: Vec<QstashResponse>) {
+        let mut entries = match self.entries.lock() {
+            Ok(e) => e,
+            Err(e) => e.into_inner(),
+        };
+
+        entries.remove(&key);
+        entries.by_key.insert(
+            key.clone(),
+            Entry {
+                responses,
+                published_at: Instant::now(),
+                last_used: 0,
+            },
+        );
+        entries.touch(&key);
+
+        while entries.by_key.len() > self.config.capacity.max(1) {
+            let oldest = match entries.by_use.values().next() {
+                Some(k) => k.clone(),
+                None => break,
+            };
+            entries.remove(&oldest);
+        }
+    }
+}
+
+impl Client {
+    /// dedup_key returns the key of the message in the deduplication cache.
+    /// It is None if the cache is disabled.
+    pub(crate) fn dedup_key(
+        &self,
+        destination: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Option<String> {
+        self.deduplicator.as_ref()?;
+        if let Some(id) = headers.get("Upstash-Deduplication-Id") {
+            return Some(format!("id:{}", String::from_utf8_lossy(id.as_bytes())));
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(destination.as_bytes());
+        let mut sorted: Vec<_> = headers.iter().collect();
+        sorted.sort_by(|a, b| (a.0.as_str(), a.1.as_bytes()).cmp(&(b.0.as_str(), b.1.as_bytes())));
+        for (name, value) in sorted {
+            hasher.update(b"\n");
+            hasher.update(name.as_str().as_bytes());
+            hasher.update(b":");
+            hasher.update(value.as_bytes());
+        }
+        hasher.update(b"\n\n");
+        hasher.update(body);
+
+        let hash: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        Some(format!("hash:{}", hash))
+    }
+
+    /// deduplicated returns the responses of the original message, marked as deduplicated,
+    /// if the message is a duplicate.
+    pub(crate) fn deduplicated(&self, key: Option<&str>) -> Option<Vec<QstashResponse>> {
+        let responses = self.deduplicator.as_ref()?.get(key?)?;
+        log::debug!("Skipped duplicate message {}", key.unwrap_or_default());
+        Some(
+            responses
+                .into_iter()
+                .map(|mut r| {
+                    r.deduplicated = Some(true);
+                    r
+                })
+                .collect(),
+        )
+    }
+
+    /// remember stores the responses of a published message, unless one of them failed.
+    pub(crate) fn remember(&self, key: Option<String>, responses: &[QstashResponse]) {
+        let (deduplicator, key) = match (&self.deduplicator, key) {
+            (Some(d), Some(k)) => (d, k),
+            _ => return,
+        };
+        if responses.iter().any(|r| r.error.is_some()) {
+            return;
+        }
+        deduplicator.insert(key, responses.to_vec());
+    }
+}
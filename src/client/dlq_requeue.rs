@@ -0,0 +1,105 @@
+//! # dlq_requeue module
+//! This module contains the bulk requeue of the dead letter queue.
+//! Every message is published again to its original destination and removed from the
+//! dead letter queue, a failed message does not stop the others.
+
+use futures::{stream, StreamExt};
+
+use super::{error::QStashError, Client};
+use crate::log;
+
+/// What happened to a message of a bulk requeue.
+#[derive(Debug, Clone)]
+pub enum RequeueOutcome {
+    /// The message was published again and removed from the dead letter queue.
+    Requeued,
+    /// The message could not be read or published again, it is still in the dead letter queue.
+    PublishFailed(QStashError),
+    /// The message was published again but could not be removed from the dead letter queue.
+    DeleteFailed(QStashError),
+}
+
+/// The progress of a bulk requeue, reported after every message.
+#[derive(Debug, Clone)]
+pub struct RequeueProgress {
+    pub dlq_id: String,
+    pub outcome: RequeueOutcome,
+    /// The amount of messages done, including this one.
+    pub completed: usize,
+    /// The amount of messages of the requeue.
+    pub total: usize,
+}
+
+/// The report of a bulk requeue, the dlq ids are in the order they were given.
+#[derive(Debug, Clone, Default)]
+pub struct RequeueReport {
+    /// The messages published again and removed from the dead letter queue.
+    pub requeued: Vec<String>,
+    /// The messages that could not be published again, with the error.
+    pub publish_failed: Vec<(String, QStashError)>,
+    /// The messages published again but not removed from the dead letter queue, with the error.
+    /// Requeueing them again publishes them twice.
+    pub delete_failed: Vec<(String, QStashError)>,
+}
+
+impl Client {
+    /// requeue_dead_letter_messages requeues every message of the dead letter queue like
+    /// [`Client::requeue_dead_letter_message`], with at most `concurrency` messages in flight,
+    /// at least one.
+    ///
+    /// The progress is reported once every message is done, in the order they finish.
+    pub async fn requeue_dead_letter_messages(
+        &self,
+        dlq_ids: Vec<String>,
+        concurrency: usize,
+        on_progress: impl Fn(RequeueProgress),
+    ) -> RequeueReport {
+        let total = dlq_ids.len();
+        let mut outcomes: Vec<(usize, String, RequeueOutcome)> = Vec::with_capacity(total);
+
+        let mut requeues = stream::iter(dlq_ids.into_iter().enumerate())
+            .map(|(index, dlq_id)| async move {
+                let outcome = self.requeue_outcome(&dlq_id).await;
+                (index, dlq_id, outcome)
+            })
+            .buffer_unordered(concurrency.max(1));
+        while let Some((index, dlq_id, outcome)) = requeues.next().await {
+            on_progress(RequeueProgress {
+                dlq_id: dlq_id.clone(),
+                outcome: outcome.clone(),
+                completed: outcomes.len() + 1,
+                total,
+            });
+            outcomes.push((index, dlq_id, outcome));
+        }
+
+        outcomes.sort_by_key(|(index, _, _)| *index);
+        let mut report = RequeueReport::default();
+        for (_, dlq_id, outcome) in outcomes {
+            match outcome {
+                RequeueOutcome::Requeued => report.requeued.push(dlq_id),
+                RequeueOutcome::PublishFailed(e) => report.publish_failed.push((dlq_id, e)),
+                RequeueOutcome::DeleteFailed(e) => report.delete_failed.push((dlq_id, e)),
+            }
+        }
+        report
+    }
+
+    /// requeue_outcome requeues the message and tells which step failed, if any.
+    async fn requeue_outcome(&self, dlq_id: &str) -> RequeueOutcome {
+        if let Err(e) = self
+            .republish_dead_letter_message(dlq_id, |request| request)
+            .await
+        {
+            log::error!("Could not publish {} again: {}", dlq_id, e);
+            return RequeueOutcome::PublishFailed(e);
+        }
+        match self.delete_dead_letter_message(dlq_id).await {
+            Ok(()) => RequeueOutcome::Requeued,
+            Err(e) => {
+                log::error!("Could not delete requeued {}: {}", dlq_id, e);
+                RequeueOutcome::DeleteFailed(e)
+            }
+        }
+    }
+}
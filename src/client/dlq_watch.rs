@@ -0,0 +1,291 @@
+//! # dlq_watch module
+//! This module contains the dead letter queue watcher.
+//! It polls the dead letter queue and hands every new dead letter to a handler once,
+//! deleting the ones the handler is done with.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    future::Future,
+    sync::Arc,
+    time::Duration,
+};
+
+use futures::{stream, StreamExt};
+use tokio::{sync::watch, task::JoinHandle};
+
+use super::{
+    dead_letter_queue::{DlqMessage, DlqRequest},
+    error::QStashError,
+    Client,
+};
+use crate::log;
+
+/// What the watcher does with a dead letter once the handler returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DlqAction {
+    /// Delete the message from the dead letter queue.
+    Delete,
+    /// Keep the message in the dead letter queue, it is not handed to the handler again.
+    Keep,
+}
+
+/// The dead letter queue watcher.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use qstash_rs::client::{
+///     dlq_watch::{DlqAction, DlqWatcher},
+///     Client,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+///
+///     let watcher = DlqWatcher::new(qstash_client, Duration::from_secs(30))
+///         .max_concurrency(4)
+///         .start(|message| async move {
+///             println!("{} failed", message.message_id);
+///             DlqAction::Delete
+///         });
+///
+///     tokio::time::sleep(Duration::from_secs(3600)).await;
+///     watcher.shutdown().await;
+/// }
+/// ```
+pub struct DlqWatcher {
+    client: Client,
+    interval: Duration,
+    max_concurrency: usize,
+    max_tracked: usize,
+    max_backoff: Duration,
+}
+
+impl DlqWatcher {
+    /// Creates a new [`DlqWatcher`] polling the dead letter queue on the interval.
+    pub fn new(client: Client, interval: Duration) -> Self {
+        Self {
+            client,
+            interval,
+            max_concurrency: 1,
+            max_tracked: 10_000,
+            max_backoff: Duration::from_secs(300),
+        }
+    }
+
+    /// spawn starts a watcher with the default configuration, see [`DlqWatcher::start`].
+    pub fn spawn<F, Fut>(client: Client, interval: Duration, handler: F) -> DlqWatcherHandle
+    where
+        F: Fn(DlqMessage) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = DlqAction> + Send,
+    {
+        Self::new(client, interval).start(handler)
+    }
+
+    /// Set the maximum amount of dead letters handled at once.
+    /// The default is 1.
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Set the maximum amount of dlq ids remembered as handled.
+    /// Ids no longer in the dead letter queue are forgotten, the oldest ones are forgotten
+    /// first past the limit, and handed to the handler again if they are still there.
+    /// The default is 10000.
+    pub fn max_tracked(mut self, max_tracked: usize) -> Self {
+        self.max_tracked = max_tracked.max(1);
+        self
+    }
+
+    /// Set the maximum time between two polls after failed polls.
+    /// The time doubles after every failed poll, starting at the interval.
+    /// The default is 5 minutes.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// start polls the dead letter queue from a background task and calls the handler
+    /// once for every dead letter it has not handed out yet.
+    ///
+    /// Failed polls are logged and retried after a backoff.
+    /// The watcher runs until [`DlqWatcherHandle::shutdown`] is called or the handle is dropped.
+    pub fn start<F, Fut>(self, handler: F) -> DlqWatcherHandle
+    where
+        F: Fn(DlqMessage) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = DlqAction> + Send,
+    {
+        let (shutdown, mut stopped) = watch::channel(false);
+        let handler = Arc::new(handler);
+
+        let handle = tokio::spawn(async move {
+            let mut handled = Handled::new(self.max_tracked);
+            let mut failures = 0;
+
+            loop {
+                let delay = match self.poll(&mut handled, handler.clone()).await {
+                    Ok(()) => {
+                        failures = 0;
+                        self.interval
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        let backoff = self
+                            .interval
+                            .saturating_mul(2u32.saturating_pow(failures))
+                            .min(self.max_backoff);
+                        log::error!("Could not poll the dead letter queue: {}", e);
+                        backoff
+                    }
+                };
+
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = stopped.changed() => return,
+                }
+            }
+        });
+
+        DlqWatcherHandle {
+            shutdown,
+            handle: Some(handle),
+        }
+    }
+
+    /// poll hands the new dead letters of every page to the handler.
+    async fn poll<F, Fut>(&self, handled: &mut Handled, handler: Arc<F>) -> Result<(), QStashError>
+    where
+        F: Fn(DlqMessage) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = DlqAction> + Send,
+    {
+        handled.start_poll();
+        let mut cursor = None;
+        loop {
+            let page = self
+                .client
+                .get_dead_letter_queue(Some(DlqRequest {
+                    cursor,
+                    ..Default::default()
+                }))
+                .await?;
+
+            let mut new = Vec::new();
+            for message in page.messages {
+                if handled.insert(message.dlq_id.clone()) {
+                    new.push(message);
+                }
+            }
+            self.handle(new, handler.clone()).await;
+
+            cursor = match page.cursor.filter(|c| !c.is_empty()) {
+                Some(c) => Some(c),
+                None => break,
+            };
+        }
+
+        handled.forget_missing();
+        Ok(())
+    }
+
+    /// handle calls the handler for the messages, deleting the ones it is done with.
+    async fn handle<F, Fut>(&self, messages: Vec<DlqMessage>, handler: Arc<F>)
+    where
+        F: Fn(DlqMessage) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = DlqAction> + Send,
+    {
+        stream::iter(messages)
+            .for_each_concurrent(self.max_concurrency, |message| {
+                let handler = handler.clone();
+                async move {
+                    let dlq_id = message.dlq_id.clone();
+                    if handler(message).await == DlqAction::Keep {
+                        return;
+                    }
+                    if let Err(e) = self.client.delete_dead_letter_message(&dlq_id).await {
+                        log::error!("Could not delete dead letter {}: {}", dlq_id, e);
+                    }
+                }
+            })
+            .await;
+    }
+}
+
+/// The dlq ids already handed to the handler, oldest first.
+struct Handled {
+    ids: HashSet<String>,
+    order: VecDeque<String>,
+    /// The handled ids seen during the current poll, never more than the handled ones.
+    present: HashSet<String>,
+    limit: usize,
+}
+
+impl Handled {
+    fn new(limit: usize) -> Self {
+        Self {
+            ids: HashSet::new(),
+            order: VecDeque::new(),
+            present: HashSet::new(),
+            limit,
+        }
+    }
+
+    /// start_poll forgets the ids seen by the previous poll, which may have failed midway.
+    fn start_poll(&mut self) {
+        self.present.clear();
+    }
+
+    /// insert marks the id as seen by the current poll and returns true if it was not
+    /// handled yet.
+    fn insert(&mut self, id: String) -> bool {
+        if self.ids.contains(&id) {
+            self.present.insert(id);
+            return false;
+        }
+        self.ids.insert(id.clone());
+        self.present.insert(id.clone());
+        self.order.push_back(id);
+        while self.order.len() > self.limit {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+                self.present.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// forget_missing forgets the ids that were not seen by the current poll,
+    /// they are no longer in the dead letter queue.
+    fn forget_missing(&mut self) {
+        let present = std::mem::take(&mut self.present);
+        self.ids.retain(|id| present.contains(id));
+        self.order.retain(|id| present.contains(id));
+    }
+}
+
+/// A running dead letter queue watcher, it stops when the handle is dropped.
+#[must_use = "the watcher stops when the handle is dropped"]
+pub struct DlqWatcherHandle {
+    shutdown: watch::Sender<bool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DlqWatcherHandle {
+    /// shutdown stops the watcher, waiting for the dead letters being handled.
+    pub async fn shutdown(mut self) {
+        let _ = self.shutdown.send(true);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+}
+
+impl Drop for DlqWatcherHandle {
+    fn drop(&mut self) {
+        if let Some(handle) = &self.handle {
+            handle.abort();
+        }
+    }
+}
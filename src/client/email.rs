@@ -0,0 +1,194 @@
+//! # email module
+//! This module contains the email functionality of the QStash client.
+//! QStash delivers emails through a transactional email provider, the message is
+//! published to the `api/email` destination instead of a url.
+//! Resend is the supported provider.
+
+use http::header::{self, HeaderMap, HeaderValue};
+use serde::Serialize;
+
+use super::{
+    batch::{BatchEntry, BatchResult},
+    error::QStashError,
+    Client, PublishOptions, PublishRequestUrl, QstashResponse,
+};
+use crate::log;
+
+/// The destination emails are published to, relative to the publish path.
+const EMAIL_DESTINATION: &str = "api/email";
+
+/// The header selecting the email provider.
+const PROVIDER_HEADER: &str = "Upstash-Api-Provider";
+
+/// The header forwarding the provider token to the email provider.
+const PROVIDER_AUTHORIZATION_HEADER: &str = "Upstash-Forward-Authorization";
+
+/// The email provider QStash sends the emails with.
+const PROVIDER: &str = "resend";
+
+/// An email sent through the email provider.
+/// Either `html` or `text` should be set.
+#[derive(Serialize, Debug, Clone)]
+pub struct EmailRequest {
+    /// The sender, for example `Acme <onboarding@example.com>`.
+    pub from: String,
+    /// The recipients.
+    pub to: Vec<String>,
+    pub subject: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cc: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bcc: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+}
+
+impl EmailRequest {
+    /// Creates a new [`EmailRequest`] without content.
+    pub fn new(from: &str, to: Vec<String>, subject: &str) -> Self {
+        Self {
+            from: from.to_string(),
+            to,
+            subject: subject.to_string(),
+            html: None,
+            text: None,
+            cc: None,
+            bcc: None,
+            reply_to: None,
+        }
+    }
+}
+
+impl Client {
+    /// publish_email publishes an email to the email destination.
+    /// The provider token is forwarded to the provider and never stored by QStash.
+    /// The email is sent like [`Client::publish_json`], with the publish defaults and the
+    /// retry policy of the client, an error answered by QStash is returned as an error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use qstash_rs::client::{email::EmailRequest, Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+    ///
+    ///     let mut email = EmailRequest::new(
+    ///         "Acme <onboarding@example.com>",
+    ///         vec![String::from("delivered@example.com")],
+    ///         "hello world",
+    ///     );
+    ///     email.html = Some(String::from("<p>it works!</p>"));
+    ///
+    ///     match qstash_client.publish_email(email, "<RESEND_TOKEN>", None).await {
+    ///         Ok(r) => println!("{:?}", r),
+    ///         Err(err) => println!("{:?}", err),
+    ///     };
+    /// }
+    /// ```
+    pub async fn publish_email(
+        &self,
+        request: EmailRequest,
+        provider_token: &str,
+        options: Option<PublishOptions>,
+    ) -> Result<QstashResponse, QStashError> {
+        let path = match self.message_url(None, EMAIL_DESTINATION) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::PublishError);
+            }
+        };
+
+        let body = match serde_json::to_vec(&request) {
+            Ok(b) => b,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::PublishError);
+            }
+        };
+        let headers = email_headers(HeaderMap::new(), provider_token)?;
+
+        let responses = self
+            .send_json_body(path, body, options, headers, false, "publish_email")
+            .await?;
+        let response = match responses.into_iter().next() {
+            Some(r) => r,
+            None => {
+                log::error!("Missing response of the email");
+                return Err(QStashError::PublishError);
+            }
+        };
+        if let Some(error) = &response.error {
+            log::error!("Could not publish email: {}", error);
+            return Err(QStashError::PublishError);
+        }
+        Ok(response)
+    }
+
+    /// publish_emails publishes many emails with a single request to the batch endpoint.
+    /// The responses are returned in the same order as the emails.
+    pub async fn publish_emails(
+        &self,
+        requests: Vec<EmailRequest>,
+        provider_token: &str,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        let mut entries = Vec::with_capacity(requests.len());
+        for request in requests {
+            entries.push(email_entry(&request, provider_token)?);
+        }
+
+        let results = self.batch(entries).await?;
+        Ok(results
+            .into_iter()
+            .flat_map(BatchResult::into_responses)
+            .collect())
+    }
+}
+
+/// email_entry converts an email into an entry of the batch endpoint.
+/// The email destination is addressed like a topic, by its path.
+fn email_entry(request: &EmailRequest, provider_token: &str) -> Result<BatchEntry, QStashError> {
+    let body = match serde_json::to_string(request) {
+        Ok(b) => b,
+        Err(e) => {
+            let formated_string = e.to_string();
+            log::error!(formated_string);
+            return Err(QStashError::BatchError);
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+
+    let mut entry = BatchEntry::new(PublishRequestUrl::Topic(EMAIL_DESTINATION.to_string()));
+    entry.body = Some(body);
+    entry.headers = Some(email_headers(headers, provider_token)?);
+    Ok(entry)
+}
+
+/// email_headers adds the provider headers to the headers of the request.
+fn email_headers(mut headers: HeaderMap, provider_token: &str) -> Result<HeaderMap, QStashError> {
+    let mut authorization = match HeaderValue::from_str(&format!("Bearer {provider_token}")) {
+        Ok(v) => v,
+        Err(e) => {
+            let formated_string = e.to_string();
+            log::error!(formated_string);
+            return Err(QStashError::TokenError);
+        }
+    };
+    authorization.set_sensitive(true);
+
+    headers.insert(PROVIDER_HEADER, HeaderValue::from_static(PROVIDER));
+    headers.insert(PROVIDER_AUTHORIZATION_HEADER, authorization);
+    Ok(headers)
+}
@@ -3,7 +3,7 @@
 //! This module contains the error type for the crate.
 //! It is used to return errors from the crate.
 
-use std::fmt;
+use std::{fmt, time::Duration};
 
 /// The error type for the crate.
 /// It is used to return errors from the crate.
@@ -12,18 +12,83 @@ use std::fmt;
 /// - ReqwestError: Reqwest failed to initialize
 /// - InvalidUrl: Invalid Url
 /// - PublishError: Error publishing message
-/// - EventError: Error getting events
-/// - DeadLetterQueueError: Error getting DLQ List
+/// - EventError: Error getting events, with the status if the API answered, the cause and the start of the body it could not parse
+/// - DeadLetterQueueError: Error with the dead letter queue, with the status and message of the API if it answered
+/// - DlqMessageNotFound: The message is not in the dead letter queue
+/// - BatchError: Error publishing batch
+/// - SignatureError: Invalid signature
+/// - MetadataError: Invalid delivery metadata
+/// - CallbackError: Invalid callback payload
+/// - BodyTooLarge: The body is larger than the configured limit
+/// - QueueError: Error managing queue, with the status and message of the API if it answered
+/// - QueueNotFound: The queue does not exist
+/// - QuotaExceeded: The queue or the account is out of quota, retry after the duration if set
+/// - ScheduleError: Error managing schedule, with the status and message of the API if it answered
+/// - ScheduleNotFound: The schedule does not exist
+/// - InvalidCron: The cron expression of a schedule is invalid
+/// - TopicError: Error managing topic, with the status and message of the API if it answered
+/// - TopicNotFound: The topic does not exist
+/// - InvalidTopicName: The topic name is empty, too long or has invalid characters
+/// - ConfigError: Invalid field of the client configuration
+/// - CursorStoreError: Error loading or saving cursor
+/// - Timeout: The awaited condition was not met before the timeout
 #[derive(Debug, Clone)]
 pub enum QStashError {
     TokenError,
     ReqwestError,
     InvalidUrl,
     PublishError,
-    EventError,
-    DeadLetterQueueError,
+    EventError {
+        status: Option<u16>,
+        cause: String,
+        body: Option<String>,
+    },
+    DeadLetterQueueError {
+        status: Option<u16>,
+        message: String,
+    },
+    DlqMessageNotFound(String),
     GetMessageError,
     DeleteMessageError,
+    BatchError,
+    SignatureError,
+    MetadataError,
+    CallbackError,
+    BodyTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    QueueError {
+        status: Option<u16>,
+        message: String,
+    },
+    QueueNotFound(String),
+    QuotaExceeded {
+        status: u16,
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    ScheduleError {
+        status: Option<u16>,
+        message: String,
+    },
+    ScheduleNotFound(String),
+    InvalidCron {
+        expression: String,
+        reason: String,
+    },
+    TopicError {
+        status: Option<u16>,
+        message: String,
+    },
+    TopicNotFound(String),
+    InvalidTopicName(String),
+    ConfigError {
+        field: String,
+        reason: String,
+    },
+    CursorStoreError,
+    Timeout,
 }
 
 impl fmt::Display for QStashError {
@@ -33,10 +98,76 @@ impl fmt::Display for QStashError {
             QStashError::ReqwestError => write!(f, "Reqwest failed to initialize"),
             QStashError::InvalidUrl => write!(f, "Invalid Url"),
             QStashError::PublishError => write!(f, "Error publishing message"),
-            QStashError::EventError => write!(f, "Error getting events"),
-            QStashError::DeadLetterQueueError => write!(f, "Error getting DLQ List"),
+            QStashError::EventError {
+                status,
+                cause,
+                body,
+            } => {
+                match status {
+                    Some(status) => write!(f, "Error getting events ({}): {}", status, cause)?,
+                    None => write!(f, "Error getting events: {}", cause)?,
+                }
+                match body {
+                    Some(body) => write!(f, ", body: {:?}", body),
+                    None => Ok(()),
+                }
+            }
+            QStashError::DeadLetterQueueError { status, message } => match status {
+                Some(status) => write!(f, "Error with dead letter queue ({}): {}", status, message),
+                None => write!(f, "Error with dead letter queue: {}", message),
+            },
+            QStashError::DlqMessageNotFound(dlq_id) => {
+                write!(f, "DLQ message {} not found", dlq_id)
+            }
             QStashError::GetMessageError => write!(f, "Error getting message"),
             QStashError::DeleteMessageError => write!(f, "Error deleting message"),
+            QStashError::BatchError => write!(f, "Error publishing batch"),
+            QStashError::SignatureError => write!(f, "Invalid signature"),
+            QStashError::MetadataError => write!(f, "Invalid delivery metadata"),
+            QStashError::CallbackError => write!(f, "Invalid callback payload"),
+            QStashError::BodyTooLarge { size, limit } => write!(
+                f,
+                "Body of {} bytes exceeds the limit of {} bytes",
+                size, limit
+            ),
+            QStashError::QueueError { status, message } => match status {
+                Some(status) => write!(f, "Error managing queue ({}): {}", status, message),
+                None => write!(f, "Error managing queue: {}", message),
+            },
+            QStashError::QueueNotFound(name) => write!(f, "Queue {} not found", name),
+            QStashError::QuotaExceeded {
+                status,
+                message,
+                retry_after,
+            } => match retry_after {
+                Some(retry_after) => write!(
+                    f,
+                    "Quota exceeded ({}): {}, retry after {:?}",
+                    status, message, retry_after
+                ),
+                None => write!(f, "Quota exceeded ({}): {}", status, message),
+            },
+            QStashError::ScheduleError { status, message } => match status {
+                Some(status) => write!(f, "Error managing schedule ({}): {}", status, message),
+                None => write!(f, "Error managing schedule: {}", message),
+            },
+            QStashError::ScheduleNotFound(id) => write!(f, "Schedule {} not found", id),
+            QStashError::InvalidCron { expression, reason } => {
+                write!(f, "Invalid cron expression {:?}: {}", expression, reason)
+            }
+            QStashError::TopicError { status, message } => match status {
+                Some(status) => write!(f, "Error managing topic ({}): {}", status, message),
+                None => write!(f, "Error managing topic: {}", message),
+            },
+            QStashError::TopicNotFound(name) => write!(f, "Topic {} not found", name),
+            QStashError::InvalidTopicName(name) => write!(f, "Invalid topic name {:?}", name),
+            QStashError::ConfigError { field, reason } => {
+                write!(f, "Invalid config field {}: {}", field, reason)
+            }
+            QStashError::CursorStoreError => write!(f, "Error loading or saving cursor"),
+            QStashError::Timeout => write!(f, "Timed out"),
         }
     }
 }
+
+impl std::error::Error for QStashError {}
@@ -2,13 +2,26 @@
 //! This module contains the methods implementation required to interact with the events endpoint.
 //! The events endpoint is used to retrieve your logs.
 
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use futures::{Stream, StreamExt, TryStreamExt};
+
+use http::Method;
 use serde::{Deserialize, Deserializer, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use super::{error::QStashError, Client};
+use super::{
+    cursor::{paginate, Checkpoint, CursorStore},
+    error::QStashError,
+    lifecycle::state_rank,
+    logging::log_response,
+    page::{parse_page_with, Page},
+    Client, ResponseParts,
+};
+use crate::log;
 
 /// The state of the message.
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum State {
     CREATED,
     ACTIVE,
@@ -20,9 +33,26 @@ pub enum State {
     FAILED,
 }
 
+impl State {
+    /// as_str returns the name of the state, as QStash spells it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            State::CREATED => "CREATED",
+            State::ACTIVE => "ACTIVE",
+            State::DELIVERED => "DELIVERED",
+            State::ERROR => "ERROR",
+            State::CANCELED => "CANCELED",
+            State::RETRY => "RETRY",
+            State::FAILED => "FAILED",
+        }
+    }
+}
+
 /// The event struct.
 /// It contains the time, state, message_id, next_delivery_time, error, url, topic_name and endpoint_name.
-#[derive(Debug, Serialize, Deserialize)]
+/// The fields QStash sends that are not modeled yet are kept in extra if requested,
+/// see [`EventRequest::include_raw`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Event {
     pub time: u64,
@@ -32,8 +62,39 @@ pub struct Event {
     pub next_delivery_time: Option<u64>,
     pub error: Option<String>,
     pub url: Option<String>,
+    /// The url group of the message of the event, also read from the `urlGroup` field.
+    #[serde(alias = "urlGroup")]
     pub topic_name: Option<String>,
     pub endpoint_name: Option<String>,
+    /// The fields not covered by this struct, only filled if [`EventRequest::include_raw`]
+    /// is set.
+    #[serde(flatten, skip_deserializing)]
+    pub extra: Map<String, Value>,
+}
+
+/// An event with the fields not covered by [`Event`].
+#[derive(Deserialize)]
+struct RawEvent {
+    #[serde(flatten)]
+    event: Event,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+impl From<RawEvent> for Event {
+    fn from(raw: RawEvent) -> Self {
+        Event {
+            extra: raw.extra,
+            ..raw.event
+        }
+    }
+}
+
+impl Event {
+    /// group_name returns the url group of the event, whichever spelling QStash used.
+    pub fn group_name(&self) -> Option<&str> {
+        self.topic_name.as_deref()
+    }
 }
 
 /// Deserialize with default value.
@@ -46,68 +107,427 @@ where
     Ok(T::deserialize(v).unwrap_or_default())
 }
 
+/// The order of the events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventOrder {
+    /// The oldest events first.
+    Asc,
+    /// The latest events first.
+    Desc,
+}
+
+impl EventOrder {
+    /// as_str returns the value of the `order` query parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventOrder::Asc => "earliestFirst",
+            EventOrder::Desc => "latestFirst",
+        }
+    }
+}
+
+/// The maximum amount of events of a page.
+pub const MAX_EVENT_COUNT: u32 = 100;
+
 /// The event request.
-/// It contains the cursor.
-#[derive(Debug)]
+/// It contains the optional cursor, the [`GetEventsResponse::cursor`] of the previous page,
+/// and the optional filters of the events, every set filter must match.
+#[derive(Debug, Clone, Default)]
 pub struct EventRequest {
-    pub cursor: Option<u32>,
+    pub cursor: Option<String>,
+    pub message_id: Option<String>,
+    pub state: Option<State>,
+    /// The destination url of the messages of the events.
+    pub url: Option<String>,
+    pub topic_name: Option<String>,
+    pub schedule_id: Option<String>,
+    pub queue_name: Option<String>,
+    /// The order of the events, the default is the order of QStash, the latest first.
+    /// The cursor of a page only continues a request with the same order.
+    pub order: Option<EventOrder>,
+    /// The earliest time of the events, in milliseconds since the epoch,
+    /// see [`EventRequest::with_from_date`].
+    pub from_date: Option<u64>,
+    /// The latest time of the events, in milliseconds since the epoch,
+    /// see [`EventRequest::with_to_date`].
+    pub to_date: Option<u64>,
+    /// The maximum amount of events of a page, between 1 and [`MAX_EVENT_COUNT`].
+    /// The default is the maximum.
+    pub count: Option<u32>,
+    /// If true, the fields of the events that are not modeled are kept in [`Event::extra`].
+    /// It is not sent to QStash.
+    pub include_raw: bool,
+}
+
+impl EventRequest {
+    /// validate checks the count of the request, before any request is sent.
+    fn validate(&self) -> Result<(), QStashError> {
+        if let Some(count) = self.count.filter(|c| !(1..=MAX_EVENT_COUNT).contains(c)) {
+            let formated_string = format!("Invalid event count {}", count);
+            log::error!(formated_string);
+            return Err(QStashError::EventError {
+                status: None,
+                cause: format!("the count must be between 1 and {}", MAX_EVENT_COUNT),
+                body: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// query_pairs returns the query parameters of the request, in the order they are sent.
+    /// The values are not encoded yet.
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let strings = [
+            ("cursor", self.cursor.clone()),
+            ("messageId", self.message_id.clone()),
+            ("state", self.state.map(|s| s.as_str().to_string())),
+            ("url", self.url.clone()),
+            ("topicName", self.topic_name.clone()),
+            ("scheduleId", self.schedule_id.clone()),
+            ("queueName", self.queue_name.clone()),
+            ("order", self.order.map(|o| o.as_str().to_string())),
+        ];
+        let numbers = [
+            ("fromDate", self.from_date),
+            ("toDate", self.to_date),
+            ("count", self.count.map(u64::from)),
+        ];
+
+        strings
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|v| (name, v)))
+            .chain(
+                numbers
+                    .into_iter()
+                    .filter_map(|(name, value)| value.map(|v| (name, v.to_string()))),
+            )
+            .collect()
+    }
+}
+
+impl From<u64> for EventRequest {
+    /// from returns the request of the page starting at the numeric cursor.
+    /// The cursor is sent as is, its meaning is up to QStash.
+    fn from(cursor: u64) -> Self {
+        EventRequest {
+            cursor: Some(cursor.to_string()),
+            ..Default::default()
+        }
+    }
 }
 
 /// The event response.
 /// It contains the cursor and the events.
+/// The events are deserialized one by one, an event that can not be deserialized is
+/// skipped so the others of the page are kept.
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "RawGetEventsResponse")]
 pub struct GetEventsResponse {
     pub cursor: Option<String>,
     pub events: Vec<Event>,
+    /// The position in the page and the error of every skipped event.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<(usize, String)>,
+}
+
+/// The event response as sent by QStash, with the events not deserialized yet.
+#[derive(Deserialize)]
+struct RawGetEventsResponse {
+    cursor: Option<String>,
+    events: Vec<Value>,
+}
+
+impl From<RawGetEventsResponse> for GetEventsResponse {
+    fn from(raw: RawGetEventsResponse) -> Self {
+        GetEventsResponse::from_raw(raw, false)
+    }
+}
+
+impl GetEventsResponse {
+    /// from_raw deserializes the events of the page, keeping the fields that are not
+    /// modeled if include_raw is set.
+    fn from_raw(raw: RawGetEventsResponse, include_raw: bool) -> Self {
+        let mut events = Vec::with_capacity(raw.events.len());
+        let mut skipped = Vec::new();
+        for (i, event) in raw.events.into_iter().enumerate() {
+            let event = match include_raw {
+                true => RawEvent::deserialize(event).map(Event::from),
+                false => Event::deserialize(event),
+            };
+            match event {
+                Ok(e) => events.push(e),
+                Err(e) => {
+                    let formated_string = format!("Skipped event {} of the page: {}", i, e);
+                    log::error!(formated_string);
+                    skipped.push((i, e.to_string()));
+                }
+            }
+        }
+        GetEventsResponse {
+            cursor: raw.cursor,
+            events,
+            skipped,
+        }
+    }
+
+    /// next_request returns the request of the page after this one, with the filters of the
+    /// request of this page, or `None` if this is the last page.
+    pub fn next_request(&self, request: &EventRequest) -> Option<EventRequest> {
+        self.cursor
+            .as_ref()
+            .filter(|c| !c.is_empty())
+            .map(|c| EventRequest {
+                cursor: Some(c.clone()),
+                ..request.clone()
+            })
+    }
+
+    /// group_by_message returns the timelines of the messages of the events of this page,
+    /// see [`group_by_message`].
+    pub fn group_by_message(&self) -> HashMap<String, MessageTimeline> {
+        group_by_message(self.events.iter().cloned())
+    }
+}
+
+/// The events of a single message, ordered by time.
+/// Unlike [`super::lifecycle::MessageLifecycle`], the transitions between the events are
+/// not validated, so the events of a partial page still make a timeline.
+#[derive(Debug, Clone)]
+pub struct MessageTimeline {
+    pub message_id: String,
+    pub events: Vec<Event>,
+}
+
+impl MessageTimeline {
+    /// final_state returns the state of the last event, or `None` if there are no events.
+    pub fn final_state(&self) -> Option<State> {
+        self.events.last().map(|e| e.state)
+    }
+}
+
+/// group_by_message groups the events by message id, the events of every message are
+/// ordered by time ascending.
+/// Events with the same time are ordered by their position within a delivery attempt,
+/// so the last event of a delivered message is always DELIVERED.
+pub fn group_by_message(
+    events: impl IntoIterator<Item = Event>,
+) -> HashMap<String, MessageTimeline> {
+    let mut timelines: HashMap<String, MessageTimeline> = HashMap::new();
+    for event in events {
+        timelines
+            .entry(event.message_id.clone())
+            .or_insert_with(|| MessageTimeline {
+                message_id: event.message_id.clone(),
+                events: Vec::new(),
+            })
+            .events
+            .push(event);
+    }
+    for timeline in timelines.values_mut() {
+        timeline
+            .events
+            .sort_by_key(|e| (e.time, state_rank(e.state)));
+    }
+    timelines
 }
 
 impl Client {
     /// Retrieve your logs.
     ///
-    /// The logs endpoint is paginated and returns only 100 logs at a time.
-    /// If you want to receive more logs, you can use the cursor to paginate.
+    /// The logs endpoint is paginated and returns at most [`MAX_EVENT_COUNT`] logs at a time,
+    /// fewer if [`EventRequest::count`] is set. A count out of range is an error,
+    /// no request is sent.
+    /// If you want to receive more logs, you can use the cursor to paginate,
+    /// see [`GetEventsResponse::next_request`].
+    ///
+    /// The cursor is opaque, pass it back verbatim to get the next page.
+    ///
+    /// The events are filtered by the request, see [`EventRequest`].
     ///
-    /// The cursor is a unix timestamp with millisecond precision
+    /// A 5xx, a 429 or a connection error is retried according to the retry policy of
+    /// the client, see [`Client::retry_policy`], there are no retries by default.
     pub async fn get_events(
         &self,
         request: Option<EventRequest>,
     ) -> Result<GetEventsResponse, QStashError> {
+        let request = request.unwrap_or_default();
+        request.validate()?;
+        self.fetch_events(&request.query_pairs(), request.include_raw)
+            .await
+    }
+
+    /// Stream every event matching the filters of the request, fetching the pages as they
+    /// are consumed.
+    /// The stream ends after the last page, the first page without a cursor, or after the
+    /// first error, which is returned as the last item.
+    /// Every page is retried like [`Client::get_events`].
+    /// The events come in the order of the request, see [`EventRequest::order`].
+    pub fn events_stream(
+        &self,
+        request: Option<EventRequest>,
+    ) -> impl Stream<Item = Result<Event, QStashError>> + Send + 'static {
+        self.paginate_events(request.unwrap_or_default(), None)
+    }
+
+    /// get_all_events returns at most `max_events` events matching the filters of the request,
+    /// following the cursors of the pages.
+    /// No page is fetched once the limit is reached, the first error is returned.
+    pub async fn get_all_events(
+        &self,
+        request: Option<EventRequest>,
+        max_events: usize,
+    ) -> Result<Vec<Event>, QStashError> {
+        self.events_stream(request)
+            .take(max_events)
+            .try_collect()
+            .await
+    }
+
+    /// Stream every event like [`Client::events_stream`], resuming from the cursor saved
+    /// under the name in the store.
+    /// The cursor of a page is saved once every event of the previous page was consumed,
+    /// so events are never skipped but a page interrupted midway is read again.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// use futures::StreamExt;
+    /// use qstash_rs::client::{Client, FileCursorStore};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+    ///     let store = Arc::new(FileCursorStore::new("/var/lib/ingest"));
+    ///
+    ///     let mut events = Box::pin(qstash_client.checkpointed_events_stream(store, "events"));
+    ///     while let Some(event) = events.next().await {
+    ///         println!("{:?}", event.expect("Could not get events"));
+    ///     }
+    /// }
+    /// ```
+    pub fn checkpointed_events_stream(
+        &self,
+        store: Arc<dyn CursorStore>,
+        name: &str,
+    ) -> impl Stream<Item = Result<Event, QStashError>> + Send + 'static {
+        let checkpoint = Checkpoint {
+            store,
+            name: name.to_string(),
+        };
+        self.paginate_events(EventRequest::default(), Some(checkpoint))
+    }
+
+    fn paginate_events(
+        &self,
+        request: EventRequest,
+        checkpoint: Option<Checkpoint>,
+    ) -> impl Stream<Item = Result<Event, QStashError>> + Send + 'static {
+        let client = self.clone();
+        let cursor = request.cursor.clone();
+        let include_raw = request.include_raw;
+        let valid = request.validate();
+        let filters = EventRequest {
+            cursor: None,
+            ..request
+        };
+        paginate(cursor, checkpoint, move |cursor| {
+            let client = client.clone();
+            let mut query = filters.query_pairs();
+            if let Some(cursor) = cursor {
+                query.insert(0, ("cursor", cursor));
+            }
+            let valid = valid.clone();
+            async move {
+                valid?;
+                let response = client.fetch_events_response(&query).await?;
+                let status = response.status().as_u16();
+                let error = move |cause, body: &[u8]| event_error(Some(status), cause, Some(body));
+                if !include_raw {
+                    return parse_page_with(response.body, "events", error);
+                }
+                let page = parse_page_with::<RawEvent, _>(response.body, "events", error)?;
+                Ok(Page {
+                    items: Box::new(page.items.map(|e| e.map(Event::from))),
+                    cursor: page.cursor,
+                })
+            }
+        })
+    }
+
+    /// fetch_events retrieves a page of events with the given query parameters,
+    /// keeping the fields that are not modeled if include_raw is set.
+    pub(crate) async fn fetch_events(
+        &self,
+        query: &[(&str, String)],
+        include_raw: bool,
+    ) -> Result<GetEventsResponse, QStashError> {
+        let response = self.fetch_events_response(query).await?;
+        match response.json() {
+            Ok(r) => Ok(GetEventsResponse::from_raw(r, include_raw)),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(event_error(
+                    Some(response.status().as_u16()),
+                    formated_string,
+                    Some(&response.body),
+                ))
+            }
+        }
+    }
+
+    /// fetch_events_response sends the request of a page and returns the response unparsed.
+    async fn fetch_events_response(
+        &self,
+        query: &[(&str, String)],
+    ) -> Result<ResponseParts, QStashError> {
         let mut path = match self.base_url.join(&format!("/{}/events", self.version)) {
             Ok(p) => p,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
-                return Err(QStashError::EventError);
+                log::error!(formated_string);
+                return Err(event_error(None, formated_string, None));
             }
         };
 
-        if let Some(request) = request {
-            if let Some(cursor) = request.cursor {
-                path.set_query(Some(&format!("cursor={}", cursor)));
-            }
-        };
+        if !query.is_empty() {
+            path.query_pairs_mut().extend_pairs(query);
+        }
 
-        let response = match self.http.get(path).send().await {
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
             Ok(r) => {
-                tracing::debug!("{:?}", r);
+                log_response("events", &r, started);
                 r
             }
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
-                return Err(QStashError::EventError);
-            }
-        };
-
-        let response = match response.json().await {
-            Ok(r) => r,
-            Err(e) => {
-                let formated_string = e.to_string();
-                tracing::error!(formated_string);
-                return Err(QStashError::EventError);
+                log::error!(formated_string);
+                return Err(event_error(None, formated_string, None));
             }
         };
 
         Ok(response)
     }
 }
+
+/// The number of bytes of the body kept in an [`QStashError::EventError`].
+const BODY_SNIPPET_LEN: usize = 512;
+
+/// event_error builds the error of the events endpoint, keeping the start of the body
+/// that could not be parsed.
+fn event_error(status: Option<u16>, cause: String, body: Option<&[u8]>) -> QStashError {
+    let body = body.map(|body| {
+        let mut snippet = String::from_utf8_lossy(&body[..body.len().min(BODY_SNIPPET_LEN)]);
+        if body.len() > BODY_SNIPPET_LEN {
+            snippet.to_mut().push_str("...");
+        }
+        snippet.into_owned()
+    });
+    QStashError::EventError {
+        status,
+        cause,
+        body,
+    }
+}
@@ -0,0 +1,271 @@
+//! # lifecycle module
+//! This module contains the lifecycle of a single message, validated from its events.
+//! The allowed transitions between states are defined once in [`allowed_transition`].
+//! It also contains [`Client::wait_for_message_state`], which polls the events of a message
+//! until it reaches one of the given states.
+
+use std::{fmt, time::Duration};
+
+use tokio::time::{self, Instant};
+
+use super::{
+    error::QStashError,
+    events::{Event, EventRequest, State},
+    Client,
+};
+use crate::log;
+
+/// The maximum amount of event pages fetched by [`Client::message_lifecycle`].
+const MAX_PAGES: usize = 100;
+
+/// The error returned when events do not form a valid lifecycle.
+#[derive(Debug, Clone)]
+pub enum LifecycleError {
+    /// There are no events for the message.
+    NoEvents,
+    /// The events belong to more than one message.
+    MixedMessages { expected: String, found: String },
+    /// The first event is not a CREATED event.
+    InvalidStart { state: State },
+    /// The event at `time` moved the message from a state it cannot leave that way.
+    InvalidTransition { from: State, to: State, time: u64 },
+    /// The events could not be fetched.
+    Request(QStashError),
+}
+
+impl fmt::Display for LifecycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LifecycleError::NoEvents => write!(f, "No events for the message"),
+            LifecycleError::MixedMessages { expected, found } => write!(
+                f,
+                "Expected events of message {} but found message {}",
+                expected, found
+            ),
+            LifecycleError::InvalidStart { state } => {
+                write!(f, "Lifecycle starts with {:?} instead of CREATED", state)
+            }
+            LifecycleError::InvalidTransition { from, to, time } => {
+                write!(
+                    f,
+                    "Invalid transition from {:?} to {:?} at {}",
+                    from, to, time
+                )
+            }
+            LifecycleError::Request(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for LifecycleError {}
+
+/// allowed_transition returns true if a message in the `from` state can move to the `to` state.
+/// DELIVERED, FAILED and CANCELED are terminal, nothing follows them.
+pub fn allowed_transition(from: State, to: State) -> bool {
+    use State::*;
+
+    match from {
+        CREATED | ACTIVE => matches!(to, ACTIVE | DELIVERED | ERROR | RETRY | FAILED | CANCELED),
+        ERROR => matches!(to, ACTIVE | RETRY | FAILED | CANCELED),
+        RETRY => matches!(to, ACTIVE | DELIVERED | ERROR | RETRY | FAILED | CANCELED),
+        DELIVERED | FAILED | CANCELED => false,
+    }
+}
+
+/// is_terminal_state returns true if nothing can follow the state.
+fn is_terminal_state(state: State) -> bool {
+    matches!(state, State::DELIVERED | State::FAILED | State::CANCELED)
+}
+
+/// The position of a state within a single attempt, used to order events with the same time.
+pub(crate) fn state_rank(state: State) -> u8 {
+    match state {
+        State::CREATED => 0,
+        State::ACTIVE => 1,
+        State::ERROR => 2,
+        State::RETRY => 3,
+        State::DELIVERED | State::FAILED | State::CANCELED => 4,
+    }
+}
+
+/// A transition between two states of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub from: State,
+    pub to: State,
+    /// Unix timestamp in milliseconds.
+    pub time: u64,
+}
+
+/// The validated lifecycle of a single message.
+#[derive(Debug, Clone)]
+pub struct MessageLifecycle {
+    message_id: String,
+    created_at: u64,
+    transitions: Vec<Transition>,
+}
+
+impl MessageLifecycle {
+    /// Build the lifecycle of a message from its events in any order.
+    /// The events are ordered by time and every transition is checked with [`allowed_transition`].
+    pub fn from_events(mut events: Vec<Event>) -> Result<Self, LifecycleError> {
+        events.sort_by_key(|e| (e.time, state_rank(e.state)));
+
+        let mut events = events.into_iter();
+        let first = match events.next() {
+            Some(e) => e,
+            None => return Err(LifecycleError::NoEvents),
+        };
+        if first.state != State::CREATED {
+            return Err(LifecycleError::InvalidStart { state: first.state });
+        }
+
+        let mut state = first.state;
+        let mut transitions = Vec::new();
+        for event in events {
+            if event.message_id != first.message_id {
+                return Err(LifecycleError::MixedMessages {
+                    expected: first.message_id,
+                    found: event.message_id,
+                });
+            }
+            if !allowed_transition(state, event.state) {
+                return Err(LifecycleError::InvalidTransition {
+                    from: state,
+                    to: event.state,
+                    time: event.time,
+                });
+            }
+
+            transitions.push(Transition {
+                from: state,
+                to: event.state,
+                time: event.time,
+            });
+            state = event.state;
+        }
+
+        Ok(Self {
+            message_id: first.message_id,
+            created_at: first.time,
+            transitions,
+        })
+    }
+
+    /// message_id returns the id of the message.
+    pub fn message_id(&self) -> &str {
+        &self.message_id
+    }
+
+    /// transitions returns the transitions of the message in order.
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+
+    /// state returns the current state of the message.
+    pub fn state(&self) -> State {
+        self.transitions.last().map_or(State::CREATED, |t| t.to)
+    }
+
+    /// attempts returns the amount of delivery attempts.
+    /// Every retry is an attempt, on top of the first one made once the message left CREATED.
+    pub fn attempts(&self) -> usize {
+        let retries = self
+            .transitions
+            .iter()
+            .filter(|t| t.to == State::RETRY)
+            .count();
+
+        match self.transitions.first() {
+            Some(t) if t.to != State::CANCELED => retries + 1,
+            _ => 0,
+        }
+    }
+
+    /// is_terminal returns true if the message has been delivered, has failed or was canceled.
+    pub fn is_terminal(&self) -> bool {
+        is_terminal_state(self.state())
+    }
+
+    /// duration_to_delivery returns the time from creation to delivery, if the message was delivered.
+    pub fn duration_to_delivery(&self) -> Option<Duration> {
+        self.transitions
+            .iter()
+            .find(|t| t.to == State::DELIVERED)
+            .map(|t| Duration::from_millis(t.time.saturating_sub(self.created_at)))
+    }
+}
+
+impl Client {
+    /// message_lifecycle fetches every event of the message and builds its lifecycle.
+    pub async fn message_lifecycle(
+        &self,
+        message_id: &str,
+    ) -> Result<MessageLifecycle, LifecycleError> {
+        let mut events = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for _ in 0..MAX_PAGES {
+            let mut query = vec![("messageId", message_id.to_string())];
+            if let Some(c) = cursor {
+                query.push(("cursor", c));
+            }
+
+            let page = match self.fetch_events(&query, false).await {
+                Ok(p) => p,
+                Err(e) => return Err(LifecycleError::Request(e)),
+            };
+            let empty = page.events.is_empty();
+            events.extend(page.events);
+
+            cursor = match page.cursor {
+                Some(c) if !c.is_empty() && !empty => Some(c),
+                _ => break,
+            };
+        }
+
+        MessageLifecycle::from_events(events)
+    }
+
+    /// wait_for_message_state polls the latest events of the message until one of them is
+    /// in one of the target states and returns it, or returns [`QStashError::Timeout`] once
+    /// the timeout elapsed.
+    /// A failed poll is returned right away.
+    ///
+    /// Dropping the future stops the polling.
+    pub async fn wait_for_message_state(
+        &self,
+        message_id: &str,
+        target: &[State],
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Event, QStashError> {
+        let deadline = Instant::now() + timeout;
+        let request = EventRequest {
+            message_id: Some(message_id.to_string()),
+            ..Default::default()
+        };
+        loop {
+            let page = self.get_events(Some(request.clone())).await?;
+            if let Some(event) = page
+                .events
+                .into_iter()
+                .find(|e| e.message_id == message_id && target.contains(&e.state))
+            {
+                return Ok(event);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                log::error!(
+                    "Message {} did not reach {:?} after {:?}",
+                    message_id,
+                    target,
+                    timeout
+                );
+                return Err(QStashError::Timeout);
+            }
+            time::sleep(poll_interval.min(deadline - now)).await;
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! # logging module
+//! This module contains the structured logging of the responses of the QStash API.
+//! Only the status, the size and the latency are logged, never the headers, so rate
+//! limit and authorization related values do not end up in the logs.
+//! Without the `tracing` feature nothing is logged.
+
+use std::time::Instant;
+
+use super::ResponseParts;
+
+/// log_response logs a response of the QStash API as a structured debug event.
+/// `endpoint` is the name of the endpoint, not the full url which can contain the destination.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn log_response(endpoint: &'static str, response: &ResponseParts, started: Instant) {
+    #[cfg(feature = "tracing")]
+    if tracing::enabled!(tracing::Level::DEBUG) {
+        tracing::debug!(
+            endpoint,
+            status = response.status().as_u16(),
+            content_length = response.body.len(),
+            latency_ms = started.elapsed().as_millis() as u64,
+            "QStash response"
+        );
+    }
+}
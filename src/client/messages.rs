@@ -1,13 +1,17 @@
 //! # messages module
 //! This module contains the methods implementation required to interact with the messages endpoint.
 
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, time::Instant};
 
-use serde::{Deserialize, Serialize};
+use http::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::client::error::QStashError;
+use crate::{client::error::QStashError, log};
 
-use super::Client;
+use super::{logging::log_response, Client};
 
 /// The message struct.
 /// It contains the message_id, url, topic_name, endpoint_name, key, method, header, body, max_retries, not_before, created_at and callback.
@@ -16,10 +20,18 @@ use super::Client;
 pub struct Message {
     pub message_id: String,
     pub url: String,
+    /// The url group of the message, also read from the `urlGroup` field.
+    #[serde(alias = "urlGroup")]
     pub topic_name: Option<String>,
     pub endpoint_name: Option<String>,
     pub key: Option<String>,
-    pub method: Option<String>,
+    /// The method the message is delivered with, see [`parse_method`] for unknown methods.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_method",
+        serialize_with = "serialize_optional_method"
+    )]
+    pub method: Option<Method>,
     pub header: Option<HashMap<String, Vec<String>>>,
     pub body: Option<String>,
     pub max_retries: Option<u32>,
@@ -28,6 +40,142 @@ pub struct Message {
     pub callback: Option<String>,
 }
 
+impl Message {
+    /// group_name returns the url group of the message, whichever spelling QStash used.
+    pub fn group_name(&self) -> Option<&str> {
+        self.topic_name.as_deref()
+    }
+
+    /// headers returns the headers of the message as a [`HeaderMap`].
+    /// The raw `header` map is kept as QStash returned it.
+    pub fn headers(&self) -> Result<HeaderMap, InvalidHeaders> {
+        header_map(self.header.as_ref())
+    }
+}
+
+/// The error returned when the headers of a message are not valid HTTP headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidHeaders {
+    pub name: String,
+    pub reason: String,
+}
+
+impl fmt::Display for InvalidHeaders {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid header {:?}: {}", self.name, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidHeaders {}
+
+/// header_map builds a [`HeaderMap`] from the headers returned by QStash.
+/// Names differing only by their casing are merged, keeping every value.
+pub(crate) fn header_map(
+    raw: Option<&HashMap<String, Vec<String>>>,
+) -> Result<HeaderMap, InvalidHeaders> {
+    let mut raw: Vec<(&String, &Vec<String>)> = raw.into_iter().flatten().collect();
+    raw.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut headers = HeaderMap::new();
+    for (name, values) in raw {
+        let header_name = match HeaderName::from_bytes(name.as_bytes()) {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(InvalidHeaders {
+                    name: name.clone(),
+                    reason: e.to_string(),
+                })
+            }
+        };
+        for value in values {
+            match HeaderValue::from_str(value) {
+                Ok(v) => headers.append(header_name.clone(), v),
+                Err(e) => {
+                    return Err(InvalidHeaders {
+                        name: name.clone(),
+                        reason: e.to_string(),
+                    })
+                }
+            };
+        }
+    }
+    Ok(headers)
+}
+
+/// The methods QStash delivers messages with.
+const METHODS: [Method; 7] = [
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::DELETE,
+    Method::PATCH,
+    Method::HEAD,
+    Method::OPTIONS,
+];
+
+/// parse_method parses a method returned by QStash, in any casing.
+/// Unknown methods fall back to `POST`, the method QStash delivers with by default.
+pub fn parse_method(method: &str) -> Method {
+    match Method::from_bytes(method.trim().to_ascii_uppercase().as_bytes()) {
+        Ok(m) if METHODS.contains(&m) => m,
+        _ => {
+            log::debug!("Unknown method {:?}, falling back to POST", method);
+            Method::POST
+        }
+    }
+}
+
+/// deserialize_method deserializes a method with [`parse_method`].
+pub(crate) fn deserialize_method<'d, D: Deserializer<'d>>(
+    deserializer: D,
+) -> Result<Method, D::Error> {
+    let method: String = Deserialize::deserialize(deserializer)?;
+    Ok(parse_method(&method))
+}
+
+/// deserialize_optional_method deserializes a method with [`parse_method`], if there is one.
+pub(crate) fn deserialize_optional_method<'d, D: Deserializer<'d>>(
+    deserializer: D,
+) -> Result<Option<Method>, D::Error> {
+    let method: Option<String> = Deserialize::deserialize(deserializer)?;
+    Ok(method.as_deref().map(parse_method))
+}
+
+/// serialize_method serializes a method as its name.
+pub(crate) fn serialize_method<S: Serializer>(
+    method: &Method,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(method.as_str())
+}
+
+/// The maximum amount of message ids sent in a single request by [`Client::cancel_messages`].
+pub const MAX_CANCEL_IDS: usize = 100;
+
+/// The amount of messages cancelled by [`Client::cancel_messages`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CancelledCount {
+    pub cancelled: u64,
+}
+
+/// The body of a bulk cancel.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelMessagesRequest<'a> {
+    message_ids: &'a [String],
+}
+
+/// serialize_optional_method serializes a method as its name, if there is one.
+pub(crate) fn serialize_optional_method<S: Serializer>(
+    method: &Option<Method>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match method {
+        Some(m) => serializer.serialize_some(m.as_str()),
+        None => serializer.serialize_none(),
+    }
+}
+
 impl Client {
     /// get_message Retrieve a message by its id
     pub async fn get_message(&self, message_id: &str) -> Result<Message, QStashError> {
@@ -38,28 +186,29 @@ impl Client {
             Ok(p) => p,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
                 return Err(QStashError::GetMessageError);
             }
         };
 
-        let response = match self.http.get(path).send().await {
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
             Ok(r) => {
-                tracing::debug!("{:?}", r);
+                log_response("get_message", &r, started);
                 r
             }
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
                 return Err(QStashError::GetMessageError);
             }
         };
 
-        let response = match response.json().await {
+        let response = match response.json() {
             Ok(r) => r,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
                 return Err(QStashError::GetMessageError);
             }
         };
@@ -78,14 +227,17 @@ impl Client {
             Ok(p) => p,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
                 return Err(QStashError::DeleteMessageError);
             }
         };
 
-        match self.http.delete(path).send().await {
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        match self.execute(self.request(Method::DELETE, path)).await {
             Ok(r) => {
-                tracing::debug!("{:?}", r);
+                log_response("cancel_message", &r, started);
                 if r.status().is_success() {
                     Ok(())
                 } else {
@@ -94,7 +246,78 @@ impl Client {
             }
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
+                Err(QStashError::DeleteMessageError)
+            }
+        }
+    }
+
+    /// cancel_messages cancels the messages with the given ids and returns how many were
+    /// cancelled, messages already delivered or cancelled are not counted.
+    /// The ids are sent in chunks of [`MAX_CANCEL_IDS`], one chunk at a time.
+    /// If a chunk fails, the messages of the chunks before it stay cancelled.
+    ///
+    /// No request is sent for an empty list, QStash would cancel every message.
+    pub async fn cancel_messages(&self, ids: &[String]) -> Result<CancelledCount, QStashError> {
+        let mut count = CancelledCount::default();
+        for chunk in ids.chunks(MAX_CANCEL_IDS) {
+            count.cancelled += self.cancel_messages_chunk(chunk).await?.cancelled;
+        }
+        Ok(count)
+    }
+
+    /// cancel_messages_chunk cancels the messages of a single request.
+    async fn cancel_messages_chunk(&self, ids: &[String]) -> Result<CancelledCount, QStashError> {
+        let path = match self.api_url(&["messages"]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::DeleteMessageError);
+            }
+        };
+
+        let request = match self
+            .request(Method::DELETE, path)
+            .json(&CancelMessagesRequest { message_ids: ids })
+        {
+            Ok(r) => r,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::DeleteMessageError);
+            }
+        };
+
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        let response = match self.execute(request).await {
+            Ok(r) => {
+                log_response("cancel_messages", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::DeleteMessageError);
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!(
+                "Cancelling {} messages failed with status {}",
+                ids.len(),
+                response.status()
+            );
+            return Err(QStashError::DeleteMessageError);
+        }
+
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
                 Err(QStashError::DeleteMessageError)
             }
         }
@@ -3,17 +3,67 @@
 //! It is initialized with a token and optionally a base url and a version.
 //! The default base url is `https://qstash.upstash.io`.
 
+mod backend;
+pub mod batch;
+pub mod batching;
+mod config;
+#[cfg(feature = "cron")]
+pub mod cron;
+mod cursor;
 pub mod dead_letter_queue;
+mod dedup;
+pub mod dlq_requeue;
+pub mod dlq_watch;
+pub mod email;
 mod error;
 pub mod events;
+pub mod lifecycle;
+mod logging;
 pub mod messages;
+mod page;
+pub mod provision;
 pub mod publish;
+pub mod queue_watch;
+pub mod queues;
+mod rate_limit;
 mod request;
+mod retry;
+pub mod schedules;
+#[cfg(feature = "tower")]
+mod service;
+mod signing;
+pub mod sink;
+pub mod stats;
+mod telemetry;
+mod timestamp;
+pub mod topics;
 
-use error::*;
+pub use backend::*;
+pub use config::*;
+pub use cursor::{CursorStore, FileCursorStore};
+pub use dedup::DedupCache;
+pub use error::*;
+pub use rate_limit::RateLimit;
 pub use request::*;
+pub use retry::RetryPolicy;
+#[cfg(feature = "tower")]
+pub use service::*;
+pub use signing::PayloadSigning;
+pub use timestamp::Timestamp;
 
-use reqwest::{header, Url};
+use std::sync::Arc;
+
+use http::header;
+use url::Url;
+
+use dedup::Deduplicator;
+use rate_limit::RateLimiter;
+use telemetry::Telemetry;
+
+use crate::log;
+
+/// The default maximum body size, 1MB is the limit of the free and pay as you go plans.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 1_048_576;
 
 /// The version of the QStash API to use.
 /// The default is V2.
@@ -26,44 +76,68 @@ pub enum Version {
 /// It is initialized with a token and optionally a base url and a version.
 /// The default base url is `https://qstash.upstash.io`.
 /// The default version is V2.
+///
+/// Cloning the client is cheap, clones share the http backend and the rate limiter.
+#[derive(Clone)]
 pub struct Client {
-    pub http: reqwest::Client,
+    backend: Arc<dyn HttpBackend>,
+    authorization: header::HeaderValue,
     base_url: Url,
     version: String,
+    rate_limiter: Option<RateLimiter>,
+    retry_policy: Option<RetryPolicy>,
+    publish_defaults: Option<PublishOptions>,
+    deduplicator: Option<Deduplicator>,
+    telemetry: Option<Telemetry>,
+    payload_signing: Option<PayloadSigning>,
+    url_groups: bool,
+    max_body_size: usize,
+    #[cfg(feature = "opentelemetry")]
+    propagate_trace_context: bool,
 }
 
 impl Client {
     /// Initialize a new QStash client.
     /// The token is required.
     /// The base url and version are optional.
+    #[cfg(feature = "reqwest")]
     pub fn new(
         token: &str,
         base_url: Option<&str>,
         version: Option<Version>,
     ) -> Result<Client, QStashError> {
-        // intialize default headers
-        let mut value = match header::HeaderValue::from_str(&format!("Bearer {token}")) {
-            Ok(v) => v,
+        // initialize reqwest client
+        let http = match reqwest::Client::builder().build() {
+            Ok(c) => c,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
-                return Err(QStashError::TokenError);
+                log::error!(formated_string);
+                return Err(QStashError::ReqwestError);
             }
         };
 
-        value.set_sensitive(true);
-        let mut headers = header::HeaderMap::new();
-        headers.insert(header::AUTHORIZATION, value);
+        Self::with_backend(token, base_url, version, ReqwestBackend::new(http))
+    }
 
-        // initialize reqwest client
-        let http = match reqwest::Client::builder().default_headers(headers).build() {
-            Ok(c) => c,
+    /// Initialize a new QStash client sending the requests through the backend.
+    /// The token is required.
+    /// The base url and version are optional.
+    pub fn with_backend(
+        token: &str,
+        base_url: Option<&str>,
+        version: Option<Version>,
+        backend: impl HttpBackend + 'static,
+    ) -> Result<Client, QStashError> {
+        // intialize the authorization header
+        let mut authorization = match header::HeaderValue::from_str(&format!("Bearer {token}")) {
+            Ok(v) => v,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
-                return Err(QStashError::ReqwestError);
+                log::error!(formated_string);
+                return Err(QStashError::TokenError);
             }
         };
+        authorization.set_sensitive(true);
 
         let version = match version.unwrap_or(Version::V2) {
             Version::V1 => String::from("v1"),
@@ -75,15 +149,142 @@ impl Client {
             Ok(u) => u,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
                 return Err(QStashError::InvalidUrl);
             }
         };
 
         Ok(Self {
-            http,
+            backend: Arc::new(backend),
+            authorization,
             base_url: url,
             version,
+            rate_limiter: None,
+            retry_policy: None,
+            publish_defaults: None,
+            deduplicator: None,
+            telemetry: Some(Telemetry::default()),
+            payload_signing: None,
+            url_groups: false,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            #[cfg(feature = "opentelemetry")]
+            propagate_trace_context: false,
         })
     }
+
+    /// Send the requests through the backend, such as a reqwest client with custom
+    /// timeouts or a test double.
+    /// The default is a [`ReqwestBackend`] with the default reqwest configuration.
+    pub fn http_backend(mut self, backend: impl HttpBackend + 'static) -> Self {
+        self.backend = Arc::new(backend);
+        self
+    }
+
+    /// Propagate the active trace context to the destination of published messages.
+    /// The `traceparent` and `tracestate` headers are forwarded through QStash so the
+    /// trace continues on the receiving side.
+    /// The default is false.
+    #[cfg(feature = "opentelemetry")]
+    pub fn propagate_trace_context(mut self, enabled: bool) -> Self {
+        self.propagate_trace_context = enabled;
+        self
+    }
+
+    /// Limit the rate of the requests sent by this client and its clones.
+    /// Requests are delayed until a token is available, every attempt of a request
+    /// consumes a token, including retries.
+    /// The default is no limit.
+    /// It returns a [`QStashError::ConfigError`] if the rate is not a positive number.
+    pub fn rate_limit(mut self, config: RateLimit) -> Result<Self, QStashError> {
+        config.validate()?;
+        self.rate_limiter = Some(RateLimiter::new(config));
+        Ok(self)
+    }
+
+    /// Retry the read only requests, such as listing events, and the publishes according
+    /// to the policy.
+    /// Publishes without a deduplication id get a generated one, reused on every attempt
+    /// and returned in [`QstashResponse::deduplication_id`].
+    /// The default is no retries.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Set the options applied to every published message, batch entry and schedule.
+    /// The options of a request take precedence over the defaults.
+    pub fn publish_defaults(mut self, options: PublishOptions) -> Self {
+        self.publish_defaults = Some(options);
+        self
+    }
+
+    /// Skip publishing a message already published recently by this client or its clones.
+    /// The duplicate publish returns the response of the original message, with
+    /// `deduplicated` set to true, without sending a request.
+    /// The default is no client side deduplication.
+    pub fn dedup_cache(mut self, config: DedupCache) -> Self {
+        self.deduplicator = Some(Deduplicator::new(config));
+        self
+    }
+
+    /// Set the maximum size in bytes of the body of a message.
+    /// Larger messages are rejected with [`QStashError::BodyTooLarge`] before being sent.
+    /// For batches, the limit applies to every entry and to the whole batch.
+    /// The default is [`DEFAULT_MAX_BODY_SIZE`], raise it to match the limit of your plan.
+    pub fn max_body_size(mut self, limit: usize) -> Self {
+        self.max_body_size = limit;
+        self
+    }
+
+    /// check_body_size returns an error if the serialized body is larger than the limit.
+    pub(crate) fn check_body_size(&self, size: usize) -> Result<(), QStashError> {
+        if size > self.max_body_size {
+            log::error!(
+                "Body of {} bytes exceeds the limit of {} bytes",
+                size,
+                self.max_body_size
+            );
+            return Err(QStashError::BodyTooLarge {
+                size,
+                limit: self.max_body_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// api_url returns the url of the API made of the segments after the version,
+    /// percent-encoding every segment so names with spaces or slashes stay one segment.
+    pub(crate) fn api_url(&self, segments: &[&str]) -> Result<Url, url::ParseError> {
+        let mut url = self.base_url.join(&format!("/{}", self.version))?;
+        url.path_segments_mut()
+            .map_err(|_| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .extend(segments);
+        Ok(url)
+    }
+
+    /// throttle waits until the rate limiter allows the next request to be sent.
+    /// It must be called before every attempt of a request.
+    pub(crate) async fn throttle(&self, read_only: bool) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire(read_only).await;
+        }
+    }
+
+    /// throttle_delay returns how long [`Client::throttle`] would wait right now,
+    /// without consuming a token.
+    #[cfg(feature = "tower")]
+    pub(crate) fn throttle_delay(&self, read_only: bool) -> std::time::Duration {
+        self.rate_limiter
+            .as_ref()
+            .map_or(std::time::Duration::ZERO, |r| r.ready_in(read_only))
+    }
+
+    /// execute sends the request through the backend, without retries.
+    /// The rate limiter must be waited for first, see [`Client::throttle`].
+    pub(crate) async fn execute(
+        &self,
+        request: RequestParts,
+    ) -> Result<ResponseParts, TransportError> {
+        self.backend.execute(request).await
+    }
 }
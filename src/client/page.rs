@@ -0,0 +1,169 @@
+//! # page module
+//! This module contains the lazy parsing of the pages of the paginated endpoints.
+//! A page is only scanned for the boundaries of its items, every item is deserialized
+//! when it is consumed, so a page of large events is never held twice in memory.
+//! An item that can not be deserialized is an error of its own, the other items of the
+//! page are still returned.
+
+use std::{fmt, ops::Range};
+
+use bytes::Bytes;
+use serde::{
+    de::{DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    Deserializer,
+};
+use serde_json::value::RawValue;
+
+use super::error::QStashError;
+use crate::log;
+
+/// A page of a paginated endpoint, with the cursor of the next one.
+pub(crate) struct Page<T> {
+    pub(crate) items: Box<dyn Iterator<Item = Result<T, QStashError>> + Send>,
+    pub(crate) cursor: Option<String>,
+}
+
+/// The boundaries found in the body of a page.
+#[derive(Default)]
+struct Scan {
+    items: Vec<Range<usize>>,
+    items_found: bool,
+    /// Set while the items are scanned, so an error in them keeps the items before it.
+    in_items: bool,
+    cursor: Option<String>,
+}
+
+struct PageSeed<'s> {
+    field: &'static str,
+    base: usize,
+    scan: &'s mut Scan,
+}
+
+impl<'de> DeserializeSeed<'de> for PageSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for PageSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a page of {}", self.field)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<&str>()? {
+            if key == self.field {
+                self.scan.items_found = true;
+                self.scan.in_items = true;
+                map.next_value_seed(ItemsSeed {
+                    base: self.base,
+                    items: &mut self.scan.items,
+                })?;
+                self.scan.in_items = false;
+            } else if key == "cursor" {
+                self.scan.cursor = map.next_value()?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct ItemsSeed<'s> {
+    base: usize,
+    items: &'s mut Vec<Range<usize>>,
+}
+
+impl<'de> DeserializeSeed<'de> for ItemsSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ItemsSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of items")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(raw) = seq.next_element::<&RawValue>()? {
+            let start = raw.get().as_ptr() as usize - self.base;
+            self.items.push(start..start + raw.get().len());
+        }
+        Ok(())
+    }
+}
+
+/// parse_page scans the body of a page whose items are in the field, returning
+/// the items deserialized lazily.
+/// If the body is cut or malformed in the middle of the items, the items before are
+/// returned followed by an error, and the page has no cursor.
+/// error is the error of the endpoint, returned for the page or for an item.
+pub(crate) fn parse_page<T: DeserializeOwned + Send + 'static>(
+    body: Bytes,
+    field: &'static str,
+    error: QStashError,
+) -> Result<Page<T>, QStashError> {
+    parse_page_with(body, field, move |_, _| error.clone())
+}
+
+/// parse_page_with is [`parse_page`] building the error from the cause and the bytes
+/// that could not be parsed, the whole body for the page or the item for an item.
+pub(crate) fn parse_page_with<T, E>(
+    body: Bytes,
+    field: &'static str,
+    error: E,
+) -> Result<Page<T>, QStashError>
+where
+    T: DeserializeOwned + Send + 'static,
+    E: Fn(String, &[u8]) -> QStashError + Send + 'static,
+{
+    let mut scan = Scan::default();
+    let mut deserializer = serde_json::Deserializer::from_slice(&body);
+    let seed = PageSeed {
+        field,
+        base: body.as_ptr() as usize,
+        scan: &mut scan,
+    };
+    let result = seed
+        .deserialize(&mut deserializer)
+        .and_then(|_| deserializer.end());
+
+    let mut trailing = None;
+    if let Err(e) = result {
+        let formated_string = e.to_string();
+        log::error!(formated_string);
+        if !scan.in_items {
+            return Err(error(formated_string, &body));
+        }
+        trailing = Some(Err(error(formated_string, &body)));
+        scan.cursor = None;
+    } else if !scan.items_found {
+        log::error!("Missing field {} in page", field);
+        return Err(error(format!("missing field `{}`", field), &body));
+    }
+
+    let items = scan.items.into_iter().map(move |range| {
+        match serde_json::from_slice::<T>(&body[range.clone()]) {
+            Ok(item) => Ok(item),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(error(formated_string, &body[range]))
+            }
+        }
+    });
+    Ok(Page {
+        items: Box::new(items.chain(trailing)),
+        cursor: scan.cursor,
+    })
+}
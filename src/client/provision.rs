@@ -0,0 +1,366 @@
+//! # provision module
+//! This module contains the [`Provisioner`], which makes the queues, URL groups and
+//! schedules of a [`ResourceSpec`] exist on QStash.
+//! The spec is diffed against the live state and only the differences are applied,
+//! so applying the same spec twice changes nothing the second time.
+
+use http::Method;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::QStashError,
+    queues::{Queue, UpsertQueueRequest},
+    schedules::{CreateScheduleRequest, Schedule},
+    topics::{Topic, TopicEndpoint},
+    Client, PublishRequestUrl,
+};
+use crate::log;
+
+/// The resources a service owns.
+/// Resources missing from the spec are left untouched.
+///
+/// It can be deserialized from any serde format, for example JSON:
+/// ```json
+/// {
+///     "queues": [{ "name": "orders", "parallelism": 5 }],
+///     "urlGroups": [{ "name": "billing", "endpoints": [{ "url": "https://example.com/billing" }] }],
+///     "schedules": [{ "id": "nightly", "destination": "https://example.com/nightly", "cron": "0 3 * * *" }]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceSpec {
+    #[serde(default)]
+    pub queues: Vec<QueueSpec>,
+    #[serde(default)]
+    pub url_groups: Vec<UrlGroupSpec>,
+    #[serde(default)]
+    pub schedules: Vec<ScheduleSpec>,
+}
+
+/// A queue and its parallelism.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSpec {
+    pub name: String,
+    pub parallelism: u32,
+}
+
+/// A URL group and exactly the endpoints it should have.
+/// An endpoint without a name matches an endpoint with the same url and any name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlGroupSpec {
+    pub name: String,
+    pub endpoints: Vec<TopicEndpoint>,
+}
+
+/// A schedule, identified by its id.
+/// The optional fields are only compared with the live schedule when they are set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleSpec {
+    pub id: String,
+    /// The url or URL group the messages are sent to.
+    pub destination: String,
+    pub cron: String,
+    pub body: Option<String>,
+    pub method: Option<String>,
+    pub retries: Option<u32>,
+    pub callback: Option<String>,
+}
+
+impl ScheduleSpec {
+    /// matches returns true if the live schedule does not need to be updated.
+    fn matches(&self, schedule: &Schedule) -> bool {
+        fn field_matches<T: PartialEq>(expected: &Option<T>, actual: &Option<T>) -> bool {
+            expected.is_none() || expected == actual
+        }
+
+        let method_matches = match (&self.method, &schedule.method) {
+            (Some(expected), Some(actual)) => expected.eq_ignore_ascii_case(actual),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+
+        self.destination == schedule.destination
+            && self.cron == schedule.cron
+            && method_matches
+            && field_matches(&self.body, &schedule.body)
+            && field_matches(&self.retries, &schedule.retries)
+            && field_matches(&self.callback, &schedule.callback)
+    }
+
+    /// to_request converts the spec into the request creating it.
+    fn to_request(&self) -> Result<CreateScheduleRequest, QStashError> {
+        let destination = match url::Url::parse(&self.destination) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+                PublishRequestUrl::Url(url)
+            }
+            _ => PublishRequestUrl::Topic(self.destination.clone()),
+        };
+
+        let method = match &self.method {
+            Some(method) => match Method::from_bytes(method.to_uppercase().as_bytes()) {
+                Ok(m) => Some(m),
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    return Err(QStashError::ScheduleError {
+                        status: None,
+                        message: formated_string,
+                    });
+                }
+            },
+            None => None,
+        };
+
+        let mut request = CreateScheduleRequest::new(destination, &self.cron);
+        request.schedule_id = Some(self.id.clone());
+        request.body = self.body.clone();
+        request.method = method;
+        request.retries = self.retries;
+        request.callback = self.callback.clone();
+        Ok(request)
+    }
+}
+
+/// The live state of the resources, as returned by the list endpoints.
+#[derive(Debug, Clone, Default)]
+pub struct LiveState {
+    pub queues: Vec<Queue>,
+    pub topics: Vec<Topic>,
+    pub schedules: Vec<Schedule>,
+}
+
+/// A change applied by the [`Provisioner`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    CreateQueue {
+        name: String,
+        parallelism: u32,
+    },
+    UpdateQueue {
+        name: String,
+        parallelism: u32,
+        previous_parallelism: u32,
+    },
+    AddEndpoints {
+        url_group: String,
+        endpoints: Vec<TopicEndpoint>,
+    },
+    RemoveEndpoints {
+        url_group: String,
+        endpoints: Vec<TopicEndpoint>,
+    },
+    CreateSchedule(ScheduleSpec),
+    UpdateSchedule(ScheduleSpec),
+}
+
+/// The changes of an apply.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeReport {
+    pub changes: Vec<Change>,
+    /// False if the changes were only computed, in dry run mode.
+    pub applied: bool,
+}
+
+impl ChangeReport {
+    /// is_empty returns true if the live state already matched the spec.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl ResourceSpec {
+    /// diff returns the changes that make the live state match the spec.
+    /// Endpoints are added before others are removed, removing the last endpoint of a
+    /// URL group would delete it.
+    pub fn diff(&self, live: &LiveState) -> Vec<Change> {
+        let mut changes = Vec::new();
+
+        for spec in &self.queues {
+            match live.queues.iter().find(|q| q.name == spec.name) {
+                None => changes.push(Change::CreateQueue {
+                    name: spec.name.clone(),
+                    parallelism: spec.parallelism,
+                }),
+                Some(queue) if queue.parallelism != spec.parallelism => {
+                    changes.push(Change::UpdateQueue {
+                        name: spec.name.clone(),
+                        parallelism: spec.parallelism,
+                        previous_parallelism: queue.parallelism,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        for spec in &self.url_groups {
+            let existing = live
+                .topics
+                .iter()
+                .find(|t| t.name == spec.name)
+                .map_or(&[][..], |t| &t.endpoints[..]);
+
+            let endpoint_matches = |expected: &TopicEndpoint, actual: &TopicEndpoint| {
+                expected.url == actual.url
+                    && (expected.name.is_none() || expected.name == actual.name)
+            };
+
+            let missing: Vec<TopicEndpoint> = spec
+                .endpoints
+                .iter()
+                .filter(|e| !existing.iter().any(|x| endpoint_matches(e, x)))
+                .cloned()
+                .collect();
+            let extra: Vec<TopicEndpoint> = existing
+                .iter()
+                .filter(|x| !spec.endpoints.iter().any(|e| endpoint_matches(e, x)))
+                .cloned()
+                .collect();
+
+            if !missing.is_empty() {
+                changes.push(Change::AddEndpoints {
+                    url_group: spec.name.clone(),
+                    endpoints: missing,
+                });
+            }
+            if !extra.is_empty() {
+                changes.push(Change::RemoveEndpoints {
+                    url_group: spec.name.clone(),
+                    endpoints: extra,
+                });
+            }
+        }
+
+        for spec in &self.schedules {
+            match live.schedules.iter().find(|s| s.schedule_id == spec.id) {
+                None => changes.push(Change::CreateSchedule(spec.clone())),
+                Some(schedule) if !spec.matches(schedule) => {
+                    changes.push(Change::UpdateSchedule(spec.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+
+        changes
+    }
+}
+
+/// Applies a [`ResourceSpec`] to QStash.
+///
+/// # Example
+/// ```no_run
+/// use qstash_rs::client::{provision::{Provisioner, ResourceSpec}, Client};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+///     let spec: ResourceSpec = serde_json::from_str(
+///         r#"{ "queues": [{ "name": "orders", "parallelism": 5 }] }"#,
+///     )
+///     .expect("Could not parse spec");
+///
+///     match Provisioner::new(qstash_client).apply(&spec).await {
+///         Ok(report) => println!("{:?}", report.changes),
+///         Err(err) => println!("{}", err),
+///     };
+/// }
+/// ```
+pub struct Provisioner {
+    client: Client,
+    dry_run: bool,
+}
+
+impl Provisioner {
+    /// Creates a new [`Provisioner`].
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            dry_run: false,
+        }
+    }
+
+    /// dry_run only computes the changes, without applying them.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// live_state fetches the live state of the kinds of resources in the spec.
+    pub async fn live_state(&self, spec: &ResourceSpec) -> Result<LiveState, QStashError> {
+        let mut live = LiveState::default();
+        if !spec.queues.is_empty() {
+            live.queues = self.client.list_queues().await?;
+        }
+        if !spec.url_groups.is_empty() {
+            live.topics = self.client.list_topics().await?;
+        }
+        if !spec.schedules.is_empty() {
+            live.schedules = self.client.list_schedules().await?;
+        }
+        Ok(live)
+    }
+
+    /// apply makes the live state match the spec and returns the changes.
+    /// The changes are applied in order and the first failure is returned,
+    /// applying the spec again resumes from there.
+    pub async fn apply(&self, spec: &ResourceSpec) -> Result<ChangeReport, QStashError> {
+        let live = self.live_state(spec).await?;
+        let changes = spec.diff(&live);
+
+        if self.dry_run {
+            return Ok(ChangeReport {
+                changes,
+                applied: false,
+            });
+        }
+
+        for change in &changes {
+            self.apply_change(change).await?;
+        }
+
+        Ok(ChangeReport {
+            changes,
+            applied: true,
+        })
+    }
+
+    async fn apply_change(&self, change: &Change) -> Result<(), QStashError> {
+        match change {
+            Change::CreateQueue { name, parallelism }
+            | Change::UpdateQueue {
+                name, parallelism, ..
+            } => {
+                self.client
+                    .upsert_queue(UpsertQueueRequest {
+                        queue_name: name.clone(),
+                        parallelism: *parallelism,
+                    })
+                    .await
+            }
+            Change::AddEndpoints {
+                url_group,
+                endpoints,
+            } => {
+                self.client
+                    .upsert_topic_endpoints(url_group, endpoints.clone())
+                    .await
+            }
+            Change::RemoveEndpoints {
+                url_group,
+                endpoints,
+            } => {
+                self.client
+                    .remove_topic_endpoints(url_group, endpoints.clone())
+                    .await?;
+                Ok(())
+            }
+            Change::CreateSchedule(spec) | Change::UpdateSchedule(spec) => {
+                self.client.create_schedule(spec.to_request()?).await?;
+                Ok(())
+            }
+        }
+    }
+}
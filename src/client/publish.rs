@@ -1,106 +1,190 @@
 //! # publish module
 //! This module contains the publish functionality of the QStash client.
 
-use reqwest::{
+use std::time::Instant;
+
+use bytes::Bytes;
+use futures::{stream, StreamExt};
+use http::{
     header::{self, HeaderMap},
     Method,
 };
 use serde::Serialize;
 
 use super::{
-    error::QStashError, Client, PublishOptions, PublishRequest, PublishRequestUrl, QstashResponse,
+    error::QStashError, logging::log_response, topics::validate_topic_name, Client, PublishOptions,
+    PublishRequest, PublishRequestUrl, QstashResponse,
 };
+use crate::log;
 
 impl Client {
     /// Publish a message to the QStash API.
     /// The message can be sent to a url or to a topic.
     /// If the message is sent to a url, it will be sent to that url.
     /// If the message is sent to a topic, it will be sent to all urls subscribed to that topic.
-    pub async fn publish<T: Into<reqwest::Body>>(
+    pub async fn publish<T: Into<Bytes>>(
         &self,
         request: PublishRequest<T>,
     ) -> Result<Vec<QstashResponse>, QStashError> {
-        let request_url = match &request.url {
-            PublishRequestUrl::Url(v) => v.to_string(),
-            PublishRequestUrl::Topic(v) => v.clone(),
+        self.send_message(None, request, "publish").await
+    }
+
+    /// publish_many publishes the requests with at most `concurrency` publishes in flight,
+    /// at least one.
+    /// The results are returned in the same order as the requests, a failed publish does not
+    /// stop the others.
+    pub async fn publish_many<T: Into<Bytes>>(
+        &self,
+        requests: Vec<PublishRequest<T>>,
+        concurrency: usize,
+    ) -> Vec<Result<Vec<QstashResponse>, QStashError>> {
+        stream::iter(requests)
+            .map(|request| self.publish(request))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// enqueue publishes the message to the queue, its delivery respects the parallelism
+    /// of the queue.
+    /// The queue is created if it does not exist.
+    pub async fn enqueue<T: Into<Bytes>>(
+        &self,
+        queue: &str,
+        request: PublishRequest<T>,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        self.send_message(Some(queue), request, "enqueue").await
+    }
+
+    /// message_url returns the url publishing the message to the destination,
+    /// or enqueuing it to the queue.
+    pub(crate) fn message_url(
+        &self,
+        queue: Option<&str>,
+        destination: &str,
+    ) -> Result<url::Url, url::ParseError> {
+        let prefix = match queue {
+            Some(queue) => self.api_url(&["enqueue", queue])?,
+            None => self.api_url(&["publish"])?,
         };
+        prefix.join(&format!("{}/{}", prefix.path(), destination))
+    }
+
+    /// send_message publishes the message, or enqueues it if a queue is set.
+    async fn send_message<T: Into<Bytes>>(
+        &self,
+        queue: Option<&str>,
+        request: PublishRequest<T>,
+        endpoint_name: &'static str,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        if let Some(topic) = request.url.topic_name() {
+            validate_topic_name(topic)?;
+        }
+        let request_url = request.url.destination_url();
 
-        let path = match self
-            .base_url
-            .join(&format!("/{}/publish/{}", self.version, request_url))
-        {
+        let path = match self.message_url(queue.or(request.url.queue_name()), &request_url) {
             Ok(p) => p,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
                 return Err(QStashError::PublishError);
             }
         };
 
-        let headers = match Client::generate_headers(PublishOptions {
-            headers: request.headers,
-            delay: request.delay,
-            not_before: request.not_before,
-            deduplication_id: request.deduplication_id,
-            content_based_deduplication: request.content_based_deduplication,
-            retries: request.retries,
-            callback: request.callback,
-            method: request.method,
-        }) {
+        let headers = match Client::generate_headers(
+            PublishOptions {
+                headers: request.headers,
+                delay: request.delay,
+                not_before: request.not_before,
+                deduplication_id: request.deduplication_id,
+                content_based_deduplication: request.content_based_deduplication,
+                retries: request.retries,
+                callback: request.callback,
+                failure_callback: request.failure_callback,
+                method: request.method,
+            }
+            .with_defaults(self.publish_defaults.as_ref()),
+        ) {
             Ok(h) => h,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
                 return Err(QStashError::PublishError);
             }
         };
 
-        let request_builder = self.http.request(Method::POST, path).headers(headers);
+        let body = request.body.map(Into::<Bytes>::into);
+        if let Some(bytes) = &body {
+            self.check_body_size(bytes.len())?;
+        }
 
-        let response = match request.body {
-            Some(b) => match request_builder.body(b).send().await {
-                Ok(r) => {
-                    tracing::debug!("{:?}", r);
-                    r
-                }
-                Err(e) => {
-                    let formated_string = e.to_string();
-                    tracing::error!(formated_string);
-                    return Err(QStashError::PublishError);
-                }
-            },
-            None => match request_builder.send().await {
-                Ok(r) => {
-                    tracing::debug!("{:?}", r);
-                    r
-                }
-                Err(e) => {
-                    let formated_string = e.to_string();
-                    tracing::error!(formated_string);
-                    return Err(QStashError::PublishError);
-                }
-            },
+        let dedup_key = self.dedup_key(path.path(), &headers, body.as_deref().unwrap_or_default());
+        if let Some(responses) = self.deduplicated(dedup_key.as_deref()) {
+            return Ok(responses);
+        }
+
+        let mut headers = headers;
+        let deduplication_id = self.attach_deduplication_id(&mut headers);
+        self.sign_payload(&mut headers, body.as_deref().unwrap_or_default());
+
+        #[cfg(feature = "opentelemetry")]
+        let headers = match self.propagate_trace_context {
+            true => crate::trace_context::inject(headers),
+            false => headers,
         };
 
-        let response: Vec<QstashResponse> = match request.url {
-            PublishRequestUrl::Url(_) => match response.json().await {
+        let started = Instant::now();
+        let mut request_parts = self.request(Method::POST, path).headers(headers);
+        if let Some(b) = body {
+            request_parts = request_parts.body(b);
+        }
+
+        let response = match self.send_publish(request_parts).await {
+            Ok(r) => {
+                log_response(endpoint_name, &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::PublishError);
+            }
+        };
+
+        if let Some(e) = response.quota_error() {
+            log::error!(
+                "Publishing failed with status {}, out of quota",
+                response.status()
+            );
+            return Err(e);
+        }
+
+        let mut response: Vec<QstashResponse> = match request.url.topic_name() {
+            None => match response.json() {
                 Ok(r) => vec![r],
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             },
-            PublishRequestUrl::Topic(_) => match response.json().await {
+            Some(_) => match response.json() {
                 Ok(r) => r,
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             },
         };
 
+        if let Some(id) = deduplication_id {
+            for r in response.iter_mut() {
+                r.deduplication_id = Some(id.clone());
+            }
+        }
+
+        self.remember(dedup_key, &response);
         Ok(response)
     }
 
@@ -113,7 +197,7 @@ impl Client {
     /// # Example
     /// ```
     /// use qstash_rs::client::{PublishRequestUrl, Client};
-    /// use std::collections::HashMap;
+    /// use std::{collections::HashMap, time::Instant};
     ///
     /// #[tokio::main]
     /// async fn main() {
@@ -142,89 +226,212 @@ impl Client {
         body: T,
         options: Option<PublishOptions>,
     ) -> Result<Vec<QstashResponse>, QStashError> {
-        let request_url = match &url {
-            PublishRequestUrl::Url(v) => v.to_string(),
-            PublishRequestUrl::Topic(v) => v.clone(),
-        };
+        self.send_json(None, url, body, options, "publish_json")
+            .await
+    }
+
+    /// enqueue_json enqueues the message to the queue like [`Client::enqueue`], serializing
+    /// the body and setting the `Content-Type` header to `application/json`
+    /// like [`Client::publish_json`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use qstash_rs::client::{Client, PublishOptions, PublishRequestUrl};
+    /// use std::collections::HashMap;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("could not initialize client");
+    ///
+    ///     match qstash_client
+    ///         .enqueue_json(
+    ///             "myqueue",
+    ///             PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL")),
+    ///             HashMap::from([("order", "42")]),
+    ///             Some(PublishOptions {
+    ///                 retries: Some(3),
+    ///                 ..Default::default()
+    ///             }),
+    ///         )
+    ///         .await
+    ///     {
+    ///         Ok(r) => println!("{:?}", r),
+    ///         Err(err) => println!("{:?}", err),
+    ///     };
+    /// }
+    /// ```
+    pub async fn enqueue_json<T: Serialize>(
+        &self,
+        queue: &str,
+        url: PublishRequestUrl,
+        body: T,
+        options: Option<PublishOptions>,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        self.send_json(Some(queue), url, body, options, "enqueue_json")
+            .await
+    }
 
-        let path = match self
-            .base_url
-            .join(&format!("/{}/publish/{}", self.version, request_url))
-        {
+    /// send_json publishes the serialized body, or enqueues it if a queue is set.
+    async fn send_json<T: Serialize>(
+        &self,
+        queue: Option<&str>,
+        url: PublishRequestUrl,
+        body: T,
+        options: Option<PublishOptions>,
+        endpoint_name: &'static str,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        if let Some(topic) = url.topic_name() {
+            validate_topic_name(topic)?;
+        }
+        let request_url = url.destination_url();
+
+        let path = match self.message_url(queue.or(url.queue_name()), &request_url) {
             Ok(p) => p,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
+                return Err(QStashError::PublishError);
+            }
+        };
+
+        let body = match serde_json::to_vec(&body) {
+            Ok(b) => b,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
                 return Err(QStashError::PublishError);
             }
         };
 
+        self.send_json_body(
+            path,
+            body,
+            options,
+            HeaderMap::new(),
+            url.topic_name().is_some(),
+            endpoint_name,
+        )
+        .await
+    }
+
+    /// send_json_body publishes the JSON body to the path, with the headers of the options
+    /// and the extra headers, which replace the headers of the same name.
+    /// The response of a topic is a list, it is a single response otherwise.
+    pub(crate) async fn send_json_body(
+        &self,
+        path: url::Url,
+        body: Vec<u8>,
+        options: Option<PublishOptions>,
+        extra_headers: HeaderMap,
+        topic: bool,
+        endpoint_name: &'static str,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        let options = match options {
+            Some(options) => Some(options.with_defaults(self.publish_defaults.as_ref())),
+            None => self.publish_defaults.clone(),
+        };
+
         let headers = match options {
             Some(options) => match Client::generate_headers(options) {
                 Ok(h) => h,
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             },
             None => header::HeaderMap::new(),
         };
+        let mut headers = headers;
+        for (name, value) in extra_headers.iter() {
+            headers.insert(name.clone(), value.clone());
+        }
 
-        let response = match self
-            .http
+        self.check_body_size(body.len())?;
+
+        let dedup_key = self.dedup_key(path.path(), &headers, &body);
+        if let Some(responses) = self.deduplicated(dedup_key.as_deref()) {
+            return Ok(responses);
+        }
+
+        let mut headers = headers;
+        let deduplication_id = self.attach_deduplication_id(&mut headers);
+        self.sign_payload(&mut headers, &body);
+
+        #[cfg(feature = "opentelemetry")]
+        let headers = match self.propagate_trace_context {
+            true => crate::trace_context::inject(headers),
+            false => headers,
+        };
+
+        let started = Instant::now();
+        let request = self
             .request(Method::POST, path)
             .headers(headers)
-            .json(&body)
-            .send()
-            .await
-        {
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body);
+        let response = match self.send_publish(request).await {
             Ok(r) => {
-                tracing::debug!("{:?}", r);
+                log_response(endpoint_name, &r, started);
                 r
             }
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
                 return Err(QStashError::PublishError);
             }
         };
 
-        let response: Vec<QstashResponse> = match url {
-            PublishRequestUrl::Url(_) => match response.json().await {
+        if let Some(e) = response.quota_error() {
+            log::error!(
+                "Publishing failed with status {}, out of quota",
+                response.status()
+            );
+            return Err(e);
+        }
+
+        let mut response: Vec<QstashResponse> = match topic {
+            false => match response.json() {
                 Ok(r) => vec![r],
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             },
-            PublishRequestUrl::Topic(_) => match response.json().await {
+            true => match response.json() {
                 Ok(r) => r,
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             },
         };
 
+        if let Some(id) = deduplication_id {
+            for r in response.iter_mut() {
+                r.deduplication_id = Some(id.clone());
+            }
+        }
+
+        self.remember(dedup_key, &response);
         Ok(response)
     }
 
     /// generate_headers generates the headers for the request.
     /// The headers are generated from the provided options.
     /// If no options are provided, the default headers are used.
-    fn generate_headers(request: PublishOptions) -> Result<HeaderMap, QStashError> {
-        let mut headers = request.headers.unwrap_or(header::HeaderMap::new());
+    pub(crate) fn generate_headers(request: PublishOptions) -> Result<HeaderMap, QStashError> {
+        let mut headers = request.headers.unwrap_or_default();
 
         let method = match header::HeaderValue::from_str(
-            request.method.unwrap_or(reqwest::Method::POST).as_str(),
+            request.method.unwrap_or(http::Method::POST).as_str(),
         ) {
             Ok(v) => v,
             Err(e) => {
                 let formated_string = e.to_string();
-                tracing::error!(formated_string);
+                log::error!(formated_string);
                 return Err(QStashError::PublishError);
             }
         };
@@ -235,7 +442,7 @@ impl Client {
                 Ok(v) => v,
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             };
@@ -247,7 +454,7 @@ impl Client {
                 Ok(v) => v,
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             };
@@ -259,7 +466,7 @@ impl Client {
                 Ok(v) => v,
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             };
@@ -275,7 +482,7 @@ impl Client {
                     Ok(v) => v,
                     Err(e) => {
                         let formated_string = e.to_string();
-                        tracing::error!(formated_string);
+                        log::error!(formated_string);
                         return Err(QStashError::PublishError);
                     }
                 };
@@ -290,7 +497,7 @@ impl Client {
                 Ok(v) => v,
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             };
@@ -302,13 +509,25 @@ impl Client {
                 Ok(v) => v,
                 Err(e) => {
                     let formated_string = e.to_string();
-                    tracing::error!(formated_string);
+                    log::error!(formated_string);
                     return Err(QStashError::PublishError);
                 }
             };
             headers.insert("Upstash-Callback", callback);
         }
 
+        if let Some(failure_callback) = request.failure_callback {
+            let failure_callback = match header::HeaderValue::from_str(&failure_callback) {
+                Ok(v) => v,
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    return Err(QStashError::PublishError);
+                }
+            };
+            headers.insert("Upstash-Failure-Callback", failure_callback);
+        }
+
         Ok(headers)
     }
 }
@@ -0,0 +1,175 @@
+//! # queue_watch module
+//! This module contains the queue lag watcher.
+//! It polls the lag of queues and calls back when it crosses a high watermark and
+//! again once it recovers below a low watermark, instead of on every poll.
+//! It also contains [`Client::wait_for_queue_drain`], which polls a queue until it is empty.
+
+use std::{future::Future, time::Duration};
+
+use tokio::{
+    task::JoinHandle,
+    time::{self, Instant, MissedTickBehavior},
+};
+
+use super::{error::QStashError, queues::Queue, Client};
+use crate::log;
+
+/// The configuration of a queue watcher.
+/// The low watermark should be below the high watermark so the alerts do not flap.
+#[derive(Debug, Clone)]
+pub struct QueueAlertConfig {
+    /// The lag at or above which an alert fires.
+    pub high_watermark: u64,
+    /// The lag below which a firing alert recovers.
+    pub low_watermark: u64,
+    /// The time between two polls.
+    pub interval: Duration,
+}
+
+/// An alert of a queue watcher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueAlert {
+    /// The lag of the queue reached the high watermark.
+    High { queue: String, lag: u64 },
+    /// The lag of the queue went below the low watermark after an alert.
+    Recovered { queue: String, lag: u64 },
+}
+
+/// A running queue watcher, it stops when the guard is dropped.
+#[must_use = "the watcher stops when the guard is dropped"]
+pub struct QueueWatchGuard {
+    handle: JoinHandle<()>,
+}
+
+impl Drop for QueueWatchGuard {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl Client {
+    /// watch_queue watches the lag of a single queue, see [`Client::watch_queues`].
+    pub fn watch_queue<F, Fut>(
+        &self,
+        name: &str,
+        config: QueueAlertConfig,
+        callback: F,
+    ) -> QueueWatchGuard
+    where
+        F: Fn(QueueAlert) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        self.watch_queues(vec![name.to_string()], config, callback)
+    }
+
+    /// watch_queues polls the lag of the queues from a single background task and calls
+    /// back once when the lag of a queue reaches the high watermark, and once more when
+    /// it goes back below the low watermark.
+    ///
+    /// Failed polls are logged and the queue is polled again on the next interval.
+    /// The watcher runs until the returned guard is dropped.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use qstash_rs::client::{queue_watch::QueueAlertConfig, Client};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+    ///
+    ///     let _guard = qstash_client.watch_queue(
+    ///         "orders",
+    ///         QueueAlertConfig {
+    ///             high_watermark: 1000,
+    ///             low_watermark: 100,
+    ///             interval: Duration::from_secs(30),
+    ///         },
+    ///         |alert| async move { println!("{:?}", alert) },
+    ///     );
+    ///
+    ///     tokio::time::sleep(Duration::from_secs(3600)).await;
+    /// }
+    /// ```
+    pub fn watch_queues<F, Fut>(
+        &self,
+        names: Vec<String>,
+        config: QueueAlertConfig,
+        callback: F,
+    ) -> QueueWatchGuard
+    where
+        F: Fn(QueueAlert) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let client = self.clone();
+        let handle = tokio::spawn(async move {
+            let mut alerting = vec![false; names.len()];
+            let mut interval = time::interval(config.interval);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+                for (name, alerting) in names.iter().zip(alerting.iter_mut()) {
+                    let lag = match client.get_queue(name).await {
+                        Ok(q) => q.lag,
+                        Err(e) => {
+                            log::error!("Could not poll queue {}: {}", name, e);
+                            continue;
+                        }
+                    };
+
+                    if !*alerting && lag >= config.high_watermark {
+                        *alerting = true;
+                        callback(QueueAlert::High {
+                            queue: name.clone(),
+                            lag,
+                        })
+                        .await;
+                    } else if *alerting && lag < config.low_watermark {
+                        *alerting = false;
+                        callback(QueueAlert::Recovered {
+                            queue: name.clone(),
+                            lag,
+                        })
+                        .await;
+                    }
+                }
+            }
+        });
+
+        QueueWatchGuard { handle }
+    }
+
+    /// wait_for_queue_drain polls the queue until its lag reaches zero and returns it,
+    /// or returns [`QStashError::Timeout`] once the timeout elapsed.
+    /// A failed poll is returned right away.
+    ///
+    /// Dropping the future stops the polling.
+    pub async fn wait_for_queue_drain(
+        &self,
+        name: &str,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Queue, QStashError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let queue = self.get_queue(name).await?;
+            if queue.lag == 0 {
+                return Ok(queue);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                log::error!(
+                    "Queue {} still has a lag of {} after {:?}",
+                    name,
+                    queue.lag,
+                    timeout
+                );
+                return Err(QStashError::Timeout);
+            }
+            time::sleep(poll_interval.min(deadline - now)).await;
+        }
+    }
+}
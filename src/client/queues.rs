@@ -0,0 +1,467 @@
+//! # queues module
+//! This module contains the queues functionality of the QStash client.
+//! Queues deliver their messages in order, with a limited parallelism.
+
+use std::time::Instant;
+
+use bytes::Bytes;
+use http::{header::HeaderMap, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::QStashError, logging::log_response, Client, PublishOptions, PublishRequest,
+    PublishRequestUrl, QstashResponse, ResponseParts,
+};
+use crate::log;
+
+/// A queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Queue {
+    pub name: String,
+    /// The maximum amount of messages delivered at the same time.
+    #[serde(default)]
+    pub parallelism: u32,
+    /// Unix timestamp in milliseconds.
+    pub created_at: Option<u64>,
+    /// Unix timestamp in milliseconds.
+    pub updated_at: Option<u64>,
+    /// The amount of messages waiting to be delivered.
+    #[serde(default)]
+    pub lag: u64,
+    /// Whether the queue is paused, a paused queue does not deliver its messages.
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// The request to create a queue or update its parallelism.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpsertQueueRequest {
+    pub queue_name: String,
+    /// The maximum amount of messages delivered at the same time.
+    pub parallelism: u32,
+}
+
+/// The outcome of [`Client::upsert_queue_checked`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    /// The queue did not exist.
+    Created,
+    /// The parallelism of the queue changed.
+    Updated { previous_parallelism: u32 },
+    /// The queue already had the parallelism.
+    Unchanged,
+}
+
+impl UpsertOutcome {
+    /// compare returns the outcome of upserting the queue with the parallelism,
+    /// given the queue before the upsert if it existed.
+    pub fn compare(existing: Option<&Queue>, parallelism: u32) -> Self {
+        match existing {
+            None => UpsertOutcome::Created,
+            Some(queue) if queue.parallelism != parallelism => UpsertOutcome::Updated {
+                previous_parallelism: queue.parallelism,
+            },
+            Some(_) => UpsertOutcome::Unchanged,
+        }
+    }
+}
+
+/// A queue of a client with the options applied to every message enqueued through it,
+/// see [`Client::queue`].
+/// The options of a call take precedence over the options of the handle, which take
+/// precedence over the publish defaults of the client.
+///
+/// # Example
+/// ```no_run
+/// use qstash_rs::client::{Client, PublishRequestUrl};
+/// use std::collections::HashMap;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+///     let orders = qstash_client
+///         .queue("orders")
+///         .retries(3)
+///         .callback("https://example.com/callback");
+///
+///     match orders
+///         .enqueue_json(
+///             PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL")),
+///             HashMap::from([("order", "42")]),
+///             None,
+///         )
+///         .await
+///     {
+///         Ok(r) => println!("{:?}", r),
+///         Err(err) => println!("{:?}", err),
+///     };
+/// }
+/// ```
+#[derive(Clone)]
+pub struct QueueHandle<'a> {
+    client: &'a Client,
+    name: String,
+    defaults: PublishOptions,
+}
+
+impl<'a> QueueHandle<'a> {
+    /// name returns the name of the queue.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Set every option applied to the messages, replacing the options set before.
+    pub fn options(mut self, options: PublishOptions) -> Self {
+        self.defaults = options;
+        self
+    }
+
+    /// Send along the headers with every message.
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.defaults.headers = Some(headers);
+        self
+    }
+
+    /// Delay the delivery of every message, in seconds.
+    pub fn delay(mut self, delay: u32) -> Self {
+        self.defaults.delay = Some(delay);
+        self
+    }
+
+    /// Set how many times the delivery of every message is retried.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.defaults.retries = Some(retries);
+        self
+    }
+
+    /// Forward the response of the destination to the callback url.
+    pub fn callback(mut self, callback: &str) -> Self {
+        self.defaults.callback = Some(callback.to_string());
+        self
+    }
+
+    /// Notify the failure callback url once a delivery failed and every retry was used.
+    pub fn failure_callback(mut self, failure_callback: &str) -> Self {
+        self.defaults.failure_callback = Some(failure_callback.to_string());
+        self
+    }
+
+    /// Set the method used to deliver the messages.
+    pub fn method(mut self, method: Method) -> Self {
+        self.defaults.method = Some(method);
+        self
+    }
+
+    /// merged_options returns the options of the call filled from the options of the handle.
+    /// Headers are merged, a header of the call replaces the header of the handle.
+    pub fn merged_options(&self, options: Option<PublishOptions>) -> PublishOptions {
+        options
+            .unwrap_or_default()
+            .with_defaults(Some(&self.defaults))
+    }
+
+    /// enqueue enqueues the message to the queue, see [`Client::enqueue`].
+    pub async fn enqueue<T: Into<Bytes>>(
+        &self,
+        mut request: PublishRequest<T>,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        let options = self.merged_options(Some(request.take_options()));
+        request.set_options(options);
+
+        self.client.enqueue(&self.name, request).await
+    }
+
+    /// enqueue_json enqueues the serialized body to the queue, see [`Client::enqueue_json`].
+    pub async fn enqueue_json<T: Serialize>(
+        &self,
+        url: PublishRequestUrl,
+        body: T,
+        options: Option<PublishOptions>,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        self.client
+            .enqueue_json(&self.name, url, body, Some(self.merged_options(options)))
+            .await
+    }
+}
+
+/// queue_error returns the error of a queue request the API answered with an error status.
+fn queue_error(response: &ResponseParts) -> QStashError {
+    response
+        .quota_error()
+        .unwrap_or_else(|| QStashError::QueueError {
+            status: Some(response.status().as_u16()),
+            message: response.error_message(),
+        })
+}
+
+impl Client {
+    /// queue returns a handle enqueuing messages to the queue with default options,
+    /// see [`QueueHandle`].
+    /// The handle borrows the client, it does not create a connection.
+    pub fn queue(&self, name: &str) -> QueueHandle<'_> {
+        QueueHandle {
+            client: self,
+            name: name.to_string(),
+            defaults: PublishOptions::default(),
+        }
+    }
+
+    /// list_queues retrieves every queue.
+    pub async fn list_queues(&self) -> Result<Vec<Queue>, QStashError> {
+        let path = match self.base_url.join(&format!("/{}/queues", self.version)) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
+            Ok(r) => {
+                log_response("list_queues", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!("Listing queues failed with status {}", response.status());
+            return Err(queue_error(&response));
+        }
+
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+
+    /// upsert_queue creates the queue, or updates its parallelism if it already exists.
+    /// A parallelism of 0 is rejected before sending the request.
+    pub async fn upsert_queue(&self, request: UpsertQueueRequest) -> Result<(), QStashError> {
+        if request.parallelism == 0 {
+            log::error!("Invalid parallelism 0 for queue {}", request.queue_name);
+            return Err(QStashError::QueueError {
+                status: None,
+                message: format!(
+                    "the parallelism of queue {} must be at least 1",
+                    request.queue_name
+                ),
+            });
+        }
+
+        let path = match self.base_url.join(&format!("/{}/queues", self.version)) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let request_parts = match self.request(Method::POST, path).json(&request) {
+            Ok(r) => r,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        let response = match self.execute(request_parts).await {
+            Ok(r) => {
+                log_response("upsert_queue", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!(
+                "Upserting queue {} failed with status {}",
+                request.queue_name,
+                response.status()
+            );
+            return Err(queue_error(&response));
+        }
+
+        Ok(())
+    }
+
+    /// upsert_queue_checked upserts the queue like [`Client::upsert_queue`] and tells whether
+    /// it was created, updated or already up to date.
+    /// The queue is retrieved first, a queue that is already up to date is not upserted.
+    /// The outcome can be wrong if the queue is changed concurrently.
+    pub async fn upsert_queue_checked(
+        &self,
+        request: UpsertQueueRequest,
+    ) -> Result<UpsertOutcome, QStashError> {
+        let existing = match self.get_queue(&request.queue_name).await {
+            Ok(q) => Some(q),
+            Err(QStashError::QueueNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+
+        let outcome = UpsertOutcome::compare(existing.as_ref(), request.parallelism);
+        if outcome != UpsertOutcome::Unchanged {
+            self.upsert_queue(request).await?;
+        }
+        Ok(outcome)
+    }
+
+    /// get_queue retrieves a queue by its name.
+    /// It returns [`QStashError::QueueNotFound`] if the queue does not exist.
+    pub async fn get_queue(&self, name: &str) -> Result<Queue, QStashError> {
+        let path = match self.api_url(&["queues", name]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
+            Ok(r) => {
+                log_response("get_queue", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(QStashError::QueueNotFound(name.to_string()));
+        }
+        if !response.status().is_success() {
+            log::error!(
+                "Getting queue {} failed with status {}",
+                name,
+                response.status()
+            );
+            return Err(queue_error(&response));
+        }
+
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+
+    /// pause_queue stops the delivery of the messages of the queue until it is resumed,
+    /// the queue keeps accepting messages.
+    pub async fn pause_queue(&self, name: &str) -> Result<(), QStashError> {
+        self.send_queue_action(name, "pause", "pause_queue").await
+    }
+
+    /// resume_queue resumes the delivery of the messages of a paused queue.
+    pub async fn resume_queue(&self, name: &str) -> Result<(), QStashError> {
+        self.send_queue_action(name, "resume", "resume_queue").await
+    }
+
+    /// send_queue_action sends the action, such as pause, to the queue.
+    async fn send_queue_action(
+        &self,
+        name: &str,
+        action: &str,
+        endpoint_name: &'static str,
+    ) -> Result<(), QStashError> {
+        let path = match self.api_url(&["queues", name, action]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        let response = match self.execute(self.request(Method::POST, path)).await {
+            Ok(r) => {
+                log_response(endpoint_name, &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::QueueError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(QStashError::QueueNotFound(name.to_string()));
+        }
+        if !response.status().is_success() {
+            log::error!(
+                "Sending {} to queue {} failed with status {}",
+                action,
+                name,
+                response.status()
+            );
+            return Err(queue_error(&response));
+        }
+
+        Ok(())
+    }
+}
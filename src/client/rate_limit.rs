@@ -0,0 +1,138 @@
+//! # rate_limit module
+//! This module contains the client side rate limiter.
+//! It is a token bucket that delays requests before they are sent, so the publish
+//! rate stays below the quota of the plan instead of reacting to 429 responses.
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use super::error::QStashError;
+use crate::log;
+
+/// The configuration of the client side rate limiter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimit {
+    /// The amount of tokens added to the bucket every second, it must be positive.
+    pub max_requests_per_second: f64,
+    /// The maximum amount of tokens in the bucket, the amount of requests that can be
+    /// sent at once after a quiet period.
+    pub burst: u32,
+    /// If true, read only requests such as listing events are not limited.
+    #[serde(default)]
+    pub exempt_reads: bool,
+}
+
+impl RateLimit {
+    /// Creates a new [`RateLimit`] that also limits read only requests.
+    /// It returns a [`QStashError::ConfigError`] if the rate is not a positive number.
+    pub fn new(max_requests_per_second: f64, burst: u32) -> Result<Self, QStashError> {
+        let config = Self {
+            max_requests_per_second,
+            burst,
+            exempt_reads: false,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// validate returns an error if the rate would make the limiter wait forever.
+    pub(crate) fn validate(&self) -> Result<(), QStashError> {
+        if !(self.max_requests_per_second > 0.0 && self.max_requests_per_second.is_finite()) {
+            log::error!(
+                "Invalid rate limit of {} requests per second",
+                self.max_requests_per_second
+            );
+            return Err(QStashError::ConfigError {
+                field: "max_requests_per_second".to_string(),
+                reason: "must be positive".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// The token bucket shared by every clone of a client.
+#[derive(Clone)]
+pub(crate) struct RateLimiter {
+    config: RateLimit,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimit) -> Self {
+        let bucket = Bucket {
+            tokens: f64::from(config.burst.max(1)),
+            last_refill: Instant::now(),
+        };
+        Self {
+            config,
+            bucket: Arc::new(Mutex::new(bucket)),
+        }
+    }
+
+    /// acquire takes a token from the bucket, waiting until one is available.
+    /// Tokens are reserved before waiting so concurrent callers are served in order.
+    pub(crate) async fn acquire(&self, read_only: bool) {
+        if read_only && self.config.exempt_reads {
+            return;
+        }
+
+        let wait = {
+            let mut bucket = self.refilled_bucket();
+            bucket.tokens -= 1.0;
+            self.time_until(bucket.tokens, 0.0)
+        };
+
+        if !wait.is_zero() {
+            log::debug!("Rate limited, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// ready_in returns how long to wait until a token is available, without taking it.
+    /// It returns zero if a request can be sent right away.
+    #[cfg(feature = "tower")]
+    pub(crate) fn ready_in(&self, read_only: bool) -> Duration {
+        if read_only && self.config.exempt_reads {
+            return Duration::ZERO;
+        }
+
+        let bucket = self.refilled_bucket();
+        self.time_until(bucket.tokens, 1.0)
+    }
+
+    /// refilled_bucket locks the bucket and adds the tokens accumulated since the last refill.
+    fn refilled_bucket(&self) -> MutexGuard<'_, Bucket> {
+        let mut bucket = match self.bucket.lock() {
+            Ok(b) => b,
+            Err(e) => e.into_inner(),
+        };
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.config.max_requests_per_second)
+            .min(f64::from(self.config.burst.max(1)));
+        bucket.last_refill = now;
+        bucket
+    }
+
+    /// time_until returns how long it takes for the bucket to go from tokens to target.
+    fn time_until(&self, tokens: f64, target: f64) -> Duration {
+        match tokens < target {
+            true => {
+                Duration::from_secs_f64((target - tokens) / self.config.max_requests_per_second)
+            }
+            false => Duration::ZERO,
+        }
+    }
+}
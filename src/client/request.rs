@@ -2,17 +2,63 @@
 //! This module contains the structs and enums that are used to make requests to the QStash API.
 //! The [`Client`] struct is the main struct that is used to make requests.
 
-use reqwest::{header::HeaderMap, Method};
+use bytes::Bytes;
+use http::{header::HeaderMap, Method};
 use serde::{Deserialize, Serialize};
 
 /// The request url.
 /// This can either be a url or a topic.
 /// If it is a url, the message will be sent to that url.
 /// If it is a topic, the message will be sent to all urls subscribed to that topic.
+/// A url group is the new name of a topic, both variants behave the same.
+/// If it is a queue, the message is enqueued to the queue and delivered to its destination
+/// respecting the parallelism of the queue.
 #[derive(Debug, Clone)]
 pub enum PublishRequestUrl {
-    Url(reqwest::Url),
+    Url(url::Url),
     Topic(String),
+    UrlGroup(String),
+    Queue {
+        queue: String,
+        destination: Box<PublishRequestUrl>,
+    },
+}
+
+impl PublishRequestUrl {
+    /// Creates a [`PublishRequestUrl::Queue`] enqueuing the messages to the queue.
+    pub fn queue(queue: &str, destination: PublishRequestUrl) -> Self {
+        Self::Queue {
+            queue: queue.to_string(),
+            destination: Box::new(destination),
+        }
+    }
+
+    /// destination_url returns the url or topic the message is delivered to.
+    pub(crate) fn destination_url(&self) -> String {
+        match self {
+            PublishRequestUrl::Url(v) => v.to_string(),
+            PublishRequestUrl::Topic(v) | PublishRequestUrl::UrlGroup(v) => v.clone(),
+            PublishRequestUrl::Queue { destination, .. } => destination.destination_url(),
+        }
+    }
+
+    /// topic_name returns the topic the message is delivered to, if it is not a url.
+    pub(crate) fn topic_name(&self) -> Option<&str> {
+        match self {
+            PublishRequestUrl::Url(_) => None,
+            PublishRequestUrl::Topic(v) | PublishRequestUrl::UrlGroup(v) => Some(v),
+            PublishRequestUrl::Queue { destination, .. } => destination.topic_name(),
+        }
+    }
+
+    /// queue_name returns the queue the message is enqueued to, the outermost one
+    /// if queues are nested.
+    pub(crate) fn queue_name(&self) -> Option<&str> {
+        match self {
+            PublishRequestUrl::Queue { queue, .. } => Some(queue),
+            _ => None,
+        }
+    }
 }
 
 /// The response from the QStash API.
@@ -26,9 +72,17 @@ pub struct QstashResponse {
     pub url: Option<String>,
     pub error: Option<String>,
     pub deduplicated: Option<bool>,
+    /// The id of the schedule created by a publish with an `Upstash-Cron` header,
+    /// which has no message id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_id: Option<String>,
+    /// The deduplication id generated by the client for a publish with retries enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deduplication_id: Option<String>,
 }
 
 /// Options that Qstash allows to be used when publishing a message.
+#[derive(Debug, Clone, Default)]
 pub struct PublishOptions {
     /// Optionally send along headers with the message.
     /// These headers will be sent to your destination.
@@ -89,6 +143,15 @@ pub struct PublishOptions {
     ///
     pub callback: Option<String>,
 
+    ///
+    /// Use a failure callback url to be notified once the delivery failed and every retry was used.
+    ///
+    /// The failure callback url must be publicly accessible
+    ///
+    /// @default None
+    ///
+    pub failure_callback: Option<String>,
+
     ///
     ///The method to use when sending a request to your API
     ///
@@ -97,12 +160,50 @@ pub struct PublishOptions {
     pub method: Option<Method>,
 }
 
+impl PublishOptions {
+    /// with_defaults fills the options that are not set from the defaults.
+    /// Headers are merged, a header set on the options replaces the default one.
+    pub(crate) fn with_defaults(self, defaults: Option<&PublishOptions>) -> PublishOptions {
+        let defaults = match defaults {
+            Some(d) => d.clone(),
+            None => return self,
+        };
+
+        let headers = match (defaults.headers, self.headers) {
+            (Some(mut merged), Some(headers)) => {
+                for name in headers.keys() {
+                    merged.remove(name);
+                }
+                for (name, value) in headers.iter() {
+                    merged.append(name, value.clone());
+                }
+                Some(merged)
+            }
+            (defaults, headers) => headers.or(defaults),
+        };
+
+        PublishOptions {
+            headers,
+            delay: self.delay.or(defaults.delay),
+            not_before: self.not_before.or(defaults.not_before),
+            deduplication_id: self.deduplication_id.or(defaults.deduplication_id),
+            content_based_deduplication: self
+                .content_based_deduplication
+                .or(defaults.content_based_deduplication),
+            retries: self.retries.or(defaults.retries),
+            callback: self.callback.or(defaults.callback),
+            failure_callback: self.failure_callback.or(defaults.failure_callback),
+            method: self.method.or(defaults.method),
+        }
+    }
+}
+
 /// The request to publish a message.
 /// This struct is used to send a message to the QStash API.
 #[derive(Debug, Clone)]
 pub struct PublishRequest<T>
 where
-    T: Into<reqwest::Body>,
+    T: Into<Bytes>,
 {
     /// The url to send the message to.
     /// This can either be a url or a topic.
@@ -171,6 +272,15 @@ where
     ///
     pub callback: Option<String>,
 
+    ///
+    /// Use a failure callback url to be notified once the delivery failed and every retry was used.
+    ///
+    /// The failure callback url must be publicly accessible
+    ///
+    /// @default None
+    ///
+    pub failure_callback: Option<String>,
+
     ///
     ///The method to use when sending a request to your API
     ///
@@ -179,7 +289,7 @@ where
     pub method: Option<Method>,
 }
 
-impl<T: Into<reqwest::Body>> PublishRequest<T> {
+impl<T: Into<Bytes>> PublishRequest<T> {
     /// Creates a new [`PublishRequest<T>`].
     pub fn new(url: PublishRequestUrl) -> Self {
         Self {
@@ -192,7 +302,36 @@ impl<T: Into<reqwest::Body>> PublishRequest<T> {
             content_based_deduplication: None,
             retries: None,
             callback: None,
+            failure_callback: None,
             method: None,
         }
     }
+
+    /// take_options moves the options out of the request, leaving them unset.
+    pub(crate) fn take_options(&mut self) -> PublishOptions {
+        PublishOptions {
+            headers: self.headers.take(),
+            delay: self.delay.take(),
+            not_before: self.not_before.take(),
+            deduplication_id: self.deduplication_id.take(),
+            content_based_deduplication: self.content_based_deduplication.take(),
+            retries: self.retries.take(),
+            callback: self.callback.take(),
+            failure_callback: self.failure_callback.take(),
+            method: self.method.take(),
+        }
+    }
+
+    /// set_options replaces every option of the request.
+    pub(crate) fn set_options(&mut self, options: PublishOptions) {
+        self.headers = options.headers;
+        self.delay = options.delay;
+        self.not_before = options.not_before;
+        self.deduplication_id = options.deduplication_id;
+        self.content_based_deduplication = options.content_based_deduplication;
+        self.retries = options.retries;
+        self.callback = options.callback;
+        self.failure_callback = options.failure_callback;
+        self.method = options.method;
+    }
 }
@@ -0,0 +1,127 @@
+//! # retry module
+//! This module contains the client side retries of requests.
+//! A publish whose response was lost could be delivered twice, so retried publishes carry
+//! a deduplication id generated before the first attempt.
+
+use std::time::Duration;
+
+use http::{
+    header::{HeaderMap, HeaderValue},
+    StatusCode,
+};
+
+use super::{Client, RequestParts, ResponseParts, TransportError};
+use crate::log;
+
+/// The retry policy of the requests.
+/// Requests failing with a connection error, a 5xx or a 429 status are sent again after
+/// an exponential backoff with jitter.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum amount of attempts of a request, including the first one.
+    pub max_attempts: u32,
+    /// The backoff after the first attempt, it doubles after every attempt.
+    pub base_backoff: Duration,
+    /// The maximum backoff between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// backoff returns the time to wait after the attempt, starting at 1.
+    /// It is between half and all of the exponential backoff.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        exponential.mul_f64(0.5 + fastrand::f64() / 2.0)
+    }
+}
+
+/// is_retryable returns true if the attempt failed in a way that can succeed on retry.
+fn is_retryable(result: &Result<ResponseParts, TransportError>) -> bool {
+    match result {
+        Ok(r) => r.status().is_server_error() || r.status() == StatusCode::TOO_MANY_REQUESTS,
+        Err(e) => e.is_connect() || e.is_timeout(),
+    }
+}
+
+impl Client {
+    /// send_read sends a read only request, retrying it according to the retry policy.
+    /// Every attempt waits for the rate limiter.
+    pub(crate) async fn send_read(
+        &self,
+        request: RequestParts,
+    ) -> Result<ResponseParts, TransportError> {
+        self.send_with_retries(request, true).await
+    }
+
+    /// send_publish sends a publish, retrying it according to the retry policy.
+    /// The headers must carry a deduplication id when retries are enabled,
+    /// see [`Client::attach_deduplication_id`].
+    pub(crate) async fn send_publish(
+        &self,
+        request: RequestParts,
+    ) -> Result<ResponseParts, TransportError> {
+        self.send_with_retries(request, false).await
+    }
+
+    /// attach_deduplication_id generates a deduplication id when retries are enabled and
+    /// the message is not deduplicated already, so a retried publish is delivered once.
+    /// It returns the generated id.
+    pub(crate) fn attach_deduplication_id(&self, headers: &mut HeaderMap) -> Option<String> {
+        self.retry_policy.as_ref()?;
+        let content_based = headers
+            .get("Upstash-Content-Based-Deduplication")
+            .is_some_and(|v| v.as_bytes().eq_ignore_ascii_case(b"true"));
+        if content_based || headers.contains_key("Upstash-Deduplication-Id") {
+            return None;
+        }
+
+        let id = format!("{:032x}", fastrand::u128(..));
+        headers.insert("Upstash-Deduplication-Id", HeaderValue::from_str(&id).ok()?);
+        Some(id)
+    }
+
+    async fn send_with_retries(
+        &self,
+        request: RequestParts,
+        read_only: bool,
+    ) -> Result<ResponseParts, TransportError> {
+        let policy = match &self.retry_policy {
+            Some(p) => p,
+            None => {
+                self.throttle(read_only).await;
+                return self.execute(request).await;
+            }
+        };
+
+        let mut attempt = 1;
+        loop {
+            self.throttle(read_only).await;
+            let result = self.execute(request.clone()).await;
+            if attempt >= policy.max_attempts || !is_retryable(&result) {
+                return result;
+            }
+
+            let backoff = policy.backoff(attempt);
+            log::debug!(
+                "Retrying request after attempt {} in {:?}",
+                attempt,
+                backoff
+            );
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,849 @@
+//! # schedules module
+//! This module contains the schedules functionality of the QStash client.
+//! Schedules publish a message to their destination on a cron expression.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use http::{
+    header::{self, HeaderMap},
+    Method, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "cron")]
+use super::cron::{CronError, CronExpression};
+use super::{
+    error::QStashError, logging::log_response, Client, PublishOptions, PublishRequestUrl,
+    ResponseParts,
+};
+use crate::log;
+
+/// A schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    pub schedule_id: String,
+    /// The cron expression of the schedule, in UTC.
+    pub cron: String,
+    /// Unix timestamp in milliseconds.
+    pub created_at: Option<u64>,
+    /// The url or topic the messages are sent to.
+    pub destination: String,
+    /// The url group of the schedule, also read from the `urlGroup` field.
+    #[serde(alias = "urlGroup")]
+    pub topic_name: Option<String>,
+    pub method: Option<String>,
+    /// The headers sent along with the messages.
+    #[serde(default)]
+    pub header: HashMap<String, Vec<String>>,
+    pub body: Option<String>,
+    pub retries: Option<u32>,
+    /// The delay of the delivery of every message.
+    /// In seconds.
+    pub delay: Option<u32>,
+    pub callback: Option<String>,
+    pub failure_callback: Option<String>,
+    /// Whether the schedule is paused, a paused schedule does not publish messages.
+    #[serde(default)]
+    pub is_paused: bool,
+    /// The last time the schedule published a message, absent if it never ran.
+    /// Unix timestamp in milliseconds.
+    pub last_schedule_time: Option<u64>,
+    /// The next time the schedule publishes a message.
+    /// Unix timestamp in milliseconds.
+    pub next_schedule_time: Option<u64>,
+}
+
+/// The request to create a schedule.
+#[derive(Debug, Clone)]
+pub struct CreateScheduleRequest {
+    /// The url or topic to send the messages to.
+    pub destination: PublishRequestUrl,
+
+    /// The cron expression of the schedule, in UTC.
+    pub cron: String,
+
+    /// Optionally set the id of the schedule.
+    /// Creating a schedule with the id of an existing one replaces it.
+    pub schedule_id: Option<String>,
+
+    /// The message to send.
+    /// Please set the `Content-Type` header accordingly.
+    pub body: Option<String>,
+
+    /// Optionally send along headers with the messages.
+    pub headers: Option<HeaderMap>,
+
+    /// Configure how many times you would like the delivery to be retried.
+    pub retries: Option<u32>,
+
+    /// Optionally delay the delivery of every message after the schedule fires.
+    /// In seconds, like the delay of a publish.
+    pub delay: Option<u32>,
+
+    /// Use a callback url to forward the response of your destination server to your callback url.
+    pub callback: Option<String>,
+
+    /// Use a failure callback url to be notified once a delivery failed and every retry was used.
+    pub failure_callback: Option<String>,
+
+    /// The method to use when sending a request to your API.
+    pub method: Option<Method>,
+
+    /// Optionally enqueue the messages to the queue, so their delivery respects its parallelism.
+    /// It takes precedence over the queue of a [`PublishRequestUrl::Queue`] destination.
+    pub queue: Option<String>,
+}
+
+impl CreateScheduleRequest {
+    /// Creates a new [`CreateScheduleRequest`].
+    pub fn new(destination: PublishRequestUrl, cron: &str) -> Self {
+        Self {
+            destination,
+            cron: cron.to_string(),
+            schedule_id: None,
+            body: None,
+            headers: None,
+            retries: None,
+            delay: None,
+            callback: None,
+            failure_callback: None,
+            method: None,
+            queue: None,
+        }
+    }
+
+    /// Creates a new [`CreateScheduleRequest`] applying the publish options to every message.
+    /// The not before and deduplication options apply to a single message and are ignored.
+    pub fn with_options(
+        destination: PublishRequestUrl,
+        cron: &str,
+        options: PublishOptions,
+    ) -> Self {
+        Self {
+            headers: options.headers,
+            retries: options.retries,
+            delay: options.delay,
+            callback: options.callback,
+            failure_callback: options.failure_callback,
+            method: options.method,
+            ..Self::new(destination, cron)
+        }
+    }
+}
+
+impl CreateScheduleRequest {
+    /// Creates a new [`CreateScheduleRequestBuilder`].
+    ///
+    /// # Example
+    /// ```
+    /// use http::Method;
+    /// use qstash_rs::client::{schedules::CreateScheduleRequest, PublishRequestUrl};
+    ///
+    /// let request = CreateScheduleRequest::builder(
+    ///     PublishRequestUrl::Url("https://example.com/job".parse().expect("Could not parse URL")),
+    ///     "0 * * * *",
+    /// )
+    /// .method(Method::PUT)
+    /// .retries(3)
+    /// .header("Content-Type", "application/json")
+    /// .body(r#"{"job":"report"}"#)
+    /// .build()
+    /// .expect("Invalid schedule");
+    /// ```
+    pub fn builder(destination: PublishRequestUrl, cron: &str) -> CreateScheduleRequestBuilder {
+        CreateScheduleRequestBuilder {
+            request: Self::new(destination, cron),
+            invalid_header: None,
+        }
+    }
+}
+
+/// The builder of a [`CreateScheduleRequest`], see [`CreateScheduleRequest::builder`].
+#[derive(Debug, Clone)]
+pub struct CreateScheduleRequestBuilder {
+    request: CreateScheduleRequest,
+    /// The first header that could not be parsed, returned by build.
+    invalid_header: Option<String>,
+}
+
+impl CreateScheduleRequestBuilder {
+    /// Set the id of the schedule, replacing the existing schedule with the same id.
+    pub fn schedule_id(mut self, schedule_id: &str) -> Self {
+        self.request.schedule_id = Some(schedule_id.to_string());
+        self
+    }
+
+    /// Set the message to send.
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.request.body = Some(body.into());
+        self
+    }
+
+    /// Add a header sent along with the messages.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        match (
+            header::HeaderName::from_bytes(name.as_bytes()),
+            header::HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                self.request
+                    .headers
+                    .get_or_insert_with(HeaderMap::new)
+                    .append(name, value);
+            }
+            _ => {
+                self.invalid_header.get_or_insert_with(|| name.to_string());
+            }
+        }
+        self
+    }
+
+    /// Set how many times the delivery is retried.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.request.retries = Some(retries);
+        self
+    }
+
+    /// Delay the delivery of every message after the schedule fires, in seconds.
+    pub fn delay(mut self, delay: u32) -> Self {
+        self.request.delay = Some(delay);
+        self
+    }
+
+    /// Forward the response of the destination to the callback url.
+    pub fn callback(mut self, callback: &str) -> Self {
+        self.request.callback = Some(callback.to_string());
+        self
+    }
+
+    /// Notify the failure callback url once a delivery failed and every retry was used.
+    pub fn failure_callback(mut self, failure_callback: &str) -> Self {
+        self.request.failure_callback = Some(failure_callback.to_string());
+        self
+    }
+
+    /// Set the method used to send the messages to the destination.
+    pub fn method(mut self, method: Method) -> Self {
+        self.request.method = Some(method);
+        self
+    }
+
+    /// Enqueue the messages to the queue.
+    pub fn queue(mut self, queue: &str) -> Self {
+        self.request.queue = Some(queue.to_string());
+        self
+    }
+
+    /// build returns the request, or an error if the cron expression is empty, invalid
+    /// with the `cron` feature, or a header could not be parsed.
+    pub fn build(self) -> Result<CreateScheduleRequest, QStashError> {
+        if self.request.cron.trim().is_empty() {
+            return Err(QStashError::InvalidCron {
+                expression: self.request.cron,
+                reason: "empty expression".to_string(),
+            });
+        }
+        #[cfg(feature = "cron")]
+        validate_cron(&self.request.cron)?;
+        if let Some(name) = self.invalid_header {
+            log::error!("Invalid header {}", name);
+            return Err(QStashError::ScheduleError {
+                status: None,
+                message: format!("invalid header {}", name),
+            });
+        }
+        Ok(self.request)
+    }
+}
+
+/// The filters of the listed schedules.
+/// The API returns every schedule, the filters are applied by the client.
+#[derive(Debug, Clone, Default)]
+pub struct ListSchedulesRequest {
+    /// Only list the schedules sending to the url or topic.
+    /// Urls are compared regardless of the case of the scheme and host and of a trailing slash.
+    pub destination: Option<String>,
+}
+
+impl ListSchedulesRequest {
+    /// matches returns true if the schedule passes the filters.
+    pub fn matches(&self, schedule: &Schedule) -> bool {
+        match &self.destination {
+            Some(destination) => {
+                normalize_destination(destination) == normalize_destination(&schedule.destination)
+            }
+            None => true,
+        }
+    }
+}
+
+/// normalize_destination returns the url without its trailing slash, with its scheme and
+/// host in lowercase, or the destination as is if it is not a url, such as a topic.
+fn normalize_destination(destination: &str) -> String {
+    match url::Url::parse(destination) {
+        Ok(url) if url.has_host() => url.as_str().trim_end_matches('/').to_string(),
+        _ => destination.to_string(),
+    }
+}
+
+/// The changes making the existing schedules match the desired ones,
+/// see [`plan_schedules`].
+#[derive(Debug, Clone)]
+pub struct SchedulePlan {
+    /// The desired schedules without an existing schedule to match.
+    pub create: Vec<CreateScheduleRequest>,
+    /// The ids of the existing schedules no desired schedule matches.
+    pub delete: Vec<String>,
+    /// The ids of the existing schedules matching a desired schedule.
+    pub unchanged: Vec<String>,
+}
+
+/// plan_schedules matches the desired schedules with the existing ones on their destination
+/// and cron expression, the other options are not compared.
+/// Every existing schedule matches at most one desired schedule, so duplicates are created.
+pub fn plan_schedules(desired: Vec<CreateScheduleRequest>, existing: &[Schedule]) -> SchedulePlan {
+    let mut matched = vec![false; existing.len()];
+    let mut create = Vec::new();
+    let mut unchanged = Vec::new();
+
+    for request in desired {
+        let destination = normalize_destination(&request.destination.destination_url());
+        let found = existing.iter().enumerate().find(|(i, schedule)| {
+            !matched[*i]
+                && schedule.cron.trim() == request.cron.trim()
+                && normalize_destination(&schedule.destination) == destination
+        });
+        match found {
+            Some((i, schedule)) => {
+                matched[i] = true;
+                unchanged.push(schedule.schedule_id.clone());
+            }
+            None => create.push(request),
+        }
+    }
+
+    let delete = existing
+        .iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(schedule, _)| schedule.schedule_id.clone())
+        .collect();
+
+    SchedulePlan {
+        create,
+        delete,
+        unchanged,
+    }
+}
+
+/// The schedules changed by [`Client::reconcile_schedules`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconcileReport {
+    pub created: Vec<String>,
+    pub deleted: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
+/// The response of a created schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateScheduleResponse {
+    pub schedule_id: String,
+}
+
+impl Schedule {
+    /// group_name returns the url group of the schedule, whichever spelling QStash used.
+    pub fn group_name(&self) -> Option<&str> {
+        self.topic_name.as_deref()
+    }
+
+    /// next_run_in returns the time left until the next run according to the system clock,
+    /// zero if the next run is already due, or None if QStash did not return it.
+    pub fn next_run_in(&self) -> Option<Duration> {
+        let next_run = UNIX_EPOCH + Duration::from_millis(self.next_schedule_time?);
+        Some(
+            next_run
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO),
+        )
+    }
+}
+
+/// The maximum amount of runs allocated up front by [`Schedule::next_runs`].
+#[cfg(feature = "cron")]
+const MAX_PREALLOCATED_RUNS: usize = 1024;
+
+#[cfg(feature = "cron")]
+impl Schedule {
+    /// next_runs computes the next `n` runs of the schedule after `from`, in UTC.
+    /// Fewer runs are returned if the schedule never runs again,
+    /// for example on the 30th of February.
+    /// A leading `CRON_TZ=` time zone other than UTC is an error, the runs can only be
+    /// computed in UTC.
+    pub fn next_runs(&self, from: SystemTime, n: usize) -> Result<Vec<SystemTime>, CronError> {
+        let expression = match split_cron_tz(&self.cron) {
+            (Some(zone), _) if !matches!(zone, "UTC" | "Etc/UTC") => {
+                return Err(CronError {
+                    expression: self.cron.clone(),
+                    reason: format!(
+                        "the time zone {} is not supported, only UTC runs can be computed",
+                        zone
+                    ),
+                });
+            }
+            (_, expression) => CronExpression::parse(expression)?,
+        };
+
+        let mut runs = Vec::with_capacity(n.min(MAX_PREALLOCATED_RUNS));
+        let mut time = from;
+        while runs.len() < n {
+            time = match expression.next_after(time) {
+                Some(t) => t,
+                None => break,
+            };
+            runs.push(time);
+        }
+
+        Ok(runs)
+    }
+}
+
+/// split_cron_tz splits the leading `CRON_TZ=` time zone, if any, from the expression.
+#[cfg(feature = "cron")]
+fn split_cron_tz(cron: &str) -> (Option<&str>, &str) {
+    match cron.trim_start().strip_prefix("CRON_TZ=") {
+        Some(rest) => match rest.split_once(char::is_whitespace) {
+            Some((zone, expression)) => (Some(zone), expression),
+            None => (Some(rest), ""),
+        },
+        None => (None, cron),
+    }
+}
+
+/// validate_cron returns an error if the cron expression can not be parsed.
+/// A leading `CRON_TZ=` time zone is left to QStash.
+#[cfg(feature = "cron")]
+fn validate_cron(cron: &str) -> Result<(), QStashError> {
+    let (_, expression) = split_cron_tz(cron);
+    match CronExpression::parse(expression) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let formated_string = e.to_string();
+            log::error!(formated_string);
+            Err(QStashError::InvalidCron {
+                expression: cron.to_string(),
+                reason: e.reason,
+            })
+        }
+    }
+}
+
+/// schedule_error returns the error of a schedule request the API answered with an error status.
+fn schedule_error(response: &ResponseParts) -> QStashError {
+    QStashError::ScheduleError {
+        status: Some(response.status().as_u16()),
+        message: response.error_message(),
+    }
+}
+
+impl Client {
+    /// create_schedule creates a schedule publishing a message to the destination on the cron expression.
+    /// With the `cron` feature, the cron expression is validated before sending the request.
+    pub async fn create_schedule(
+        &self,
+        request: CreateScheduleRequest,
+    ) -> Result<CreateScheduleResponse, QStashError> {
+        #[cfg(feature = "cron")]
+        validate_cron(&request.cron)?;
+
+        let destination = request.destination.destination_url();
+
+        let path = match self
+            .base_url
+            .join(&format!("/{}/schedules/{}", self.version, destination))
+        {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let mut headers = match Client::generate_headers(
+            PublishOptions {
+                headers: request.headers,
+                delay: request.delay,
+                not_before: None,
+                deduplication_id: None,
+                content_based_deduplication: None,
+                retries: request.retries,
+                callback: request.callback,
+                failure_callback: request.failure_callback,
+                method: request.method,
+            }
+            .with_defaults(self.publish_defaults.as_ref()),
+        ) {
+            Ok(h) => h,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let cron = match header::HeaderValue::from_str(&request.cron) {
+            Ok(v) => v,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+        headers.insert("Upstash-Cron", cron);
+
+        if let Some(schedule_id) = request.schedule_id {
+            let schedule_id = match header::HeaderValue::from_str(&schedule_id) {
+                Ok(v) => v,
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    return Err(QStashError::ScheduleError {
+                        status: None,
+                        message: formated_string,
+                    });
+                }
+            };
+            headers.insert("Upstash-Schedule-Id", schedule_id);
+        }
+
+        let queue = request
+            .queue
+            .or_else(|| request.destination.queue_name().map(String::from));
+        if let Some(queue) = queue {
+            let queue = match header::HeaderValue::from_str(&queue) {
+                Ok(v) => v,
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    return Err(QStashError::ScheduleError {
+                        status: None,
+                        message: formated_string,
+                    });
+                }
+            };
+            headers.insert("Upstash-Queue-Name", queue);
+        }
+
+        let body = request.body.unwrap_or_default();
+        self.check_body_size(body.len())?;
+
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        let request = self.request(Method::POST, path).headers(headers).body(body);
+        let response = match self.execute(request).await {
+            Ok(r) => {
+                log_response("create_schedule", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!("Creating schedule failed with status {}", response.status());
+            return Err(schedule_error(&response));
+        }
+
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+
+    /// get_schedule retrieves a schedule by its id.
+    /// It returns [`QStashError::ScheduleNotFound`] if the schedule does not exist.
+    pub async fn get_schedule(&self, schedule_id: &str) -> Result<Schedule, QStashError> {
+        let path = match self.api_url(&["schedules", schedule_id]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
+            Ok(r) => {
+                log_response("get_schedule", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(QStashError::ScheduleNotFound(schedule_id.to_string()));
+        }
+        if !response.status().is_success() {
+            log::error!(
+                "Getting schedule {} failed with status {}",
+                schedule_id,
+                response.status()
+            );
+            return Err(schedule_error(&response));
+        }
+
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+
+    /// delete_schedule deletes the schedule, the messages already published are still delivered.
+    /// A schedule that does not exist returns a [`QStashError::ScheduleError`] with the 404 status.
+    pub async fn delete_schedule(&self, schedule_id: &str) -> Result<(), QStashError> {
+        let path = match self.api_url(&["schedules", schedule_id]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        let response = match self.execute(self.request(Method::DELETE, path)).await {
+            Ok(r) => {
+                log_response("delete_schedule", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!(
+                "Deleting schedule {} failed with status {}",
+                schedule_id,
+                response.status()
+            );
+            return Err(schedule_error(&response));
+        }
+
+        Ok(())
+    }
+
+    /// pause_schedule pauses the schedule until it is resumed, it keeps its id and options.
+    pub async fn pause_schedule(&self, schedule_id: &str) -> Result<(), QStashError> {
+        self.send_schedule_action(schedule_id, "pause", "pause_schedule")
+            .await
+    }
+
+    /// resume_schedule resumes a paused schedule.
+    pub async fn resume_schedule(&self, schedule_id: &str) -> Result<(), QStashError> {
+        self.send_schedule_action(schedule_id, "resume", "resume_schedule")
+            .await
+    }
+
+    /// send_schedule_action sends the action, such as pause, to the schedule.
+    async fn send_schedule_action(
+        &self,
+        schedule_id: &str,
+        action: &str,
+        endpoint_name: &'static str,
+    ) -> Result<(), QStashError> {
+        let path = match self.api_url(&["schedules", schedule_id, action]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        let response = match self.execute(self.request(Method::POST, path)).await {
+            Ok(r) => {
+                log_response(endpoint_name, &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!(
+                "Sending {} to schedule {} failed with status {}",
+                action,
+                schedule_id,
+                response.status()
+            );
+            return Err(schedule_error(&response));
+        }
+
+        Ok(())
+    }
+
+    /// list_schedules_matching retrieves the schedules passing the filters of the request.
+    pub async fn list_schedules_matching(
+        &self,
+        request: ListSchedulesRequest,
+    ) -> Result<Vec<Schedule>, QStashError> {
+        let mut schedules = self.list_schedules().await?;
+        schedules.retain(|s| request.matches(s));
+        Ok(schedules)
+    }
+
+    /// find_schedules_for retrieves the schedules sending to the url or topic,
+    /// see [`ListSchedulesRequest::destination`].
+    pub async fn find_schedules_for(
+        &self,
+        url_or_topic: &str,
+    ) -> Result<Vec<Schedule>, QStashError> {
+        self.list_schedules_matching(ListSchedulesRequest {
+            destination: Some(url_or_topic.to_string()),
+        })
+        .await
+    }
+
+    /// reconcile_schedules makes the existing schedules match the desired ones, see
+    /// [`plan_schedules`] for the matching.
+    /// The missing schedules are created before the others are deleted, the first failure
+    /// is returned and reconciling again resumes from there.
+    pub async fn reconcile_schedules(
+        &self,
+        desired: Vec<CreateScheduleRequest>,
+    ) -> Result<ReconcileReport, QStashError> {
+        let existing = self.list_schedules().await?;
+        let plan = plan_schedules(desired, &existing);
+
+        let mut created = Vec::with_capacity(plan.create.len());
+        for request in plan.create {
+            created.push(self.create_schedule(request).await?.schedule_id);
+        }
+        for schedule_id in &plan.delete {
+            self.delete_schedule(schedule_id).await?;
+        }
+
+        Ok(ReconcileReport {
+            created,
+            deleted: plan.delete,
+            unchanged: plan.unchanged,
+        })
+    }
+
+    /// list_schedules retrieves every schedule.
+    pub async fn list_schedules(&self) -> Result<Vec<Schedule>, QStashError> {
+        let path = match self.base_url.join(&format!("/{}/schedules", self.version)) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
+            Ok(r) => {
+                log_response("list_schedules", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!("Listing schedules failed with status {}", response.status());
+            return Err(schedule_error(&response));
+        }
+
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::ScheduleError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+}
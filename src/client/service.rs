@@ -0,0 +1,82 @@
+//! # service module
+//! This module contains a [`tower::Service`](tower_service::Service) implementation
+//! for publishing messages, so the client can be composed with tower middleware
+//! such as timeouts, concurrency limits or load shedding.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use tokio::time::Sleep;
+use tower_service::Service;
+
+use super::{error::QStashError, Client, PublishRequest, QstashResponse};
+
+/// A tower service that publishes messages to the QStash API.
+/// The service is not ready while the rate limiter of the client has no token available.
+///
+/// # Example
+/// ```
+/// use qstash_rs::client::{Client, QstashPublishService};
+///
+/// let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+/// let service = tower::ServiceBuilder::new()
+///     .concurrency_limit(10)
+///     .service(QstashPublishService::new(qstash_client));
+/// ```
+pub struct QstashPublishService {
+    client: Arc<Client>,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl QstashPublishService {
+    /// Initialize a new publish service from a client.
+    pub fn new(client: impl Into<Arc<Client>>) -> Self {
+        Self {
+            client: client.into(),
+            sleep: None,
+        }
+    }
+}
+
+/// The clone waits for the rate limiter on its own.
+impl Clone for QstashPublishService {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            sleep: None,
+        }
+    }
+}
+
+impl Service<PublishRequest<Bytes>> for QstashPublishService {
+    type Response = Vec<QstashResponse>;
+    type Error = QStashError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        loop {
+            if let Some(sleep) = self.sleep.as_mut() {
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                self.sleep = None;
+            }
+
+            let wait = self.client.throttle_delay(false);
+            if wait.is_zero() {
+                return Poll::Ready(Ok(()));
+            }
+            self.sleep = Some(Box::pin(tokio::time::sleep(wait)));
+        }
+    }
+
+    fn call(&mut self, request: PublishRequest<Bytes>) -> Self::Future {
+        let client = self.client.clone();
+        Box::pin(async move { client.publish(request).await })
+    }
+}
@@ -0,0 +1,100 @@
+//! # signing module
+//! This module contains the client side signing of payloads.
+//! The HMAC-SHA256 of the exact bytes published is forwarded to the destination in a header,
+//! so destinations without QStash signature verification can check where a message comes from
+//! with their existing webhook tooling.
+//! The receiving side is [`verify_payload_signature`](crate::server::verify_payload_signature).
+
+use std::fmt;
+
+use hmac::{Hmac, Mac};
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use sha2::Sha256;
+
+use super::{error::QStashError, Client};
+use crate::log;
+
+/// The secret and the header used to sign the payloads.
+#[derive(Clone)]
+pub struct PayloadSigning {
+    secret: String,
+    header: HeaderName,
+}
+
+impl PayloadSigning {
+    /// Creates a new [`PayloadSigning`] sending the signature to the destination in the header,
+    /// such as `X-Signature`.
+    /// It is forwarded by QStash as an `Upstash-Forward-` header.
+    pub fn new(secret: &str, header_name: &str) -> Result<Self, QStashError> {
+        let header =
+            match HeaderName::from_bytes(format!("Upstash-Forward-{}", header_name).as_bytes()) {
+                Ok(h) => h,
+                Err(e) => {
+                    return Err(QStashError::ConfigError {
+                        field: "header_name".to_string(),
+                        reason: e.to_string(),
+                    })
+                }
+            };
+        Ok(Self {
+            secret: secret.to_string(),
+            header,
+        })
+    }
+
+    /// sign returns the lowercase hex HMAC-SHA256 of the body.
+    pub fn sign(&self, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC can take a key of any size");
+        mac.update(body);
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+impl fmt::Debug for PayloadSigning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PayloadSigning")
+            .field("header", &self.header)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Sign the body of every published message, including batch entries, and forward
+    /// the signature to the destination.
+    /// The signature is computed over the exact bytes sent, after serialization and any
+    /// compression done by the caller.
+    /// The default is no signing.
+    pub fn payload_signing(mut self, signing: PayloadSigning) -> Self {
+        self.payload_signing = Some(signing);
+        self
+    }
+
+    /// sign_payload adds the signature of the body to the headers if signing is enabled.
+    pub(crate) fn sign_payload(&self, headers: &mut HeaderMap, body: &[u8]) {
+        let signing = match &self.payload_signing {
+            Some(s) => s,
+            None => return,
+        };
+        match HeaderValue::from_str(&signing.sign(body)) {
+            Ok(v) => {
+                headers.insert(signing.header.clone(), v);
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+            }
+        }
+    }
+
+    /// payload_signature returns the forwarded header and the signature of the body
+    /// if signing is enabled.
+    pub(crate) fn payload_signature(&self, body: &[u8]) -> Option<(String, String)> {
+        let signing = self.payload_signing.as_ref()?;
+        Some((signing.header.to_string(), signing.sign(body)))
+    }
+}
@@ -0,0 +1,211 @@
+//! # sink module
+//! This module contains the [`PublishSink`], a [`futures::Sink`] that publishes a
+//! stream of messages through a [`BatchingPublisher`].
+
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{future::BoxFuture, stream::FuturesUnordered, FutureExt, Sink, StreamExt};
+use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use serde::Serialize;
+
+use super::{
+    batch::BatchEntry, batching::BatchingPublisher, error::QStashError, PublishRequestUrl,
+    QstashResponse,
+};
+use crate::log;
+
+/// The error returned by a [`PublishSink`].
+/// It contains the position of the failed item in the stream, starting at 0.
+#[derive(Debug, Clone)]
+pub struct PublishSinkError {
+    pub index: usize,
+    pub error: QStashError,
+}
+
+impl fmt::Display for PublishSinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Item {} failed: {}", self.index, self.error)
+    }
+}
+
+impl std::error::Error for PublishSinkError {}
+
+type InFlight = BoxFuture<'static, (usize, Result<Vec<QstashResponse>, QStashError>)>;
+
+/// A sink that publishes the items sent to it through a [`BatchingPublisher`].
+///
+/// It applies backpressure once too many items are awaiting their response.
+/// Flushing the sink publishes the pending items right away and waits for all of them,
+/// closing it does the same without shutting down the publisher.
+///
+/// Items can be sent as a [`BatchEntry`] or as a destination and a serializable body,
+/// which is sent as JSON.
+///
+/// # Example
+/// ```no_run
+/// use futures::{stream, StreamExt};
+/// use qstash_rs::client::{
+///     batching::{BatchingConfig, BatchingPublisher},
+///     Client, PublishRequestUrl,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let qstash_client = Client::new("<QSTASH_TOKEN>", None, None).expect("Could not create client");
+///     let publisher = BatchingPublisher::spawn(qstash_client, BatchingConfig::default());
+///     let url: url::Url = "https://example.com".parse().expect("Could not parse URL");
+///
+///     let result = stream::iter(0..1000)
+///         .map(|i| Ok((PublishRequestUrl::Url(url.clone()), i)))
+///         .forward(publisher.sink(4))
+///         .await;
+///
+///     match result {
+///         Ok(()) => println!("Published every item"),
+///         Err(err) => println!("{}", err),
+///     };
+/// }
+/// ```
+pub struct PublishSink {
+    publisher: BatchingPublisher,
+    max_in_flight: usize,
+    in_flight: FuturesUnordered<InFlight>,
+    next_index: usize,
+    flush_requested: bool,
+}
+
+impl PublishSink {
+    pub(crate) fn new(publisher: BatchingPublisher, max_in_flight: usize) -> Self {
+        Self {
+            publisher,
+            max_in_flight,
+            in_flight: FuturesUnordered::new(),
+            next_index: 0,
+            flush_requested: false,
+        }
+    }
+
+    /// poll_in_flight collects the completed items and returns the first error found.
+    /// It is ready once at most `limit` items are still in flight.
+    fn poll_in_flight(
+        &mut self,
+        cx: &mut Context<'_>,
+        limit: usize,
+    ) -> Poll<Result<(), PublishSinkError>> {
+        loop {
+            match self.in_flight.poll_next_unpin(cx) {
+                Poll::Ready(Some((_, Ok(_)))) => continue,
+                Poll::Ready(Some((index, Err(error)))) => {
+                    return Poll::Ready(Err(PublishSinkError { index, error }))
+                }
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending if self.in_flight.len() > limit => return Poll::Pending,
+                Poll::Pending => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+
+    /// poll_drain publishes the pending items and waits for every one of them.
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), PublishSinkError>> {
+        if !self.in_flight.is_empty() && !self.flush_requested {
+            self.publisher.request_flush();
+            self.flush_requested = true;
+        }
+
+        let result = futures::ready!(self.poll_in_flight(cx, 0));
+        self.flush_requested = false;
+        Poll::Ready(result)
+    }
+
+    /// send_entry hands the entry to the publisher.
+    fn send_entry(&mut self, entry: BatchEntry) -> Result<(), PublishSinkError> {
+        let index = self.next_index;
+        self.next_index += 1;
+
+        let receiver = match self.publisher.submit(entry) {
+            Ok(r) => r,
+            Err(error) => return Err(PublishSinkError { index, error }),
+        };
+
+        self.in_flight.push(
+            receiver
+                .map(move |r| match r {
+                    Ok(r) => (index, r),
+                    Err(_) => {
+                        log::error!("Batching publisher stopped before publishing the entry");
+                        (index, Err(QStashError::BatchError))
+                    }
+                })
+                .boxed(),
+        );
+        Ok(())
+    }
+}
+
+impl Sink<BatchEntry> for PublishSink {
+    type Error = PublishSinkError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let limit = self.max_in_flight - 1;
+        self.poll_in_flight(cx, limit)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: BatchEntry) -> Result<(), Self::Error> {
+        self.send_entry(item)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_drain(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_drain(cx)
+    }
+}
+
+impl<T: Serialize> Sink<(PublishRequestUrl, T)> for PublishSink {
+    type Error = PublishSinkError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Sink::<BatchEntry>::poll_ready(self, cx)
+    }
+
+    fn start_send(
+        mut self: Pin<&mut Self>,
+        (destination, body): (PublishRequestUrl, T),
+    ) -> Result<(), Self::Error> {
+        let body = match serde_json::to_string(&body) {
+            Ok(b) => b,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                let index = self.next_index;
+                self.next_index += 1;
+                return Err(PublishSinkError {
+                    index,
+                    error: QStashError::BatchError,
+                });
+            }
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        let mut entry = BatchEntry::new(destination);
+        entry.body = Some(body);
+        entry.headers = Some(headers);
+        self.send_entry(entry)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Sink::<BatchEntry>::poll_flush(self, cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Sink::<BatchEntry>::poll_close(self, cx)
+    }
+}
@@ -0,0 +1,277 @@
+//! # stats module
+//! This module contains the delivery statistics computed from the events log.
+//! The events of every message are correlated by message id, from its CREATED
+//! event to its DELIVERED, FAILED or CANCELED event.
+//! It also contains the counts of the events of every state, see [`state_counts`].
+
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::{future, TryStreamExt};
+
+use super::{
+    error::QStashError,
+    events::{Event, EventRequest, State},
+    Client,
+};
+use crate::log;
+
+/// The maximum amount of event pages fetched by [`Client::delivery_stats`].
+const MAX_PAGES: usize = 1000;
+
+/// Restricts the statistics to the messages sent to a destination.
+/// Every field that is set must match.
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryFilter {
+    pub url: Option<String>,
+    pub topic_name: Option<String>,
+    pub endpoint_name: Option<String>,
+}
+
+impl DeliveryFilter {
+    /// matches returns true if the event belongs to the destination of the filter.
+    pub fn matches(&self, event: &Event) -> bool {
+        fn field_matches(expected: &Option<String>, actual: &Option<String>) -> bool {
+            match expected {
+                Some(expected) => actual.as_ref() == Some(expected),
+                None => true,
+            }
+        }
+
+        field_matches(&self.url, &event.url)
+            && field_matches(&self.topic_name, &event.topic_name)
+            && field_matches(&self.endpoint_name, &event.endpoint_name)
+    }
+}
+
+/// The time window of the statistics.
+/// Unix timestamps in milliseconds, the start is inclusive and the end exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl TimeWindow {
+    /// Creates a new [`TimeWindow`] from unix timestamps in milliseconds.
+    pub fn new(start: u64, end: u64) -> Self {
+        Self { start, end }
+    }
+
+    /// Creates a new [`TimeWindow`] ending now.
+    pub fn last(duration: Duration) -> Self {
+        let end = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Self {
+            start: end.saturating_sub(duration.as_millis() as u64),
+            end,
+        }
+    }
+
+    /// contains returns true if the timestamp is within the window.
+    pub fn contains(&self, time: u64) -> bool {
+        self.start <= time && time < self.end
+    }
+}
+
+/// The delivery statistics of the messages created within a time window.
+/// Events after the end of the window are ignored, so a message delivered after
+/// the window is counted as in flight.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeliveryStats {
+    /// The amount of messages created within the window.
+    pub messages: usize,
+    pub delivered: usize,
+    pub failed: usize,
+    pub canceled: usize,
+    /// The amount of messages without a final state at the end of the window.
+    pub in_flight: usize,
+    /// The amount of messages that were retried at least once.
+    pub retried_messages: usize,
+    /// The total amount of retries.
+    pub retries: usize,
+    /// The latency percentiles from creation to delivery of the delivered messages.
+    pub p50: Option<Duration>,
+    pub p95: Option<Duration>,
+    pub p99: Option<Duration>,
+}
+
+/// The outcome of a single message.
+#[derive(Default)]
+struct MessageOutcome {
+    created: Option<u64>,
+    delivered: Option<u64>,
+    failed: bool,
+    canceled: bool,
+    retries: usize,
+}
+
+impl DeliveryStats {
+    /// Compute the statistics from events in any order.
+    /// Messages whose CREATED event is missing or outside of the window are ignored.
+    pub fn from_events<'a>(
+        events: impl IntoIterator<Item = &'a Event>,
+        window: TimeWindow,
+    ) -> Self {
+        let mut outcomes: HashMap<&str, MessageOutcome> = HashMap::new();
+        for event in events {
+            if event.time >= window.end {
+                continue;
+            }
+
+            let outcome = outcomes.entry(event.message_id.as_str()).or_default();
+            match event.state {
+                State::CREATED => {
+                    outcome.created =
+                        Some(outcome.created.map_or(event.time, |t| t.min(event.time)))
+                }
+                State::DELIVERED => {
+                    outcome.delivered =
+                        Some(outcome.delivered.map_or(event.time, |t| t.min(event.time)))
+                }
+                State::FAILED => outcome.failed = true,
+                State::CANCELED => outcome.canceled = true,
+                State::RETRY => outcome.retries += 1,
+                State::ACTIVE | State::ERROR => {}
+            }
+        }
+
+        let mut stats = DeliveryStats::default();
+        let mut latencies = Vec::new();
+        for outcome in outcomes.values() {
+            let created = match outcome.created {
+                Some(created) if window.contains(created) => created,
+                _ => continue,
+            };
+
+            stats.messages += 1;
+            stats.retries += outcome.retries;
+            if outcome.retries > 0 {
+                stats.retried_messages += 1;
+            }
+
+            match outcome.delivered {
+                Some(delivered) => {
+                    stats.delivered += 1;
+                    latencies.push(Duration::from_millis(delivered.saturating_sub(created)));
+                }
+                None if outcome.failed => stats.failed += 1,
+                None if outcome.canceled => stats.canceled += 1,
+                None => stats.in_flight += 1,
+            }
+        }
+
+        latencies.sort();
+        stats.p50 = percentile(&latencies, 50);
+        stats.p95 = percentile(&latencies, 95);
+        stats.p99 = percentile(&latencies, 99);
+        stats
+    }
+
+    /// success_rate returns the share of delivered messages among the messages that
+    /// reached a final state, or None if none did.
+    pub fn success_rate(&self) -> Option<f64> {
+        let completed = self.delivered + self.failed + self.canceled;
+        match completed {
+            0 => None,
+            _ => Some(self.delivered as f64 / completed as f64),
+        }
+    }
+
+    /// retry_rate returns the share of messages that were retried at least once,
+    /// or None if there are no messages.
+    pub fn retry_rate(&self) -> Option<f64> {
+        match self.messages {
+            0 => None,
+            _ => Some(self.retried_messages as f64 / self.messages as f64),
+        }
+    }
+}
+
+/// state_counts counts the events of every state, the states without events are missing.
+pub fn state_counts<'a>(events: impl IntoIterator<Item = &'a Event>) -> HashMap<State, u64> {
+    let mut counts = HashMap::new();
+    for event in events {
+        count_state(&mut counts, event);
+    }
+    counts
+}
+
+fn count_state(counts: &mut HashMap<State, u64>, event: &Event) {
+    *counts.entry(event.state).or_default() += 1;
+}
+
+/// percentile returns the nearest rank percentile of sorted values.
+fn percentile(sorted: &[Duration], percentile: usize) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percentile * sorted.len()).div_ceil(100);
+    Some(sorted[rank.saturating_sub(1)])
+}
+
+impl Client {
+    /// delivery_stats computes the delivery statistics of the messages created within
+    /// the window and sent to the destination of the filter.
+    ///
+    /// The events are paged from the newest until the window is covered.
+    pub async fn delivery_stats(
+        &self,
+        filter: DeliveryFilter,
+        window: TimeWindow,
+    ) -> Result<DeliveryStats, QStashError> {
+        let mut events = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for _ in 0..MAX_PAGES {
+            let query: Vec<(&str, String)> = cursor.map(|c| ("cursor", c)).into_iter().collect();
+            let page = self.fetch_events(&query, false).await?;
+
+            let oldest = page.events.iter().map(|e| e.time).min();
+            events.extend(page.events.into_iter().filter(|e| filter.matches(e)));
+
+            cursor = match (page.cursor, oldest) {
+                (Some(c), Some(oldest)) if oldest >= window.start && !c.is_empty() => Some(c),
+                _ => break,
+            };
+        }
+
+        Ok(DeliveryStats::from_events(&events, window))
+    }
+
+    /// event_state_counts counts the events of every state between `from` and `to`,
+    /// unix timestamps in milliseconds, see [`state_counts`].
+    ///
+    /// Every page of events within the window is fetched, one page at a time.
+    pub async fn event_state_counts(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<HashMap<State, u64>, QStashError> {
+        if from > to {
+            let formated_string = format!("Invalid window from {} to {}", from, to);
+            log::error!(formated_string);
+            return Err(QStashError::EventError {
+                status: None,
+                cause: formated_string,
+                body: None,
+            });
+        }
+
+        let request = EventRequest {
+            from_date: Some(from),
+            to_date: Some(to),
+            ..Default::default()
+        };
+        self.events_stream(Some(request))
+            .try_fold(HashMap::new(), |mut counts, event| {
+                count_state(&mut counts, &event);
+                future::ready(Ok(counts))
+            })
+            .await
+    }
+}
@@ -0,0 +1,73 @@
+//! # telemetry module
+//! This module contains the telemetry headers sent along with every request, the same
+//! ones as the official SDKs, so Upstash can see the versions of the clients.
+
+use http::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    Method,
+};
+use url::Url;
+
+use super::{Client, RequestParts};
+use crate::log;
+
+/// The telemetry headers of a client.
+#[derive(Clone)]
+pub(crate) struct Telemetry {
+    headers: HeaderMap,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Upstash-Telemetry-Sdk",
+            HeaderValue::from_static(concat!("qstash-rs/", env!("CARGO_PKG_VERSION"))),
+        );
+        headers.insert(
+            "Upstash-Telemetry-Runtime",
+            HeaderValue::from_static(concat!("rust/", env!("QSTASH_RUSTC_VERSION"))),
+        );
+        Self { headers }
+    }
+}
+
+impl Client {
+    /// Stop sending the `Upstash-Telemetry-*` headers along with the requests.
+    /// The default is to send them.
+    pub fn disable_telemetry(mut self) -> Self {
+        self.telemetry = None;
+        self
+    }
+
+    /// Set the framework sent in the `Upstash-Telemetry-Framework` header, such as `axum`.
+    /// It is ignored if telemetry is disabled or the framework is not a valid header value.
+    pub fn telemetry_framework(mut self, framework: &str) -> Self {
+        let value = match HeaderValue::from_str(framework) {
+            Ok(v) => v,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return self;
+            }
+        };
+        if let Some(telemetry) = self.telemetry.as_mut() {
+            telemetry
+                .headers
+                .insert("Upstash-Telemetry-Framework", value);
+        }
+        self
+    }
+
+    /// request starts a request to QStash, with the authorization and telemetry headers.
+    pub(crate) fn request(&self, method: Method, url: Url) -> RequestParts {
+        let mut request = RequestParts::new(method, url);
+        request
+            .headers
+            .insert(AUTHORIZATION, self.authorization.clone());
+        match &self.telemetry {
+            Some(telemetry) => request.headers(telemetry.headers.clone()),
+            None => request,
+        }
+    }
+}
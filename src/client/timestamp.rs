@@ -0,0 +1,206 @@
+//! # timestamp module
+//! This module contains the conversions between the unix timestamps of the QStash API and
+//! the date time types of the `chrono` and `time` features.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use serde::Serialize;
+
+use super::{
+    error::QStashError,
+    events::{Event, EventRequest},
+    Client, PublishOptions, PublishRequest, PublishRequestUrl, QstashResponse,
+};
+use crate::log;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A point in time a message can be delivered at.
+/// It is implemented for [`SystemTime`], `chrono::DateTime` with the `chrono` feature and
+/// `time::OffsetDateTime` with the `time` feature.
+pub trait Timestamp: sealed::Sealed {
+    /// unix_timestamp returns the seconds since the unix epoch.
+    fn unix_timestamp(&self) -> i64;
+
+    /// unix_timestamp_millis returns the milliseconds since the unix epoch.
+    fn unix_timestamp_millis(&self) -> i64 {
+        self.unix_timestamp().saturating_mul(1000)
+    }
+}
+
+impl sealed::Sealed for SystemTime {}
+
+impl Timestamp for SystemTime {
+    fn unix_timestamp(&self) -> i64 {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        }
+    }
+
+    fn unix_timestamp_millis(&self) -> i64 {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_millis() as i64,
+            Err(e) => -(e.duration().as_millis() as i64),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> sealed::Sealed for chrono::DateTime<Tz> {}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Timestamp for chrono::DateTime<Tz> {
+    fn unix_timestamp(&self) -> i64 {
+        self.timestamp()
+    }
+
+    fn unix_timestamp_millis(&self) -> i64 {
+        self.timestamp_millis()
+    }
+}
+
+#[cfg(feature = "time")]
+impl sealed::Sealed for time::OffsetDateTime {}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::OffsetDateTime {
+    fn unix_timestamp(&self) -> i64 {
+        time::OffsetDateTime::unix_timestamp(*self)
+    }
+
+    fn unix_timestamp_millis(&self) -> i64 {
+        (self.unix_timestamp_nanos() / 1_000_000) as i64
+    }
+}
+
+/// not_before_seconds converts the timestamp to the unix seconds of the Upstash-Not-Before header.
+fn not_before_seconds(at: &impl Timestamp) -> Result<u32, QStashError> {
+    let seconds = at.unix_timestamp();
+    match u32::try_from(seconds) {
+        Ok(s) => Ok(s),
+        Err(_) => {
+            log::error!("Invalid not before timestamp {}", seconds);
+            Err(QStashError::PublishError)
+        }
+    }
+}
+
+/// filter_millis converts the timestamp to the unix milliseconds of a date filter of the events.
+fn filter_millis(at: &impl Timestamp, filter: &str) -> Result<u64, QStashError> {
+    let millis = at.unix_timestamp_millis();
+    match u64::try_from(millis) {
+        Ok(m) => Ok(m),
+        Err(_) => {
+            let formated_string = format!("Invalid {} timestamp {}", filter, millis);
+            log::error!(formated_string);
+            Err(QStashError::EventError {
+                status: None,
+                cause: formated_string,
+                body: None,
+            })
+        }
+    }
+}
+
+impl<T: Into<Bytes>> PublishRequest<T> {
+    /// with_not_before sets the time the message will not be delivered before.
+    pub fn with_not_before(mut self, at: impl Timestamp) -> Result<Self, QStashError> {
+        self.not_before = Some(not_before_seconds(&at)?);
+        Ok(self)
+    }
+}
+
+impl PublishOptions {
+    /// with_not_before sets the time the message will not be delivered before.
+    pub fn with_not_before(mut self, at: impl Timestamp) -> Result<Self, QStashError> {
+        self.not_before = Some(not_before_seconds(&at)?);
+        Ok(self)
+    }
+}
+
+impl EventRequest {
+    /// with_from_date sets the time the events are filtered from.
+    pub fn with_from_date(mut self, at: impl Timestamp) -> Result<Self, QStashError> {
+        self.from_date = Some(filter_millis(&at, "from date")?);
+        Ok(self)
+    }
+
+    /// with_to_date sets the time the events are filtered to.
+    pub fn with_to_date(mut self, at: impl Timestamp) -> Result<Self, QStashError> {
+        self.to_date = Some(filter_millis(&at, "to date")?);
+        Ok(self)
+    }
+}
+
+/// millis_utc converts unix milliseconds, saturating at the maximum date chrono can represent.
+#[cfg(feature = "chrono")]
+fn millis_utc(millis: u64) -> chrono::DateTime<chrono::Utc> {
+    i64::try_from(millis)
+        .ok()
+        .and_then(chrono::DateTime::from_timestamp_millis)
+        .unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC)
+}
+
+/// millis_odt converts unix milliseconds, saturating at the maximum date time can represent.
+#[cfg(feature = "time")]
+fn millis_odt(millis: u64) -> time::OffsetDateTime {
+    time::OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000)
+        .unwrap_or(time::PrimitiveDateTime::MAX.assume_utc())
+}
+
+impl Event {
+    /// time_utc returns the time of the event.
+    /// It saturates at the maximum date chrono can represent.
+    #[cfg(feature = "chrono")]
+    pub fn time_utc(&self) -> chrono::DateTime<chrono::Utc> {
+        millis_utc(self.time)
+    }
+
+    /// next_delivery_utc returns the time of the next delivery of the message, if any.
+    /// It saturates at the maximum date chrono can represent.
+    #[cfg(feature = "chrono")]
+    pub fn next_delivery_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.next_delivery_time.map(millis_utc)
+    }
+
+    /// time_odt returns the time of the event.
+    /// It saturates at the maximum date time can represent.
+    #[cfg(feature = "time")]
+    pub fn time_odt(&self) -> time::OffsetDateTime {
+        millis_odt(self.time)
+    }
+
+    /// next_delivery_odt returns the time of the next delivery of the message, if any.
+    /// It saturates at the maximum date time can represent.
+    #[cfg(feature = "time")]
+    pub fn next_delivery_odt(&self) -> Option<time::OffsetDateTime> {
+        self.next_delivery_time.map(millis_odt)
+    }
+}
+
+impl Client {
+    /// publish_at publishes a message that will not be delivered before the given time.
+    pub async fn publish_at<T: Into<Bytes>>(
+        &self,
+        request: PublishRequest<T>,
+        at: impl Timestamp,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        self.publish(request.with_not_before(at)?).await
+    }
+
+    /// publish_json_at publishes a JSON message that will not be delivered before the given time.
+    pub async fn publish_json_at<T: Serialize>(
+        &self,
+        url: PublishRequestUrl,
+        body: T,
+        at: impl Timestamp,
+        options: Option<PublishOptions>,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        let options = options.unwrap_or_default().with_not_before(at)?;
+        self.publish_json(url, body, Some(options)).await
+    }
+}
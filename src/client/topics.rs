@@ -0,0 +1,434 @@
+//! # topics module
+//! This module contains the topics functionality of the QStash client.
+//! A topic, also called URL group, sends every message published to it to all of its endpoints.
+
+use std::time::Instant;
+
+use http::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::QStashError,
+    events::{Event, State},
+    logging::log_response,
+    Client, PublishOptions, PublishRequestUrl, QstashResponse, ResponseParts,
+};
+use crate::log;
+
+/// An endpoint of a topic.
+/// To remove an endpoint by name only, leave the url empty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicEndpoint {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+}
+
+/// A topic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Topic {
+    pub name: String,
+    /// Unix timestamp in milliseconds.
+    pub created_at: Option<u64>,
+    /// Unix timestamp in milliseconds.
+    pub updated_at: Option<u64>,
+    #[serde(default)]
+    pub endpoints: Vec<TopicEndpoint>,
+}
+
+impl From<Topic> for PublishRequestUrl {
+    fn from(topic: Topic) -> Self {
+        PublishRequestUrl::Topic(topic.name)
+    }
+}
+
+impl From<&Topic> for PublishRequestUrl {
+    fn from(topic: &Topic) -> Self {
+        PublishRequestUrl::Topic(topic.name.clone())
+    }
+}
+
+/// The maximum length of a topic name accepted by the client.
+pub const MAX_TOPIC_NAME_LENGTH: usize = 256;
+
+/// validate_topic_name returns an error if the name is empty, too long or contains
+/// characters other than ASCII letters, digits, `-`, `_` and `.`.
+pub fn validate_topic_name(name: &str) -> Result<(), QStashError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_TOPIC_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        log::error!("Invalid topic name {:?}", name);
+        return Err(QStashError::InvalidTopicName(name.to_string()));
+    }
+    Ok(())
+}
+
+/// topic_error returns the error of a topic request the API answered with an error status.
+fn topic_error(response: &ResponseParts) -> QStashError {
+    QStashError::TopicError {
+        status: Some(response.status().as_u16()),
+        message: response.error_message(),
+    }
+}
+
+/// The delivery counts of an endpoint of a topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointHealth {
+    pub endpoint: TopicEndpoint,
+    pub delivered: usize,
+    pub failed: usize,
+}
+
+/// The endpoints of a topic with their delivery counts over a page of events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicOverview {
+    pub name: String,
+    pub endpoints: Vec<EndpointHealth>,
+}
+
+impl TopicOverview {
+    /// from_events counts the delivered and failed events of every endpoint of the topic.
+    /// An event belongs to an endpoint by endpoint name, or by url if either has no name.
+    /// Events of other topics and of unknown endpoints are ignored.
+    pub fn from_events(topic: &Topic, events: &[Event]) -> Self {
+        let mut endpoints: Vec<EndpointHealth> = topic
+            .endpoints
+            .iter()
+            .map(|endpoint| EndpointHealth {
+                endpoint: endpoint.clone(),
+                delivered: 0,
+                failed: 0,
+            })
+            .collect();
+
+        for event in events {
+            if event.group_name() != Some(topic.name.as_str()) {
+                continue;
+            }
+            let health =
+                endpoints
+                    .iter_mut()
+                    .find(|h| match (&h.endpoint.name, &event.endpoint_name) {
+                        (Some(name), Some(event_name)) => name == event_name,
+                        _ => event.url.as_deref() == Some(h.endpoint.url.as_str()),
+                    });
+            match (health, event.state) {
+                (Some(h), State::DELIVERED) => h.delivered += 1,
+                (Some(h), State::FAILED) => h.failed += 1,
+                _ => {}
+            }
+        }
+
+        Self {
+            name: topic.name.clone(),
+            endpoints,
+        }
+    }
+}
+
+/// The body of the endpoints requests.
+#[derive(Serialize)]
+struct EndpointsRequest<'a> {
+    endpoints: &'a [TopicEndpoint],
+}
+
+impl Client {
+    /// Send the topic requests to the `/url-groups` endpoints, the new name of topics,
+    /// instead of the `/topics` endpoints.
+    /// The default is false.
+    pub fn url_groups(mut self, enabled: bool) -> Self {
+        self.url_groups = enabled;
+        self
+    }
+
+    /// topics_path returns the path segment of the topic endpoints.
+    fn topics_path(&self) -> &'static str {
+        if self.url_groups {
+            "url-groups"
+        } else {
+            "topics"
+        }
+    }
+
+    /// list_topics retrieves every topic.
+    pub async fn list_topics(&self) -> Result<Vec<Topic>, QStashError> {
+        let path = match self.api_url(&[self.topics_path()]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
+            Ok(r) => {
+                log_response("list_topics", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!("Listing topics failed with status {}", response.status());
+            return Err(topic_error(&response));
+        }
+
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+
+    /// get_topic retrieves the topic with the name.
+    /// It returns [`QStashError::TopicNotFound`] if the topic does not exist.
+    pub async fn get_topic(&self, name: &str) -> Result<Topic, QStashError> {
+        validate_topic_name(name)?;
+        let path = match self.api_url(&[self.topics_path(), name]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let started = Instant::now();
+        let response = match self.send_read(self.request(Method::GET, path)).await {
+            Ok(r) => {
+                log_response("get_topic", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(QStashError::TopicNotFound(name.to_string()));
+        }
+        if !response.status().is_success() {
+            log::error!(
+                "Getting topic {} failed with status {}",
+                name,
+                response.status()
+            );
+            return Err(topic_error(&response));
+        }
+
+        match response.json() {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                })
+            }
+        }
+    }
+
+    /// get_topic_with_stats retrieves the topic and the last page of its events, and counts
+    /// the delivered and failed events of every endpoint, see [`TopicOverview::from_events`].
+    pub async fn get_topic_with_stats(&self, name: &str) -> Result<TopicOverview, QStashError> {
+        let topic = self.get_topic(name).await?;
+        let events = self
+            .fetch_events(&[("topicName", name.to_string())], false)
+            .await?;
+        Ok(TopicOverview::from_events(&topic, &events.events))
+    }
+
+    /// publish_to_topic publishes the body serialized as JSON to every endpoint of the topic,
+    /// returning a response per endpoint.
+    pub async fn publish_to_topic<T: Serialize>(
+        &self,
+        topic: &Topic,
+        body: T,
+        options: Option<PublishOptions>,
+    ) -> Result<Vec<QstashResponse>, QStashError> {
+        self.publish_json(topic.into(), body, options).await
+    }
+
+    /// upsert_topic_endpoints adds the endpoints to the topic, creating it if it does not exist.
+    pub async fn upsert_topic_endpoints(
+        &self,
+        topic_name: &str,
+        endpoints: Vec<TopicEndpoint>,
+    ) -> Result<(), QStashError> {
+        self.send_topic_endpoints(
+            Method::POST,
+            "upsert_topic_endpoints",
+            topic_name,
+            &endpoints,
+        )
+        .await
+    }
+
+    /// remove_topic_endpoints removes the endpoints from the topic, matched by name or url,
+    /// and returns the topic with its remaining endpoints.
+    /// Removing the last endpoint of a topic deletes it, None is returned then.
+    pub async fn remove_topic_endpoints(
+        &self,
+        topic_name: &str,
+        endpoints: Vec<TopicEndpoint>,
+    ) -> Result<Option<Topic>, QStashError> {
+        self.send_topic_endpoints(
+            Method::DELETE,
+            "remove_topic_endpoints",
+            topic_name,
+            &endpoints,
+        )
+        .await?;
+
+        match self.get_topic(topic_name).await {
+            Ok(topic) => Ok(Some(topic)),
+            Err(QStashError::TopicNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// delete_topic deletes the topic with all of its endpoints.
+    /// A topic that does not exist returns a [`QStashError::TopicError`] with the 404 status.
+    pub async fn delete_topic(&self, name: &str) -> Result<(), QStashError> {
+        validate_topic_name(name)?;
+        let path = match self.api_url(&[self.topics_path(), name]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        let response = match self.execute(self.request(Method::DELETE, path)).await {
+            Ok(r) => {
+                log_response("delete_topic", &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!(
+                "Deleting topic {} failed with status {}",
+                name,
+                response.status()
+            );
+            return Err(topic_error(&response));
+        }
+
+        Ok(())
+    }
+
+    /// send_topic_endpoints sends the endpoints to the endpoints path of the topic.
+    async fn send_topic_endpoints(
+        &self,
+        method: Method,
+        endpoint_name: &'static str,
+        topic_name: &str,
+        endpoints: &[TopicEndpoint],
+    ) -> Result<(), QStashError> {
+        validate_topic_name(topic_name)?;
+        let path = match self.api_url(&[self.topics_path(), topic_name, "endpoints"]) {
+            Ok(p) => p,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        let request = match self
+            .request(method, path)
+            .json(&EndpointsRequest { endpoints })
+        {
+            Ok(r) => r,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        self.throttle(false).await;
+
+        let started = Instant::now();
+        let response = match self.execute(request).await {
+            Ok(r) => {
+                log_response(endpoint_name, &r, started);
+                r
+            }
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::TopicError {
+                    status: None,
+                    message: formated_string,
+                });
+            }
+        };
+
+        if !response.status().is_success() {
+            log::error!(
+                "Updating endpoints of topic {} failed with status {}",
+                topic_name,
+                response.status()
+            );
+            return Err(topic_error(&response));
+        }
+
+        Ok(())
+    }
+}
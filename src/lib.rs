@@ -59,6 +59,7 @@
 //!            content_based_deduplication: None,
 //!            retries: None,
 //!            callback: None,
+//!            failure_callback: None,
 //!            method: None,
 //!        })
 //!        .await
@@ -78,3 +79,7 @@
 //! Happy coding!
 
 pub mod client;
+mod log;
+pub mod server;
+#[cfg(feature = "opentelemetry")]
+mod trace_context;
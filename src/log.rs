@@ -0,0 +1,47 @@
+//! # log module
+//! This module contains the logging macros used across the crate.
+//! They forward to `tracing` when the `tracing` feature is enabled and compile to
+//! nothing otherwise, so the rest of the crate does not need to be `cfg` gated.
+
+/// Log an error, with the same arguments as `tracing::error!`.
+#[cfg(feature = "tracing")]
+macro_rules! qstash_error {
+    ($($arg:tt)+) => {
+        tracing::error!($($arg)+)
+    };
+}
+
+/// Log an error, with the same arguments as `tracing::error!`.
+/// The arguments are only borrowed, they are neither formatted nor logged.
+#[cfg(not(feature = "tracing"))]
+macro_rules! qstash_error {
+    ($message:literal $(, $arg:expr)* $(,)?) => {{
+        $(let _ = &$arg;)*
+    }};
+    ($value:expr) => {{
+        let _ = &$value;
+    }};
+}
+
+/// Log a debug message, with the same arguments as `tracing::debug!`.
+#[cfg(feature = "tracing")]
+macro_rules! qstash_debug {
+    ($($arg:tt)+) => {
+        tracing::debug!($($arg)+)
+    };
+}
+
+/// Log a debug message, with the same arguments as `tracing::debug!`.
+/// The arguments are only borrowed, they are neither formatted nor logged.
+#[cfg(not(feature = "tracing"))]
+macro_rules! qstash_debug {
+    ($message:literal $(, $arg:expr)* $(,)?) => {{
+        $(let _ = &$arg;)*
+    }};
+    ($value:expr) => {{
+        let _ = &$value;
+    }};
+}
+
+pub(crate) use qstash_debug as debug;
+pub(crate) use qstash_error as error;
@@ -0,0 +1,73 @@
+//! # callback module
+//! This module contains the payload QStash posts to the callback url of a message
+//! once the destination has responded.
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::{client::QStashError, log};
+
+/// The body QStash sends to a callback url.
+/// It wraps the response of the destination, the body of that response is base64 encoded.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CallbackPayload {
+    /// The status code of the response of the destination.
+    pub status: u16,
+    /// The headers of the response of the destination.
+    #[serde(default)]
+    pub header: HashMap<String, Vec<String>>,
+    /// The base64 encoded body of the response of the destination.
+    #[serde(default)]
+    pub body: String,
+    /// How many times the delivery has been retried.
+    #[serde(default)]
+    pub retried: u32,
+    pub max_retries: Option<u32>,
+    /// The id of the message the callback belongs to.
+    pub source_message_id: Option<String>,
+    pub topic_name: Option<String>,
+    pub endpoint_name: Option<String>,
+    pub url: Option<String>,
+    pub method: Option<String>,
+    /// The base64 encoded body of the message.
+    pub source_body: Option<String>,
+    pub not_before: Option<u64>,
+    pub created_at: Option<u64>,
+    pub schedule_id: Option<String>,
+    #[serde(rename = "callerIP")]
+    pub caller_ip: Option<String>,
+}
+
+impl CallbackPayload {
+    /// Parse a callback payload from the body of the request QStash sent to the callback url.
+    pub fn from_slice(body: &[u8]) -> Result<Self, QStashError> {
+        match serde_json::from_slice(body) {
+            Ok(p) => Ok(p),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::CallbackError)
+            }
+        }
+    }
+
+    /// decoded_body decodes the body of the response of the destination.
+    pub fn decoded_body(&self) -> Result<Vec<u8>, QStashError> {
+        match STANDARD.decode(&self.body) {
+            Ok(b) => Ok(b),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::CallbackError)
+            }
+        }
+    }
+
+    /// is_success returns true if the destination responded with a 2xx status code.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
@@ -0,0 +1,244 @@
+//! # dispatcher module
+//! This module contains a framework agnostic dispatcher that maps url paths to
+//! typed job handlers.
+//!
+//! The dispatcher verifies the signature, parses the [`DeliveryMetadata`],
+//! deserializes the body and invokes the handler. The [`DispatchResult`] it returns
+//! can be mapped to a response in whichever web framework you use.
+
+use std::{collections::HashMap, fmt::Display, future::Future, panic::AssertUnwindSafe, sync::Arc};
+
+use async_trait::async_trait;
+use futures::{future::BoxFuture, FutureExt};
+use http::header::HeaderMap;
+use serde::de::DeserializeOwned;
+
+use super::{DeliveryMetadata, Receiver, SIGNATURE_HEADER};
+use crate::log;
+
+/// A job handler.
+/// It receives the deserialized body of the delivery along with its metadata.
+///
+/// It is implemented for any async closure or function taking `(T, DeliveryMetadata)`
+/// and returning a `Result<(), E>` where `E` implements [`Display`].
+#[async_trait]
+pub trait JobHandler<T>: Send + Sync + 'static {
+    /// Handle a job.
+    /// Returning an error makes the delivery fail, so QStash will retry it.
+    async fn handle(&self, job: T, metadata: DeliveryMetadata) -> Result<(), String>;
+}
+
+#[async_trait]
+impl<T, F, Fut, E> JobHandler<T> for F
+where
+    T: Send + 'static,
+    F: Fn(T, DeliveryMetadata) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), E>> + Send,
+    E: Display,
+{
+    async fn handle(&self, job: T, metadata: DeliveryMetadata) -> Result<(), String> {
+        self(job, metadata).await.map_err(|e| e.to_string())
+    }
+}
+
+/// The result of dispatching a delivery.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DispatchResult {
+    /// The handler succeeded.
+    Ok,
+    /// There is no handler registered for the path.
+    NotFound,
+    /// The signature is missing or invalid.
+    Unauthorized,
+    /// The metadata or the body could not be parsed.
+    BadRequest(String),
+    /// The handler returned an error.
+    HandlerError(String),
+    /// The handler panicked.
+    Panicked,
+}
+
+impl DispatchResult {
+    /// The http status code that should be returned to QStash.
+    /// QStash retries the delivery for any status code outside of the 200-299 range.
+    pub fn status(&self) -> u16 {
+        match self {
+            DispatchResult::Ok => 200,
+            DispatchResult::NotFound => 404,
+            DispatchResult::Unauthorized => 401,
+            DispatchResult::BadRequest(_) => 400,
+            DispatchResult::HandlerError(_) => 500,
+            DispatchResult::Panicked => 500,
+        }
+    }
+}
+
+/// A type erased handler that deserializes the body and runs the job.
+type BoxedHandler =
+    Arc<dyn Fn(&[u8], DeliveryMetadata) -> BoxFuture<'static, DispatchResult> + Send + Sync>;
+
+/// A dispatcher that maps url paths to typed job handlers.
+///
+/// # Example
+/// ```
+/// use qstash_rs::server::{DeliveryMetadata, JobDispatcher, Receiver};
+/// use http::header::HeaderMap;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Resize {
+///     image: String,
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let dispatcher = JobDispatcher::new(Receiver::new("<CURRENT_KEY>", "<NEXT_KEY>"))
+///         .register("/jobs/resize", |job: Resize, _: DeliveryMetadata| async move {
+///             println!("resizing {}", job.image);
+///             Ok::<(), String>(())
+///         });
+///
+///     // inside your framework's request handler
+///     let result = dispatcher
+///         .dispatch("/jobs/resize", &HeaderMap::new(), br#"{"image":"cat.png"}"#)
+///         .await;
+///     println!("responding with {}", result.status());
+/// }
+/// ```
+pub struct JobDispatcher {
+    receiver: Receiver,
+    base_url: Option<String>,
+    catch_panics: bool,
+    handlers: HashMap<String, BoxedHandler>,
+}
+
+impl JobDispatcher {
+    /// Initialize a new dispatcher that verifies requests with the given receiver.
+    pub fn new(receiver: Receiver) -> Self {
+        Self {
+            receiver,
+            base_url: None,
+            catch_panics: false,
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Set the public base url of your service, e.g. `https://example.com`.
+    /// When set, the url the message was sent to is verified as well.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Catch handler panics and report them as [`DispatchResult::Panicked`].
+    pub fn catch_panics(mut self, catch_panics: bool) -> Self {
+        self.catch_panics = catch_panics;
+        self
+    }
+
+    /// Register a handler for the given path.
+    /// The body of the request is deserialized from JSON into `T`.
+    pub fn register<T, H>(mut self, path: &str, handler: H) -> Self
+    where
+        T: DeserializeOwned + Send + 'static,
+        H: JobHandler<T>,
+    {
+        let handler = Arc::new(handler);
+        let handler: BoxedHandler = Arc::new(move |body: &[u8], metadata: DeliveryMetadata| {
+            let job: T = match serde_json::from_slice(body) {
+                Ok(j) => j,
+                Err(e) => {
+                    let formated_string = e.to_string();
+                    log::error!(formated_string);
+                    return futures::future::ready(DispatchResult::BadRequest(formated_string))
+                        .boxed();
+                }
+            };
+
+            let handler = handler.clone();
+            async move {
+                match handler.handle(job, metadata).await {
+                    Ok(()) => DispatchResult::Ok,
+                    Err(e) => {
+                        log::error!(e);
+                        DispatchResult::HandlerError(e)
+                    }
+                }
+            }
+            .boxed()
+        });
+
+        self.handlers.insert(path.to_string(), handler);
+        self
+    }
+
+    /// The paths that have a registered handler.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.handlers.keys().map(|p| p.as_str())
+    }
+
+    /// Dispatch a delivery to the handler registered for its path.
+    ///
+    /// The path may contain a query string, which is ignored when looking up the
+    /// handler but is part of the url that is verified.
+    pub async fn dispatch(&self, path: &str, headers: &HeaderMap, body: &[u8]) -> DispatchResult {
+        let route = path.split('?').next().unwrap_or(path);
+        self.dispatch_route(route, path, headers, body).await
+    }
+
+    /// dispatch_route dispatches a delivery to the handler registered for the route,
+    /// verifying the url made of the base url and the path.
+    /// The path can differ from the route, for a router nested under a prefix.
+    pub(crate) async fn dispatch_route(
+        &self,
+        route: &str,
+        path: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> DispatchResult {
+        let handler = match self.handlers.get(route) {
+            Some(h) => h,
+            None => return DispatchResult::NotFound,
+        };
+
+        let signature = match headers.get(SIGNATURE_HEADER).and_then(|v| v.to_str().ok()) {
+            Some(s) => s,
+            None => {
+                log::error!("Missing {} header", SIGNATURE_HEADER);
+                return DispatchResult::Unauthorized;
+            }
+        };
+
+        let url = self
+            .base_url
+            .as_ref()
+            .map(|base| format!("{}{}", base, path));
+        if self
+            .receiver
+            .verify(signature, body, url.as_deref())
+            .is_err()
+        {
+            return DispatchResult::Unauthorized;
+        }
+
+        let metadata = match DeliveryMetadata::from_headers(headers) {
+            Ok(m) => m,
+            Err(e) => return DispatchResult::BadRequest(e.to_string()),
+        };
+
+        if self.catch_panics {
+            match AssertUnwindSafe(handler(body, metadata))
+                .catch_unwind()
+                .await
+            {
+                Ok(result) => result,
+                Err(_) => {
+                    log::error!("Handler for {} panicked", route);
+                    DispatchResult::Panicked
+                }
+            }
+        } else {
+            handler(body, metadata).await
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! # llm module
+//! This module contains the typed chat completion QStash posts to the callback url
+//! of a message sent to an LLM provider.
+//!
+//! The types follow the OpenAI chat completion format, fields specific to a provider
+//! are kept in the `extras` map of the struct they belong to.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::CallbackPayload;
+use crate::{client::QStashError, log};
+
+/// A chat completion returned by an LLM provider.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LlmCallbackResponse {
+    pub id: Option<String>,
+    pub object: Option<String>,
+    /// Unix timestamp in seconds.
+    pub created: Option<u64>,
+    pub model: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<LlmChoice>,
+    pub usage: Option<LlmUsage>,
+    /// The fields not covered by this struct.
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+}
+
+/// A single completion choice.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LlmChoice {
+    #[serde(default)]
+    pub index: u32,
+    pub message: Option<LlmMessage>,
+    pub finish_reason: Option<FinishReason>,
+    /// The fields not covered by this struct.
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+}
+
+/// The message of a completion choice.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LlmMessage {
+    pub role: Option<String>,
+    pub content: Option<String>,
+    /// The fields not covered by this struct, such as tool calls.
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+}
+
+/// The token usage of a completion.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LlmUsage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub total_tokens: u64,
+    /// The fields not covered by this struct.
+    #[serde(flatten)]
+    pub extras: HashMap<String, serde_json::Value>,
+}
+
+/// The reason the model stopped generating.
+/// Reasons specific to a provider are kept in the Other variant.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum FinishReason {
+    Stop,
+    Length,
+    ToolCalls,
+    ContentFilter,
+    FunctionCall,
+    Other(String),
+}
+
+impl From<String> for FinishReason {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "stop" => FinishReason::Stop,
+            "length" => FinishReason::Length,
+            "tool_calls" => FinishReason::ToolCalls,
+            "content_filter" => FinishReason::ContentFilter,
+            "function_call" => FinishReason::FunctionCall,
+            _ => FinishReason::Other(value),
+        }
+    }
+}
+
+impl From<FinishReason> for String {
+    fn from(value: FinishReason) -> Self {
+        match value {
+            FinishReason::Stop => String::from("stop"),
+            FinishReason::Length => String::from("length"),
+            FinishReason::ToolCalls => String::from("tool_calls"),
+            FinishReason::ContentFilter => String::from("content_filter"),
+            FinishReason::FunctionCall => String::from("function_call"),
+            FinishReason::Other(v) => v,
+        }
+    }
+}
+
+impl LlmCallbackResponse {
+    /// Parse the chat completion wrapped in a callback payload.
+    /// It fails if the provider did not respond with a 2xx status code.
+    pub fn from_callback(payload: &CallbackPayload) -> Result<Self, QStashError> {
+        if !payload.is_success() {
+            log::error!("LLM provider responded with status {}", payload.status);
+            return Err(QStashError::CallbackError);
+        }
+
+        let body = payload.decoded_body()?;
+        match serde_json::from_slice(&body) {
+            Ok(r) => Ok(r),
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                Err(QStashError::CallbackError)
+            }
+        }
+    }
+
+    /// first_message_text returns the content of the message of the first choice.
+    pub fn first_message_text(&self) -> Option<&str> {
+        self.choices
+            .first()
+            .and_then(|c| c.message.as_ref())
+            .and_then(|m| m.content.as_deref())
+    }
+
+    /// finish_reason returns the finish reason of the first choice.
+    pub fn finish_reason(&self) -> Option<&FinishReason> {
+        self.choices.first().and_then(|c| c.finish_reason.as_ref())
+    }
+}
+
+impl TryFrom<&CallbackPayload> for LlmCallbackResponse {
+    type Error = QStashError;
+
+    fn try_from(payload: &CallbackPayload) -> Result<Self, Self::Error> {
+        LlmCallbackResponse::from_callback(payload)
+    }
+}
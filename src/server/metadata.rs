@@ -0,0 +1,73 @@
+//! # metadata module
+//! This module contains the metadata QStash sends along with every delivery.
+
+use http::header::HeaderMap;
+
+use crate::{client::QStashError, log};
+
+/// The metadata of a delivery.
+/// It is parsed from the `Upstash-*` headers QStash adds to every request it
+/// sends to your destination.
+#[derive(Debug, Clone)]
+pub struct DeliveryMetadata {
+    /// The id of the message being delivered.
+    pub message_id: String,
+    /// How many times the delivery has been retried so far.
+    pub retried: u32,
+    /// The id of the schedule that created the message, if any.
+    pub schedule_id: Option<String>,
+    /// The ip of the caller that published the message, if any.
+    pub caller_ip: Option<String>,
+    /// All the headers of the delivery.
+    pub headers: HeaderMap,
+}
+
+impl DeliveryMetadata {
+    /// Parse the delivery metadata from the headers of a request.
+    /// The `Upstash-Message-Id` header is required.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, QStashError> {
+        let message_id = match header_str(headers, "Upstash-Message-Id") {
+            Some(v) => v,
+            None => {
+                log::error!("Missing Upstash-Message-Id header");
+                return Err(QStashError::MetadataError);
+            }
+        };
+
+        let retried = match header_str(headers, "Upstash-Retried") {
+            Some(v) => match v.parse() {
+                Ok(r) => r,
+                Err(e) => {
+                    let formated_string = format!("Invalid Upstash-Retried header: {}", e);
+                    log::error!(formated_string);
+                    return Err(QStashError::MetadataError);
+                }
+            },
+            None => 0,
+        };
+
+        Ok(Self {
+            message_id,
+            retried,
+            schedule_id: header_str(headers, "Upstash-Schedule-Id"),
+            caller_ip: header_str(headers, "Upstash-Caller-Ip"),
+            headers: headers.clone(),
+        })
+    }
+
+    /// The trace context of the publisher, restored from the forwarded
+    /// `traceparent` and `tracestate` headers.
+    /// Use it as the parent of the span handling the delivery.
+    #[cfg(feature = "opentelemetry")]
+    pub fn trace_context(&self) -> Option<opentelemetry::Context> {
+        crate::trace_context::extract(&self.headers)
+    }
+}
+
+/// header_str returns the value of a header as a string, if present and valid.
+fn header_str(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
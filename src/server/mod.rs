@@ -0,0 +1,26 @@
+//! # Server module.
+//! This module contains the tools you need on the receiving side of QStash:
+//! verifying the signature of incoming requests, reading the metadata
+//! QStash sends along with every delivery and parsing callbacks.
+//!
+//! The [`JobDispatcher`] maps url paths to typed job handlers in a framework
+//! agnostic way. With the `axum` feature enabled, [`QstashRouter`] exposes the same
+//! functionality as an `axum::Router`.
+
+mod callback;
+mod dispatcher;
+mod llm;
+mod metadata;
+mod payload_signature;
+mod receiver;
+#[cfg(feature = "axum")]
+mod router;
+
+pub use callback::*;
+pub use dispatcher::*;
+pub use llm::*;
+pub use metadata::*;
+pub use payload_signature::*;
+pub use receiver::*;
+#[cfg(feature = "axum")]
+pub use router::*;
@@ -0,0 +1,60 @@
+//! # payload_signature module
+//! This module contains the verification of the payload signatures added by
+//! [`Client::payload_signing`](crate::client::Client::payload_signing).
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{client::QStashError, log};
+
+/// Verify the payload signature forwarded along with a message.
+///
+/// The signature is the lowercase or uppercase hex HMAC-SHA256 of the body with the shared
+/// secret, it is compared in constant time.
+///
+/// # Example
+/// ```
+/// use qstash_rs::server::verify_payload_signature;
+///
+/// let body = br#"{"hello":"world"}"#;
+/// let signature = "not a signature";
+/// assert!(verify_payload_signature("secret", signature, body).is_err());
+/// ```
+pub fn verify_payload_signature(
+    secret: &str,
+    signature: &str,
+    body: &[u8],
+) -> Result<(), QStashError> {
+    let signature = match decode_hex(signature.trim()) {
+        Some(s) => s,
+        None => {
+            log::error!("Payload signature is not hex encoded");
+            return Err(QStashError::SignatureError);
+        }
+    };
+
+    let mut mac = match Hmac::<Sha256>::new_from_slice(secret.as_bytes()) {
+        Ok(m) => m,
+        Err(e) => {
+            let formated_string = e.to_string();
+            log::error!(formated_string);
+            return Err(QStashError::SignatureError);
+        }
+    };
+    mac.update(body);
+    match mac.verify_slice(&signature) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(QStashError::SignatureError),
+    }
+}
+
+/// decode_hex decodes a hex string, returning None if it is not valid hex.
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
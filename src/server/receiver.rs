@@ -0,0 +1,161 @@
+//! # receiver module
+//! This module contains the signature verification used to make sure a request
+//! was actually sent by QStash.
+//!
+//! QStash signs every delivery with a JWT in the `Upstash-Signature` header.
+//! The token is signed with your current signing key (or the next one while
+//! keys are being rotated) and contains a hash of the body that was delivered.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{client::QStashError, log};
+
+/// The name of the header QStash uses to send the signature.
+pub const SIGNATURE_HEADER: &str = "Upstash-Signature";
+
+/// The claims contained in the QStash signature.
+#[derive(Debug, Deserialize)]
+struct Claims {
+    iss: String,
+    sub: String,
+    exp: u64,
+    nbf: u64,
+    body: String,
+}
+
+/// The receiver verifies the signature of incoming QStash requests.
+/// It is initialized with the current and next signing keys found in the
+/// QStash console.
+#[derive(Debug, Clone)]
+pub struct Receiver {
+    current_signing_key: String,
+    next_signing_key: String,
+    clock_tolerance: u64,
+}
+
+impl Receiver {
+    /// Initialize a new receiver with the current and next signing keys.
+    pub fn new(current_signing_key: &str, next_signing_key: &str) -> Self {
+        Self {
+            current_signing_key: current_signing_key.to_string(),
+            next_signing_key: next_signing_key.to_string(),
+            clock_tolerance: 0,
+        }
+    }
+
+    /// Allow the `exp` and `nbf` claims to be off by the given amount of seconds.
+    /// Useful when the clock of your server is not perfectly in sync.
+    pub fn clock_tolerance(mut self, seconds: u64) -> Self {
+        self.clock_tolerance = seconds;
+        self
+    }
+
+    /// Verify the signature of a request.
+    ///
+    /// The signature is first checked against the current signing key and, if that
+    /// fails, against the next signing key.
+    /// If an url is provided it must match the url the message was sent to.
+    pub fn verify(
+        &self,
+        signature: &str,
+        body: &[u8],
+        url: Option<&str>,
+    ) -> Result<(), QStashError> {
+        match self.verify_with_key(&self.current_signing_key, signature, body, url) {
+            Ok(()) => Ok(()),
+            Err(_) => self.verify_with_key(&self.next_signing_key, signature, body, url),
+        }
+    }
+
+    /// verify_with_key verifies the signature against a single signing key.
+    fn verify_with_key(
+        &self,
+        key: &str,
+        signature: &str,
+        body: &[u8],
+        url: Option<&str>,
+    ) -> Result<(), QStashError> {
+        let parts: Vec<&str> = signature.split('.').collect();
+        if parts.len() != 3 {
+            log::error!("Signature is not a valid JWT");
+            return Err(QStashError::SignatureError);
+        }
+
+        let signed_signature = match URL_SAFE_NO_PAD.decode(parts[2]) {
+            Ok(s) => s,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::SignatureError);
+            }
+        };
+
+        let mut mac = match Hmac::<Sha256>::new_from_slice(key.as_bytes()) {
+            Ok(m) => m,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::SignatureError);
+            }
+        };
+        mac.update(format!("{}.{}", parts[0], parts[1]).as_bytes());
+        if mac.verify_slice(&signed_signature).is_err() {
+            return Err(QStashError::SignatureError);
+        }
+
+        let claims = match URL_SAFE_NO_PAD.decode(parts[1]) {
+            Ok(c) => c,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::SignatureError);
+            }
+        };
+        let claims: Claims = match serde_json::from_slice(&claims) {
+            Ok(c) => c,
+            Err(e) => {
+                let formated_string = e.to_string();
+                log::error!(formated_string);
+                return Err(QStashError::SignatureError);
+            }
+        };
+
+        if claims.iss != "Upstash" {
+            log::error!("Invalid issuer: {}", claims.iss);
+            return Err(QStashError::SignatureError);
+        }
+
+        if let Some(url) = url {
+            if claims.sub != url {
+                log::error!("Invalid subject: {}", claims.sub);
+                return Err(QStashError::SignatureError);
+            }
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        if now > claims.exp + self.clock_tolerance {
+            log::error!("Signature has expired");
+            return Err(QStashError::SignatureError);
+        }
+        if now + self.clock_tolerance < claims.nbf {
+            log::error!("Signature is not valid yet");
+            return Err(QStashError::SignatureError);
+        }
+
+        let body_hash = URL_SAFE_NO_PAD.encode(Sha256::digest(body));
+        if claims.body.trim_end_matches('=') != body_hash {
+            log::error!("Body hash does not match");
+            return Err(QStashError::SignatureError);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+//! # router module
+//! This module contains an [axum](https://docs.rs/axum) router that maps url paths
+//! to typed job handlers.
+//!
+//! Every request is verified with the [`Receiver`] before it is dispatched, the body
+//! is deserialized from JSON and the handler receives it along with the
+//! [`DeliveryMetadata`](super::DeliveryMetadata) of the delivery.
+//!
+//! The handlers' result is mapped to a status code so that QStash's retry semantics work:
+//! - 200: the handler succeeded
+//! - 400: the body or the metadata could not be parsed
+//! - 401: the signature is missing or invalid
+//! - 500: the handler failed (or panicked, if panics are caught)
+
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::OriginalUri,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use serde::de::DeserializeOwned;
+
+use super::{JobDispatcher, JobHandler, Receiver};
+
+/// A router that maps url paths to typed job handlers.
+/// It is a thin axum layer on top of the [`JobDispatcher`].
+///
+/// # Example
+/// ```
+/// use qstash_rs::server::{DeliveryMetadata, QstashRouter, Receiver};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Resize {
+///     image: String,
+/// }
+///
+/// async fn resize(job: Resize, metadata: DeliveryMetadata) -> Result<(), String> {
+///     println!("resizing {} ({})", job.image, metadata.message_id);
+///     Ok(())
+/// }
+///
+/// let router: axum::Router = QstashRouter::new(Receiver::new("<CURRENT_KEY>", "<NEXT_KEY>"))
+///     .route("/jobs/resize", resize)
+///     .catch_panics(true)
+///     .into_router();
+/// ```
+pub struct QstashRouter {
+    dispatcher: JobDispatcher,
+}
+
+impl QstashRouter {
+    /// Initialize a new router that verifies requests with the given receiver.
+    pub fn new(receiver: Receiver) -> Self {
+        Self {
+            dispatcher: JobDispatcher::new(receiver),
+        }
+    }
+
+    /// Set the public base url of your service, e.g. `https://example.com`.
+    /// When set, the url the message was sent to is verified as well.
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.dispatcher = self.dispatcher.base_url(base_url);
+        self
+    }
+
+    /// Catch handler panics and respond with a 500 instead of dropping the connection.
+    pub fn catch_panics(mut self, catch_panics: bool) -> Self {
+        self.dispatcher = self.dispatcher.catch_panics(catch_panics);
+        self
+    }
+
+    /// Register a handler for the given path.
+    /// The body of the request is deserialized from JSON into `T`.
+    pub fn route<T, H>(mut self, path: &str, handler: H) -> Self
+    where
+        T: DeserializeOwned + Send + 'static,
+        H: JobHandler<T>,
+    {
+        self.dispatcher = self.dispatcher.register(path, handler);
+        self
+    }
+
+    /// Build the [`axum::Router`].
+    /// Paths without a handler respond with a 404, so the router can be merged
+    /// into your application.
+    pub fn into_router<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let paths: Vec<String> = self.dispatcher.paths().map(String::from).collect();
+        let dispatcher = Arc::new(self.dispatcher);
+        let mut router = Router::new();
+
+        for path in paths {
+            let dispatcher = dispatcher.clone();
+            let route = path.clone();
+            router = router.route(
+                &path,
+                post(
+                    move |uri: OriginalUri, headers: HeaderMap, body: Bytes| async move {
+                        // The original uri keeps the prefix of a nested router, it is only
+                        // used to verify the url the message was sent to.
+                        let url_path = uri.0.path_and_query().map(|p| p.as_str()).unwrap_or("");
+                        let result = dispatcher
+                            .dispatch_route(&route, url_path, &headers, &body)
+                            .await;
+                        StatusCode::from_u16(result.status())
+                            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+                    },
+                ),
+            );
+        }
+
+        router
+    }
+}
@@ -0,0 +1,88 @@
+//! # trace_context module
+//! This module contains the [W3C trace context](https://www.w3.org/TR/trace-context/)
+//! propagation through QStash.
+//!
+//! On the publishing side the active context is injected as `Upstash-Forward-traceparent`
+//! and `Upstash-Forward-tracestate` headers, which QStash forwards to the destination
+//! as `traceparent` and `tracestate`. On the receiving side those headers are turned
+//! back into the parent context.
+
+use std::str::FromStr;
+
+use http::header::{HeaderMap, HeaderValue};
+use opentelemetry::{
+    trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+    Context,
+};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+const TRACEPARENT_HEADER: &str = "traceparent";
+const TRACESTATE_HEADER: &str = "tracestate";
+const FORWARD_TRACEPARENT_HEADER: &str = "Upstash-Forward-traceparent";
+const FORWARD_TRACESTATE_HEADER: &str = "Upstash-Forward-tracestate";
+
+/// inject adds the active trace context to the headers of a publish request.
+/// The context of the current tracing span is used if it has one, otherwise the
+/// current OpenTelemetry context. Nothing is added when there is no active context.
+pub(crate) fn inject(mut headers: HeaderMap) -> HeaderMap {
+    let mut context = tracing::Span::current().context();
+    if !context.span().span_context().is_valid() {
+        context = Context::current();
+    }
+
+    let span = context.span();
+    let span_context = span.span_context();
+    if !span_context.is_valid() {
+        return headers;
+    }
+
+    let traceparent = format!(
+        "00-{}-{}-{:02x}",
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags().to_u8()
+    );
+    if let Ok(value) = HeaderValue::from_str(&traceparent) {
+        headers.insert(FORWARD_TRACEPARENT_HEADER, value);
+    }
+
+    let tracestate = span_context.trace_state().header();
+    if !tracestate.is_empty() {
+        if let Ok(value) = HeaderValue::from_str(&tracestate) {
+            headers.insert(FORWARD_TRACESTATE_HEADER, value);
+        }
+    }
+
+    headers
+}
+
+/// extract restores the parent trace context from the headers of a delivery.
+/// It returns `None` if there is no valid `traceparent` header.
+pub(crate) fn extract(headers: &HeaderMap) -> Option<Context> {
+    let traceparent = headers.get(TRACEPARENT_HEADER)?.to_str().ok()?;
+
+    let parts: Vec<&str> = traceparent.trim().split('-').collect();
+    if parts.len() < 4 || parts[0].len() != 2 || parts[0] == "ff" {
+        return None;
+    }
+    if parts[1].len() != 32 || parts[2].len() != 16 || parts[3].len() != 2 {
+        return None;
+    }
+
+    let trace_id = TraceId::from_hex(parts[1]).ok()?;
+    let span_id = SpanId::from_hex(parts[2]).ok()?;
+    let trace_flags = TraceFlags::new(u8::from_str_radix(parts[3], 16).ok()?);
+
+    let trace_state = headers
+        .get(TRACESTATE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| TraceState::from_str(v).ok())
+        .unwrap_or_default();
+
+    let span_context = SpanContext::new(trace_id, span_id, trace_flags, true, trace_state);
+    if !span_context.is_valid() {
+        return None;
+    }
+
+    Some(Context::new().with_remote_span_context(span_context))
+}
@@ -0,0 +1,146 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use http::{HeaderMap, Method, StatusCode};
+use qstash_rs::client::{
+    Client, HttpBackend, PublishRequestUrl, QStashError, RequestParts, ResponseParts, RetryPolicy,
+    TransportError, TransportErrorKind,
+};
+
+/// Scripted records the requests and answers them with the scripted results,
+/// then with an empty JSON object.
+#[derive(Debug, Clone, Default)]
+struct Scripted {
+    requests: Arc<Mutex<Vec<RequestParts>>>,
+    results: Arc<Mutex<VecDeque<Result<ResponseParts, TransportError>>>>,
+}
+
+impl Scripted {
+    fn then(self, status: u16, body: &'static str) -> Self {
+        self.results
+            .lock()
+            .expect("Could not lock results")
+            .push_back(Ok(ResponseParts {
+                status: StatusCode::from_u16(status).expect("Invalid status"),
+                headers: HeaderMap::new(),
+                body: body.into(),
+            }));
+        self
+    }
+
+    fn then_fail(self, kind: TransportErrorKind) -> Self {
+        self.results
+            .lock()
+            .expect("Could not lock results")
+            .push_back(Err(TransportError::new(kind, "scripted failure")));
+        self
+    }
+
+    fn requests(&self) -> Vec<RequestParts> {
+        self.requests
+            .lock()
+            .expect("Could not lock requests")
+            .clone()
+    }
+}
+
+#[async_trait]
+impl HttpBackend for Scripted {
+    async fn execute(&self, request: RequestParts) -> Result<ResponseParts, TransportError> {
+        self.requests
+            .lock()
+            .expect("Could not lock requests")
+            .push(request);
+        self.results
+            .lock()
+            .expect("Could not lock results")
+            .pop_front()
+            .unwrap_or_else(|| {
+                Ok(ResponseParts {
+                    status: StatusCode::OK,
+                    headers: HeaderMap::new(),
+                    body: "{}".into(),
+                })
+            })
+    }
+}
+
+fn client(backend: &Scripted) -> Client {
+    Client::with_backend(
+        "token",
+        Some("https://qstash.example.com"),
+        None,
+        backend.clone(),
+    )
+    .expect("Could not initialize client")
+}
+
+fn url() -> PublishRequestUrl {
+    PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL"))
+}
+
+#[tokio::test]
+async fn publish_json_should_go_through_the_backend() {
+    let backend = Scripted::default().then(201, r#"{"messageId":"msg_1"}"#);
+
+    let responses = client(&backend)
+        .publish_json(url(), serde_json::json!({ "hello": "world" }), None)
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(responses[0].message_id.as_deref(), Some("msg_1"));
+    let requests = backend.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.method, Method::POST);
+    assert_eq!(
+        request.url.as_str(),
+        "https://qstash.example.com/v2/publish/https://example.com/"
+    );
+    assert_eq!(
+        request.headers.get("Authorization").map(|v| v.as_bytes()),
+        Some(&b"Bearer token"[..])
+    );
+    assert_eq!(
+        request.headers.get("Content-Type").map(|v| v.as_bytes()),
+        Some(&b"application/json"[..])
+    );
+    assert!(request.headers.contains_key("Upstash-Telemetry-Sdk"));
+    assert_eq!(request.body.as_deref(), Some(&br#"{"hello":"world"}"#[..]));
+}
+
+#[tokio::test]
+async fn reads_should_retry_the_transport_errors_of_the_backend() {
+    let backend = Scripted::default()
+        .then_fail(TransportErrorKind::Connect)
+        .then_fail(TransportErrorKind::Timeout)
+        .then(200, r#"{"events":[]}"#);
+    let qstash_client = client(&backend).retry_policy(RetryPolicy {
+        max_attempts: 3,
+        base_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(1),
+    });
+
+    let events = qstash_client
+        .get_events(None)
+        .await
+        .expect("Could not get events");
+
+    assert!(events.events.is_empty());
+    assert_eq!(backend.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn other_transport_errors_should_not_be_retried() {
+    let backend = Scripted::default().then_fail(TransportErrorKind::Other);
+    let qstash_client = client(&backend).retry_policy(RetryPolicy::default());
+
+    let result = qstash_client.get_events(None).await;
+
+    assert!(matches!(result, Err(QStashError::EventError { .. })));
+    assert_eq!(backend.requests().len(), 1);
+}
@@ -0,0 +1,324 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use common::echo_batch;
+use http::{header::HeaderMap, Method};
+use qstash_rs::client::{
+    batch::{BatchEntry, BatchResult},
+    Client, PublishOptions, PublishRequest, PublishRequestUrl, QStashError,
+};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn client(server: &MockServer) -> Client {
+    Client::new("token", Some(&server.uri()), None).expect("Could not initialize client")
+}
+
+fn url(url: &str) -> PublishRequestUrl {
+    PublishRequestUrl::Url(url.parse().expect("Could not parse URL"))
+}
+
+/// sent_messages returns the messages of the batch received by the server.
+async fn sent_messages(server: &MockServer) -> Vec<serde_json::Value> {
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    serde_json::from_slice(&requests[0].body).expect("Batch body should be a JSON array")
+}
+
+#[tokio::test]
+async fn batch_should_send_every_entry_in_one_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .expect(1)
+        .mount(&server)
+        .await;
+    let bodies: Vec<String> = (0..100).map(|i| format!("message {}", i)).collect();
+    let entries = bodies
+        .iter()
+        .map(|body| {
+            let mut entry = BatchEntry::new(url("https://example.com/job"));
+            entry.body = Some(body.clone());
+            entry
+        })
+        .collect();
+
+    let responses = client(&server)
+        .batch(entries)
+        .await
+        .expect("Could not publish batch");
+
+    let ids: Vec<String> = responses
+        .into_iter()
+        .flat_map(BatchResult::into_responses)
+        .map(|r| r.message_id.unwrap_or_default())
+        .collect();
+    assert_eq!(ids, bodies);
+}
+
+#[tokio::test]
+async fn batch_should_serialize_the_options_of_every_entry() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "messageId": "msg_1" },
+            { "messageId": "msg_2" },
+        ])))
+        .mount(&server)
+        .await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "Content-Type",
+        "text/plain".parse().expect("Invalid header"),
+    );
+    let mut first = BatchEntry::new(url("https://example.com/job"));
+    first.body = Some("report".to_string());
+    first.headers = Some(headers);
+    first.delay = Some(30);
+    first.retries = Some(2);
+    first.callback = Some("https://example.com/callback".to_string());
+    first.deduplication_id = Some("report-1".to_string());
+    first.method = Some(Method::PUT);
+    let mut second = BatchEntry::new(PublishRequestUrl::Topic("billing".to_string()));
+    second.queue = Some("orders".to_string());
+    second.content_based_deduplication = Some(true);
+
+    client(&server)
+        .batch(vec![first, second])
+        .await
+        .expect("Could not publish batch");
+
+    let messages = sent_messages(&server).await;
+    assert_eq!(
+        messages[0],
+        serde_json::json!({
+            "destination": "https://example.com/job",
+            "headers": {
+                "content-type": "text/plain",
+                "upstash-method": "PUT",
+                "upstash-delay": "30s",
+                "upstash-retries": "2",
+                "upstash-callback": "https://example.com/callback",
+                "upstash-deduplication-id": "report-1",
+            },
+            "body": "report",
+        })
+    );
+    assert_eq!(
+        messages[1],
+        serde_json::json!({
+            "destination": "billing",
+            "queue": "orders",
+            "headers": {
+                "upstash-method": "POST",
+                "upstash-content-based-deduplication": "true",
+            },
+        })
+    );
+}
+
+#[tokio::test]
+async fn batch_json_should_send_every_body_with_the_shared_options() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "messageId": "msg_1" },
+            { "messageId": "msg_2" },
+            { "messageId": "msg_3" },
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "Upstash-Forward-Team",
+        "billing".parse().expect("Invalid header"),
+    );
+    let responses = client(&server)
+        .batch_json(
+            url("https://example.com/job"),
+            vec![
+                serde_json::json!({ "order": 1 }),
+                serde_json::json!({ "order": 2 }),
+                serde_json::json!({ "order": 3 }),
+            ],
+            Some(PublishOptions {
+                headers: Some(headers),
+                retries: Some(2),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("Could not publish batch");
+
+    assert_eq!(responses.len(), 3);
+    let messages = sent_messages(&server).await;
+    assert_eq!(messages.len(), 3);
+    for (i, message) in messages.iter().enumerate() {
+        assert_eq!(message["destination"], "https://example.com/job");
+        assert_eq!(message["headers"]["content-type"], "application/json");
+        assert_eq!(message["headers"]["upstash-forward-team"], "billing");
+        assert_eq!(message["headers"]["upstash-retries"], "2");
+        let body: serde_json::Value =
+            serde_json::from_str(message["body"].as_str().expect("Body should be a string"))
+                .expect("Body should be JSON");
+        assert_eq!(body, serde_json::json!({ "order": i + 1 }));
+    }
+}
+
+#[tokio::test]
+async fn builder_should_serialize_the_per_entry_overrides() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+
+    let json = BatchEntry::builder(url("https://example.com/orders"))
+        .retries(5)
+        .deduplication_id("order-42")
+        .body_json(&serde_json::json!({ "order": 42 }))
+        .build()
+        .expect("Invalid entry");
+    let raw = BatchEntry::builder(url("https://example.com/reports"))
+        .method(Method::PUT)
+        .queue("reports")
+        .body_raw("a,b\n1,2", "text/csv")
+        .build()
+        .expect("Invalid entry");
+    let mut request = PublishRequest::new(PublishRequestUrl::Topic("billing".to_string()));
+    request.body = Some("invoice");
+    request.callback = Some("https://example.com/callback".to_string());
+
+    client(&server)
+        .batch(vec![json, raw, BatchEntry::from(request)])
+        .await
+        .expect("Could not publish batch");
+
+    let messages = sent_messages(&server).await;
+    assert_eq!(
+        messages,
+        vec![
+            serde_json::json!({
+                "destination": "https://example.com/orders",
+                "headers": {
+                    "content-type": "application/json",
+                    "upstash-method": "POST",
+                    "upstash-retries": "5",
+                    "upstash-deduplication-id": "order-42",
+                },
+                "body": r#"{"order":42}"#,
+            }),
+            serde_json::json!({
+                "destination": "https://example.com/reports",
+                "queue": "reports",
+                "headers": {
+                    "content-type": "text/csv",
+                    "upstash-method": "PUT",
+                },
+                "body": "a,b\n1,2",
+            }),
+            serde_json::json!({
+                "destination": "billing",
+                "headers": {
+                    "upstash-method": "POST",
+                    "upstash-callback": "https://example.com/callback",
+                },
+                "body": "invoice",
+            }),
+        ]
+    );
+}
+
+#[test]
+fn builder_should_reject_invalid_bodies_and_headers() {
+    assert!(matches!(
+        BatchEntry::builder(url("https://example.com"))
+            .body_raw(vec![0xff, 0xfe], "application/octet-stream")
+            .build(),
+        Err(QStashError::BatchError)
+    ));
+    assert!(matches!(
+        BatchEntry::builder(url("https://example.com"))
+            .header("Upstash-Forward-Team", "bill\ning")
+            .build(),
+        Err(QStashError::BatchError)
+    ));
+}
+
+#[tokio::test]
+async fn batch_should_group_the_responses_of_topic_entries() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "messageId": "msg_1" },
+            [
+                { "messageId": "msg_2", "url": "https://example.com/a" },
+                { "messageId": "msg_3", "url": "https://example.com/b" },
+            ],
+            { "messageId": "msg_4" },
+        ])))
+        .mount(&server)
+        .await;
+
+    let results = client(&server)
+        .batch(vec![
+            BatchEntry::new(url("https://example.com/job")),
+            BatchEntry::new(PublishRequestUrl::Topic("billing".to_string())),
+            BatchEntry::new(url("https://example.com/job")),
+        ])
+        .await
+        .expect("Could not publish batch");
+
+    assert_eq!(results.len(), 3);
+    assert!(
+        matches!(&results[0], BatchResult::Single(r) if r.message_id.as_deref() == Some("msg_1"))
+    );
+    match &results[1] {
+        BatchResult::Multi(responses) => {
+            let urls: Vec<&str> = responses.iter().filter_map(|r| r.url.as_deref()).collect();
+            assert_eq!(urls, ["https://example.com/a", "https://example.com/b"]);
+        }
+        BatchResult::Single(_) => panic!("Expected one response per endpoint of the topic"),
+    }
+    let ids: Vec<&str> = results
+        .iter()
+        .flat_map(BatchResult::responses)
+        .filter_map(|r| r.message_id.as_deref())
+        .collect();
+    assert_eq!(ids, ["msg_1", "msg_2", "msg_3", "msg_4"]);
+}
+
+#[tokio::test]
+async fn batch_should_reject_a_response_per_entry_mismatch() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!([{ "messageId": "msg_1" }])),
+        )
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client(&server)
+            .batch(vec![
+                BatchEntry::new(url("https://example.com/job")),
+                BatchEntry::new(PublishRequestUrl::Topic("billing".to_string())),
+            ])
+            .await,
+        Err(QStashError::BatchError)
+    ));
+}
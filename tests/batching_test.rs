@@ -0,0 +1,198 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::echo_batch;
+use qstash_rs::client::{
+    batch::BatchEntry,
+    batching::{BatchingConfig, BatchingPublisher},
+    Client, PublishRequestUrl, QStashError,
+};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn entry(body: &str) -> BatchEntry {
+    let mut entry = BatchEntry::new(PublishRequestUrl::Url(
+        "https://example.com".parse().expect("Could not parse URL"),
+    ));
+    entry.body = Some(body.to_string());
+    entry
+}
+
+fn message_id(response: Result<Vec<qstash_rs::client::QstashResponse>, QStashError>) -> String {
+    response.expect("Entry should be published")[0]
+        .message_id
+        .clone()
+        .expect("Should contain a message_id")
+}
+
+async fn batch_sizes(server: &MockServer) -> Vec<usize> {
+    server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded")
+        .iter()
+        .map(|r| {
+            serde_json::from_slice::<Vec<serde_json::Value>>(&r.body)
+                .expect("Batch body should be a JSON array")
+                .len()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn batching_publisher_should_coalesce_entries() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let publisher = BatchingPublisher::spawn(
+        qstash_client,
+        BatchingConfig {
+            max_entries: 3,
+            max_delay: Duration::from_secs(60),
+            ..BatchingConfig::default()
+        },
+    );
+
+    let (a, b, c, d, e, f) = tokio::join!(
+        publisher.send(entry("a")),
+        publisher.send(entry("b")),
+        publisher.send(entry("c")),
+        publisher.send(entry("d")),
+        publisher.send(entry("e")),
+        publisher.send(entry("f")),
+    );
+
+    assert_eq!(
+        [a, b, c, d, e, f].map(message_id),
+        ["a", "b", "c", "d", "e", "f"]
+    );
+    assert_eq!(batch_sizes(&server).await, vec![3, 3]);
+}
+
+#[tokio::test]
+async fn batching_publisher_should_respect_max_delay_and_bytes() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let publisher = BatchingPublisher::spawn(
+        qstash_client,
+        BatchingConfig {
+            max_entries: 100,
+            max_delay: Duration::from_millis(50),
+            max_bytes: 200,
+        },
+    );
+
+    // a single entry is published once the delay elapses
+    assert_eq!(message_id(publisher.send(entry("a")).await), "a");
+    assert_eq!(batch_sizes(&server).await, vec![1]);
+
+    // entries that would exceed the size limit go into the next batch
+    let large = "x".repeat(100);
+    let (b, c) = tokio::join!(publisher.send(entry(&large)), publisher.send(entry(&large)));
+    assert_eq!(message_id(b), large);
+    assert_eq!(message_id(c), large);
+    assert_eq!(batch_sizes(&server).await, vec![1, 1, 1]);
+}
+
+#[tokio::test]
+async fn batching_publisher_should_fail_only_the_failed_batch() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(
+            ResponseTemplate::new(500).set_body_json(serde_json::json!({ "error": "boom" })),
+        )
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let publisher = BatchingPublisher::spawn(
+        qstash_client,
+        BatchingConfig {
+            max_entries: 2,
+            max_delay: Duration::from_secs(60),
+            ..BatchingConfig::default()
+        },
+    );
+
+    let (a, b, c, d) = tokio::join!(
+        publisher.send(entry("a")),
+        publisher.send(entry("b")),
+        publisher.send(entry("c")),
+        publisher.send(entry("d")),
+    );
+
+    assert!(matches!(a, Err(QStashError::BatchError)));
+    assert!(matches!(b, Err(QStashError::BatchError)));
+    assert_eq!(message_id(c), "c");
+    assert_eq!(message_id(d), "d");
+}
+
+#[tokio::test]
+async fn batching_publisher_should_flush_and_shutdown() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let publisher = BatchingPublisher::spawn(
+        qstash_client,
+        BatchingConfig {
+            max_entries: 100,
+            max_delay: Duration::from_secs(3600),
+            ..BatchingConfig::default()
+        },
+    );
+
+    let handle = publisher.clone();
+    let a = tokio::spawn(async move { handle.send(entry("a")).await });
+    tokio::task::yield_now().await;
+    publisher.flush().await;
+    assert_eq!(message_id(a.await.expect("Task panicked")), "a");
+
+    let handle = publisher.clone();
+    let b = tokio::spawn(async move { handle.send(entry("b")).await });
+    let handle = publisher.clone();
+    let c = tokio::spawn(async move { handle.send(entry("c")).await });
+    tokio::task::yield_now().await;
+    publisher.shutdown().await;
+
+    assert_eq!(message_id(b.await.expect("Task panicked")), "b");
+    assert_eq!(message_id(c.await.expect("Task panicked")), "c");
+    assert_eq!(batch_sizes(&server).await, vec![1, 2]);
+
+    assert!(matches!(
+        publisher.send(entry("d")).await,
+        Err(QStashError::BatchError)
+    ));
+}
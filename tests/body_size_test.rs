@@ -0,0 +1,153 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use common::echo_batch;
+use qstash_rs::client::{
+    batch::BatchEntry,
+    batching::{BatchingConfig, BatchingPublisher},
+    Client, PublishRequest, PublishRequestUrl, QStashError, DEFAULT_MAX_BODY_SIZE,
+};
+use wiremock::{
+    matchers::{method, path, path_regex},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/publish/"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_123" })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+    server
+}
+
+fn url() -> PublishRequestUrl {
+    PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL"))
+}
+
+fn entry(body: &str) -> BatchEntry {
+    let mut entry = BatchEntry::new(url());
+    entry.body = Some(body.to_string());
+    entry
+}
+
+async fn request_count(server: &MockServer) -> usize {
+    server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded")
+        .len()
+}
+
+#[tokio::test]
+async fn publish_should_reject_large_bodies() {
+    let server = mock_server().await;
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .max_body_size(10);
+
+    let mut request = PublishRequest::<String>::new(url());
+    request.body = Some("x".repeat(11));
+    assert!(matches!(
+        qstash_client.publish(request).await,
+        Err(QStashError::BodyTooLarge {
+            size: 11,
+            limit: 10
+        })
+    ));
+
+    let mut request = PublishRequest::<String>::new(url());
+    request.body = Some("x".repeat(10));
+    qstash_client
+        .publish(request)
+        .await
+        .expect("Body within the limit should be published");
+    assert_eq!(request_count(&server).await, 1);
+}
+
+#[tokio::test]
+async fn publish_json_should_check_the_serialized_size() {
+    let server = mock_server().await;
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .max_body_size(10);
+
+    // 9 characters in memory, 12 bytes once serialized with the quotes and escape
+    assert!(matches!(
+        qstash_client.publish_json(url(), "xxxxxxxx\"", None).await,
+        Err(QStashError::BodyTooLarge {
+            size: 12,
+            limit: 10
+        })
+    ));
+    assert_eq!(request_count(&server).await, 0);
+}
+
+#[tokio::test]
+async fn batch_should_check_entries_and_total_size() {
+    let server = mock_server().await;
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .max_body_size(200);
+
+    // a single entry over the limit
+    assert!(matches!(
+        qstash_client.batch(vec![entry(&"x".repeat(201))]).await,
+        Err(QStashError::BodyTooLarge {
+            size: 201,
+            limit: 200
+        })
+    ));
+
+    // every entry within the limit but not the whole batch
+    let error = qstash_client
+        .batch(vec![entry(&"x".repeat(100)), entry(&"x".repeat(100))])
+        .await
+        .expect_err("Batch over the limit should be rejected");
+    assert!(matches!(error, QStashError::BodyTooLarge { size, limit: 200 } if size > 200));
+    assert_eq!(request_count(&server).await, 0);
+
+    qstash_client
+        .batch(vec![entry("a"), entry("b")])
+        .await
+        .expect("Batch within the limit should be published");
+    assert_eq!(request_count(&server).await, 1);
+}
+
+#[tokio::test]
+async fn batching_publisher_should_reject_only_large_entries() {
+    let server = mock_server().await;
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .max_body_size(200);
+    let publisher = BatchingPublisher::spawn(qstash_client, BatchingConfig::default());
+
+    assert!(matches!(
+        publisher.send(entry(&"x".repeat(201))).await,
+        Err(QStashError::BodyTooLarge {
+            size: 201,
+            limit: 200
+        })
+    ));
+
+    // the batches are split to stay under the limit of the client
+    let large = "x".repeat(100);
+    let (a, b) = tokio::join!(publisher.send(entry(&large)), publisher.send(entry(&large)));
+    a.expect("Entry should be published");
+    b.expect("Entry should be published");
+    assert_eq!(request_count(&server).await, 2);
+}
+
+#[test]
+fn default_max_body_size_should_be_one_megabyte() {
+    assert_eq!(DEFAULT_MAX_BODY_SIZE, 1024 * 1024);
+}
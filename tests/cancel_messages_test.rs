@@ -0,0 +1,135 @@
+#![cfg(feature = "reqwest")]
+
+use qstash_rs::client::{
+    messages::{CancelledCount, MAX_CANCEL_IDS},
+    Client, QStashError,
+};
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, Request, ResponseTemplate,
+};
+
+/// cancelled answers a bulk cancel with every message of the body cancelled.
+fn cancelled(request: &Request) -> ResponseTemplate {
+    let body: serde_json::Value = request.body_json().expect("Could not parse body");
+    let ids = body["messageIds"].as_array().map_or(0, Vec::len);
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({ "cancelled": ids }))
+}
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages"))
+        .and(header("content-type", "application/json"))
+        .respond_with(cancelled)
+        .mount(&server)
+        .await;
+    server
+}
+
+fn ids(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("msg_{}", i)).collect()
+}
+
+async fn sent_ids(server: &MockServer) -> Vec<Vec<String>> {
+    server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded")
+        .iter()
+        .map(|r| {
+            let body: serde_json::Value = r.body_json().expect("Could not parse body");
+            serde_json::from_value(body["messageIds"].clone()).expect("Could not parse ids")
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn cancel_messages_should_send_the_ids() {
+    let server = mock_server().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let count = qstash_client
+        .cancel_messages(&ids(3))
+        .await
+        .expect("Could not cancel messages");
+
+    assert_eq!(count, CancelledCount { cancelled: 3 });
+    assert_eq!(sent_ids(&server).await, [ids(3)]);
+}
+
+#[tokio::test]
+async fn cancel_messages_should_chunk_long_lists() {
+    let server = mock_server().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let ids = ids(2 * MAX_CANCEL_IDS + 50);
+
+    let count = qstash_client
+        .cancel_messages(&ids)
+        .await
+        .expect("Could not cancel messages");
+
+    assert_eq!(count.cancelled, ids.len() as u64);
+    let sent = sent_ids(&server).await;
+    let sizes: Vec<usize> = sent.iter().map(Vec::len).collect();
+    assert_eq!(sizes, [MAX_CANCEL_IDS, MAX_CANCEL_IDS, 50]);
+    assert_eq!(sent.concat(), ids);
+}
+
+#[tokio::test]
+async fn cancel_messages_should_not_send_an_empty_list() {
+    let server = mock_server().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let count = qstash_client
+        .cancel_messages(&[])
+        .await
+        .expect("Could not cancel messages");
+
+    assert_eq!(count.cancelled, 0);
+    assert!(sent_ids(&server).await.is_empty());
+}
+
+#[tokio::test]
+async fn cancel_messages_should_stop_at_the_first_failed_chunk() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages"))
+        .respond_with(cancelled)
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client
+        .cancel_messages(&ids(3 * MAX_CANCEL_IDS))
+        .await;
+
+    assert!(matches!(result, Err(QStashError::DeleteMessageError)));
+    assert_eq!(sent_ids(&server).await.len(), 2);
+}
+
+#[tokio::test]
+async fn cancel_messages_should_fail_on_an_invalid_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client.cancel_messages(&ids(1)).await;
+
+    assert!(matches!(result, Err(QStashError::DeleteMessageError)));
+}
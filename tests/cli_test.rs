@@ -0,0 +1,117 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use std::process::Output;
+
+use common::header;
+use serde_json::{json, Value};
+use tokio::process::Command;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+/// qstash runs the CLI against the mock server.
+async fn qstash(server: &MockServer, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_qstash"))
+        .args(args)
+        .env("QSTASH_TOKEN", "token")
+        .env("QSTASH_URL", server.uri())
+        .output()
+        .await
+        .expect("Could not run the CLI")
+}
+
+fn stdout_json(output: &Output) -> Value {
+    serde_json::from_slice(&output.stdout).expect("The CLI did not print JSON")
+}
+
+#[tokio::test]
+async fn publish_should_send_the_message_and_print_the_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/https://example.com/orders"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "messageId": "msg_1" })))
+        .mount(&server)
+        .await;
+
+    let output = qstash(
+        &server,
+        &[
+            "publish",
+            "https://example.com/orders",
+            "--body",
+            "{\"id\":1}",
+            "-H",
+            "Content-Type: application/json",
+            "--retries",
+            "2",
+            "--method",
+            "put",
+        ],
+    )
+    .await;
+
+    assert!(output.status.success());
+    assert_eq!(stdout_json(&output)[0]["messageId"], "msg_1");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    assert_eq!(
+        header(&requests[0], "Authorization").as_deref(),
+        Some("Bearer token")
+    );
+    assert_eq!(
+        header(&requests[0], "Content-Type").as_deref(),
+        Some("application/json")
+    );
+    assert_eq!(
+        header(&requests[0], "Upstash-Retries").as_deref(),
+        Some("2")
+    );
+    assert_eq!(
+        header(&requests[0], "Upstash-Method").as_deref(),
+        Some("PUT")
+    );
+    assert_eq!(requests[0].body, b"{\"id\":1}");
+}
+
+#[tokio::test]
+async fn message_get_should_print_the_message() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/messages/msg_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "messageId": "msg_1",
+            "url": "https://example.com/orders",
+            "createdAt": 1_699_000_000_000u64,
+        })))
+        .mount(&server)
+        .await;
+
+    let output = qstash(&server, &["message", "get", "msg_1"]).await;
+
+    assert!(output.status.success());
+    let message = stdout_json(&output);
+    assert_eq!(message["messageId"], "msg_1");
+    assert_eq!(message["url"], "https://example.com/orders");
+}
+
+#[tokio::test]
+async fn dlq_delete_should_fail_when_qstash_fails() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/dlq/dlq_1"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let output = qstash(&server, &["dlq", "delete", "dlq_1"]).await;
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("error:"));
+}
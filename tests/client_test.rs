@@ -1,3 +1,5 @@
+#![cfg(feature = "reqwest")]
+
 use qstash_rs::client::{Client, PublishRequest, PublishRequestUrl};
 use serde::Deserialize;
 use std::{collections::HashMap, sync::Once};
@@ -66,6 +68,7 @@ async fn publish_should_work() {
             content_based_deduplication: None,
             retries: None,
             callback: None,
+            failure_callback: None,
             method: None,
         })
         .await
@@ -114,6 +117,7 @@ async fn publish_should_contain_error() {
             content_based_deduplication: None,
             retries: None,
             callback: None,
+            failure_callback: None,
             method: None,
         })
         .await
@@ -0,0 +1,280 @@
+//! A mock QStash server that misbehaves on demand.
+//!
+//! It answers publishes with a new message id and reads with empty pages, unless a failure
+//! was scheduled with [`MockQStash::fail_next`]. Every connection is closed after one
+//! response, so a failure never leaks into the next request.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+/// The ways the mock server can fail a request.
+#[derive(Debug, Clone)]
+pub enum Failure {
+    /// Answer with a 429 and the burst rate limit headers, the limit resets after `reset`.
+    RateLimited { reset: Duration },
+    /// Answer with a 500.
+    ServerError,
+    /// Answer with a 503, as QStash does during maintenance.
+    Unavailable,
+    /// Answer normally after the delay.
+    Delayed(Duration),
+    /// Send the headers and half of the body, then close the connection.
+    DropConnection,
+    /// Answer with a body that is not JSON.
+    MalformedJson,
+}
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct Received {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// When the request was read.
+    pub at: Instant,
+}
+
+impl Received {
+    /// Get the value of a header, the name is case insensitive.
+    pub fn header(&self, name: &str) -> Option<String> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+    }
+}
+
+#[derive(Default)]
+struct Scenario {
+    failures: VecDeque<Failure>,
+    latency: Duration,
+    received: Vec<Received>,
+    published: usize,
+}
+
+pub struct MockQStash {
+    uri: String,
+    scenario: Arc<Mutex<Scenario>>,
+    task: JoinHandle<()>,
+}
+
+impl MockQStash {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Could not bind the mock server");
+        let uri = format!(
+            "http://{}",
+            listener.local_addr().expect("Could not get the address")
+        );
+        let scenario = Arc::new(Mutex::new(Scenario::default()));
+
+        let accepted = scenario.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, accepted.clone()));
+            }
+        });
+
+        Self {
+            uri,
+            scenario,
+            task,
+        }
+    }
+
+    pub fn uri(&self) -> String {
+        self.uri.clone()
+    }
+
+    /// fail_next fails the next `n` requests with the failure, after the ones already scheduled.
+    pub fn fail_next(&self, n: usize, failure: Failure) {
+        let mut scenario = self.scenario.lock().expect("Could not lock scenario");
+        scenario.failures.extend(std::iter::repeat_n(failure, n));
+    }
+
+    /// latency delays every response by the duration.
+    pub fn latency(&self, latency: Duration) {
+        self.scenario
+            .lock()
+            .expect("Could not lock scenario")
+            .latency = latency;
+    }
+
+    /// received returns the requests received so far, including the failed ones.
+    pub fn received(&self) -> Vec<Received> {
+        self.scenario
+            .lock()
+            .expect("Could not lock scenario")
+            .received
+            .clone()
+    }
+
+    /// attempts returns the amount of requests received so far.
+    pub fn attempts(&self) -> usize {
+        self.scenario
+            .lock()
+            .expect("Could not lock scenario")
+            .received
+            .len()
+    }
+
+    pub fn assert_attempts(&self, expected: usize) {
+        assert_eq!(
+            self.attempts(),
+            expected,
+            "unexpected amount of attempts: {:?}",
+            self.received()
+        );
+    }
+}
+
+impl Drop for MockQStash {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// serve answers the request of a connection and closes it.
+async fn serve(stream: TcpStream, scenario: Arc<Mutex<Scenario>>) {
+    let mut stream = BufReader::new(stream);
+    let received = match read_request(&mut stream).await {
+        Some(r) => r,
+        None => return,
+    };
+
+    let (failure, latency, (status, body)) = {
+        let mut scenario = scenario.lock().expect("Could not lock scenario");
+        scenario.received.push(received.clone());
+        let failure = scenario.failures.pop_front();
+        // rejected requests do not publish anything
+        let response = match failure {
+            Some(Failure::ServerError)
+            | Some(Failure::Unavailable)
+            | Some(Failure::RateLimited { .. }) => (0, String::new()),
+            _ => respond(&received, &mut scenario),
+        };
+        (failure, scenario.latency, response)
+    };
+
+    tokio::time::sleep(latency).await;
+    let (status, headers, body, sent) = match failure {
+        None => (status, Vec::new(), body.clone(), body.len()),
+        Some(Failure::Delayed(delay)) => {
+            tokio::time::sleep(delay).await;
+            (status, Vec::new(), body.clone(), body.len())
+        }
+        Some(Failure::DropConnection) => (status, Vec::new(), body.clone(), body.len() / 2),
+        Some(Failure::MalformedJson) => {
+            let body = r#"{"messageId": "msg_"#.to_string();
+            (status, Vec::new(), body.clone(), body.len())
+        }
+        Some(Failure::ServerError) => {
+            let body = r#"{"error":"internal server error"}"#.to_string();
+            (500, Vec::new(), body.clone(), body.len())
+        }
+        Some(Failure::Unavailable) => {
+            let body = r#"{"error":"service unavailable"}"#.to_string();
+            (503, Vec::new(), body.clone(), body.len())
+        }
+        Some(Failure::RateLimited { reset }) => {
+            let reset_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Clock went backwards")
+                + reset;
+            let headers = vec![
+                ("Burst-RateLimit-Limit", "100".to_string()),
+                ("Burst-RateLimit-Remaining", "0".to_string()),
+                ("Burst-RateLimit-Reset", reset_at.as_secs().to_string()),
+                ("Retry-After", reset.as_secs().to_string()),
+            ];
+            let body = r#"{"error":"burst rate limit exceeded"}"#.to_string();
+            (429, headers, body.clone(), body.len())
+        }
+    };
+
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    let stream = stream.get_mut();
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(&body.as_bytes()[..sent]).await;
+    let _ = stream.shutdown().await;
+}
+
+/// respond returns the status and body QStash answers the request with.
+fn respond(received: &Received, scenario: &mut Scenario) -> (u16, String) {
+    let mut message_id = || {
+        scenario.published += 1;
+        serde_json::json!({ "messageId": format!("msg_{}", scenario.published) })
+    };
+
+    match (received.method.as_str(), received.path.as_str()) {
+        ("POST", "/v2/batch") => {
+            let messages: Vec<serde_json::Value> =
+                serde_json::from_slice(&received.body).unwrap_or_default();
+            let responses: Vec<_> = messages.iter().map(|_| message_id()).collect();
+            (200, serde_json::Value::from(responses).to_string())
+        }
+        ("POST", p) if p.starts_with("/v2/publish/") || p.starts_with("/v2/enqueue/") => {
+            (201, message_id().to_string())
+        }
+        ("GET", "/v2/events") => (200, r#"{"events":[]}"#.to_string()),
+        ("GET", "/v2/dlq") => (200, r#"{"messages":[]}"#.to_string()),
+        _ => (200, "{}".to_string()),
+    }
+}
+
+/// read_request reads the request line, the headers and the body of a request.
+async fn read_request(stream: &mut BufReader<TcpStream>) -> Option<Received> {
+    let mut line = String::new();
+    stream.read_line(&mut line).await.ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.split('?').next()?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        stream.read_line(&mut line).await.ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await.ok()?;
+
+    Some(Received {
+        method,
+        path,
+        headers,
+        body,
+        at: Instant::now(),
+    })
+}
@@ -0,0 +1,61 @@
+#![allow(dead_code)]
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+pub mod mock_qstash;
+
+pub const CURRENT_SIGNING_KEY: &str = "sig_current";
+pub const NEXT_SIGNING_KEY: &str = "sig_next";
+
+/// Sign a delivery the same way QStash does.
+pub fn sign(key: &str, url: &str, body: &[u8]) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Clock went backwards")
+        .as_secs();
+
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+    let claims = URL_SAFE_NO_PAD.encode(
+        serde_json::json!({
+            "iss": "Upstash",
+            "sub": url,
+            "exp": now + 300,
+            "nbf": now,
+            "iat": now,
+            "jti": "jwt_test",
+            "body": URL_SAFE_NO_PAD.encode(Sha256::digest(body)),
+        })
+        .to_string(),
+    );
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).expect("Invalid key");
+    mac.update(format!("{}.{}", header, claims).as_bytes());
+    let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+
+    format!("{}.{}.{}", header, claims, signature)
+}
+
+/// Get the value of a header of a request received by the mock server.
+pub fn header(request: &wiremock::Request, name: &str) -> Option<String> {
+    request
+        .headers
+        .iter()
+        .find(|(k, _)| k.as_str().eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.last().as_str().to_string())
+}
+
+/// Respond to a batch request with one response per message, using the body of
+/// each message as its message id.
+pub fn echo_batch(request: &wiremock::Request) -> wiremock::ResponseTemplate {
+    let messages: Vec<serde_json::Value> =
+        serde_json::from_slice(&request.body).expect("Batch body should be a JSON array");
+    let responses: Vec<serde_json::Value> = messages
+        .iter()
+        .map(|m| serde_json::json!({ "messageId": m["body"].as_str().unwrap_or_default() }))
+        .collect();
+    wiremock::ResponseTemplate::new(200).set_body_json(responses)
+}
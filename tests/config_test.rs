@@ -0,0 +1,184 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use qstash_rs::client::{Client, ClientConfig, PublishOptions, PublishRequestUrl, QStashError};
+use wiremock::{
+    matchers::{method, path, path_regex},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use common::header;
+
+fn sample_config(server: &MockServer) -> ClientConfig {
+    std::env::set_var("QSTASH_CONFIG_TEST_TOKEN", "token_from_env");
+    let mut config: ClientConfig = toml::from_str(include_str!("fixtures/client_config.toml"))
+        .expect("Could not parse config");
+    config.base_url = Some(server.uri());
+    config
+}
+
+fn url() -> PublishRequestUrl {
+    PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL"))
+}
+
+#[tokio::test]
+async fn from_config_should_apply_the_publish_defaults() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/publish/"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_1" })),
+        )
+        .mount(&server)
+        .await;
+    let qstash_client = Client::from_config(sample_config(&server)).expect("Invalid config");
+
+    qstash_client
+        .publish_json(url(), "hello", None)
+        .await
+        .expect("Could not publish");
+    qstash_client
+        .publish_json(
+            url(),
+            "hello",
+            Some(PublishOptions {
+                retries: Some(5),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("Could not publish");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    let defaults = &requests[0];
+    assert_eq!(
+        header(defaults, "Authorization").as_deref(),
+        Some("Bearer token_from_env")
+    );
+    assert_eq!(header(defaults, "Upstash-Retries").as_deref(), Some("3"));
+    assert_eq!(header(defaults, "Upstash-Method").as_deref(), Some("PUT"));
+    assert_eq!(
+        header(defaults, "Upstash-Failure-Callback").as_deref(),
+        Some("https://example.com/failed")
+    );
+    assert_eq!(
+        header(defaults, "Upstash-Forward-Team").as_deref(),
+        Some("billing")
+    );
+
+    let overridden = &requests[1];
+    assert_eq!(header(overridden, "Upstash-Retries").as_deref(), Some("5"));
+    assert_eq!(
+        header(overridden, "Upstash-Failure-Callback").as_deref(),
+        Some("https://example.com/failed")
+    );
+}
+
+#[tokio::test]
+async fn from_config_should_apply_the_limits() {
+    let server = MockServer::start().await;
+    let qstash_client = Client::from_config(sample_config(&server)).expect("Invalid config");
+
+    let result = qstash_client
+        .publish_json(url(), "x".repeat(4096), None)
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(QStashError::BodyTooLarge { limit: 2048, .. })
+    ));
+    assert!(server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded")
+        .is_empty());
+}
+
+#[tokio::test]
+async fn from_config_should_retry_reads() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "events": [] })))
+        .mount(&server)
+        .await;
+    let qstash_client = Client::from_config(sample_config(&server)).expect("Invalid config");
+
+    qstash_client
+        .get_events(None)
+        .await
+        .expect("Could not get events");
+
+    assert_eq!(
+        server
+            .received_requests()
+            .await
+            .expect("Requests are not recorded")
+            .len(),
+        3
+    );
+}
+
+#[test]
+fn from_config_should_name_the_invalid_field() {
+    std::env::set_var("QSTASH_CONFIG_TEST_SET", "token");
+    std::env::remove_var("QSTASH_CONFIG_TEST_UNSET");
+
+    let cases = [
+        ("", "token"),
+        ("token = \"env:QSTASH_CONFIG_TEST_UNSET\"", "token"),
+        ("token_env = \"QSTASH_CONFIG_TEST_UNSET\"", "token_env"),
+        (
+            "token = \"t\"\ntoken_env = \"QSTASH_CONFIG_TEST_SET\"",
+            "token",
+        ),
+        ("token = \"t\"\nversion = \"v3\"", "version"),
+        ("token = \"t\"\nbase_url = \"not a url\"", "base_url"),
+        (
+            "token = \"t\"\n[retry]\nmax_attempts = 0",
+            "retry.max_attempts",
+        ),
+        (
+            "token = \"t\"\n[rate_limit]\nmax_requests_per_second = 0\nburst = 1",
+            "rate_limit.max_requests_per_second",
+        ),
+        (
+            "token = \"t\"\n[publish_defaults]\nmethod = \"GET POST\"",
+            "publish_defaults.method",
+        ),
+        (
+            "token = \"t\"\n[publish_defaults.headers]\n\"bad header\" = \"x\"",
+            "publish_defaults.headers.bad header",
+        ),
+    ];
+
+    for (config, expected) in cases {
+        let config: ClientConfig = toml::from_str(config).expect("Could not parse config");
+        match Client::from_config(config) {
+            Err(QStashError::ConfigError { field, .. }) => assert_eq!(field, expected),
+            Err(e) => panic!("{}: unexpected error {}", expected, e),
+            Ok(_) => panic!("{}: expected an error", expected),
+        }
+    }
+
+    let config: ClientConfig =
+        toml::from_str("token_env = \"QSTASH_CONFIG_TEST_SET\"").expect("Could not parse config");
+    assert!(Client::from_config(config).is_ok());
+}
+
+#[test]
+fn client_config_should_reject_unknown_fields() {
+    let error = toml::from_str::<ClientConfig>("tokn = \"t\"").expect_err("Unknown field");
+    assert!(error.to_string().contains("tokn"));
+}
@@ -0,0 +1,237 @@
+#![cfg(feature = "cron")]
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use qstash_rs::client::{cron::CronExpression, schedules::Schedule};
+
+/// utc returns the time of a date in UTC.
+fn utc(year: i64, month: i64, day: i64, hour: u64, minute: u64) -> SystemTime {
+    // days from civil, see http://howardhinnant.github.io/date_algorithms.html
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = (era * 146_097 + doe - 719_468) as u64;
+
+    UNIX_EPOCH + Duration::from_secs(days * 86_400 + hour * 3600 + minute * 60)
+}
+
+fn schedule(cron: &str) -> Schedule {
+    serde_json::from_value(serde_json::json!({
+        "scheduleId": "scd_1",
+        "cron": cron,
+        "destination": "https://example.com",
+    }))
+    .expect("Could not parse schedule")
+}
+
+#[test]
+fn next_runs_should_follow_steps() {
+    let from = utc(2024, 1, 1, 0, 7) + Duration::from_secs(30);
+    let runs = schedule("*/15 * * * *")
+        .next_runs(from, 5)
+        .expect("Could not compute runs");
+
+    assert_eq!(
+        runs,
+        vec![
+            utc(2024, 1, 1, 0, 15),
+            utc(2024, 1, 1, 0, 30),
+            utc(2024, 1, 1, 0, 45),
+            utc(2024, 1, 1, 1, 0),
+            utc(2024, 1, 1, 1, 15),
+        ]
+    );
+}
+
+#[test]
+fn next_runs_should_be_strictly_after_from() {
+    let runs = schedule("0 * * * *")
+        .next_runs(utc(2024, 1, 1, 10, 0), 2)
+        .expect("Could not compute runs");
+
+    assert_eq!(runs, vec![utc(2024, 1, 1, 11, 0), utc(2024, 1, 1, 12, 0)]);
+}
+
+#[test]
+fn next_runs_should_match_day_of_month_or_day_of_week() {
+    // the 13th or every friday, the 13th of september 2024 is a friday
+    let runs = schedule("0 12 13 * 5")
+        .next_runs(utc(2024, 9, 1, 0, 0), 7)
+        .expect("Could not compute runs");
+
+    assert_eq!(
+        runs,
+        vec![
+            utc(2024, 9, 6, 12, 0),
+            utc(2024, 9, 13, 12, 0),
+            utc(2024, 9, 20, 12, 0),
+            utc(2024, 9, 27, 12, 0),
+            utc(2024, 10, 4, 12, 0),
+            utc(2024, 10, 11, 12, 0),
+            utc(2024, 10, 13, 12, 0),
+        ]
+    );
+}
+
+#[test]
+fn next_runs_should_match_day_of_week_when_day_of_month_is_any() {
+    // mondays and sundays, with sunday written as 7 and month names
+    let runs = schedule("30 9 * JAN-MAR mon,7")
+        .next_runs(utc(2024, 3, 28, 0, 0), 3)
+        .expect("Could not compute runs");
+
+    assert_eq!(
+        runs,
+        vec![
+            utc(2024, 3, 31, 9, 30),
+            utc(2025, 1, 5, 9, 30),
+            utc(2025, 1, 6, 9, 30),
+        ]
+    );
+}
+
+#[test]
+fn next_runs_should_handle_leap_days() {
+    let runs = schedule("0 0 29 2 *")
+        .next_runs(utc(2025, 1, 1, 0, 0), 2)
+        .expect("Could not compute runs");
+
+    assert_eq!(runs, vec![utc(2028, 2, 29, 0, 0), utc(2032, 2, 29, 0, 0)]);
+}
+
+#[test]
+fn next_runs_should_stop_when_the_schedule_never_runs() {
+    for cron in ["0 0 30 2 *", "0 0 31 4,6,9,11 *"] {
+        let runs = schedule(cron)
+            .next_runs(utc(2024, 1, 1, 0, 0), 5)
+            .expect("Could not compute runs");
+        assert!(runs.is_empty(), "{}", cron);
+    }
+}
+
+#[test]
+fn cron_expression_should_reject_invalid_expressions() {
+    for cron in [
+        "* * * *",
+        "* * * * * *",
+        "60 * * * *",
+        "* 24 * * *",
+        "* * 0 * *",
+        "* * * 13 *",
+        "* * * * 8",
+        "*/0 * * * *",
+        "5-1 * * * *",
+        "a * * * *",
+    ] {
+        let error = CronExpression::parse(cron).expect_err(cron);
+        assert_eq!(error.expression, cron);
+    }
+
+    assert!(schedule("* * * *")
+        .next_runs(utc(2024, 1, 1, 0, 0), 1)
+        .is_err());
+}
+
+#[test]
+fn cron_expression_should_expand_macros() {
+    let from = utc(2024, 1, 1, 0, 7);
+    for (name, expression) in [
+        ("@yearly", "0 0 1 1 *"),
+        ("@annually", "0 0 1 1 *"),
+        ("@monthly", "0 0 1 * *"),
+        ("@weekly", "0 0 * * 0"),
+        ("@daily", "0 0 * * *"),
+        ("@midnight", "0 0 * * *"),
+        ("@hourly", "0 * * * *"),
+    ] {
+        assert_eq!(
+            schedule(name).next_runs(from, 3).expect(name),
+            schedule(expression).next_runs(from, 3).expect(expression),
+            "{}",
+            name
+        );
+    }
+    assert!(CronExpression::parse("@sometimes").is_err());
+}
+
+#[cfg(feature = "reqwest")]
+#[tokio::test]
+async fn create_schedule_should_reject_invalid_cron_before_sending() {
+    use qstash_rs::client::{
+        schedules::CreateScheduleRequest, Client, PublishRequestUrl, QStashError,
+    };
+
+    let server = wiremock::MockServer::start().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let destination = PublishRequestUrl::Url(
+        "https://example.com/job"
+            .parse()
+            .expect("Could not parse URL"),
+    );
+
+    let result = qstash_client
+        .create_schedule(CreateScheduleRequest::new(destination.clone(), "* * * *"))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(QStashError::InvalidCron { expression, reason })
+            if expression == "* * * *" && reason == "expected 5 fields, found 4"
+    ));
+    assert!(server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded")
+        .is_empty());
+
+    wiremock::Mock::given(wiremock::matchers::method("POST"))
+        .respond_with(
+            wiremock::ResponseTemplate::new(201)
+                .set_body_json(serde_json::json!({ "scheduleId": "scd_1" })),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    for cron in ["@hourly", "CRON_TZ=Europe/Paris 0 9 * * 1-5"] {
+        qstash_client
+            .create_schedule(CreateScheduleRequest::new(destination.clone(), cron))
+            .await
+            .expect(cron);
+    }
+}
+
+#[test]
+fn next_runs_should_compute_utc_time_zones() {
+    let runs = schedule("CRON_TZ=UTC 0 9 * * *")
+        .next_runs(utc(2024, 1, 1, 10, 0), 2)
+        .expect("Could not compute runs");
+
+    assert_eq!(runs, vec![utc(2024, 1, 2, 9, 0), utc(2024, 1, 3, 9, 0)]);
+}
+
+#[test]
+fn next_runs_should_reject_other_time_zones() {
+    let error = schedule("CRON_TZ=America/New_York 0 9 * * *")
+        .next_runs(utc(2024, 1, 1, 10, 0), 2)
+        .expect_err("Time zone should be rejected");
+
+    assert_eq!(error.expression, "CRON_TZ=America/New_York 0 9 * * *");
+    assert!(
+        error.reason.contains("America/New_York"),
+        "{}",
+        error.reason
+    );
+}
+
+#[test]
+fn next_runs_should_not_preallocate_huge_counts() {
+    let runs = schedule("0 0 30 2 *")
+        .next_runs(utc(2024, 1, 1, 0, 0), usize::MAX)
+        .expect("Could not compute runs");
+
+    assert!(runs.is_empty());
+}
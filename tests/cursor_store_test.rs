@@ -0,0 +1,173 @@
+#![cfg(feature = "reqwest")]
+
+use std::{path::PathBuf, sync::Arc};
+
+use futures::StreamExt;
+use qstash_rs::client::{Client, CursorStore, FileCursorStore, QStashError};
+use wiremock::{matchers::method, Mock, MockServer, Request, ResponseTemplate};
+
+/// temp_dir returns an empty directory for the cursors of the test.
+fn temp_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("qstash-cursor-{}-{}", std::process::id(), test));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// paginated answers with three pages, keyed by the cursor query parameter.
+fn paginated(items: &'static str) -> impl Fn(&Request) -> ResponseTemplate {
+    move |request: &Request| {
+        let cursor = request
+            .url
+            .query_pairs()
+            .find(|(k, _)| k == "cursor")
+            .map(|(_, v)| v.into_owned());
+        let (ids, next) = match cursor.as_deref() {
+            None => (vec!["1", "2"], Some("c1")),
+            Some("c1") => (vec!["3", "4"], Some("c2")),
+            Some("c2") => (vec!["5"], None),
+            Some(_) => return ResponseTemplate::new(400),
+        };
+        let page: Vec<serde_json::Value> = ids
+            .into_iter()
+            .map(|id| match items {
+                "events" => serde_json::json!({
+                    "time": 1_700_000_000_000u64,
+                    "state": "DELIVERED",
+                    "messageId": format!("msg_{}", id),
+                }),
+                _ => serde_json::json!({
+                    "messageId": format!("msg_{}", id),
+                    "url": "https://example.com",
+                    "method": "POST",
+                    "createdAt": 1_700_000_000_000u64,
+                    "dlqId": format!("dlq_{}", id),
+                }),
+            })
+            .collect();
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            items: page,
+            "cursor": next,
+        }))
+    }
+}
+
+async fn mock_server(items: &'static str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(paginated(items))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn checkpointed_events_stream_should_resume_without_losing_events() {
+    let server = mock_server("events").await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let dir = temp_dir("events");
+    let store = Arc::new(FileCursorStore::new(&dir));
+    let mut seen = Vec::new();
+
+    // the first run stops in the middle of the second page
+    {
+        let mut events =
+            Box::pin(qstash_client.checkpointed_events_stream(store.clone(), "events"));
+        for _ in 0..2 {
+            let event = events.next().await.expect("Missing event");
+            seen.push(event.expect("Could not get event").message_id);
+            assert_eq!(store.load("events").await.expect("Could not load"), None);
+        }
+        let event = events.next().await.expect("Missing event");
+        seen.push(event.expect("Could not get event").message_id);
+        assert_eq!(
+            store.load("events").await.expect("Could not load"),
+            Some("c1".to_string())
+        );
+    }
+
+    let events: Vec<_> = qstash_client
+        .checkpointed_events_stream(store.clone(), "events")
+        .map(|e| e.expect("Could not get event").message_id)
+        .collect()
+        .await;
+    seen.extend(events);
+
+    assert_eq!(
+        seen,
+        ["msg_1", "msg_2", "msg_3", "msg_3", "msg_4", "msg_5"].map(String::from)
+    );
+    // the last page has no next cursor, the cursor of the last page is kept
+    assert_eq!(
+        store.load("events").await.expect("Could not load"),
+        Some("c2".to_string())
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn checkpointed_dead_letter_queue_stream_should_resume_after_the_last_full_page() {
+    let server = mock_server("messages").await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let dir = temp_dir("dlq");
+    let store = Arc::new(FileCursorStore::new(&dir));
+    store.save("dlq", "c1").await.expect("Could not save");
+
+    let messages: Vec<_> = qstash_client
+        .checkpointed_dead_letter_queue_stream(store.clone(), "dlq")
+        .map(|m| m.expect("Could not get message").dlq_id)
+        .collect()
+        .await;
+
+    assert_eq!(messages, ["dlq_3", "dlq_4", "dlq_5"].map(String::from));
+    assert_eq!(
+        store.load("dlq").await.expect("Could not load"),
+        Some("c2".to_string())
+    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn events_stream_should_read_every_page() {
+    let server = mock_server("events").await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let events: Vec<_> = qstash_client
+        .events_stream(None)
+        .map(|e| e.expect("Could not get event").message_id)
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 5);
+    assert_eq!(
+        server
+            .received_requests()
+            .await
+            .expect("Requests are not recorded")
+            .len(),
+        3
+    );
+}
+
+#[tokio::test]
+async fn file_cursor_store_should_reject_invalid_names() {
+    let dir = temp_dir("names");
+    let store = FileCursorStore::new(&dir);
+
+    assert_eq!(store.load("missing").await.expect("Could not load"), None);
+    store.save("events", "c1").await.expect("Could not save");
+    store.save("events", "c2").await.expect("Could not save");
+    assert_eq!(
+        store.load("events").await.expect("Could not load"),
+        Some("c2".to_string())
+    );
+    for name in ["", "../events", ".hidden", "a/b"] {
+        assert!(matches!(
+            store.save(name, "c1").await,
+            Err(QStashError::CursorStoreError)
+        ));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
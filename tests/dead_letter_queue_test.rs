@@ -0,0 +1,1102 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use std::{collections::HashMap, time::Duration};
+
+use common::header;
+use futures::StreamExt;
+use http::{header::HeaderValue, Method};
+use qstash_rs::client::{
+    dead_letter_queue::{summarize, DlqMessage, DlqOrder, DlqRequest, DlqSummary, MAX_DLQ_COUNT},
+    Client, PublishOptions, QStashError,
+};
+use wiremock::{
+    matchers::{method, path, path_regex, query_param, query_param_is_missing},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn dlq_message(body: serde_json::Value) -> serde_json::Value {
+    let mut message = serde_json::json!({
+        "messageId": "msg_123",
+        "url": "https://example.com/orders",
+        "method": "PUT",
+        "header": {
+            "Content-Type": ["application/json"],
+            "X-Tenant": ["acme"],
+        },
+        "maxRetries": 3,
+        "createdAt": 1_699_000_000_000u64,
+        "dlqId": "dlq_123",
+    });
+    message
+        .as_object_mut()
+        .expect("Message should be an object")
+        .extend(body.as_object().expect("Body should be an object").clone());
+    message
+}
+
+async fn mock_server(message: serde_json::Value, publish_status: u16) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq/dlq_123"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(message))
+        .mount(&server)
+        .await;
+    let publish_response = match publish_status {
+        201 => serde_json::json!({ "messageId": "msg_456" }),
+        _ => serde_json::json!({ "error": "invalid destination" }),
+    };
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/publish/"))
+        .respond_with(ResponseTemplate::new(publish_status).set_body_json(publish_response))
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/dlq/dlq_123"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    server
+}
+
+async fn requests(server: &MockServer, request_method: &str) -> Vec<wiremock::Request> {
+    server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded")
+        .into_iter()
+        .filter(|r| r.method.to_string() == request_method)
+        .collect()
+}
+
+#[tokio::test]
+async fn requeue_dlq_message_with_should_patch_the_body() {
+    let server = mock_server(
+        dlq_message(serde_json::json!({ "body": r#"{"amount":"1"}"# })),
+        201,
+    )
+    .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let responses = qstash_client
+        .requeue_dlq_message_with("dlq_123", |mut request| {
+            assert_eq!(request.body.as_deref(), Some(&br#"{"amount":"1"}"#[..]));
+            assert_eq!(request.method, Some(Method::PUT));
+            request.body = Some(br#"{"amount":1}"#.to_vec());
+            request
+        })
+        .await
+        .expect("Could not requeue message");
+    assert_eq!(responses[0].message_id.as_deref(), Some("msg_456"));
+
+    let published = requests(&server, "POST").await;
+    assert_eq!(published.len(), 1);
+    assert!(published[0]
+        .url
+        .path()
+        .ends_with("/https://example.com/orders"));
+    assert_eq!(published[0].body, br#"{"amount":1}"#);
+    assert_eq!(
+        header(&published[0], "Content-Type").as_deref(),
+        Some("application/json")
+    );
+    assert_eq!(
+        header(&published[0], "Upstash-Method").as_deref(),
+        Some("PUT")
+    );
+    assert_eq!(
+        header(&published[0], "Upstash-Retries").as_deref(),
+        Some("3")
+    );
+    assert_eq!(requests(&server, "DELETE").await.len(), 1);
+}
+
+#[tokio::test]
+async fn requeue_dlq_message_with_should_add_headers() {
+    // non UTF-8 bodies are stored base64 encoded
+    let server = mock_server(
+        dlq_message(serde_json::json!({ "bodyBase64": "AAEC/w==" })),
+        201,
+    )
+    .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    qstash_client
+        .requeue_dlq_message_with("dlq_123", |mut request| {
+            assert_eq!(request.body.as_deref(), Some(&[0u8, 1, 2, 255][..]));
+            request.headers.get_or_insert_with(Default::default).insert(
+                "Upstash-Forward-X-Requeued",
+                HeaderValue::from_static("true"),
+            );
+            request
+        })
+        .await
+        .expect("Could not requeue message");
+
+    let published = requests(&server, "POST").await;
+    assert_eq!(published[0].body, [0u8, 1, 2, 255]);
+    assert_eq!(
+        header(&published[0], "Upstash-Forward-X-Requeued").as_deref(),
+        Some("true")
+    );
+    assert_eq!(
+        header(&published[0], "Upstash-Forward-X-Tenant").as_deref(),
+        Some("acme")
+    );
+    assert_eq!(requests(&server, "DELETE").await.len(), 1);
+}
+
+#[tokio::test]
+async fn requeue_dlq_message_with_should_keep_the_entry_on_failure() {
+    let server = mock_server(dlq_message(serde_json::json!({ "body": "hello" })), 400).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    assert!(matches!(
+        qstash_client
+            .requeue_dlq_message_with("dlq_123", |request| request)
+            .await,
+        Err(QStashError::PublishError)
+    ));
+    assert!(requests(&server, "DELETE").await.is_empty());
+}
+
+fn multi_valued_headers() -> serde_json::Value {
+    serde_json::json!({
+        "header": {
+            "content-TYPE": ["application/json"],
+            "X-Tenant": ["acme"],
+            "x-TENANT": ["globex", "initech"],
+        },
+    })
+}
+
+#[test]
+fn headers_should_merge_names_of_any_casing() {
+    let message: DlqMessage =
+        serde_json::from_value(dlq_message(multi_valued_headers())).expect("Invalid message");
+
+    let headers = message.headers().expect("Invalid headers");
+
+    assert_eq!(headers.len(), 4);
+    assert_eq!(
+        headers.get("Content-Type"),
+        Some(&HeaderValue::from_static("application/json"))
+    );
+    let tenants: Vec<&str> = headers
+        .get_all("x-tenant")
+        .iter()
+        .map(|v| v.to_str().expect("Invalid value"))
+        .collect();
+    assert_eq!(tenants, ["acme", "globex", "initech"]);
+    assert_eq!(message.header.expect("Raw headers are kept").len(), 3);
+}
+
+#[test]
+fn headers_should_reject_invalid_names() {
+    let message: DlqMessage = serde_json::from_value(dlq_message(serde_json::json!({
+        "header": { "Bad Header": ["x"] },
+    })))
+    .expect("Invalid message");
+
+    let error = message.headers().expect_err("The name is invalid");
+
+    assert_eq!(error.name, "Bad Header");
+}
+
+#[tokio::test]
+async fn requeue_dlq_message_with_should_keep_every_header_value() {
+    let server = mock_server(dlq_message(multi_valued_headers()), 201).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    qstash_client
+        .requeue_dlq_message_with("dlq_123", |request| request)
+        .await
+        .expect("Could not requeue message");
+
+    let published = requests(&server, "POST").await;
+    let tenants: Vec<String> = published[0]
+        .headers
+        .iter()
+        .filter(|(name, _)| {
+            name.as_str()
+                .eq_ignore_ascii_case("upstash-forward-x-tenant")
+        })
+        .flat_map(|(_, values)| values.iter().map(|v| v.as_str().to_string()))
+        .collect();
+    assert_eq!(tenants, ["acme", "globex", "initech"]);
+    assert_eq!(
+        header(&published[0], "Content-Type").as_deref(),
+        Some("application/json")
+    );
+}
+
+#[tokio::test]
+async fn get_dead_letter_message_should_work() {
+    let server = mock_server(dlq_message(serde_json::json!({ "body": "hello" })), 201).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let message = qstash_client
+        .get_dead_letter_message("dlq_123")
+        .await
+        .expect("Could not get message");
+
+    assert_eq!(message.dlq_id, "dlq_123");
+    assert_eq!(message.message_id, "msg_123");
+    assert_eq!(message.body.as_deref(), Some("hello"));
+}
+
+#[tokio::test]
+async fn get_dead_letter_message_should_report_missing_messages() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq/dlq_404"))
+        .respond_with(
+            ResponseTemplate::new(404).set_body_json(serde_json::json!({ "error": "not found" })),
+        )
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let error = qstash_client
+        .get_dead_letter_message("dlq_404")
+        .await
+        .expect_err("The message does not exist");
+
+    assert!(matches!(&error, QStashError::DlqMessageNotFound(id) if id == "dlq_404"));
+    assert_eq!(error.to_string(), "DLQ message dlq_404 not found");
+    assert!(matches!(
+        qstash_client
+            .requeue_dlq_message_with("dlq_404", |request| request)
+            .await,
+        Err(QStashError::DlqMessageNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn get_dead_letter_message_should_fail_on_server_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq/dlq_123"))
+        .respond_with(
+            ResponseTemplate::new(400).set_body_json(serde_json::json!({ "error": "bad request" })),
+        )
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    assert!(matches!(
+        qstash_client.get_dead_letter_message("dlq_123").await,
+        Err(QStashError::DeadLetterQueueError { .. })
+    ));
+}
+
+#[tokio::test]
+async fn delete_dead_letter_message_should_report_missing_messages() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/dlq/dlq_404"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    assert!(matches!(
+        qstash_client.delete_dead_letter_message("dlq_404").await,
+        Err(QStashError::DlqMessageNotFound(id)) if id == "dlq_404"
+    ));
+}
+
+#[tokio::test]
+async fn get_dead_letter_queue_should_page_with_opaque_cursors() {
+    let server = MockServer::start().await;
+    let page = |dlq_id: &str, cursor: Option<&str>| {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "messages": [dlq_message(serde_json::json!({ "dlqId": dlq_id }))],
+            "cursor": cursor,
+        }))
+    };
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .and(query_param("cursor", "1699000000000-0/a+b"))
+        .respond_with(page("dlq_2", Some("")))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .respond_with(page("dlq_1", Some("1699000000000-0/a+b")))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let mut dlq_ids = Vec::new();
+    let mut request = Some(DlqRequest::default());
+    while let Some(r) = request {
+        let page = qstash_client
+            .get_dead_letter_queue(Some(r.clone()))
+            .await
+            .expect("Could not get dead letter queue");
+        dlq_ids.extend(page.messages.iter().map(|m| m.dlq_id.clone()));
+        request = page.next_request(&r);
+    }
+
+    assert_eq!(dlq_ids, ["dlq_1", "dlq_2"]);
+}
+
+#[test]
+fn dlq_request_should_build_the_query_of_every_filter() {
+    let request = DlqRequest {
+        cursor: Some("c1".to_string()),
+        count: Some(25),
+        order: Some(DlqOrder::Asc),
+        message_id: Some("msg_123".to_string()),
+        url: Some("https://example.com/orders?tenant=acme&x=1".to_string()),
+        topic_name: Some("billing".to_string()),
+        schedule_id: Some("scd_123".to_string()),
+        queue_name: Some("orders".to_string()),
+        from_date: Some(1_699_000_000_000),
+        to_date: Some(1_699_100_000_000),
+        response_status: Some(503),
+    };
+
+    assert_eq!(
+        request.query_pairs(),
+        [
+            ("cursor", "c1".to_string()),
+            ("messageId", "msg_123".to_string()),
+            (
+                "url",
+                "https://example.com/orders?tenant=acme&x=1".to_string()
+            ),
+            ("topicName", "billing".to_string()),
+            ("scheduleId", "scd_123".to_string()),
+            ("queueName", "orders".to_string()),
+            ("order", "earliestFirst".to_string()),
+            ("count", "25".to_string()),
+            ("fromDate", "1699000000000".to_string()),
+            ("toDate", "1699100000000".to_string()),
+            ("responseStatus", "503".to_string()),
+        ]
+    );
+    assert!(DlqRequest::default().query_pairs().is_empty());
+}
+
+#[tokio::test]
+async fn get_dead_letter_queue_should_send_the_filters_encoded() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .and(query_param(
+            "url",
+            "https://example.com/orders?tenant=acme&x=1",
+        ))
+        .and(query_param("responseStatus", "503"))
+        .and(query_param("queueName", "orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "messages": [dlq_message(serde_json::json!({}))],
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let page = qstash_client
+        .get_dead_letter_queue(Some(DlqRequest {
+            url: Some("https://example.com/orders?tenant=acme&x=1".to_string()),
+            queue_name: Some("orders".to_string()),
+            response_status: Some(503),
+            ..Default::default()
+        }))
+        .await
+        .expect("Could not get dead letter queue");
+
+    assert_eq!(page.messages.len(), 1);
+    let requests = requests(&server, "GET").await;
+    assert_eq!(
+        requests[0].url.query(),
+        Some("url=https%3A%2F%2Fexample.com%2Forders%3Ftenant%3Dacme%26x%3D1&queueName=orders&responseStatus=503")
+    );
+}
+
+#[tokio::test]
+async fn dead_letter_queue_stream_should_keep_the_filters_on_every_page() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .and(query_param("cursor", "c1"))
+        .and(query_param("topicName", "billing"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "messages": [dlq_message(serde_json::json!({ "dlqId": "dlq_2" }))],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .and(query_param("topicName", "billing"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "messages": [dlq_message(serde_json::json!({ "dlqId": "dlq_1" }))],
+            "cursor": "c1",
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let dlq_ids: Vec<String> = qstash_client
+        .dead_letter_queue_stream(Some(DlqRequest {
+            topic_name: Some("billing".to_string()),
+            ..Default::default()
+        }))
+        .map(|m| m.expect("Could not get message").dlq_id)
+        .collect()
+        .await;
+
+    assert_eq!(dlq_ids, ["dlq_1", "dlq_2"]);
+}
+
+#[test]
+fn dlq_message_should_deserialize_the_failed_response() {
+    let message: DlqMessage = serde_json::from_value(dlq_message(serde_json::json!({
+        "responseStatus": 503,
+        "responseHeader": { "Retry-After": ["30"] },
+        "responseBody": "c2VydmljZSB1bmF2YWlsYWJsZQ==",
+    })))
+    .expect("Invalid message");
+
+    assert_eq!(message.response_status, Some(503));
+    assert_eq!(
+        message
+            .response_headers()
+            .expect("Invalid headers")
+            .get("retry-after"),
+        Some(&HeaderValue::from_static("30"))
+    );
+    assert_eq!(
+        message.response_body_decoded().expect("Invalid body"),
+        b"service unavailable"
+    );
+    assert_eq!(
+        message.response_body_utf8().expect("Invalid body"),
+        "service unavailable"
+    );
+}
+
+#[test]
+fn dlq_message_should_deserialize_without_the_failed_response() {
+    let message: DlqMessage =
+        serde_json::from_value(dlq_message(serde_json::json!({}))).expect("Invalid message");
+
+    assert_eq!(message.response_status, None);
+    assert!(message
+        .response_headers()
+        .expect("Invalid headers")
+        .is_empty());
+    assert!(message
+        .response_body_decoded()
+        .expect("Invalid body")
+        .is_empty());
+    assert_eq!(message.response_body_utf8().expect("Invalid body"), "");
+}
+
+#[test]
+fn dlq_message_should_reject_an_invalid_response_body() {
+    let message: DlqMessage = serde_json::from_value(dlq_message(serde_json::json!({
+        "responseBody": "not base64!",
+    })))
+    .expect("Invalid message");
+
+    assert!(matches!(
+        message.response_body_decoded(),
+        Err(QStashError::DeadLetterQueueError { .. })
+    ));
+}
+
+#[tokio::test]
+async fn requeue_dead_letter_message_should_override_the_options() {
+    let server = mock_server(dlq_message(serde_json::json!({ "body": "hello" })), 201).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let responses = qstash_client
+        .requeue_dead_letter_message(
+            "dlq_123",
+            Some(PublishOptions {
+                retries: Some(5),
+                deduplication_id: Some("retry-dlq_123".to_string()),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("Could not requeue message");
+    assert_eq!(responses[0].message_id.as_deref(), Some("msg_456"));
+
+    let published = requests(&server, "POST").await;
+    assert_eq!(published.len(), 1);
+    assert!(published[0]
+        .url
+        .path()
+        .ends_with("/https://example.com/orders"));
+    assert_eq!(published[0].body, b"hello");
+    assert_eq!(
+        header(&published[0], "Upstash-Method").as_deref(),
+        Some("PUT")
+    );
+    assert_eq!(
+        header(&published[0], "Upstash-Forward-X-Tenant").as_deref(),
+        Some("acme")
+    );
+    assert_eq!(
+        header(&published[0], "Upstash-Retries").as_deref(),
+        Some("5")
+    );
+    assert_eq!(
+        header(&published[0], "Upstash-Deduplication-Id").as_deref(),
+        Some("retry-dlq_123")
+    );
+    assert_eq!(requests(&server, "DELETE").await.len(), 1);
+}
+
+#[tokio::test]
+async fn requeue_dead_letter_message_should_keep_the_entry_on_publish_failure() {
+    let server = mock_server(dlq_message(serde_json::json!({ "body": "hello" })), 400).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    assert!(matches!(
+        qstash_client
+            .requeue_dead_letter_message("dlq_123", None)
+            .await,
+        Err(QStashError::PublishError)
+    ));
+    assert_eq!(requests(&server, "POST").await.len(), 1);
+    assert!(requests(&server, "DELETE").await.is_empty());
+}
+
+#[tokio::test]
+async fn requeue_dead_letter_message_should_report_a_failed_delete() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq/dlq_123"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(dlq_message(serde_json::json!({ "body": "hello" }))),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/publish/"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_456" })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/dlq/dlq_123"))
+        .respond_with(ResponseTemplate::new(400))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    assert!(matches!(
+        qstash_client
+            .requeue_dead_letter_message("dlq_123", None)
+            .await,
+        Err(QStashError::DeadLetterQueueError { .. })
+    ));
+    assert_eq!(requests(&server, "POST").await.len(), 1);
+    assert_eq!(requests(&server, "DELETE").await.len(), 1);
+}
+
+/// three_pages serves a dead letter queue of three pages of two messages,
+/// the second page answering after the delay.
+async fn three_pages(delay: Duration) -> MockServer {
+    let server = MockServer::start().await;
+    let pages = [(None, "c2", 1), (Some("c2"), "c3", 3), (Some("c3"), "", 5)];
+    for (cursor, next, first) in pages {
+        let body = serde_json::json!({
+            "messages": [
+                dlq_message(serde_json::json!({ "dlqId": format!("dlq_{}", first) })),
+                dlq_message(serde_json::json!({ "dlqId": format!("dlq_{}", first + 1) })),
+            ],
+            "cursor": next,
+        });
+        let mock = Mock::given(method("GET")).and(path("/v2/dlq"));
+        let mock = match cursor {
+            Some(c) => mock.and(query_param("cursor", c)).respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(body)
+                    .set_delay(if c == "c2" { delay } else { Duration::ZERO }),
+            ),
+            None => mock
+                .and(query_param_is_missing("cursor"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(body)),
+        };
+        mock.mount(&server).await;
+    }
+    server
+}
+
+#[tokio::test]
+async fn dead_letter_queue_stream_should_read_every_page_lazily() {
+    let server = three_pages(Duration::ZERO).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let mut stream = Box::pin(qstash_client.dead_letter_queue_stream(None));
+    let first = stream
+        .next()
+        .await
+        .expect("The stream ended")
+        .expect("Could not get message");
+    assert_eq!(first.dlq_id, "dlq_1");
+    assert_eq!(requests(&server, "GET").await.len(), 1);
+
+    let mut dlq_ids = vec![first.dlq_id];
+    while let Some(message) = stream.next().await {
+        dlq_ids.push(message.expect("Could not get message").dlq_id);
+    }
+    assert_eq!(
+        dlq_ids,
+        ["dlq_1", "dlq_2", "dlq_3", "dlq_4", "dlq_5", "dlq_6"]
+    );
+    assert_eq!(requests(&server, "GET").await.len(), 3);
+}
+
+#[tokio::test]
+async fn dead_letter_queue_stream_should_be_cancel_safe() {
+    let server = three_pages(Duration::from_millis(300)).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let mut stream = Box::pin(qstash_client.dead_letter_queue_stream(None));
+    stream.next().await.expect("The stream ended").ok();
+    stream.next().await.expect("The stream ended").ok();
+    // the fetch of the second page is interrupted and resumed, not sent again
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), stream.next())
+            .await
+            .is_err()
+    );
+
+    let mut dlq_ids = Vec::new();
+    while let Some(message) = stream.next().await {
+        dlq_ids.push(message.expect("Could not get message").dlq_id);
+    }
+    assert_eq!(dlq_ids, ["dlq_3", "dlq_4", "dlq_5", "dlq_6"]);
+    assert_eq!(requests(&server, "GET").await.len(), 3);
+}
+
+/// failing_server answers every request with the response.
+async fn failing_server(response: ResponseTemplate) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(wiremock::matchers::any())
+        .respond_with(response)
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn dead_letter_queue_should_report_error_statuses() {
+    let server = failing_server(
+        ResponseTemplate::new(401).set_body_json(serde_json::json!({ "error": "unauthorized" })),
+    )
+    .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let error = qstash_client
+        .get_dead_letter_queue(None)
+        .await
+        .expect_err("The request is unauthorized");
+    assert!(matches!(
+        &error,
+        QStashError::DeadLetterQueueError { status: Some(401), message } if message == "unauthorized"
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Error with dead letter queue (401): unauthorized"
+    );
+
+    let messages: Vec<_> = qstash_client.dead_letter_queue_stream(None).collect().await;
+    assert!(matches!(
+        messages[..],
+        [Err(QStashError::DeadLetterQueueError {
+            status: Some(401),
+            ..
+        })]
+    ));
+    assert!(matches!(
+        qstash_client.get_dead_letter_message("dlq_123").await,
+        Err(QStashError::DeadLetterQueueError {
+            status: Some(401),
+            ..
+        })
+    ));
+    assert!(matches!(
+        qstash_client.delete_dead_letter_message("dlq_123").await,
+        Err(QStashError::DeadLetterQueueError {
+            status: Some(401),
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn dead_letter_queue_should_report_malformed_responses() {
+    let server = failing_server(ResponseTemplate::new(200).set_body_string("not json")).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    assert!(matches!(
+        qstash_client.get_dead_letter_queue(None).await,
+        Err(QStashError::DeadLetterQueueError { status: None, .. })
+    ));
+    let messages: Vec<_> = qstash_client.dead_letter_queue_stream(None).collect().await;
+    assert!(matches!(
+        messages[..],
+        [Err(QStashError::DeadLetterQueueError { status: None, .. })]
+    ));
+    assert!(matches!(
+        qstash_client.get_dead_letter_message("dlq_123").await,
+        Err(QStashError::DeadLetterQueueError { status: None, .. })
+    ));
+}
+
+#[tokio::test]
+async fn dead_letter_queue_should_report_transport_errors() {
+    // nothing listens on the port
+    let qstash_client = Client::new("token", Some("http://127.0.0.1:1"), None)
+        .expect("Could not initialize client");
+
+    assert!(matches!(
+        qstash_client.get_dead_letter_queue(None).await,
+        Err(QStashError::DeadLetterQueueError { status: None, .. })
+    ));
+    assert!(matches!(
+        qstash_client.get_dead_letter_message("dlq_123").await,
+        Err(QStashError::DeadLetterQueueError { status: None, .. })
+    ));
+    assert!(matches!(
+        qstash_client.delete_dead_letter_message("dlq_123").await,
+        Err(QStashError::DeadLetterQueueError { status: None, .. })
+    ));
+}
+
+#[tokio::test]
+async fn get_dead_letter_queue_should_send_the_count_with_the_cursor() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "messages": [],
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let requests_sent = [(Some("c2"), None), (None, Some(10)), (Some("c2"), Some(10))];
+    for (cursor, count) in requests_sent {
+        qstash_client
+            .get_dead_letter_queue(Some(DlqRequest {
+                cursor: cursor.map(str::to_string),
+                count,
+                ..Default::default()
+            }))
+            .await
+            .expect("Could not get dead letter queue");
+    }
+
+    let queries: Vec<Option<String>> = requests(&server, "GET")
+        .await
+        .iter()
+        .map(|r| r.url.query().map(str::to_string))
+        .collect();
+    assert_eq!(
+        queries,
+        [
+            Some("cursor=c2".to_string()),
+            Some("count=10".to_string()),
+            Some("cursor=c2&count=10".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn get_dead_letter_queue_should_reject_an_invalid_count() {
+    let server = MockServer::start().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    for count in [0, MAX_DLQ_COUNT + 1] {
+        let request = DlqRequest {
+            count: Some(count),
+            ..Default::default()
+        };
+        assert!(matches!(
+            qstash_client
+                .get_dead_letter_queue(Some(request.clone()))
+                .await,
+            Err(QStashError::DeadLetterQueueError { status: None, .. })
+        ));
+        let messages: Vec<_> = qstash_client
+            .dead_letter_queue_stream(Some(request))
+            .collect()
+            .await;
+        assert!(matches!(
+            messages[..],
+            [Err(QStashError::DeadLetterQueueError { status: None, .. })]
+        ));
+    }
+    assert!(requests(&server, "GET").await.is_empty());
+}
+
+#[test]
+fn dlq_message_should_type_the_method() {
+    let method = |value: serde_json::Value| {
+        let mut message = dlq_message(serde_json::json!({}));
+        match value {
+            serde_json::Value::Null => message
+                .as_object_mut()
+                .expect("Message should be an object")
+                .remove("method"),
+            value => message
+                .as_object_mut()
+                .expect("Message should be an object")
+                .insert("method".to_string(), value),
+        };
+        serde_json::from_value::<DlqMessage>(message)
+            .expect("Invalid message")
+            .method
+    };
+
+    assert_eq!(method(serde_json::json!("PUT")), Method::PUT);
+    assert_eq!(method(serde_json::json!("patch")), Method::PATCH);
+    assert_eq!(method(serde_json::json!("FETCH")), Method::POST);
+    assert_eq!(method(serde_json::json!("")), Method::POST);
+    assert_eq!(method(serde_json::Value::Null), Method::POST);
+
+    let message: DlqMessage =
+        serde_json::from_value(dlq_message(serde_json::json!({ "method": "delete" })))
+            .expect("Invalid message");
+    assert_eq!(
+        serde_json::to_value(&message).expect("Could not serialize message")["method"],
+        "DELETE"
+    );
+}
+
+fn failed_message(url: &str, status: Option<u16>) -> DlqMessage {
+    serde_json::from_value(dlq_message(serde_json::json!({
+        "url": url,
+        "responseStatus": status,
+    })))
+    .expect("Invalid message")
+}
+
+#[test]
+fn summarize_should_count_by_url_and_status() {
+    let summary = summarize(&[
+        failed_message("https://example.com/orders", Some(500)),
+        failed_message("https://example.com/orders", Some(503)),
+        failed_message("https://example.com/orders", Some(500)),
+        failed_message("https://example.com/billing", Some(404)),
+        failed_message("https://example.com/billing", None),
+    ]);
+
+    assert_eq!(summary.total, 5);
+    assert_eq!(
+        summary.by_url,
+        HashMap::from([
+            ("https://example.com/orders".to_string(), 3),
+            ("https://example.com/billing".to_string(), 2),
+        ])
+    );
+    assert_eq!(
+        summary.by_status,
+        HashMap::from([(500, 2), (503, 1), (404, 1)])
+    );
+}
+
+#[test]
+fn summarize_should_be_empty_without_messages() {
+    assert_eq!(summarize(&[]), DlqSummary::default());
+}
+
+#[tokio::test]
+async fn dead_letter_summary_should_count_every_page() {
+    let server = three_pages(Duration::ZERO).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let summary = qstash_client
+        .dead_letter_summary(None)
+        .await
+        .expect("Could not summarize dead letter queue");
+
+    assert_eq!(summary.total, 6);
+    assert_eq!(
+        summary.by_url,
+        HashMap::from([("https://example.com/orders".to_string(), 6)])
+    );
+    assert!(summary.by_status.is_empty());
+    assert_eq!(requests(&server, "GET").await.len(), 3);
+}
+
+#[tokio::test]
+async fn dead_letter_summary_should_return_the_first_error() {
+    let server = failing_server(
+        ResponseTemplate::new(401).set_body_json(serde_json::json!({ "error": "unauthorized" })),
+    )
+    .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    assert!(matches!(
+        qstash_client.dead_letter_summary(None).await,
+        Err(QStashError::DeadLetterQueueError {
+            status: Some(401),
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn dead_letter_queue_stream_should_combine_the_order_with_the_cursor_and_filters() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .and(query_param("cursor", "c1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "messages": [dlq_message(serde_json::json!({ "dlqId": "dlq_2" }))],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "messages": [dlq_message(serde_json::json!({ "dlqId": "dlq_1" }))],
+            "cursor": "c1",
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let dlq_ids: Vec<String> = qstash_client
+        .dead_letter_queue_stream(Some(DlqRequest {
+            order: Some(DlqOrder::Asc),
+            url: Some("https://example.com/orders".to_string()),
+            response_status: Some(500),
+            count: Some(1),
+            ..Default::default()
+        }))
+        .map(|m| m.expect("Could not get message").dlq_id)
+        .collect()
+        .await;
+
+    assert_eq!(dlq_ids, ["dlq_1", "dlq_2"]);
+    let queries: Vec<String> = requests(&server, "GET")
+        .await
+        .iter()
+        .map(|r| r.url.query().unwrap_or_default().to_string())
+        .collect();
+    assert_eq!(
+        queries,
+        [
+            "url=https%3A%2F%2Fexample.com%2Forders&order=earliestFirst&count=1&responseStatus=500",
+            "cursor=c1&url=https%3A%2F%2Fexample.com%2Forders&order=earliestFirst&count=1&responseStatus=500",
+        ]
+    );
+}
+
+#[test]
+fn dlq_order_should_map_to_the_query_value() {
+    let order = |order| {
+        DlqRequest {
+            order: Some(order),
+            ..Default::default()
+        }
+        .query_pairs()
+    };
+
+    assert_eq!(
+        order(DlqOrder::Asc),
+        [("order", "earliestFirst".to_string())]
+    );
+    assert_eq!(
+        order(DlqOrder::Desc),
+        [("order", "latestFirst".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn requeue_dead_letter_message_should_republish_to_the_endpoint_of_a_topic() {
+    let server = mock_server(
+        dlq_message(serde_json::json!({ "body": "hello", "topicName": "billing" })),
+        201,
+    )
+    .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    qstash_client
+        .requeue_dead_letter_message("dlq_123", None)
+        .await
+        .expect("Could not requeue message");
+
+    let published = requests(&server, "POST").await;
+    assert_eq!(published.len(), 1);
+    assert_eq!(
+        published[0].url.path(),
+        "/v2/publish/https://example.com/orders"
+    );
+}
+
+#[tokio::test]
+async fn requeue_dead_letter_message_should_forward_the_delivered_headers() {
+    let server = mock_server(
+        dlq_message(serde_json::json!({
+            "body": "hello",
+            "header": {
+                "Content-Type": ["text/plain"],
+                "X-Tenant": ["acme"],
+                "Upstash-Forward-X-Trace": ["abc"],
+            },
+        })),
+        201,
+    )
+    .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    qstash_client
+        .requeue_dead_letter_message("dlq_123", None)
+        .await
+        .expect("Could not requeue message");
+
+    let published = requests(&server, "POST").await;
+    assert_eq!(
+        header(&published[0], "Upstash-Forward-X-Tenant").as_deref(),
+        Some("acme")
+    );
+    assert_eq!(header(&published[0], "X-Tenant"), None);
+    assert_eq!(
+        header(&published[0], "Content-Type").as_deref(),
+        Some("text/plain")
+    );
+    assert_eq!(
+        header(&published[0], "Upstash-Forward-X-Trace").as_deref(),
+        Some("abc")
+    );
+}
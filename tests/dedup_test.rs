@@ -0,0 +1,198 @@
+#![cfg(feature = "reqwest")]
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use qstash_rs::client::{Client, DedupCache, PublishOptions, PublishRequestUrl, ReqwestBackend};
+use wiremock::{matchers::method, Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// NumberedMessages answers every publish with a new message id,
+/// and with an error for the destinations containing `fail`.
+#[derive(Default)]
+struct NumberedMessages {
+    published: AtomicUsize,
+}
+
+impl Respond for NumberedMessages {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        if request.url.path().contains("fail") {
+            return ResponseTemplate::new(400)
+                .set_body_json(serde_json::json!({ "error": "invalid destination" }));
+        }
+        let id = self.published.fetch_add(1, Ordering::SeqCst) + 1;
+        ResponseTemplate::new(201)
+            .set_body_json(serde_json::json!({ "messageId": format!("msg_{}", id) }))
+    }
+}
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(NumberedMessages::default())
+        .mount(&server)
+        .await;
+    server
+}
+
+fn client(server: &MockServer, cache: Option<DedupCache>) -> Client {
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .http_backend(ReqwestBackend::new(
+            reqwest::Client::builder()
+                .pool_idle_timeout(None)
+                .build()
+                .expect("Could not initialize http client"),
+        ));
+    match cache {
+        Some(cache) => qstash_client.dedup_cache(cache),
+        None => qstash_client,
+    }
+}
+
+fn url(path: &str) -> PublishRequestUrl {
+    PublishRequestUrl::Url(
+        format!("https://example.com/{}", path)
+            .parse()
+            .expect("Could not parse URL"),
+    )
+}
+
+fn with_id(id: &str) -> Option<PublishOptions> {
+    Some(PublishOptions {
+        deduplication_id: Some(id.to_string()),
+        ..Default::default()
+    })
+}
+
+async fn received(server: &MockServer) -> usize {
+    server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded")
+        .len()
+}
+
+#[tokio::test]
+async fn publish_should_skip_duplicates_within_the_ttl() {
+    let server = mock_server().await;
+    let qstash_client = client(&server, Some(DedupCache::new(16, Duration::from_secs(60))));
+
+    let first = qstash_client
+        .publish_json(url("orders"), "hello", None)
+        .await
+        .expect("Could not publish");
+    let duplicate = qstash_client
+        .publish_json(url("orders"), "hello", None)
+        .await
+        .expect("Could not publish");
+    qstash_client
+        .publish_json(url("orders"), "other", None)
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(received(&server).await, 2);
+    assert_eq!(first[0].deduplicated, None);
+    assert_eq!(duplicate[0].message_id.as_deref(), Some("msg_1"));
+    assert_eq!(duplicate[0].deduplicated, Some(true));
+}
+
+#[tokio::test(start_paused = true)]
+async fn publish_should_send_the_same_id_again_after_the_ttl() {
+    let server = mock_server().await;
+    let qstash_client = client(&server, Some(DedupCache::new(16, Duration::from_secs(60))));
+
+    qstash_client
+        .publish_json(url("orders"), "hello", with_id("order_1"))
+        .await
+        .expect("Could not publish");
+    tokio::time::advance(Duration::from_secs(59)).await;
+    let within = qstash_client
+        .publish_json(url("orders"), "hello again", with_id("order_1"))
+        .await
+        .expect("Could not publish");
+    tokio::time::advance(Duration::from_secs(1)).await;
+    let after = qstash_client
+        .publish_json(url("orders"), "hello again", with_id("order_1"))
+        .await
+        .expect("Could not publish");
+    let remembered = qstash_client
+        .publish_json(url("orders"), "hello", with_id("order_1"))
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(received(&server).await, 2);
+    assert_eq!(within[0].deduplicated, Some(true));
+    assert_eq!(within[0].message_id.as_deref(), Some("msg_1"));
+    assert_eq!(after[0].deduplicated, None);
+    assert_eq!(after[0].message_id.as_deref(), Some("msg_2"));
+    assert_eq!(remembered[0].message_id.as_deref(), Some("msg_2"));
+}
+
+#[tokio::test]
+async fn publish_should_not_deduplicate_by_default() {
+    let server = mock_server().await;
+    let qstash_client = client(&server, None);
+
+    for _ in 0..2 {
+        let responses = qstash_client
+            .publish_json(url("orders"), "hello", with_id("order_1"))
+            .await
+            .expect("Could not publish");
+        assert_eq!(responses[0].deduplicated, None);
+    }
+
+    assert_eq!(received(&server).await, 2);
+}
+
+#[tokio::test]
+async fn dedup_cache_should_be_shared_by_clones() {
+    let server = mock_server().await;
+    let qstash_client = client(&server, Some(DedupCache::new(16, Duration::from_secs(60))));
+    let clone = qstash_client.clone();
+
+    qstash_client
+        .publish_json(url("orders"), "hello", None)
+        .await
+        .expect("Could not publish");
+    let duplicate = clone
+        .publish_json(url("orders"), "hello", None)
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(received(&server).await, 1);
+    assert_eq!(duplicate[0].deduplicated, Some(true));
+}
+
+#[tokio::test]
+async fn dedup_cache_should_evict_the_least_recently_used() {
+    let server = mock_server().await;
+    let qstash_client = client(&server, Some(DedupCache::new(2, Duration::from_secs(60))));
+
+    for id in ["a", "b", "a", "c", "b", "a"] {
+        qstash_client
+            .publish_json(url("orders"), "hello", with_id(id))
+            .await
+            .expect("Could not publish");
+    }
+
+    // a and b are published, a is used again, c evicts b, b evicts a
+    assert_eq!(received(&server).await, 5);
+}
+
+#[tokio::test]
+async fn publish_should_not_remember_failed_messages() {
+    let server = mock_server().await;
+    let qstash_client = client(&server, Some(DedupCache::new(16, Duration::from_secs(60))));
+
+    for _ in 0..2 {
+        let responses = qstash_client
+            .publish_json(url("fail"), "hello", None)
+            .await
+            .expect("Could not publish");
+        assert!(responses[0].error.is_some());
+    }
+
+    assert_eq!(received(&server).await, 2);
+}
@@ -0,0 +1,154 @@
+#![cfg(feature = "reqwest")]
+
+use std::sync::Mutex;
+
+use qstash_rs::client::{
+    dlq_requeue::{RequeueOutcome, RequeueProgress},
+    Client, QStashError,
+};
+use wiremock::{matchers::any, Mock, MockServer, Request, ResponseTemplate};
+
+/// fake_dlq serves a dead letter queue whose message `dlq_<name>` is published to
+/// `https://example.com/<name>`.
+/// Publishing to a destination containing `fail` fails, deleting `dlq_undeletable` fails,
+/// and the messages containing `missing` are not in the dead letter queue.
+fn fake_dlq(request: &Request) -> ResponseTemplate {
+    let path = request.url.path();
+    match request.method.to_string().as_str() {
+        "GET" => {
+            let dlq_id = path.trim_start_matches("/v2/dlq/");
+            if dlq_id.contains("missing") {
+                return ResponseTemplate::new(404);
+            }
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "messageId": format!("msg_{}", dlq_id),
+                "url": format!("https://example.com/{}", dlq_id.trim_start_matches("dlq_")),
+                "method": "POST",
+                "body": "hello",
+                "createdAt": 1_699_000_000_000u64,
+                "dlqId": dlq_id,
+            }))
+        }
+        "POST" if path.contains("fail") => ResponseTemplate::new(400)
+            .set_body_json(serde_json::json!({ "error": "invalid destination" })),
+        "POST" => {
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_new" }))
+        }
+        "DELETE" if path.ends_with("dlq_undeletable") => ResponseTemplate::new(500),
+        _ => ResponseTemplate::new(200),
+    }
+}
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(fake_dlq)
+        .mount(&server)
+        .await;
+    server
+}
+
+fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+async fn deleted(server: &MockServer) -> Vec<String> {
+    let mut deleted: Vec<String> = server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded")
+        .into_iter()
+        .filter(|r| r.method.to_string() == "DELETE")
+        .map(|r| r.url.path().trim_start_matches("/v2/dlq/").to_string())
+        .collect();
+    deleted.sort();
+    deleted
+}
+
+#[tokio::test]
+async fn requeue_dead_letter_messages_should_report_every_message() {
+    let server = mock_server().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let progress = Mutex::new(Vec::new());
+
+    let report = qstash_client
+        .requeue_dead_letter_messages(
+            ids(&[
+                "dlq_a",
+                "dlq_fail",
+                "dlq_b",
+                "dlq_undeletable",
+                "dlq_missing",
+                "dlq_c",
+            ]),
+            3,
+            |p: RequeueProgress| progress.lock().expect("Could not lock progress").push(p),
+        )
+        .await;
+
+    assert_eq!(report.requeued, ["dlq_a", "dlq_b", "dlq_c"]);
+    let publish_failed: Vec<&str> = report
+        .publish_failed
+        .iter()
+        .map(|(id, _)| id.as_str())
+        .collect();
+    assert_eq!(publish_failed, ["dlq_fail", "dlq_missing"]);
+    assert!(matches!(
+        report.publish_failed[0].1,
+        QStashError::PublishError
+    ));
+    assert!(matches!(
+        report.publish_failed[1].1,
+        QStashError::DlqMessageNotFound(_)
+    ));
+    assert_eq!(report.delete_failed.len(), 1);
+    assert_eq!(report.delete_failed[0].0, "dlq_undeletable");
+    assert!(matches!(
+        report.delete_failed[0].1,
+        QStashError::DeadLetterQueueError {
+            status: Some(500),
+            ..
+        }
+    ));
+    assert_eq!(
+        deleted(&server).await,
+        ["dlq_a", "dlq_b", "dlq_c", "dlq_undeletable"]
+    );
+
+    let progress = progress.into_inner().expect("Could not lock progress");
+    let completed: Vec<(usize, usize)> = progress.iter().map(|p| (p.completed, p.total)).collect();
+    assert_eq!(completed, [(1, 6), (2, 6), (3, 6), (4, 6), (5, 6), (6, 6)]);
+    let mut reported: Vec<&str> = progress.iter().map(|p| p.dlq_id.as_str()).collect();
+    reported.sort();
+    assert_eq!(
+        reported,
+        [
+            "dlq_a",
+            "dlq_b",
+            "dlq_c",
+            "dlq_fail",
+            "dlq_missing",
+            "dlq_undeletable"
+        ]
+    );
+    assert!(progress
+        .iter()
+        .any(|p| p.dlq_id == "dlq_undeletable"
+            && matches!(p.outcome, RequeueOutcome::DeleteFailed(_))));
+}
+
+#[tokio::test]
+async fn requeue_dead_letter_messages_should_handle_no_messages() {
+    let server = mock_server().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let report = qstash_client
+        .requeue_dead_letter_messages(Vec::new(), 0, |_| panic!("Nothing to report"))
+        .await;
+
+    assert!(report.requeued.is_empty());
+    assert!(report.publish_failed.is_empty());
+    assert!(report.delete_failed.is_empty());
+}
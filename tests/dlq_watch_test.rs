@@ -0,0 +1,359 @@
+#![cfg(feature = "reqwest")]
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use http::Method;
+use qstash_rs::client::{
+    dlq_watch::{DlqAction, DlqWatcher},
+    Client, HttpBackend, RequestParts, ReqwestBackend, ResponseParts, TransportError,
+};
+use serde_json::{json, Value};
+use tokio::time::Instant;
+use wiremock::{matchers::any, Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// FakeDlq serves a dead letter queue whose contents the tests change between polls.
+/// The first `failures` listings fail.
+#[derive(Clone, Default)]
+struct FakeDlq {
+    messages: Arc<Mutex<Vec<Value>>>,
+    failures: Arc<AtomicUsize>,
+    listings: Arc<AtomicUsize>,
+}
+
+impl FakeDlq {
+    fn add(&self, dlq_id: &str) {
+        self.messages
+            .lock()
+            .expect("Could not lock messages")
+            .push(json!({
+                "messageId": format!("msg_{}", dlq_id),
+                "url": "https://example.com",
+                "method": "POST",
+                "createdAt": 1_699_000_000_000u64,
+                "dlqId": dlq_id,
+            }));
+    }
+
+    fn remove(&self, dlq_id: &str) {
+        self.messages
+            .lock()
+            .expect("Could not lock messages")
+            .retain(|m| m["dlqId"] != dlq_id);
+    }
+
+    fn dlq_ids(&self) -> Vec<String> {
+        self.messages
+            .lock()
+            .expect("Could not lock messages")
+            .iter()
+            .map(|m| m["dlqId"].as_str().expect("Missing dlq id").to_string())
+            .collect()
+    }
+
+    fn listings(&self) -> usize {
+        self.listings.load(Ordering::SeqCst)
+    }
+}
+
+impl Respond for FakeDlq {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let mut messages = self.messages.lock().expect("Could not lock messages");
+        match request.method.to_string().as_str() {
+            "GET" => {
+                self.listings.fetch_add(1, Ordering::SeqCst);
+                let failed = self
+                    .failures
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |f| f.checked_sub(1))
+                    .is_ok();
+                match failed {
+                    true => ResponseTemplate::new(500),
+                    false => {
+                        ResponseTemplate::new(200).set_body_json(json!({ "messages": *messages }))
+                    }
+                }
+            }
+            "DELETE" => {
+                let dlq_id = request.url.path().trim_start_matches("/v2/dlq/");
+                messages.retain(|m| m["dlqId"] != dlq_id);
+                ResponseTemplate::new(200)
+            }
+            _ => ResponseTemplate::new(404),
+        }
+    }
+}
+
+async fn mock_server(dlq: &FakeDlq) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(dlq.clone())
+        .mount(&server)
+        .await;
+    server
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .http_backend(backend())
+}
+
+fn backend() -> ReqwestBackend {
+    ReqwestBackend::new(
+        reqwest::Client::builder()
+            .pool_idle_timeout(None)
+            .build()
+            .expect("Could not initialize http client"),
+    )
+}
+
+/// Timed records when every listing of the dead letter queue started and ended.
+/// The paused clock advances while a request is in flight, so only the time between
+/// two listings is the time the watcher waited.
+#[derive(Debug, Clone, Default)]
+struct Timed {
+    inner: ReqwestBackend,
+    listings: Arc<Mutex<Vec<(Instant, Instant)>>>,
+}
+
+#[async_trait]
+impl HttpBackend for Timed {
+    async fn execute(&self, request: RequestParts) -> Result<ResponseParts, TransportError> {
+        let listing = request.method == Method::GET && request.url.path() == "/v2/dlq";
+        let started = Instant::now();
+        let response = self.inner.execute(request).await;
+        if listing {
+            self.listings
+                .lock()
+                .expect("Could not lock listings")
+                .push((started, Instant::now()));
+        }
+        response
+    }
+}
+
+/// Recorder records the dlq ids handed to the handler and when they were.
+#[derive(Clone, Default)]
+struct Recorder {
+    handled: Arc<Mutex<Vec<(String, Instant)>>>,
+}
+
+impl Recorder {
+    fn record(&self, dlq_id: &str) {
+        self.handled
+            .lock()
+            .expect("Could not lock handled")
+            .push((dlq_id.to_string(), Instant::now()));
+    }
+
+    fn dlq_ids(&self) -> Vec<String> {
+        self.handled
+            .lock()
+            .expect("Could not lock handled")
+            .iter()
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+}
+
+/// wait_until advances the paused clock until the condition holds.
+async fn wait_until(condition: impl Fn() -> bool) {
+    while !condition() {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn watcher_should_hand_new_dead_letters_once() {
+    let dlq = FakeDlq::default();
+    dlq.add("dlq_1");
+    let server = mock_server(&dlq).await;
+    let recorder = Recorder::default();
+
+    let handler_recorder = recorder.clone();
+    let watcher = DlqWatcher::spawn(client(&server), Duration::from_secs(10), move |message| {
+        let recorder = handler_recorder.clone();
+        async move {
+            recorder.record(&message.dlq_id);
+            DlqAction::Keep
+        }
+    });
+
+    wait_until(|| recorder.dlq_ids().len() == 1).await;
+    dlq.add("dlq_2");
+    wait_until(|| recorder.dlq_ids().len() == 2).await;
+    let listings = dlq.listings();
+    wait_until(|| dlq.listings() >= listings + 3).await;
+    watcher.shutdown().await;
+
+    assert_eq!(recorder.dlq_ids(), ["dlq_1", "dlq_2"]);
+    assert_eq!(dlq.dlq_ids(), ["dlq_1", "dlq_2"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn watcher_should_forget_dead_letters_no_longer_in_the_queue() {
+    let dlq = FakeDlq::default();
+    dlq.add("dlq_1");
+    dlq.add("dlq_2");
+    let server = mock_server(&dlq).await;
+    let recorder = Recorder::default();
+
+    let handler_recorder = recorder.clone();
+    let watcher = DlqWatcher::spawn(client(&server), Duration::from_secs(10), move |message| {
+        let recorder = handler_recorder.clone();
+        async move {
+            recorder.record(&message.dlq_id);
+            DlqAction::Keep
+        }
+    });
+
+    wait_until(|| recorder.dlq_ids().len() == 2).await;
+    dlq.remove("dlq_1");
+    let listings = dlq.listings();
+    wait_until(|| dlq.listings() >= listings + 2).await;
+    dlq.add("dlq_1");
+    wait_until(|| recorder.dlq_ids().len() == 3).await;
+    let listings = dlq.listings();
+    wait_until(|| dlq.listings() >= listings + 2).await;
+    watcher.shutdown().await;
+
+    // dlq_2 stayed in the queue the whole time, it is only handed out once.
+    assert_eq!(recorder.dlq_ids(), ["dlq_1", "dlq_2", "dlq_1"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn watcher_should_delete_the_acknowledged_dead_letters() {
+    let dlq = FakeDlq::default();
+    dlq.add("dlq_1");
+    dlq.add("dlq_2");
+    let server = mock_server(&dlq).await;
+    let recorder = Recorder::default();
+
+    let handler_recorder = recorder.clone();
+    let watcher = DlqWatcher::spawn(client(&server), Duration::from_secs(10), move |message| {
+        let recorder = handler_recorder.clone();
+        async move {
+            recorder.record(&message.dlq_id);
+            match message.dlq_id.as_str() {
+                "dlq_1" => DlqAction::Delete,
+                _ => DlqAction::Keep,
+            }
+        }
+    });
+
+    wait_until(|| dlq.listings() >= 3).await;
+    watcher.shutdown().await;
+
+    assert_eq!(recorder.dlq_ids(), ["dlq_1", "dlq_2"]);
+    assert_eq!(dlq.dlq_ids(), ["dlq_2"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn watcher_should_back_off_and_keep_polling_after_errors() {
+    let dlq = FakeDlq::default();
+    dlq.add("dlq_1");
+    dlq.failures.store(2, Ordering::SeqCst);
+    let server = mock_server(&dlq).await;
+    let recorder = Recorder::default();
+    let timed = Timed {
+        inner: backend(),
+        ..Timed::default()
+    };
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .http_backend(timed.clone());
+
+    let handler_recorder = recorder.clone();
+    let watcher = DlqWatcher::new(qstash_client, Duration::from_secs(10))
+        .max_backoff(Duration::from_secs(30))
+        .start(move |message| {
+            let recorder = handler_recorder.clone();
+            async move {
+                recorder.record(&message.dlq_id);
+                DlqAction::Delete
+            }
+        });
+
+    wait_until(|| recorder.dlq_ids().len() == 1).await;
+    watcher.shutdown().await;
+
+    // the failed polls wait 20s, then 30s instead of 40s
+    let listings = timed
+        .listings
+        .lock()
+        .expect("Could not lock listings")
+        .clone();
+    let waits: Vec<Duration> = listings
+        .windows(2)
+        .map(|w| w[1].0.duration_since(w[0].1))
+        .collect();
+    assert!(waits.len() >= 2, "{:?}", waits);
+    for (wait, backoff) in waits.iter().zip([20, 30]) {
+        let backoff = Duration::from_secs(backoff);
+        assert!(
+            *wait >= backoff && *wait < backoff + Duration::from_secs(1),
+            "{:?}",
+            waits
+        );
+    }
+    assert!(dlq.listings() >= 3);
+    assert!(dlq.dlq_ids().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn watcher_should_bound_the_concurrency() {
+    let dlq = FakeDlq::default();
+    for i in 0..6 {
+        dlq.add(&format!("dlq_{}", i));
+    }
+    let server = mock_server(&dlq).await;
+    let recorder = Recorder::default();
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+    let (handler_recorder, handler_in_flight, handler_max) =
+        (recorder.clone(), in_flight.clone(), max_in_flight.clone());
+    let watcher = DlqWatcher::new(client(&server), Duration::from_secs(10))
+        .max_concurrency(2)
+        .start(move |message| {
+            let (recorder, in_flight, max_in_flight) = (
+                handler_recorder.clone(),
+                handler_in_flight.clone(),
+                handler_max.clone(),
+            );
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                recorder.record(&message.dlq_id);
+                DlqAction::Keep
+            }
+        });
+
+    wait_until(|| recorder.dlq_ids().len() == 6).await;
+    watcher.shutdown().await;
+
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn shutdown_should_stop_polling() {
+    let dlq = FakeDlq::default();
+    let server = mock_server(&dlq).await;
+
+    let watcher = DlqWatcher::spawn(client(&server), Duration::from_secs(10), |_| async {
+        DlqAction::Keep
+    });
+    wait_until(|| dlq.listings() == 1).await;
+    watcher.shutdown().await;
+    tokio::time::sleep(Duration::from_secs(60)).await;
+
+    assert_eq!(dlq.listings(), 1);
+}
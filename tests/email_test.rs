@@ -0,0 +1,248 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::{echo_batch, header};
+use qstash_rs::client::{email::EmailRequest, Client, PublishOptions, QStashError, RetryPolicy};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn email(to: &str) -> EmailRequest {
+    let mut email = EmailRequest::new(
+        "Acme <onboarding@example.com>",
+        vec![to.to_string()],
+        "hello world",
+    );
+    email.html = Some(String::from("<p>it works!</p>"));
+    email
+}
+
+#[test]
+fn email_request_should_serialize() {
+    let mut request = email("delivered@example.com");
+    request.text = Some(String::from("it works!"));
+    request.reply_to = Some(String::from("support@example.com"));
+
+    assert_eq!(
+        serde_json::to_value(&request).expect("Could not serialize email"),
+        serde_json::json!({
+            "from": "Acme <onboarding@example.com>",
+            "to": ["delivered@example.com"],
+            "subject": "hello world",
+            "html": "<p>it works!</p>",
+            "text": "it works!",
+            "reply_to": "support@example.com",
+        })
+    );
+}
+
+#[tokio::test]
+async fn publish_email_should_work() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/api/email"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_123" })),
+        )
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let response = qstash_client
+        .publish_email(email("delivered@example.com"), "re_123", None)
+        .await
+        .expect("Could not publish email");
+    assert_eq!(response.message_id.as_deref(), Some("msg_123"));
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded");
+    let request = &requests[0];
+    assert_eq!(
+        header(request, "Upstash-Api-Provider").as_deref(),
+        Some("resend")
+    );
+    assert_eq!(
+        header(request, "Upstash-Forward-Authorization").as_deref(),
+        Some("Bearer re_123")
+    );
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&request.body).expect("Body should be JSON"),
+        serde_json::json!({
+            "from": "Acme <onboarding@example.com>",
+            "to": ["delivered@example.com"],
+            "subject": "hello world",
+            "html": "<p>it works!</p>",
+        })
+    );
+}
+
+#[tokio::test]
+async fn publish_emails_should_work() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let responses = qstash_client
+        .publish_emails(
+            vec![email("a@example.com"), email("b@example.com")],
+            "re_123",
+        )
+        .await
+        .expect("Could not publish emails");
+    assert_eq!(responses.len(), 2);
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded");
+    let body: serde_json::Value =
+        serde_json::from_slice(&requests[0].body).expect("Body should be JSON");
+    let expected = |to: &str| {
+        serde_json::json!({
+            "destination": "api/email",
+            "headers": {
+                "content-type": "application/json",
+                "upstash-api-provider": "resend",
+                "upstash-forward-authorization": "Bearer re_123",
+                "upstash-method": "POST",
+            },
+            "body": serde_json::to_string(&email(to)).expect("Could not serialize email"),
+        })
+    };
+    assert_eq!(
+        body,
+        serde_json::json!([expected("a@example.com"), expected("b@example.com")])
+    );
+}
+
+async fn email_requests(server: &MockServer) -> Vec<wiremock::Request> {
+    server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded")
+}
+
+#[tokio::test]
+async fn publish_email_should_retry_a_server_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/api/email"))
+        .respond_with(
+            ResponseTemplate::new(500)
+                .set_body_json(serde_json::json!({ "error": "internal server error" })),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/api/email"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_123" })),
+        )
+        .mount(&server)
+        .await;
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        })
+        .publish_defaults(PublishOptions {
+            retries: Some(2),
+            ..Default::default()
+        });
+
+    let response = qstash_client
+        .publish_email(email("delivered@example.com"), "re_123", None)
+        .await
+        .expect("Could not publish email");
+
+    assert_eq!(response.message_id.as_deref(), Some("msg_123"));
+    let requests = email_requests(&server).await;
+    assert_eq!(requests.len(), 2);
+    let ids: Vec<Option<String>> = requests
+        .iter()
+        .map(|r| header(r, "Upstash-Deduplication-Id"))
+        .collect();
+    assert!(ids[0].is_some());
+    assert_eq!(ids[0], ids[1]);
+    assert_eq!(response.deduplication_id, ids[0]);
+    for request in &requests {
+        assert_eq!(header(request, "Upstash-Retries").as_deref(), Some("2"));
+        assert_eq!(
+            header(request, "Upstash-Forward-Authorization").as_deref(),
+            Some("Bearer re_123")
+        );
+    }
+}
+
+#[tokio::test]
+async fn publish_email_should_return_quota_errors() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/api/email"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "30")
+                .set_body_json(serde_json::json!({ "error": "daily quota exceeded" })),
+        )
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client
+        .publish_email(email("delivered@example.com"), "re_123", None)
+        .await;
+
+    assert!(
+        matches!(
+            &result,
+            Err(QStashError::QuotaExceeded {
+                status: 429,
+                retry_after: Some(d),
+                ..
+            }) if *d == Duration::from_secs(30)
+        ),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn publish_email_should_fail_on_an_error_response() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/api/email"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .set_body_json(serde_json::json!({ "error": "invalid provider token" })),
+        )
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client
+        .publish_email(email("delivered@example.com"), "re_123", None)
+        .await;
+
+    assert!(
+        matches!(result, Err(QStashError::PublishError)),
+        "{:?}",
+        result
+    );
+}
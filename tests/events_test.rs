@@ -0,0 +1,837 @@
+#![cfg(feature = "reqwest")]
+
+use futures::StreamExt;
+use qstash_rs::client::{
+    events::{
+        group_by_message, Event, EventOrder, EventRequest, GetEventsResponse, State,
+        MAX_EVENT_COUNT,
+    },
+    Client, QStashError,
+};
+use wiremock::{
+    matchers::{method, path, query_param, query_param_is_missing},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn event(message_id: &str, state: &str, time: u64) -> serde_json::Value {
+    serde_json::json!({
+        "time": time,
+        "state": state,
+        "messageId": message_id,
+        "url": "https://example.com/orders",
+    })
+}
+
+async fn queries(server: &MockServer) -> Vec<String> {
+    server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded")
+        .iter()
+        .map(|r| r.url.query().unwrap_or_default().to_string())
+        .collect()
+}
+
+#[test]
+fn event_request_should_build_the_query_of_every_filter() {
+    let request = EventRequest {
+        cursor: Some("1000".to_string()),
+        message_id: Some("msg_123".to_string()),
+        state: Some(State::FAILED),
+        url: Some("https://example.com/orders?tenant=acme".to_string()),
+        topic_name: Some("billing".to_string()),
+        schedule_id: Some("scd_123".to_string()),
+        queue_name: Some("orders".to_string()),
+        order: Some(EventOrder::Desc),
+        from_date: Some(1_699_000_000_000),
+        to_date: Some(1_699_100_000_000),
+        count: Some(50),
+        include_raw: true,
+    };
+
+    assert_eq!(
+        request.query_pairs(),
+        [
+            ("cursor", "1000".to_string()),
+            ("messageId", "msg_123".to_string()),
+            ("state", "FAILED".to_string()),
+            ("url", "https://example.com/orders?tenant=acme".to_string()),
+            ("topicName", "billing".to_string()),
+            ("scheduleId", "scd_123".to_string()),
+            ("queueName", "orders".to_string()),
+            ("order", "latestFirst".to_string()),
+            ("fromDate", "1699000000000".to_string()),
+            ("toDate", "1699100000000".to_string()),
+            ("count", "50".to_string()),
+        ]
+    );
+    assert!(EventRequest::default().query_pairs().is_empty());
+}
+
+#[tokio::test]
+async fn get_events_should_send_the_filters() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("messageId", "msg_123"))
+        .and(query_param("state", "FAILED"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "events": [event("msg_123", "FAILED", 1_699_000_000_000)],
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let response = qstash_client
+        .get_events(Some(EventRequest {
+            message_id: Some("msg_123".to_string()),
+            state: Some(State::FAILED),
+            url: Some("https://example.com/orders?tenant=acme".to_string()),
+            ..Default::default()
+        }))
+        .await
+        .expect("Could not get events");
+
+    assert_eq!(response.events.len(), 1);
+    assert_eq!(response.events[0].state, State::FAILED);
+    assert_eq!(
+        queries(&server).await,
+        ["messageId=msg_123&state=FAILED&url=https%3A%2F%2Fexample.com%2Forders%3Ftenant%3Dacme"]
+    );
+}
+
+#[tokio::test]
+async fn events_stream_should_keep_the_filters_on_every_page() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("cursor", "1699000000000"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "events": [event("msg_123", "FAILED", 1_698_000_000_000)],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "events": [event("msg_123", "FAILED", 1_699_000_000_000)],
+            "cursor": "1699000000000",
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let times: Vec<u64> = qstash_client
+        .events_stream(Some(EventRequest {
+            message_id: Some("msg_123".to_string()),
+            state: Some(State::FAILED),
+            count: Some(1),
+            ..Default::default()
+        }))
+        .map(|e| e.expect("Could not get event").time)
+        .collect()
+        .await;
+
+    assert_eq!(times, [1_699_000_000_000, 1_698_000_000_000]);
+    assert_eq!(
+        queries(&server).await,
+        [
+            "messageId=msg_123&state=FAILED&count=1",
+            "cursor=1699000000000&messageId=msg_123&state=FAILED&count=1",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn get_events_should_page_with_the_returned_cursor() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("cursor", "1699000000000"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "events": [event("msg_1", "DELIVERED", 1_698_000_000_000)],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "events": [event("msg_2", "DELIVERED", 1_699_000_000_001)],
+            "cursor": "1699000000000",
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let mut message_ids = Vec::new();
+    let mut request = Some(EventRequest {
+        queue_name: Some("orders".to_string()),
+        ..Default::default()
+    });
+    while let Some(r) = request {
+        let page = qstash_client
+            .get_events(Some(r.clone()))
+            .await
+            .expect("Could not get events");
+        message_ids.extend(page.events.iter().map(|e| e.message_id.clone()));
+        request = page.next_request(&r);
+    }
+
+    assert_eq!(message_ids, ["msg_2", "msg_1"]);
+    assert_eq!(
+        queries(&server).await,
+        ["queueName=orders", "cursor=1699000000000&queueName=orders"]
+    );
+}
+
+#[test]
+fn event_request_should_start_at_a_millisecond_cursor() {
+    let request = EventRequest::from(1_699_000_000_000u64);
+
+    assert_eq!(request.cursor.as_deref(), Some("1699000000000"));
+    assert_eq!(
+        request.query_pairs(),
+        [("cursor", "1699000000000".to_string())]
+    );
+}
+
+/// three_pages serves three pages of two events, the last page without a cursor.
+/// The page after the cursor `broken` answers with an error when `broken` is set.
+async fn three_pages(broken: bool) -> MockServer {
+    let server = MockServer::start().await;
+    let pages = [
+        (Some("c3"), None, 5),
+        (Some("c2"), Some("c3"), 3),
+        (None, Some("c2"), 1),
+    ];
+    for (cursor, next, first) in pages {
+        let response = match (cursor, broken) {
+            (Some("c2"), true) => ResponseTemplate::new(401)
+                .set_body_json(serde_json::json!({ "error": "unauthorized" })),
+            _ => ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "events": [
+                    event(&format!("msg_{}", first), "DELIVERED", 1_699_000_000_000),
+                    event(&format!("msg_{}", first + 1), "DELIVERED", 1_699_000_000_000),
+                ],
+                "cursor": next,
+            })),
+        };
+        let mock = Mock::given(method("GET")).and(path("/v2/events"));
+        let mock = match cursor {
+            Some(c) => mock.and(query_param("cursor", c)),
+            None => mock,
+        };
+        mock.respond_with(response).mount(&server).await;
+    }
+    server
+}
+
+#[tokio::test]
+async fn events_stream_should_follow_every_cursor() {
+    let server = three_pages(false).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let message_ids: Vec<String> = qstash_client
+        .events_stream(None)
+        .map(|e| e.expect("Could not get event").message_id)
+        .collect()
+        .await;
+
+    assert_eq!(
+        message_ids,
+        ["msg_1", "msg_2", "msg_3", "msg_4", "msg_5", "msg_6"]
+    );
+    assert_eq!(queries(&server).await, ["", "cursor=c2", "cursor=c3"]);
+}
+
+#[tokio::test]
+async fn events_stream_should_end_with_a_mid_stream_error() {
+    let server = three_pages(true).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let events: Vec<_> = qstash_client.events_stream(None).collect().await;
+
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[0].as_ref().expect("Could not get event").message_id,
+        "msg_1"
+    );
+    assert_eq!(
+        events[1].as_ref().expect("Could not get event").message_id,
+        "msg_2"
+    );
+    assert!(events[2].is_err());
+    assert_eq!(queries(&server).await, ["", "cursor=c2"]);
+}
+
+#[tokio::test]
+async fn get_all_events_should_stop_mid_page() {
+    let server = three_pages(false).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let events = qstash_client
+        .get_all_events(None, 1)
+        .await
+        .expect("Could not get events");
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].message_id, "msg_1");
+    assert_eq!(queries(&server).await, [""]);
+}
+
+#[tokio::test]
+async fn get_all_events_should_span_pages_up_to_the_limit() {
+    let server = three_pages(false).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let events = qstash_client
+        .get_all_events(None, 3)
+        .await
+        .expect("Could not get events");
+
+    let message_ids: Vec<&str> = events.iter().map(|e| e.message_id.as_str()).collect();
+    assert_eq!(message_ids, ["msg_1", "msg_2", "msg_3"]);
+    assert_eq!(queries(&server).await, ["", "cursor=c2"]);
+}
+
+#[tokio::test]
+async fn get_all_events_should_return_every_event_below_the_limit() {
+    let server = three_pages(false).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let events = qstash_client
+        .get_all_events(None, 100)
+        .await
+        .expect("Could not get events");
+
+    assert_eq!(events.len(), 6);
+    assert_eq!(queries(&server).await, ["", "cursor=c2", "cursor=c3"]);
+    assert!(qstash_client
+        .get_all_events(None, 0)
+        .await
+        .expect("Could not get events")
+        .is_empty());
+    assert_eq!(queries(&server).await.len(), 3);
+}
+
+#[tokio::test]
+async fn get_all_events_should_return_the_first_error() {
+    let server = three_pages(true).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    assert!(qstash_client.get_all_events(None, 100).await.is_err());
+}
+
+async fn invalid_json_server(body: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn get_events_should_return_the_body_it_could_not_parse() {
+    let server = invalid_json_server(r#"{"events": [{"time": "soon"}"#).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client.get_events(None).await;
+
+    match result {
+        Err(QStashError::EventError {
+            status,
+            cause,
+            body,
+        }) => {
+            assert_eq!(status, Some(200));
+            assert!(cause.contains("EOF while parsing"), "{}", cause);
+            assert_eq!(body.as_deref(), Some(r#"{"events": [{"time": "soon"}"#));
+        }
+        other => panic!("expected an event error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn get_events_should_cut_a_long_body() {
+    let body = format!("<html>{}</html>", "a".repeat(2_000));
+    let server = invalid_json_server(&body).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client.get_events(None).await;
+
+    match result {
+        Err(QStashError::EventError {
+            body: Some(snippet),
+            ..
+        }) => {
+            assert_eq!(snippet, format!("{}...", &body[..512]));
+        }
+        other => panic!("expected an event error with a body, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn events_stream_should_return_the_item_it_could_not_parse() {
+    let body = serde_json::json!({
+        "events": [event("msg_1", "CREATED", 1000), { "messageId": "msg_2", "state": "LOST" }],
+    });
+    let server = invalid_json_server(&body.to_string()).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let events: Vec<_> = qstash_client.events_stream(None).collect().await;
+
+    assert_eq!(events.len(), 2);
+    assert!(events[0].is_ok());
+    match &events[1] {
+        Err(QStashError::EventError {
+            status,
+            cause,
+            body,
+        }) => {
+            assert_eq!(*status, Some(200));
+            assert!(cause.contains("missing field `time`"), "{}", cause);
+            assert_eq!(
+                body.as_deref(),
+                Some(r#"{"messageId":"msg_2","state":"LOST"}"#)
+            );
+        }
+        other => panic!("expected an event error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn get_events_should_not_keep_a_body_for_transport_errors() {
+    let qstash_client = Client::new("token", Some("http://127.0.0.1:1"), None)
+        .expect("Could not initialize client");
+
+    let result = qstash_client.get_events(None).await;
+
+    assert!(
+        matches!(
+            result,
+            Err(QStashError::EventError {
+                status: None,
+                body: None,
+                ..
+            })
+        ),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn get_events_should_reject_a_count_out_of_range() {
+    let server = invalid_json_server("{}").await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    for count in [0, MAX_EVENT_COUNT + 1] {
+        let result = qstash_client
+            .get_events(Some(EventRequest {
+                count: Some(count),
+                ..Default::default()
+            }))
+            .await;
+
+        assert!(
+            matches!(result, Err(QStashError::EventError { status: None, .. })),
+            "{}: {:?}",
+            count,
+            result
+        );
+    }
+    assert!(queries(&server).await.is_empty());
+}
+
+#[tokio::test]
+async fn events_stream_should_end_with_an_error_for_a_count_out_of_range() {
+    let server = invalid_json_server("{}").await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let events: Vec<_> = qstash_client
+        .events_stream(Some(EventRequest {
+            count: Some(0),
+            ..Default::default()
+        }))
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], Err(QStashError::EventError { .. })));
+    assert!(queries(&server).await.is_empty());
+}
+
+#[tokio::test]
+async fn get_events_should_send_the_count_with_the_cursor_and_filters() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "events": [event("msg_123", "DELIVERED", 1_699_000_000_000)],
+            "cursor": "c2",
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let request = EventRequest {
+        queue_name: Some("orders".to_string()),
+        from_date: Some(1_698_000_000_000),
+        count: Some(MAX_EVENT_COUNT),
+        ..Default::default()
+    };
+
+    let page = qstash_client
+        .get_events(Some(request.clone()))
+        .await
+        .expect("Could not get events");
+    let next = page.next_request(&request).expect("Missing next request");
+    qstash_client
+        .get_events(Some(next))
+        .await
+        .expect("Could not get events");
+
+    assert_eq!(
+        queries(&server).await,
+        [
+            "queueName=orders&fromDate=1698000000000&count=100",
+            "cursor=c2&queueName=orders&fromDate=1698000000000&count=100",
+        ]
+    );
+}
+
+fn parsed_events(events: &[(&str, &str, u64)]) -> Vec<Event> {
+    events
+        .iter()
+        .map(|(message_id, state, time)| {
+            serde_json::from_value(event(message_id, state, *time)).expect("Could not parse event")
+        })
+        .collect()
+}
+
+#[test]
+fn group_by_message_should_order_every_timeline_by_time() {
+    let response = GetEventsResponse {
+        cursor: None,
+        events: parsed_events(&[
+            ("msg_1", "DELIVERED", 4_000),
+            ("msg_2", "ERROR", 2_500),
+            ("msg_1", "RETRY", 3_000),
+            ("msg_2", "CREATED", 1_500),
+            ("msg_1", "CREATED", 1_000),
+            ("msg_1", "ACTIVE", 2_000),
+            ("msg_2", "ACTIVE", 2_000),
+        ]),
+        skipped: Vec::new(),
+    };
+
+    let timelines = response.group_by_message();
+
+    assert_eq!(timelines.len(), 2);
+    let states = |message_id: &str| -> Vec<(State, u64)> {
+        timelines[message_id]
+            .events
+            .iter()
+            .map(|e| (e.state, e.time))
+            .collect()
+    };
+    assert_eq!(
+        states("msg_1"),
+        [
+            (State::CREATED, 1_000),
+            (State::ACTIVE, 2_000),
+            (State::RETRY, 3_000),
+            (State::DELIVERED, 4_000),
+        ]
+    );
+    assert_eq!(
+        states("msg_2"),
+        [
+            (State::CREATED, 1_500),
+            (State::ACTIVE, 2_000),
+            (State::ERROR, 2_500),
+        ]
+    );
+    assert_eq!(timelines["msg_1"].message_id, "msg_1");
+    assert_eq!(timelines["msg_1"].final_state(), Some(State::DELIVERED));
+    assert_eq!(timelines["msg_2"].final_state(), Some(State::ERROR));
+}
+
+#[test]
+fn group_by_message_should_order_events_of_the_same_time_by_attempt() {
+    let timelines = group_by_message(parsed_events(&[
+        ("msg_1", "DELIVERED", 1_000),
+        ("msg_1", "ACTIVE", 1_000),
+        ("msg_1", "CREATED", 1_000),
+    ]));
+
+    let states: Vec<State> = timelines["msg_1"].events.iter().map(|e| e.state).collect();
+    assert_eq!(states, [State::CREATED, State::ACTIVE, State::DELIVERED]);
+    assert_eq!(timelines["msg_1"].final_state(), Some(State::DELIVERED));
+}
+
+#[test]
+fn group_by_message_should_return_no_timelines_without_events() {
+    assert!(group_by_message(Vec::new()).is_empty());
+}
+
+#[tokio::test]
+async fn get_events_should_skip_the_events_it_could_not_deserialize() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "cursor": "c2",
+            "events": [
+                event("msg_1", "CREATED", 1_000),
+                { "time": "yesterday", "state": "CREATED", "messageId": "msg_2" },
+                event("msg_3", "DELIVERED", 3_000),
+                "not an event",
+            ],
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let response = qstash_client
+        .get_events(None)
+        .await
+        .expect("Could not get events");
+
+    let ids: Vec<&str> = response
+        .events
+        .iter()
+        .map(|e| e.message_id.as_str())
+        .collect();
+    assert_eq!(ids, ["msg_1", "msg_3"]);
+    assert_eq!(response.cursor.as_deref(), Some("c2"));
+    let positions: Vec<usize> = response.skipped.iter().map(|(i, _)| *i).collect();
+    assert_eq!(positions, [1, 3]);
+    assert!(
+        response.skipped[0].1.contains("invalid type"),
+        "{:?}",
+        response.skipped
+    );
+}
+
+#[test]
+fn get_events_response_should_not_serialize_an_empty_skipped_list() {
+    let response: GetEventsResponse = serde_json::from_value(serde_json::json!({
+        "cursor": null,
+        "events": [event("msg_1", "CREATED", 1_000)],
+    }))
+    .expect("Could not parse response");
+
+    assert!(response.skipped.is_empty());
+    let serialized = serde_json::to_value(&response).expect("Could not serialize");
+    assert!(serialized.get("skipped").is_none());
+}
+
+/// unknown_fields_event returns an event with fields that are not modeled by [`Event`].
+fn unknown_fields_event() -> serde_json::Value {
+    serde_json::json!({
+        "time": 1_000,
+        "state": "DELIVERED",
+        "messageId": "msg_1",
+        "url": "https://example.com/orders",
+        "urlGroup": "billing",
+        "responseStatus": 200,
+        "header": { "Content-Type": ["application/json"] },
+    })
+}
+
+#[tokio::test]
+async fn get_events_should_keep_the_fields_it_does_not_model_if_requested() {
+    let body = serde_json::json!({ "events": [unknown_fields_event()] });
+    let server = invalid_json_server(&body.to_string()).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let response = qstash_client
+        .get_events(Some(EventRequest {
+            include_raw: true,
+            ..Default::default()
+        }))
+        .await
+        .expect("Could not get events");
+
+    let event = &response.events[0];
+    assert_eq!(event.group_name(), Some("billing"));
+    assert_eq!(event.extra.len(), 2);
+    assert_eq!(event.extra["responseStatus"], 200);
+    assert_eq!(
+        event.extra["header"],
+        serde_json::json!({ "Content-Type": ["application/json"] })
+    );
+    assert_eq!(queries(&server).await, [""]);
+
+    let serialized = serde_json::to_value(event).expect("Could not serialize event");
+    assert_eq!(serialized["responseStatus"], 200);
+    assert_eq!(serialized["header"]["Content-Type"][0], "application/json");
+}
+
+#[tokio::test]
+async fn get_events_should_not_keep_the_fields_it_does_not_model_by_default() {
+    let body = serde_json::json!({ "events": [unknown_fields_event()] });
+    let server = invalid_json_server(&body.to_string()).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let response = qstash_client
+        .get_events(None)
+        .await
+        .expect("Could not get events");
+
+    assert_eq!(response.events[0].group_name(), Some("billing"));
+    assert!(response.events[0].extra.is_empty());
+
+    let event: Event =
+        serde_json::from_value(unknown_fields_event()).expect("Could not parse event");
+    assert!(event.extra.is_empty());
+}
+
+#[tokio::test]
+async fn events_stream_should_keep_the_fields_it_does_not_model_if_requested() {
+    let mut unknown = event("msg_1", "CREATED", 1_000);
+    unknown["flowControlKey"] = serde_json::json!("tenant-1");
+    let body = serde_json::json!({ "events": [unknown, event("msg_2", "CREATED", 2_000)] });
+    let server = invalid_json_server(&body.to_string()).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let events: Vec<Event> = qstash_client
+        .events_stream(Some(EventRequest {
+            include_raw: true,
+            ..Default::default()
+        }))
+        .map(|e| e.expect("Could not get event"))
+        .collect()
+        .await;
+
+    assert_eq!(events[0].extra.len(), 1);
+    assert_eq!(events[0].extra["flowControlKey"], "tenant-1");
+    assert!(events[1].extra.is_empty());
+
+    let events: Vec<Event> = qstash_client
+        .events_stream(None)
+        .map(|e| e.expect("Could not get event"))
+        .collect()
+        .await;
+
+    assert!(events[0].extra.is_empty());
+}
+
+/// ordered_pages mounts three events over two pages for each order, the cursor of the
+/// second page depends on the order like the cursors of QStash.
+async fn ordered_pages() -> MockServer {
+    let server = MockServer::start().await;
+    let pages = [
+        ("earliestFirst", None, vec![1_000, 2_000], Some("asc_2")),
+        ("earliestFirst", Some("asc_2"), vec![3_000], None),
+        ("latestFirst", None, vec![3_000, 2_000], Some("desc_2")),
+        ("latestFirst", Some("desc_2"), vec![1_000], None),
+    ];
+    for (order, cursor, times, next) in pages {
+        let events: Vec<_> = times
+            .iter()
+            .map(|t| event(&format!("msg_{}", t), "DELIVERED", *t))
+            .collect();
+        let mock = Mock::given(method("GET"))
+            .and(path("/v2/events"))
+            .and(query_param("order", order));
+        let mock = match cursor {
+            Some(cursor) => mock.and(query_param("cursor", cursor)),
+            None => mock.and(query_param_is_missing("cursor")),
+        };
+        mock.respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "events": events, "cursor": next })),
+        )
+        .mount(&server)
+        .await;
+    }
+    server
+}
+
+async fn streamed_times(server: &MockServer, order: EventOrder) -> Vec<u64> {
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    qstash_client
+        .events_stream(Some(EventRequest {
+            order: Some(order),
+            count: Some(2),
+            ..Default::default()
+        }))
+        .map(|e| e.expect("Could not get event").time)
+        .collect()
+        .await
+}
+
+#[tokio::test]
+async fn events_stream_should_chain_the_pages_of_the_earliest_first() {
+    let server = ordered_pages().await;
+
+    let times = streamed_times(&server, EventOrder::Asc).await;
+
+    assert_eq!(times, [1_000, 2_000, 3_000]);
+    assert_eq!(
+        queries(&server).await,
+        [
+            "order=earliestFirst&count=2",
+            "cursor=asc_2&order=earliestFirst&count=2",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn events_stream_should_chain_the_pages_of_the_latest_first() {
+    let server = ordered_pages().await;
+
+    let times = streamed_times(&server, EventOrder::Desc).await;
+
+    assert_eq!(times, [3_000, 2_000, 1_000]);
+    assert_eq!(
+        queries(&server).await,
+        [
+            "order=latestFirst&count=2",
+            "cursor=desc_2&order=latestFirst&count=2",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn next_request_should_keep_the_order() {
+    let server = ordered_pages().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let request = EventRequest {
+        order: Some(EventOrder::Desc),
+        ..Default::default()
+    };
+
+    let page = qstash_client
+        .get_events(Some(request.clone()))
+        .await
+        .expect("Could not get events");
+    let next = page.next_request(&request).expect("Missing next request");
+
+    assert_eq!(next.order, Some(EventOrder::Desc));
+    assert_eq!(next.cursor.as_deref(), Some("desc_2"));
+}
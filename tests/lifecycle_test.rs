@@ -0,0 +1,371 @@
+#![cfg(feature = "reqwest")]
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use http::{HeaderMap, StatusCode};
+use qstash_rs::client::{
+    events::{Event, State},
+    lifecycle::{allowed_transition, LifecycleError, MessageLifecycle},
+    Client, HttpBackend, QStashError, RequestParts, ResponseParts, TransportError,
+};
+use wiremock::{
+    matchers::{method, path, query_param, query_param_is_missing},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use State::*;
+
+fn event(message_id: &str, state: State, time: u64) -> Event {
+    Event {
+        time,
+        state,
+        message_id: message_id.to_string(),
+        next_delivery_time: None,
+        error: None,
+        url: Some("https://example.com".to_string()),
+        topic_name: None,
+        endpoint_name: None,
+        extra: serde_json::Map::new(),
+    }
+}
+
+/// events creates the events of a single message, one millisecond apart.
+fn events(states: &[State]) -> Vec<Event> {
+    states
+        .iter()
+        .enumerate()
+        .map(|(i, state)| event("msg_1", *state, 1_000 + i as u64))
+        .collect()
+}
+
+#[test]
+fn allowed_transition_should_match_the_table() {
+    let states = [CREATED, ACTIVE, ERROR, RETRY, DELIVERED, FAILED, CANCELED];
+    // rows are the current state, columns the next state in the order above
+    let table: [(State, [bool; 7]); 7] = [
+        (CREATED, [false, true, true, true, true, true, true]),
+        (ACTIVE, [false, true, true, true, true, true, true]),
+        (ERROR, [false, true, false, true, false, true, true]),
+        (RETRY, [false, true, true, true, true, true, true]),
+        (DELIVERED, [false; 7]),
+        (FAILED, [false; 7]),
+        (CANCELED, [false; 7]),
+    ];
+
+    for (from, row) in table {
+        for (to, expected) in states.iter().zip(row) {
+            assert_eq!(
+                allowed_transition(from, *to),
+                expected,
+                "{:?} -> {:?}",
+                from,
+                to
+            );
+        }
+    }
+}
+
+#[test]
+fn message_lifecycle_should_validate_sequences() {
+    // states, attempts, terminal state or None if not terminal
+    let valid: &[(&[State], usize, Option<State>)] = &[
+        (&[CREATED], 0, None),
+        (&[CREATED, ACTIVE], 1, None),
+        (&[CREATED, ACTIVE, DELIVERED], 1, Some(DELIVERED)),
+        (&[CREATED, DELIVERED], 1, Some(DELIVERED)),
+        (&[CREATED, CANCELED], 0, Some(CANCELED)),
+        (
+            &[CREATED, ACTIVE, ERROR, RETRY, ACTIVE, DELIVERED],
+            2,
+            Some(DELIVERED),
+        ),
+        (&[CREATED, ACTIVE, ERROR, RETRY, ACTIVE, ERROR], 2, None),
+        (
+            &[CREATED, ACTIVE, RETRY, RETRY, ERROR, FAILED],
+            3,
+            Some(FAILED),
+        ),
+    ];
+
+    for (states, attempts, terminal) in valid {
+        let lifecycle = MessageLifecycle::from_events(events(states))
+            .unwrap_or_else(|e| panic!("{:?}: {}", states, e));
+        assert_eq!(lifecycle.attempts(), *attempts, "{:?}", states);
+        assert_eq!(lifecycle.is_terminal(), terminal.is_some(), "{:?}", states);
+        if let Some(terminal) = terminal {
+            assert_eq!(lifecycle.state(), *terminal, "{:?}", states);
+        }
+        assert_eq!(lifecycle.transitions().len(), states.len() - 1);
+    }
+
+    // states, the invalid transition
+    let invalid: &[(&[State], State, State)] = &[
+        (&[CREATED, ACTIVE, DELIVERED, RETRY], DELIVERED, RETRY),
+        (&[CREATED, FAILED, ACTIVE], FAILED, ACTIVE),
+        (&[CREATED, CANCELED, DELIVERED], CANCELED, DELIVERED),
+        (&[CREATED, CREATED], CREATED, CREATED),
+        (&[CREATED, ACTIVE, ERROR, DELIVERED], ERROR, DELIVERED),
+    ];
+
+    for (states, from, to) in invalid {
+        match MessageLifecycle::from_events(events(states)) {
+            Err(LifecycleError::InvalidTransition {
+                from: found_from,
+                to: found_to,
+                ..
+            }) => assert_eq!((found_from, found_to), (*from, *to), "{:?}", states),
+            other => panic!(
+                "{:?}: expected an invalid transition, got {:?}",
+                states, other
+            ),
+        }
+    }
+}
+
+#[test]
+fn message_lifecycle_should_order_events_by_time() {
+    let lifecycle = MessageLifecycle::from_events(vec![
+        event("msg_1", DELIVERED, 1_500),
+        event("msg_1", ACTIVE, 1_000),
+        event("msg_1", CREATED, 1_000),
+    ])
+    .expect("Could not build lifecycle");
+
+    assert_eq!(lifecycle.message_id(), "msg_1");
+    assert_eq!(
+        lifecycle.duration_to_delivery(),
+        Some(Duration::from_millis(500))
+    );
+}
+
+#[test]
+fn message_lifecycle_should_reject_invalid_events() {
+    assert!(matches!(
+        MessageLifecycle::from_events(Vec::new()),
+        Err(LifecycleError::NoEvents)
+    ));
+    assert!(matches!(
+        MessageLifecycle::from_events(events(&[ACTIVE, DELIVERED])),
+        Err(LifecycleError::InvalidStart { state: ACTIVE })
+    ));
+    assert!(matches!(
+        MessageLifecycle::from_events(vec![
+            event("msg_1", CREATED, 1_000),
+            event("msg_2", ACTIVE, 1_001),
+        ]),
+        Err(LifecycleError::MixedMessages { .. })
+    ));
+}
+
+#[tokio::test]
+async fn message_lifecycle_should_fetch_every_page() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("messageId", "msg_1"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "cursor": "1500",
+            "events": [
+                { "time": 3000, "state": "DELIVERED", "messageId": "msg_1" },
+                { "time": 2000, "state": "ACTIVE", "messageId": "msg_1" },
+            ]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("messageId", "msg_1"))
+        .and(query_param("cursor", "1500"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "events": [
+                { "time": 1000, "state": "CREATED", "messageId": "msg_1" },
+            ]
+        })))
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let lifecycle = qstash_client
+        .message_lifecycle("msg_1")
+        .await
+        .expect("Could not get lifecycle");
+
+    assert_eq!(lifecycle.attempts(), 1);
+    assert!(lifecycle.is_terminal());
+    assert_eq!(
+        lifecycle.duration_to_delivery(),
+        Some(Duration::from_millis(2000))
+    );
+}
+
+/// Polls answers the n-th request for events with the n-th response, repeating the last one,
+/// and records the number of requests and their queries.
+#[derive(Debug, Clone, Default)]
+struct Polls {
+    responses: Arc<Vec<(StatusCode, String)>>,
+    calls: Arc<AtomicUsize>,
+    queries: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+impl Polls {
+    fn new(responses: Vec<(StatusCode, serde_json::Value)>) -> Self {
+        Polls {
+            responses: Arc::new(
+                responses
+                    .into_iter()
+                    .map(|(status, body)| (status, body.to_string()))
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+
+    fn client(&self) -> Client {
+        Client::with_backend(
+            "token",
+            Some("https://qstash.example.com"),
+            None,
+            self.clone(),
+        )
+        .expect("Could not initialize client")
+    }
+}
+
+#[async_trait]
+impl HttpBackend for Polls {
+    async fn execute(&self, request: RequestParts) -> Result<ResponseParts, TransportError> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        self.queries
+            .lock()
+            .expect("Could not lock queries")
+            .push(request.url.query().unwrap_or_default().to_string());
+        let (status, body) = &self.responses[call.min(self.responses.len() - 1)];
+        Ok(ResponseParts {
+            status: *status,
+            headers: HeaderMap::new(),
+            body: body.clone().into(),
+        })
+    }
+}
+
+fn page(states: &[&str]) -> (StatusCode, serde_json::Value) {
+    let events: Vec<serde_json::Value> = states
+        .iter()
+        .enumerate()
+        .map(|(i, state)| {
+            serde_json::json!({ "time": 1000 + i, "state": state, "messageId": "msg_1" })
+        })
+        .collect();
+    (StatusCode::OK, serde_json::json!({ "events": events }))
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_for_message_state_should_return_the_matching_event() {
+    let polls = Polls::new(vec![
+        page(&["CREATED"]),
+        page(&["ACTIVE", "CREATED"]),
+        page(&["ERROR", "ACTIVE", "CREATED"]),
+        page(&["DELIVERED", "ACTIVE", "ERROR", "ACTIVE", "CREATED"]),
+    ]);
+
+    let event = polls
+        .client()
+        .wait_for_message_state(
+            "msg_1",
+            &[DELIVERED, FAILED],
+            Duration::from_secs(60),
+            Duration::from_secs(1),
+        )
+        .await
+        .expect("Could not wait for message state");
+
+    assert_eq!(event.state, DELIVERED);
+    assert_eq!(event.message_id, "msg_1");
+    assert_eq!(polls.calls(), 4);
+    let queries = polls.queries.lock().expect("Could not lock queries");
+    assert!(
+        queries.iter().all(|q| q == "messageId=msg_1"),
+        "{:?}",
+        queries
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_for_message_state_should_time_out() {
+    let polls = Polls::new(vec![page(&["ACTIVE", "CREATED"])]);
+    let start = tokio::time::Instant::now();
+
+    let result = polls
+        .client()
+        .wait_for_message_state(
+            "msg_1",
+            &[DELIVERED],
+            Duration::from_millis(2500),
+            Duration::from_secs(1),
+        )
+        .await;
+
+    assert!(matches!(result, Err(QStashError::Timeout)), "{:?}", result);
+    // polls at 0s, 1s, 2s and a last time at the deadline
+    assert_eq!(polls.calls(), 4);
+    assert_eq!(start.elapsed(), Duration::from_millis(2500));
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_for_message_state_should_return_api_errors() {
+    let polls = Polls::new(vec![
+        page(&["CREATED"]),
+        (
+            StatusCode::UNAUTHORIZED,
+            serde_json::json!({ "error": "invalid token" }),
+        ),
+    ]);
+
+    let result = polls
+        .client()
+        .wait_for_message_state(
+            "msg_1",
+            &[DELIVERED],
+            Duration::from_secs(60),
+            Duration::from_secs(1),
+        )
+        .await;
+
+    assert!(result.is_err());
+    assert!(!matches!(result, Err(QStashError::Timeout)), "{:?}", result);
+    assert_eq!(polls.calls(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_for_message_state_should_stop_polling_when_dropped() {
+    let polls = Polls::new(vec![page(&["CREATED"])]);
+    let client = polls.client();
+
+    let waited = tokio::time::timeout(
+        Duration::from_millis(2500),
+        client.wait_for_message_state(
+            "msg_1",
+            &[DELIVERED],
+            Duration::from_secs(60),
+            Duration::from_secs(1),
+        ),
+    )
+    .await;
+    tokio::time::sleep(Duration::from_secs(10)).await;
+
+    assert!(waited.is_err());
+    assert_eq!(polls.calls(), 3);
+}
@@ -0,0 +1,107 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use qstash_rs::{
+    client::QStashError,
+    server::{CallbackPayload, FinishReason, LlmCallbackResponse},
+};
+
+const CHAT_COMPLETION: &str = include_str!("fixtures/openai_chat_completion.json");
+const TOOL_CALLS: &str = include_str!("fixtures/openai_tool_calls.json");
+
+fn callback(status: u16, body: &str) -> CallbackPayload {
+    let envelope = serde_json::json!({
+        "status": status,
+        "header": { "Content-Type": ["application/json"] },
+        "body": STANDARD.encode(body),
+        "retried": 0,
+        "maxRetries": 3,
+        "sourceMessageId": "msg_123",
+        "url": "https://api.openai.com/v1/chat/completions",
+        "method": "POST",
+        "sourceBody": STANDARD.encode(r#"{"model":"gpt-3.5-turbo"}"#),
+        "createdAt": 1699000000000u64,
+        "callerIP": "127.0.0.1",
+    });
+    CallbackPayload::from_slice(envelope.to_string().as_bytes()).expect("Could not parse callback")
+}
+
+#[test]
+fn callback_payload_should_decode_body() {
+    let payload = callback(200, CHAT_COMPLETION);
+    assert!(payload.is_success());
+    assert_eq!(payload.source_message_id.as_deref(), Some("msg_123"));
+    assert_eq!(payload.caller_ip.as_deref(), Some("127.0.0.1"));
+    assert_eq!(
+        payload.decoded_body().expect("Could not decode body"),
+        CHAT_COMPLETION.as_bytes()
+    );
+}
+
+#[test]
+fn llm_callback_response_should_parse_chat_completion() {
+    let response = LlmCallbackResponse::from_callback(&callback(200, CHAT_COMPLETION))
+        .expect("Could not parse chat completion");
+
+    assert_eq!(response.model.as_deref(), Some("gpt-3.5-turbo-0613"));
+    assert_eq!(
+        response.first_message_text(),
+        Some("Hello! How can I help you today?")
+    );
+    assert_eq!(response.finish_reason(), Some(&FinishReason::Stop));
+
+    let usage = response.usage.as_ref().expect("Should contain usage");
+    assert_eq!(usage.prompt_tokens, 12);
+    assert_eq!(usage.completion_tokens, 9);
+    assert_eq!(usage.total_tokens, 21);
+
+    assert_eq!(
+        response.extras.get("system_fingerprint"),
+        Some(&serde_json::json!("fp_44709d6fcb"))
+    );
+    assert!(response.choices[0].extras.contains_key("logprobs"));
+}
+
+#[test]
+fn llm_callback_response_should_keep_tool_calls() {
+    let response = LlmCallbackResponse::try_from(&callback(200, TOOL_CALLS))
+        .expect("Could not parse chat completion");
+
+    assert_eq!(response.first_message_text(), None);
+    assert_eq!(response.finish_reason(), Some(&FinishReason::ToolCalls));
+
+    let message = response.choices[0]
+        .message
+        .as_ref()
+        .expect("Should contain a message");
+    assert_eq!(
+        message.extras["tool_calls"][0]["function"]["name"],
+        "get_current_weather"
+    );
+}
+
+#[test]
+fn llm_callback_response_should_reject_failed_calls() {
+    let error = LlmCallbackResponse::from_callback(&callback(
+        429,
+        r#"{"error":{"message":"Rate limit reached"}}"#,
+    ))
+    .expect_err("Failed calls should be rejected");
+    assert!(matches!(error, QStashError::CallbackError));
+
+    let mut payload = callback(200, CHAT_COMPLETION);
+    payload.body = String::from("not base64!");
+    assert!(matches!(
+        LlmCallbackResponse::from_callback(&payload),
+        Err(QStashError::CallbackError)
+    ));
+}
+
+#[test]
+fn finish_reason_should_keep_unknown_values() {
+    let reason: FinishReason =
+        serde_json::from_str(r#""end_turn""#).expect("Could not parse finish reason");
+    assert_eq!(reason, FinishReason::Other(String::from("end_turn")));
+    assert_eq!(
+        serde_json::to_string(&reason).expect("Could not serialize finish reason"),
+        r#""end_turn""#
+    );
+}
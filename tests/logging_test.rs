@@ -0,0 +1,60 @@
+#![cfg(feature = "tracing")]
+
+use qstash_rs::client::{Client, PublishRequestUrl};
+use tracing_test::traced_test;
+use wiremock::{
+    matchers::{method, path_regex},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+#[traced_test]
+async fn response_logging_should_be_structured() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/publish/"))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .set_body_json(serde_json::json!({ "messageId": "msg_123" }))
+                .insert_header("Upstash-RateLimit-Remaining", "99"),
+        )
+        .mount(&server)
+        .await;
+
+    let qstash_client = Client::new("secret-token", Some(&server.uri()), None)
+        .expect("Could not initialize client");
+    qstash_client
+        .publish_json(
+            PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL")),
+            "hello",
+            None,
+        )
+        .await
+        .expect("Could not publish");
+
+    logs_assert(|lines: &[&str]| {
+        let events: Vec<&&str> = lines
+            .iter()
+            .filter(|l| l.contains("QStash response"))
+            .collect();
+        match events.as_slice() {
+            [event] => {
+                for field in [
+                    "endpoint=\"publish_json\"",
+                    "status=201",
+                    "content_length=",
+                    "latency_ms=",
+                ] {
+                    if !event.contains(field) {
+                        return Err(format!("Missing {field} in {event}"));
+                    }
+                }
+                Ok(())
+            }
+            _ => Err(format!("Expected one response event, got {}", events.len())),
+        }
+    });
+    assert!(!logs_contain("secret-token"));
+    assert!(!logs_contain("Upstash-RateLimit-Remaining"));
+    assert!(!logs_contain("upstash-ratelimit-remaining"));
+}
@@ -0,0 +1,38 @@
+use http::Method;
+use qstash_rs::client::messages::{parse_method, Message};
+
+fn message(method: Option<&str>) -> Message {
+    let mut message = serde_json::json!({
+        "messageId": "msg_123",
+        "url": "https://example.com/orders",
+        "createdAt": 1_699_000_000_000u64,
+    });
+    if let Some(method) = method {
+        message["method"] = serde_json::json!(method);
+    }
+    serde_json::from_value(message).expect("Invalid message")
+}
+
+#[test]
+fn message_should_type_the_method() {
+    assert_eq!(message(Some("GET")).method, Some(Method::GET));
+    assert_eq!(message(Some("put")).method, Some(Method::PUT));
+    assert_eq!(message(Some("Options")).method, Some(Method::OPTIONS));
+    assert_eq!(message(Some("BREW")).method, Some(Method::POST));
+    assert_eq!(message(None).method, None);
+}
+
+#[test]
+fn message_should_serialize_the_method_name() {
+    let serialized = |method| serde_json::to_value(message(method)).expect("Invalid message");
+
+    assert_eq!(serialized(Some("patch"))["method"], "PATCH");
+    assert!(serialized(None)["method"].is_null());
+}
+
+#[test]
+fn parse_method_should_fall_back_to_post() {
+    assert_eq!(parse_method(" head "), Method::HEAD);
+    assert_eq!(parse_method("CONNECT"), Method::POST);
+    assert_eq!(parse_method("not a method"), Method::POST);
+}
@@ -0,0 +1,164 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use http::{HeaderMap, StatusCode};
+use qstash_rs::client::{
+    Client, HttpBackend, QStashError, RequestParts, ResponseParts, TransportError,
+};
+
+/// Counting records the bytes allocated by the current thread and their peak,
+/// the tests run on a current thread runtime.
+struct Counting;
+
+thread_local! {
+    static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATED.try_with(|a| {
+            a.set(a.get() + layout.size() as isize);
+            let _ = PEAK.try_with(|p| p.set(p.get().max(a.get())));
+        });
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = ALLOCATED.try_with(|a| a.set(a.get() - layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// peak_growth returns the peak of the bytes allocated by the future above the bytes
+/// allocated before it.
+async fn peak_growth<F: std::future::Future>(future: F) -> isize {
+    let before = ALLOCATED.with(|a| a.get());
+    PEAK.with(|p| p.set(before));
+    future.await;
+    PEAK.with(|p| p.get()) - before
+}
+
+/// Page answers every request with the same body.
+#[derive(Debug)]
+struct Page(bytes::Bytes);
+
+#[async_trait]
+impl HttpBackend for Page {
+    async fn execute(&self, _request: RequestParts) -> Result<ResponseParts, TransportError> {
+        Ok(ResponseParts {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: self.0.clone(),
+        })
+    }
+}
+
+fn client(body: impl Into<bytes::Bytes>) -> Client {
+    Client::with_backend(
+        "token",
+        Some("https://qstash.example.com"),
+        None,
+        Page(body.into()),
+    )
+    .expect("Could not initialize client")
+}
+
+fn event(id: usize, error: &str) -> serde_json::Value {
+    serde_json::json!({
+        "time": 1_700_000_000_000u64,
+        "state": "ERROR",
+        "messageId": format!("msg_{}", id),
+        "error": error,
+    })
+}
+
+#[tokio::test]
+async fn events_stream_should_not_hold_a_whole_page_of_events() {
+    let error = "x".repeat(100 * 1024);
+    let events: Vec<_> = (0..100).map(|id| event(id, &error)).collect();
+    let body =
+        serde_json::to_vec(&serde_json::json!({ "events": events })).expect("Could not serialize");
+    assert!(body.len() > 10_000_000);
+    let qstash_client = client(body);
+
+    let eager = peak_growth(async {
+        let page = qstash_client
+            .get_events(None)
+            .await
+            .expect("Could not get events");
+        assert_eq!(page.events.len(), 100);
+    })
+    .await;
+    let lazy = peak_growth(async {
+        let mut events = Box::pin(qstash_client.events_stream(None));
+        let mut count = 0;
+        while let Some(event) = events.next().await {
+            assert_eq!(
+                event.expect("Could not get event").error.map(|e| e.len()),
+                Some(error.len())
+            );
+            count += 1;
+        }
+        assert_eq!(count, 100);
+    })
+    .await;
+
+    assert!(eager > 10_000_000, "eager peak {}", eager);
+    assert!(lazy < 1_000_000, "lazy peak {}", lazy);
+}
+
+#[tokio::test]
+async fn events_stream_should_return_invalid_items_as_errors() {
+    let body = serde_json::json!({
+        "events": [event(1, ""), { "messageId": "msg_2" }, event(3, "")],
+        "cursor": null,
+    });
+    let qstash_client = client(serde_json::to_vec(&body).expect("Could not serialize"));
+
+    let events: Vec<_> = qstash_client.events_stream(None).collect().await;
+
+    assert_eq!(events.len(), 3);
+    assert_eq!(
+        events[0].as_ref().expect("Missing event").message_id,
+        "msg_1"
+    );
+    assert!(matches!(events[1], Err(QStashError::EventError { .. })));
+    assert_eq!(
+        events[2].as_ref().expect("Missing event").message_id,
+        "msg_3"
+    );
+}
+
+#[tokio::test]
+async fn events_stream_should_keep_the_items_before_a_malformed_one() {
+    let valid = serde_json::to_string(&event(1, "")).expect("Could not serialize");
+    let body = format!(r#"{{"events":[{},{{"time":17"#, valid);
+    let qstash_client = client(body);
+
+    let events: Vec<_> = qstash_client.events_stream(None).collect().await;
+
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        events[0].as_ref().expect("Missing event").message_id,
+        "msg_1"
+    );
+    assert!(matches!(events[1], Err(QStashError::EventError { .. })));
+}
+
+#[tokio::test]
+async fn dead_letter_queue_stream_should_fail_on_a_page_without_messages() {
+    let qstash_client = client(r#"{"error":"unauthorized"}"#);
+
+    let messages: Vec<_> = qstash_client.dead_letter_queue_stream(None).collect().await;
+
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].is_err());
+}
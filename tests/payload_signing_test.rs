@@ -0,0 +1,167 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use std::io::{Read, Write};
+
+use common::header;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use http::{header::HeaderValue, HeaderMap};
+use qstash_rs::{
+    client::{
+        batch::BatchEntry, Client, PayloadSigning, PublishRequest, PublishRequestUrl, QStashError,
+    },
+    server::verify_payload_signature,
+};
+use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+const SECRET: &str = "shared_secret";
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_1" })),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .payload_signing(
+            PayloadSigning::new(SECRET, "X-Signature").expect("Invalid payload signing"),
+        )
+}
+
+fn url() -> PublishRequestUrl {
+    PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL"))
+}
+
+/// delivered returns the body and forwarded signature of the only request received.
+async fn delivered(server: &MockServer) -> (Vec<u8>, String) {
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    assert_eq!(requests.len(), 1);
+    let signature = header(&requests[0], "Upstash-Forward-X-Signature").expect("Missing signature");
+    (requests[0].body.clone(), signature)
+}
+
+#[tokio::test]
+async fn publish_json_should_sign_the_serialized_body() {
+    let server = mock_server().await;
+
+    client(&server)
+        .publish_json(url(), serde_json::json!({ "order": 1 }), None)
+        .await
+        .expect("Could not publish");
+
+    let (body, signature) = delivered(&server).await;
+    assert_eq!(body, br#"{"order":1}"#);
+    assert_eq!(signature.len(), 64);
+    verify_payload_signature(SECRET, &signature, &body).expect("Invalid signature");
+}
+
+#[tokio::test]
+async fn publish_should_sign_the_raw_body() {
+    let server = mock_server().await;
+
+    let mut request = PublishRequest::<Vec<u8>>::new(url());
+    request.body = Some(vec![0, 159, 146, 150]);
+    client(&server)
+        .publish(request)
+        .await
+        .expect("Could not publish");
+
+    let (body, signature) = delivered(&server).await;
+    assert_eq!(body, [0, 159, 146, 150]);
+    verify_payload_signature(SECRET, &signature, &body).expect("Invalid signature");
+}
+
+#[tokio::test]
+async fn publish_should_sign_the_compressed_body() {
+    let server = mock_server().await;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(br#"{"order":1}"#)
+        .expect("Could not compress");
+    let compressed = encoder.finish().expect("Could not compress");
+
+    let mut request = PublishRequest::<Vec<u8>>::new(url());
+    request.body = Some(compressed.clone());
+    request.headers = Some(HeaderMap::from_iter([(
+        http::header::CONTENT_ENCODING,
+        HeaderValue::from_static("gzip"),
+    )]));
+    client(&server)
+        .publish(request)
+        .await
+        .expect("Could not publish");
+
+    let (body, signature) = delivered(&server).await;
+    assert_eq!(body, compressed);
+    verify_payload_signature(SECRET, &signature, &body).expect("Invalid signature");
+    let mut decompressed = String::new();
+    GzDecoder::new(&body[..])
+        .read_to_string(&mut decompressed)
+        .expect("Could not decompress");
+    assert_eq!(decompressed, r#"{"order":1}"#);
+}
+
+#[tokio::test]
+async fn batch_should_sign_every_entry() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(common::echo_batch)
+        .mount(&server)
+        .await;
+
+    let entries = ["first", "second"].map(|body| {
+        let mut entry = BatchEntry::new(url());
+        entry.body = Some(body.to_string());
+        entry
+    });
+    client(&server)
+        .batch(entries.to_vec())
+        .await
+        .expect("Could not publish");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    let messages: Vec<serde_json::Value> =
+        serde_json::from_slice(&requests[0].body).expect("Invalid batch");
+    assert_eq!(messages.len(), 2);
+    for message in messages {
+        let body = message["body"].as_str().expect("Missing body");
+        let signature = message["headers"]["upstash-forward-x-signature"]
+            .as_str()
+            .expect("Missing signature");
+        verify_payload_signature(SECRET, signature, body.as_bytes()).expect("Invalid signature");
+    }
+}
+
+#[test]
+fn verify_payload_signature_should_reject_other_bodies_and_secrets() {
+    let signing = PayloadSigning::new(SECRET, "X-Signature").expect("Invalid payload signing");
+    let signature = signing.sign(b"hello");
+
+    assert!(verify_payload_signature(SECRET, &signature, b"hello").is_ok());
+    assert!(verify_payload_signature(SECRET, &signature.to_uppercase(), b"hello").is_ok());
+    assert!(verify_payload_signature(SECRET, &signature, b"hello!").is_err());
+    assert!(verify_payload_signature("other", &signature, b"hello").is_err());
+    assert!(verify_payload_signature(SECRET, "zz", b"hello").is_err());
+}
+
+#[test]
+fn payload_signing_should_reject_invalid_header_names() {
+    assert!(matches!(
+        PayloadSigning::new(SECRET, "X Signature"),
+        Err(QStashError::ConfigError { field, .. }) if field == "header_name"
+    ));
+}
@@ -0,0 +1,332 @@
+#![cfg(feature = "reqwest")]
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use qstash_rs::client::{
+    provision::{
+        Change, LiveState, Provisioner, QueueSpec, ResourceSpec, ScheduleSpec, UrlGroupSpec,
+    },
+    queues::Queue,
+    schedules::Schedule,
+    topics::{Topic, TopicEndpoint},
+    Client,
+};
+use serde_json::{json, Value};
+use wiremock::{matchers::any, Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// FakeState is the state of a fake QStash, it counts every mutation.
+#[derive(Default)]
+struct FakeState {
+    queues: BTreeMap<String, u32>,
+    topics: BTreeMap<String, Vec<Value>>,
+    schedules: BTreeMap<String, Value>,
+    mutations: usize,
+}
+
+struct FakeQStash {
+    state: Arc<Mutex<FakeState>>,
+}
+
+impl Respond for FakeQStash {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let mut state = self.state.lock().expect("Could not lock state");
+        let path = request.url.path().to_string();
+        let method = request.method.to_string();
+        let body: Value = serde_json::from_slice(&request.body).unwrap_or(Value::Null);
+
+        match (method.as_str(), path.as_str()) {
+            ("GET", "/v2/queues") => {
+                let queues: Vec<Value> = state
+                    .queues
+                    .iter()
+                    .map(|(name, parallelism)| json!({ "name": name, "parallelism": parallelism, "lag": 0 }))
+                    .collect();
+                ResponseTemplate::new(200).set_body_json(queues)
+            }
+            ("POST", "/v2/queues") => {
+                state.mutations += 1;
+                let name = body["queueName"].as_str().expect("Missing queue name");
+                let parallelism = body["parallelism"].as_u64().expect("Missing parallelism");
+                state.queues.insert(name.to_string(), parallelism as u32);
+                ResponseTemplate::new(200)
+            }
+            ("GET", "/v2/topics") => {
+                let topics: Vec<Value> = state
+                    .topics
+                    .iter()
+                    .map(|(name, endpoints)| json!({ "name": name, "endpoints": endpoints }))
+                    .collect();
+                ResponseTemplate::new(200).set_body_json(topics)
+            }
+            ("GET", "/v2/schedules") => {
+                let schedules: Vec<&Value> = state.schedules.values().collect();
+                ResponseTemplate::new(200).set_body_json(schedules)
+            }
+            ("GET", path) if path.starts_with("/v2/topics/") => {
+                let name = path.trim_start_matches("/v2/topics/");
+                match state.topics.get(name) {
+                    Some(endpoints) => ResponseTemplate::new(200)
+                        .set_body_json(json!({ "name": name, "endpoints": endpoints })),
+                    None => ResponseTemplate::new(404),
+                }
+            }
+            (_, path) if path.starts_with("/v2/topics/") => {
+                state.mutations += 1;
+                let name = path
+                    .trim_start_matches("/v2/topics/")
+                    .trim_end_matches("/endpoints")
+                    .to_string();
+                let endpoints = body["endpoints"]
+                    .as_array()
+                    .expect("Missing endpoints")
+                    .clone();
+                let topic = state.topics.entry(name.clone()).or_default();
+                match method.as_str() {
+                    "POST" => topic.extend(endpoints),
+                    _ => topic.retain(|e| !endpoints.iter().any(|r| r["url"] == e["url"])),
+                }
+                if topic.is_empty() {
+                    state.topics.remove(&name);
+                }
+                ResponseTemplate::new(200)
+            }
+            ("POST", path) if path.starts_with("/v2/schedules/") => {
+                state.mutations += 1;
+                let header = |name: &str| {
+                    request
+                        .headers
+                        .get(&name.into())
+                        .map(|v| v.as_str().to_string())
+                };
+                let id = header("Upstash-Schedule-Id").expect("Missing schedule id");
+                let schedule = json!({
+                    "scheduleId": id,
+                    "cron": header("Upstash-Cron").expect("Missing cron"),
+                    "destination": path.trim_start_matches("/v2/schedules/"),
+                    "method": header("Upstash-Method"),
+                    "body": String::from_utf8_lossy(&request.body),
+                    "retries": header("Upstash-Retries").map(|r| r.parse::<u32>().expect("Invalid retries")),
+                });
+                state.schedules.insert(id.clone(), schedule);
+                ResponseTemplate::new(201).set_body_json(json!({ "scheduleId": id }))
+            }
+            _ => ResponseTemplate::new(404),
+        }
+    }
+}
+
+async fn fake_qstash(state: FakeState) -> (MockServer, Arc<Mutex<FakeState>>) {
+    let server = MockServer::start().await;
+    let state = Arc::new(Mutex::new(state));
+    Mock::given(any())
+        .respond_with(FakeQStash {
+            state: state.clone(),
+        })
+        .mount(&server)
+        .await;
+    (server, state)
+}
+
+fn endpoint(name: Option<&str>, url: &str) -> TopicEndpoint {
+    TopicEndpoint {
+        name: name.map(str::to_string),
+        url: url.to_string(),
+    }
+}
+
+fn spec() -> ResourceSpec {
+    serde_json::from_value(json!({
+        "queues": [
+            { "name": "orders", "parallelism": 5 },
+            { "name": "emails", "parallelism": 1 },
+        ],
+        "urlGroups": [{
+            "name": "billing",
+            "endpoints": [
+                { "name": "invoices", "url": "https://example.com/invoices" },
+                { "url": "https://example.com/ledger" },
+            ],
+        }],
+        "schedules": [{
+            "id": "nightly",
+            "destination": "https://example.com/nightly",
+            "cron": "0 3 * * *",
+            "method": "PUT",
+            "retries": 2,
+        }],
+    }))
+    .expect("Could not parse spec")
+}
+
+fn nightly(cron: &str) -> Schedule {
+    serde_json::from_value(json!({
+        "scheduleId": "nightly",
+        "cron": cron,
+        "destination": "https://example.com/nightly",
+        "method": "PUT",
+        "retries": 2,
+    }))
+    .expect("Could not parse schedule")
+}
+
+#[test]
+fn diff_should_only_contain_the_differences() {
+    let live = LiveState {
+        queues: vec![
+            serde_json::from_value::<Queue>(json!({ "name": "orders", "parallelism": 2 }))
+                .expect("Could not parse queue"),
+            serde_json::from_value::<Queue>(json!({ "name": "emails", "parallelism": 1 }))
+                .expect("Could not parse queue"),
+        ],
+        topics: vec![Topic {
+            name: "billing".to_string(),
+            created_at: None,
+            updated_at: None,
+            endpoints: vec![
+                endpoint(Some("ledger"), "https://example.com/ledger"),
+                endpoint(None, "https://example.com/legacy"),
+            ],
+        }],
+        schedules: vec![nightly("0 4 * * *")],
+    };
+
+    assert_eq!(
+        spec().diff(&live),
+        vec![
+            Change::UpdateQueue {
+                name: "orders".to_string(),
+                parallelism: 5,
+                previous_parallelism: 2,
+            },
+            Change::AddEndpoints {
+                url_group: "billing".to_string(),
+                endpoints: vec![endpoint(Some("invoices"), "https://example.com/invoices")],
+            },
+            Change::RemoveEndpoints {
+                url_group: "billing".to_string(),
+                endpoints: vec![endpoint(None, "https://example.com/legacy")],
+            },
+            Change::UpdateSchedule(spec().schedules[0].clone()),
+        ]
+    );
+}
+
+#[test]
+fn diff_should_create_missing_resources() {
+    let changes = spec().diff(&LiveState::default());
+
+    assert_eq!(
+        changes,
+        vec![
+            Change::CreateQueue {
+                name: "orders".to_string(),
+                parallelism: 5,
+            },
+            Change::CreateQueue {
+                name: "emails".to_string(),
+                parallelism: 1,
+            },
+            Change::AddEndpoints {
+                url_group: "billing".to_string(),
+                endpoints: spec().url_groups[0].endpoints.clone(),
+            },
+            Change::CreateSchedule(spec().schedules[0].clone()),
+        ]
+    );
+}
+
+#[test]
+fn diff_should_be_empty_when_the_state_matches() {
+    let live = LiveState {
+        queues: vec![
+            serde_json::from_value(json!({ "name": "orders", "parallelism": 5 }))
+                .expect("Could not parse queue"),
+            serde_json::from_value(json!({ "name": "emails", "parallelism": 1 }))
+                .expect("Could not parse queue"),
+        ],
+        topics: vec![Topic {
+            name: "billing".to_string(),
+            created_at: None,
+            updated_at: None,
+            endpoints: vec![
+                endpoint(Some("ledger"), "https://example.com/ledger"),
+                endpoint(Some("invoices"), "https://example.com/invoices"),
+            ],
+        }],
+        schedules: vec![nightly("0 3 * * *")],
+    };
+
+    assert!(spec().diff(&live).is_empty());
+}
+
+#[tokio::test]
+async fn apply_should_be_idempotent() {
+    let mut state = FakeState::default();
+    state.queues.insert("orders".to_string(), 2);
+    state.topics.insert(
+        "billing".to_string(),
+        vec![json!({ "url": "https://example.com/legacy" })],
+    );
+    let (server, state) = fake_qstash(state).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let provisioner = Provisioner::new(qstash_client);
+
+    let report = provisioner.apply(&spec()).await.expect("Could not apply");
+    assert!(report.applied);
+    assert_eq!(report.changes.len(), 5);
+    let mutations = state.lock().expect("Could not lock state").mutations;
+    assert_eq!(mutations, 5);
+
+    let report = provisioner.apply(&spec()).await.expect("Could not apply");
+    assert!(report.is_empty());
+    assert_eq!(
+        state.lock().expect("Could not lock state").mutations,
+        mutations
+    );
+
+    let state = state.lock().expect("Could not lock state");
+    assert_eq!(state.queues.get("orders"), Some(&5));
+    assert_eq!(state.topics["billing"].len(), 2);
+    assert_eq!(state.schedules["nightly"]["cron"], "0 3 * * *");
+}
+
+#[tokio::test]
+async fn apply_should_not_mutate_in_dry_run() {
+    let (server, state) = fake_qstash(FakeState::default()).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let report = Provisioner::new(qstash_client)
+        .dry_run(true)
+        .apply(&spec())
+        .await
+        .expect("Could not apply");
+
+    assert!(!report.applied);
+    assert_eq!(report.changes.len(), 4);
+    assert_eq!(state.lock().expect("Could not lock state").mutations, 0);
+}
+
+#[test]
+fn resource_spec_should_default_missing_kinds() {
+    let spec: ResourceSpec = serde_json::from_value(json!({
+        "queues": [{ "name": "orders", "parallelism": 5 }],
+    }))
+    .expect("Could not parse spec");
+
+    assert_eq!(
+        spec,
+        ResourceSpec {
+            queues: vec![QueueSpec {
+                name: "orders".to_string(),
+                parallelism: 5,
+            }],
+            url_groups: Vec::<UrlGroupSpec>::new(),
+            schedules: Vec::<ScheduleSpec>::new(),
+        }
+    );
+}
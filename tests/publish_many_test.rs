@@ -0,0 +1,137 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use http::{HeaderMap, StatusCode};
+use qstash_rs::client::{
+    Client, HttpBackend, PublishRequest, PublishRequestUrl, QStashError, QstashResponse,
+    RequestParts, ResponseParts, TransportError,
+};
+
+/// InFlight answers every publish with the destination path as message id,
+/// after a delay that gets shorter for every later destination,
+/// and with a body that is not JSON for the destinations containing `fail`.
+/// It records the highest number of publishes in flight at the same time.
+#[derive(Debug, Clone, Default)]
+struct InFlight {
+    current: Arc<AtomicUsize>,
+    highest: Arc<AtomicUsize>,
+}
+
+impl InFlight {
+    fn highest(&self) -> usize {
+        self.highest.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl HttpBackend for InFlight {
+    async fn execute(&self, request: RequestParts) -> Result<ResponseParts, TransportError> {
+        let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+        self.highest.fetch_max(current, Ordering::SeqCst);
+
+        let destination = request
+            .url
+            .path()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+        let index: u64 = destination
+            .trim_start_matches("fail")
+            .parse()
+            .unwrap_or_default();
+        tokio::time::sleep(Duration::from_millis(100 - index * 10)).await;
+        self.current.fetch_sub(1, Ordering::SeqCst);
+
+        if destination.starts_with("fail") {
+            return Ok(ResponseParts {
+                status: StatusCode::BAD_REQUEST,
+                headers: HeaderMap::new(),
+                body: "invalid destination".into(),
+            });
+        }
+        Ok(ResponseParts {
+            status: StatusCode::CREATED,
+            headers: HeaderMap::new(),
+            body: format!(r#"{{"messageId":"{}"}}"#, destination).into(),
+        })
+    }
+}
+
+fn client(backend: &InFlight) -> Client {
+    Client::with_backend(
+        "token",
+        Some("https://qstash.example.com"),
+        None,
+        backend.clone(),
+    )
+    .expect("Could not initialize client")
+}
+
+fn request(path: &str) -> PublishRequest<String> {
+    PublishRequest::new(PublishRequestUrl::Url(
+        format!("https://example.com/{}", path)
+            .parse()
+            .expect("Could not parse URL"),
+    ))
+}
+
+fn message_id(result: &Result<Vec<QstashResponse>, QStashError>) -> &str {
+    result.as_ref().expect("Could not publish")[0]
+        .message_id
+        .as_deref()
+        .unwrap_or_default()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_publish_many_keeps_the_request_order() {
+    let backend = InFlight::default();
+    let requests = (0..6).map(|i| request(&i.to_string())).collect();
+
+    let results = client(&backend).publish_many(requests, 6).await;
+
+    let ids: Vec<&str> = results.iter().map(message_id).collect();
+    assert_eq!(ids, ["0", "1", "2", "3", "4", "5"]);
+    assert_eq!(backend.highest(), 6);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_publish_many_caps_the_publishes_in_flight() {
+    let backend = InFlight::default();
+    let requests = (0..6).map(|i| request(&i.to_string())).collect();
+
+    let results = client(&backend).publish_many(requests, 2).await;
+
+    assert_eq!(results.len(), 6);
+    assert!(results.iter().all(Result::is_ok));
+    assert_eq!(backend.highest(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_publish_many_publishes_one_at_a_time_for_zero_concurrency() {
+    let backend = InFlight::default();
+    let requests = (0..3).map(|i| request(&i.to_string())).collect();
+
+    let results = client(&backend).publish_many(requests, 0).await;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(backend.highest(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_publish_many_continues_after_a_failed_publish() {
+    let backend = InFlight::default();
+    let requests = vec![request("0"), request("fail1"), request("2")];
+
+    let results = client(&backend).publish_many(requests, 3).await;
+
+    assert_eq!(message_id(&results[0]), "0");
+    assert!(matches!(results[1], Err(QStashError::PublishError)));
+    assert_eq!(message_id(&results[2]), "2");
+}
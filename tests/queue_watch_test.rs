@@ -0,0 +1,268 @@
+#![cfg(feature = "reqwest")]
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use qstash_rs::client::{
+    queue_watch::{QueueAlert, QueueAlertConfig},
+    Client, QStashError, ReqwestBackend,
+};
+use wiremock::{matchers::method, Mock, MockServer, Request, Respond, ResponseTemplate};
+
+/// ScriptedQueues answers every poll of a queue with the next scripted lag.
+/// A lag of None answers with an error, the last lag is repeated once the script runs out.
+struct ScriptedQueues {
+    scripts: Mutex<HashMap<String, VecDeque<Option<u64>>>>,
+}
+
+impl Respond for ScriptedQueues {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let name = request
+            .url
+            .path()
+            .trim_start_matches("/v2/queues/")
+            .to_string();
+        let mut scripts = self.scripts.lock().expect("Could not lock scripts");
+        let script = scripts.get_mut(&name).expect("Unexpected queue");
+        let lag = match script.len() {
+            1 => script[0],
+            _ => script.pop_front().expect("Empty script"),
+        };
+
+        match lag {
+            Some(lag) => ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "name": name,
+                "parallelism": 1,
+                "createdAt": 1700000000000u64,
+                "updatedAt": 1700000000000u64,
+                "lag": lag,
+            })),
+            None => ResponseTemplate::new(500),
+        }
+    }
+}
+
+async fn mock_server(scripts: &[(&str, &[Option<u64>])]) -> MockServer {
+    let server = MockServer::start().await;
+    let scripts = scripts
+        .iter()
+        .map(|(name, lags)| (name.to_string(), lags.iter().copied().collect()))
+        .collect();
+    Mock::given(method("GET"))
+        .respond_with(ScriptedQueues {
+            scripts: Mutex::new(scripts),
+        })
+        .mount(&server)
+        .await;
+    server
+}
+
+/// client creates a client for the mock server.
+/// The connection pool has no idle timeout, its timer would otherwise make the paused
+/// clock jump ahead while a request waits on the mock server.
+fn client(server: &MockServer) -> Client {
+    Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .http_backend(ReqwestBackend::new(
+            reqwest::Client::builder()
+                .pool_idle_timeout(None)
+                .build()
+                .expect("Could not initialize http client"),
+        ))
+}
+
+fn config() -> QueueAlertConfig {
+    QueueAlertConfig {
+        high_watermark: 100,
+        low_watermark: 20,
+        interval: Duration::from_secs(10),
+    }
+}
+
+async fn received(server: &MockServer) -> usize {
+    server
+        .received_requests()
+        .await
+        .map_or(0, |requests| requests.len())
+}
+
+/// wait_for_polls advances the paused clock one interval at a time until the mock
+/// server has received the amount of polls.
+async fn wait_for_polls(server: &MockServer, polls: usize) {
+    while received(server).await < polls {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+fn recorder() -> (
+    Arc<Mutex<Vec<QueueAlert>>>,
+    impl Fn(QueueAlert) -> futures::future::Ready<()> + Send + Sync + 'static,
+) {
+    let alerts = Arc::new(Mutex::new(Vec::new()));
+    let sink = alerts.clone();
+    let callback = move |alert| {
+        sink.lock().expect("Could not lock alerts").push(alert);
+        futures::future::ready(())
+    };
+    (alerts, callback)
+}
+
+#[tokio::test(start_paused = true)]
+async fn watch_queue_should_alert_once_per_crossing() {
+    let server = mock_server(&[(
+        "orders",
+        &[
+            Some(10),
+            Some(150),
+            Some(300),
+            Some(50),
+            Some(120),
+            Some(15),
+            Some(10),
+            Some(150),
+        ],
+    )])
+    .await;
+    let (alerts, callback) = recorder();
+
+    let _guard = client(&server).watch_queue("orders", config(), callback);
+    wait_for_polls(&server, 8).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(
+        *alerts.lock().expect("Could not lock alerts"),
+        vec![
+            QueueAlert::High {
+                queue: "orders".to_string(),
+                lag: 150
+            },
+            QueueAlert::Recovered {
+                queue: "orders".to_string(),
+                lag: 15
+            },
+            QueueAlert::High {
+                queue: "orders".to_string(),
+                lag: 150
+            },
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn watch_queue_should_keep_polling_after_errors() {
+    let server = mock_server(&[("orders", &[None, None, Some(200), None, Some(5)])]).await;
+    let (alerts, callback) = recorder();
+
+    let _guard = client(&server).watch_queue("orders", config(), callback);
+    wait_for_polls(&server, 5).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(
+        *alerts.lock().expect("Could not lock alerts"),
+        vec![
+            QueueAlert::High {
+                queue: "orders".to_string(),
+                lag: 200
+            },
+            QueueAlert::Recovered {
+                queue: "orders".to_string(),
+                lag: 5
+            },
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn watch_queues_should_track_every_queue_separately() {
+    let server = mock_server(&[
+        ("orders", &[Some(150), Some(150), Some(150)]),
+        ("emails", &[Some(0), Some(500), Some(0)]),
+    ])
+    .await;
+    let (alerts, callback) = recorder();
+
+    let _guard = client(&server).watch_queues(
+        vec!["orders".to_string(), "emails".to_string()],
+        config(),
+        callback,
+    );
+    wait_for_polls(&server, 6).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(
+        *alerts.lock().expect("Could not lock alerts"),
+        vec![
+            QueueAlert::High {
+                queue: "orders".to_string(),
+                lag: 150
+            },
+            QueueAlert::High {
+                queue: "emails".to_string(),
+                lag: 500
+            },
+            QueueAlert::Recovered {
+                queue: "emails".to_string(),
+                lag: 0
+            },
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn watch_queue_should_stop_when_the_guard_is_dropped() {
+    let server = mock_server(&[("orders", &[Some(0)])]).await;
+    let (_, callback) = recorder();
+
+    let guard = client(&server).watch_queue("orders", config(), callback);
+    wait_for_polls(&server, 2).await;
+    drop(guard);
+
+    let polls = received(&server).await;
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(received(&server).await, polls);
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_for_queue_drain_should_poll_until_the_lag_is_zero() {
+    let server = mock_server(&[("orders", &[Some(30), Some(12), Some(4), Some(0)])]).await;
+
+    let queue = client(&server)
+        .wait_for_queue_drain("orders", Duration::from_secs(60), Duration::from_secs(5))
+        .await
+        .expect("Could not wait for the queue");
+
+    assert_eq!(queue.lag, 0);
+    assert_eq!(received(&server).await, 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_for_queue_drain_should_time_out() {
+    let server = mock_server(&[("orders", &[Some(30), Some(20)])]).await;
+
+    let result = client(&server)
+        .wait_for_queue_drain("orders", Duration::from_secs(12), Duration::from_secs(5))
+        .await;
+
+    assert!(matches!(result, Err(QStashError::Timeout)));
+    assert_eq!(received(&server).await, 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn wait_for_queue_drain_should_return_failed_polls() {
+    let server = mock_server(&[("orders", &[Some(30), None])]).await;
+
+    let result = client(&server)
+        .wait_for_queue_drain("orders", Duration::from_secs(60), Duration::from_secs(5))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(QStashError::QueueError {
+            status: Some(500),
+            ..
+        })
+    ));
+}
@@ -0,0 +1,787 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use std::time::Duration;
+
+use qstash_rs::client::{
+    batch::{BatchEntry, BatchResult, MAX_BATCH_ENTRIES},
+    queues::{Queue, UpsertOutcome, UpsertQueueRequest},
+    Client, PublishOptions, PublishRequest, PublishRequestUrl, QStashError,
+};
+use wiremock::{
+    matchers::{any, body_json, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use common::{echo_batch, header};
+
+fn client(server: &MockServer) -> Client {
+    Client::new("token", Some(&server.uri()), None).expect("Could not initialize client")
+}
+
+#[tokio::test]
+async fn upsert_queue_should_post_the_name_and_parallelism() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/queues"))
+        .and(body_json(serde_json::json!({
+            "queueName": "orders",
+            "parallelism": 5,
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client(&server)
+        .upsert_queue(UpsertQueueRequest {
+            queue_name: "orders".to_string(),
+            parallelism: 5,
+        })
+        .await
+        .expect("Could not upsert queue");
+}
+
+#[tokio::test]
+async fn upsert_queue_should_reject_a_parallelism_of_zero() {
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let result = client(&server)
+        .upsert_queue(UpsertQueueRequest {
+            queue_name: "orders".to_string(),
+            parallelism: 0,
+        })
+        .await;
+
+    match result {
+        Err(QStashError::QueueError {
+            status: None,
+            message,
+        }) => assert!(message.contains("parallelism"), "{}", message),
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn upsert_queue_should_return_the_status_and_message_of_the_api() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .set_body_json(serde_json::json!({ "error": "parallelism exceeds your plan" })),
+        )
+        .mount(&server)
+        .await;
+
+    let result = client(&server)
+        .upsert_queue(UpsertQueueRequest {
+            queue_name: "orders".to_string(),
+            parallelism: 500,
+        })
+        .await;
+
+    match result {
+        Err(QStashError::QueueError { status, message }) => {
+            assert_eq!(status, Some(400));
+            assert_eq!(message, "parallelism exceeds your plan");
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn list_queues_should_return_every_queue() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/queues"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            {
+                "name": "orders",
+                "parallelism": 2,
+                "createdAt": 1_700_000_000_000u64,
+                "updatedAt": 1_700_000_100_000u64,
+                "lag": 0,
+            },
+            { "name": "emails" },
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let queues = client(&server)
+        .list_queues()
+        .await
+        .expect("Could not list queues");
+
+    assert_eq!(queues.len(), 2);
+    assert_eq!(queues[0].name, "orders");
+    assert_eq!(queues[0].parallelism, 2);
+    assert_eq!(queues[0].created_at, Some(1_700_000_000_000));
+    assert_eq!(queues[0].updated_at, Some(1_700_000_100_000));
+    assert_eq!(queues[0].lag, 0);
+    assert_eq!(queues[1].name, "emails");
+    assert_eq!(queues[1].created_at, None);
+    assert_eq!(queues[1].updated_at, None);
+    assert_eq!(queues[1].lag, 0);
+}
+
+#[tokio::test]
+async fn get_queue_should_return_not_found_for_missing_queues() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("queue not found"))
+        .mount(&server)
+        .await;
+
+    let result = client(&server).get_queue("orders").await;
+
+    assert!(matches!(result, Err(QStashError::QueueNotFound(name)) if name == "orders"));
+}
+
+#[tokio::test]
+async fn get_queue_should_encode_the_name() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/queues/orders%2Feu%20west"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "name": "orders/eu west",
+            "parallelism": 1,
+            "lag": 3,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let queue = client(&server)
+        .get_queue("orders/eu west")
+        .await
+        .expect("Could not get queue");
+
+    assert_eq!(queue.name, "orders/eu west");
+    assert_eq!(queue.lag, 3);
+}
+
+#[tokio::test]
+async fn pause_and_resume_queue_should_post_the_action() {
+    let server = MockServer::start().await;
+    for action in ["pause", "resume"] {
+        Mock::given(method("POST"))
+            .and(path(format!("/v2/queues/orders/{}", action)))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/v2/queues/orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "name": "orders",
+            "parallelism": 1,
+            "paused": true,
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client = client(&server);
+
+    qstash_client
+        .pause_queue("orders")
+        .await
+        .expect("Could not pause queue");
+    let queue = qstash_client
+        .get_queue("orders")
+        .await
+        .expect("Could not get queue");
+    assert!(queue.paused);
+    qstash_client
+        .resume_queue("orders")
+        .await
+        .expect("Could not resume queue");
+
+    let result = qstash_client.pause_queue("missing").await;
+    assert!(matches!(result, Err(QStashError::QueueNotFound(name)) if name == "missing"));
+}
+
+#[tokio::test]
+async fn enqueue_should_post_to_the_queue_and_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/enqueue/orders/https://example.com/job"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_1" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut request = PublishRequest::new(PublishRequestUrl::Url(
+        "https://example.com/job"
+            .parse()
+            .expect("Could not parse URL"),
+    ));
+    request.body = Some("report");
+    request.retries = Some(2);
+    let response = client(&server)
+        .enqueue("orders", request)
+        .await
+        .expect("Could not enqueue message");
+
+    assert_eq!(response.len(), 1);
+    assert_eq!(response[0].message_id.as_deref(), Some("msg_1"));
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    assert_eq!(requests[0].body, b"report");
+    assert_eq!(
+        header(&requests[0], "Upstash-Retries").as_deref(),
+        Some("2")
+    );
+}
+
+#[tokio::test]
+async fn enqueue_should_post_to_the_queue_and_topic() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/enqueue/orders%20eu/billing"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!([
+            { "messageId": "msg_1", "url": "https://billing.example.com" },
+            { "messageId": "msg_2", "url": "https://audit.example.com" },
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client(&server)
+        .enqueue(
+            "orders eu",
+            PublishRequest::<String>::new(PublishRequestUrl::Topic("billing".to_string())),
+        )
+        .await
+        .expect("Could not enqueue message");
+
+    let ids: Vec<_> = response
+        .iter()
+        .map(|r| r.message_id.as_deref().unwrap_or_default())
+        .collect();
+    assert_eq!(ids, vec!["msg_1", "msg_2"]);
+}
+
+#[tokio::test]
+async fn enqueue_json_should_send_the_json_body_and_options() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/enqueue/myqueue/https://example.com/"))
+        .and(body_json(serde_json::json!({ "order": 42 })))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_1" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client(&server)
+        .enqueue_json(
+            "myqueue",
+            PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL")),
+            serde_json::json!({ "order": 42 }),
+            Some(PublishOptions {
+                callback: Some("https://example.com/callback".to_string()),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("Could not enqueue message");
+
+    assert_eq!(response[0].message_id.as_deref(), Some("msg_1"));
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    assert_eq!(
+        header(&requests[0], "Content-Type").as_deref(),
+        Some("application/json")
+    );
+    assert_eq!(
+        header(&requests[0], "Upstash-Callback").as_deref(),
+        Some("https://example.com/callback")
+    );
+}
+
+#[tokio::test]
+async fn publish_to_a_queue_destination_should_enqueue() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/enqueue/orders/https://example.com/job"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_1" })),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/enqueue/orders/billing"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!([
+            { "messageId": "msg_2", "url": "https://billing.example.com" },
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let qstash_client = client(&server);
+    let job = PublishRequestUrl::Url(
+        "https://example.com/job"
+            .parse()
+            .expect("Could not parse URL"),
+    );
+
+    let response = qstash_client
+        .publish(PublishRequest::<String>::new(PublishRequestUrl::queue(
+            "orders",
+            job.clone(),
+        )))
+        .await
+        .expect("Could not publish message");
+    assert_eq!(response[0].message_id.as_deref(), Some("msg_1"));
+
+    qstash_client
+        .publish_json(
+            PublishRequestUrl::queue("orders", job),
+            serde_json::json!({ "order": 42 }),
+            None,
+        )
+        .await
+        .expect("Could not publish message");
+
+    let response = qstash_client
+        .publish(PublishRequest::<String>::new(PublishRequestUrl::queue(
+            "orders",
+            PublishRequestUrl::Topic("billing".to_string()),
+        )))
+        .await
+        .expect("Could not publish message");
+    assert_eq!(response[0].message_id.as_deref(), Some("msg_2"));
+}
+
+#[tokio::test]
+async fn publish_without_a_queue_should_not_enqueue() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/https://example.com/job"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_1" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/billing"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!([
+            { "messageId": "msg_2", "url": "https://billing.example.com" },
+            { "messageId": "msg_3", "url": "https://audit.example.com" },
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let qstash_client = client(&server);
+
+    qstash_client
+        .publish(PublishRequest::<String>::new(PublishRequestUrl::Url(
+            "https://example.com/job"
+                .parse()
+                .expect("Could not parse URL"),
+        )))
+        .await
+        .expect("Could not publish message");
+    let response = qstash_client
+        .publish_json(
+            PublishRequestUrl::Topic("billing".to_string()),
+            serde_json::json!({ "order": 42 }),
+            None,
+        )
+        .await
+        .expect("Could not publish message");
+    assert_eq!(response.len(), 2);
+}
+
+#[tokio::test]
+async fn batch_entries_to_a_queue_destination_should_set_the_queue() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!([{ "messageId": "msg_1" }])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client(&server)
+        .batch(vec![BatchEntry::new(PublishRequestUrl::queue(
+            "orders",
+            PublishRequestUrl::Url(
+                "https://example.com/job"
+                    .parse()
+                    .expect("Could not parse URL"),
+            ),
+        ))])
+        .await
+        .expect("Could not publish batch");
+
+    assert_eq!(
+        response[0].responses()[0].message_id.as_deref(),
+        Some("msg_1")
+    );
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    let messages: serde_json::Value =
+        serde_json::from_slice(&requests[0].body).expect("Could not parse batch");
+    assert_eq!(messages[0]["destination"], "https://example.com/job");
+    assert_eq!(messages[0]["queue"], "orders");
+}
+
+fn job(body: String) -> PublishRequest<String> {
+    let mut request = PublishRequest::new(PublishRequestUrl::Url(
+        "https://example.com/job"
+            .parse()
+            .expect("Could not parse URL"),
+    ));
+    request.body = Some(body);
+    request
+}
+
+/// sent_batches returns the bodies of the messages of every batch received by the server.
+async fn sent_batches(server: &MockServer) -> Vec<Vec<String>> {
+    server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded")
+        .iter()
+        .map(|r| {
+            serde_json::from_slice::<Vec<serde_json::Value>>(&r.body)
+                .expect("Batch body should be a JSON array")
+                .iter()
+                .map(|m| {
+                    assert_eq!(m["queue"], "orders");
+                    m["body"].as_str().unwrap_or_default().to_string()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn enqueue_batch_should_chunk_the_messages_in_order() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+    let bodies: Vec<String> = (0..MAX_BATCH_ENTRIES * 2 + 50)
+        .map(|i| i.to_string())
+        .collect();
+
+    let response = client(&server)
+        .enqueue_batch("orders", bodies.iter().cloned().map(job).collect())
+        .await
+        .expect("Could not enqueue batch");
+
+    let ids: Vec<String> = response
+        .into_iter()
+        .flat_map(BatchResult::into_responses)
+        .map(|r| r.message_id.unwrap_or_default())
+        .collect();
+    assert_eq!(ids, bodies);
+    let batches = sent_batches(&server).await;
+    let sizes: Vec<usize> = batches.iter().map(Vec::len).collect();
+    assert_eq!(sizes, vec![MAX_BATCH_ENTRIES, MAX_BATCH_ENTRIES, 50]);
+    assert_eq!(batches.concat(), bodies);
+}
+
+#[tokio::test]
+async fn enqueue_batch_should_chunk_by_the_maximum_body_size() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+    let bodies: Vec<String> = (0..5).map(|i| format!("{}{}", i, "x".repeat(99))).collect();
+
+    let response = client(&server)
+        .max_body_size(500)
+        .enqueue_batch("orders", bodies.iter().cloned().map(job).collect())
+        .await
+        .expect("Could not enqueue batch");
+
+    assert_eq!(response.len(), 5);
+    let batches = sent_batches(&server).await;
+    assert!(batches.len() > 1, "{:?}", batches);
+    assert!(server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded")
+        .iter()
+        .all(|r| r.body.len() <= 500));
+    assert_eq!(batches.concat(), bodies);
+}
+
+#[tokio::test]
+async fn quota_responses_should_return_quota_exceeded() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/enqueue/orders/https://example.com/job"))
+        .respond_with(
+            ResponseTemplate::new(429)
+                .insert_header("Retry-After", "30")
+                .set_body_json(serde_json::json!({ "error": "too many requests" })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/https://example.com/job"))
+        .respond_with(
+            ResponseTemplate::new(412)
+                .set_body_json(serde_json::json!({ "error": "daily quota exceeded" })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/queues"))
+        .respond_with(
+            ResponseTemplate::new(412)
+                .set_body_json(serde_json::json!({ "error": "queue limit reached" })),
+        )
+        .mount(&server)
+        .await;
+    let qstash_client = client(&server);
+
+    let result = qstash_client
+        .enqueue("orders", job("report".to_string()))
+        .await;
+    match result {
+        Err(QStashError::QuotaExceeded {
+            status,
+            message,
+            retry_after,
+        }) => {
+            assert_eq!(status, 429);
+            assert_eq!(message, "too many requests");
+            assert_eq!(retry_after, Some(Duration::from_secs(30)));
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+
+    let result = qstash_client.publish(job("report".to_string())).await;
+    assert!(matches!(
+        result,
+        Err(QStashError::QuotaExceeded {
+            status: 412,
+            retry_after: None,
+            ..
+        })
+    ));
+
+    let result = qstash_client
+        .upsert_queue(UpsertQueueRequest {
+            queue_name: "orders".to_string(),
+            parallelism: 1,
+        })
+        .await;
+    assert!(matches!(
+        result,
+        Err(QStashError::QuotaExceeded { status: 412, message, .. }) if message == "queue limit reached"
+    ));
+}
+
+#[test]
+fn upsert_outcome_should_compare_the_parallelism() {
+    let queue: Queue = serde_json::from_value(serde_json::json!({
+        "name": "orders",
+        "parallelism": 2,
+    }))
+    .expect("Could not parse queue");
+
+    assert_eq!(UpsertOutcome::compare(None, 2), UpsertOutcome::Created);
+    assert_eq!(
+        UpsertOutcome::compare(Some(&queue), 5),
+        UpsertOutcome::Updated {
+            previous_parallelism: 2
+        }
+    );
+    assert_eq!(
+        UpsertOutcome::compare(Some(&queue), 2),
+        UpsertOutcome::Unchanged
+    );
+}
+
+#[tokio::test]
+async fn upsert_queue_checked_should_create_missing_queues() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/queues"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let outcome = client(&server)
+        .upsert_queue_checked(UpsertQueueRequest {
+            queue_name: "orders".to_string(),
+            parallelism: 2,
+        })
+        .await
+        .expect("Could not upsert queue");
+
+    assert_eq!(outcome, UpsertOutcome::Created);
+}
+
+#[tokio::test]
+async fn upsert_queue_checked_should_skip_unchanged_queues() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/queues/orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "name": "orders",
+            "parallelism": 2,
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/queues"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let qstash_client = client(&server);
+
+    let unchanged = qstash_client
+        .upsert_queue_checked(UpsertQueueRequest {
+            queue_name: "orders".to_string(),
+            parallelism: 2,
+        })
+        .await
+        .expect("Could not upsert queue");
+    let updated = qstash_client
+        .upsert_queue_checked(UpsertQueueRequest {
+            queue_name: "orders".to_string(),
+            parallelism: 4,
+        })
+        .await
+        .expect("Could not upsert queue");
+
+    assert_eq!(unchanged, UpsertOutcome::Unchanged);
+    assert_eq!(
+        updated,
+        UpsertOutcome::Updated {
+            previous_parallelism: 2
+        }
+    );
+}
+
+#[test]
+fn queue_handle_should_merge_the_call_options_over_its_defaults() {
+    let qstash_client =
+        Client::new("token", Some("http://localhost"), None).expect("Could not initialize client");
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+        "Upstash-Forward-Team",
+        "billing".parse().expect("Invalid header"),
+    );
+    headers.insert(
+        "Upstash-Forward-Region",
+        "eu".parse().expect("Invalid header"),
+    );
+    let orders = qstash_client
+        .queue("orders")
+        .retries(3)
+        .callback("https://example.com/callback")
+        .headers(headers);
+
+    let mut call_headers = http::HeaderMap::new();
+    call_headers.insert(
+        "Upstash-Forward-Team",
+        "shipping".parse().expect("Invalid header"),
+    );
+    let options = orders.merged_options(Some(PublishOptions {
+        retries: Some(1),
+        delay: Some(10),
+        headers: Some(call_headers),
+        ..Default::default()
+    }));
+
+    assert_eq!(options.retries, Some(1));
+    assert_eq!(options.delay, Some(10));
+    assert_eq!(
+        options.callback.as_deref(),
+        Some("https://example.com/callback")
+    );
+    let headers = options.headers.expect("Missing headers");
+    assert_eq!(headers["Upstash-Forward-Team"], "shipping");
+    assert_eq!(headers["Upstash-Forward-Region"], "eu");
+
+    let options = orders.merged_options(None);
+    assert_eq!(options.retries, Some(3));
+    assert_eq!(options.delay, None);
+}
+
+#[tokio::test]
+async fn queue_handle_should_enqueue_with_its_defaults() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/enqueue/orders/https://example.com/job"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_1" })),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+    let qstash_client = client(&server);
+    let orders = qstash_client.queue("orders").retries(3).delay(5);
+
+    let mut request = job("report".to_string());
+    request.retries = Some(1);
+    orders
+        .enqueue(request)
+        .await
+        .expect("Could not enqueue message");
+    orders
+        .enqueue_json(
+            PublishRequestUrl::Url(
+                "https://example.com/job"
+                    .parse()
+                    .expect("Could not parse URL"),
+            ),
+            serde_json::json!({ "order": 42 }),
+            None,
+        )
+        .await
+        .expect("Could not enqueue message");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    assert_eq!(
+        header(&requests[0], "Upstash-Retries").as_deref(),
+        Some("1")
+    );
+    assert_eq!(header(&requests[0], "Upstash-Delay").as_deref(), Some("5s"));
+    assert_eq!(
+        header(&requests[1], "Upstash-Retries").as_deref(),
+        Some("3")
+    );
+    assert_eq!(header(&requests[1], "Upstash-Delay").as_deref(), Some("5s"));
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "reqwest")]
+
+use std::time::Duration;
+
+use qstash_rs::client::{Client, PublishRequestUrl, QStashError, RateLimit, ReqwestBackend};
+use tokio::time::Instant;
+use wiremock::{
+    matchers::{method, path, path_regex},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/publish/"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_123" })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "events": [] })))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// client creates a rate limited client for the mock server.
+/// The connection pool has no idle timeout, its timer would otherwise make the paused
+/// clock jump ahead while a request waits on the mock server.
+fn client(server: &MockServer, config: RateLimit) -> Client {
+    Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .rate_limit(config)
+        .expect("Invalid rate limit")
+        .http_backend(ReqwestBackend::new(
+            reqwest::Client::builder()
+                .pool_idle_timeout(None)
+                .build()
+                .expect("Could not initialize http client"),
+        ))
+}
+
+async fn publish(client: &Client) {
+    client
+        .publish_json(
+            PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL")),
+            "hello",
+            None,
+        )
+        .await
+        .expect("Could not publish");
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limit_should_delay_requests_over_the_burst() {
+    let server = mock_server().await;
+    let qstash_client = client(&server, RateLimit::new(2.0, 3).expect("Invalid rate limit"));
+
+    let start = Instant::now();
+    for _ in 0..3 {
+        publish(&qstash_client).await;
+    }
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // the bucket is empty, every request waits for the next token
+    for _ in 0..4 {
+        publish(&qstash_client).await;
+    }
+    assert!(start.elapsed() >= Duration::from_secs(2));
+    assert!(start.elapsed() < Duration::from_millis(2500));
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limit_should_be_shared_across_clones() {
+    let server = mock_server().await;
+    let qstash_client = client(&server, RateLimit::new(1.0, 1).expect("Invalid rate limit"));
+    let clone = qstash_client.clone();
+
+    let start = Instant::now();
+    tokio::join!(
+        publish(&qstash_client),
+        publish(&clone),
+        publish(&qstash_client),
+        publish(&clone),
+    );
+    assert!(start.elapsed() >= Duration::from_secs(3));
+    assert_eq!(server.received_requests().await.map(|r| r.len()), Some(4));
+}
+
+#[tokio::test(start_paused = true)]
+async fn rate_limit_should_exempt_reads() {
+    let server = mock_server().await;
+    let qstash_client = client(
+        &server,
+        RateLimit {
+            exempt_reads: true,
+            ..RateLimit::new(1.0, 1).expect("Invalid rate limit")
+        },
+    );
+
+    let start = Instant::now();
+    publish(&qstash_client).await;
+    for _ in 0..5 {
+        qstash_client
+            .get_events(None)
+            .await
+            .expect("Could not get events");
+    }
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    publish(&qstash_client).await;
+    assert!(start.elapsed() >= Duration::from_secs(1));
+}
+
+#[test]
+fn rate_limit_should_reject_rates_that_are_not_positive() {
+    for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        assert!(
+            matches!(
+                RateLimit::new(rate, 1),
+                Err(QStashError::ConfigError { ref field, .. }) if field == "max_requests_per_second"
+            ),
+            "{} should be rejected",
+            rate
+        );
+
+        let config = RateLimit {
+            max_requests_per_second: rate,
+            burst: 1,
+            exempt_reads: false,
+        };
+        let result = Client::new("token", None, None)
+            .expect("Could not initialize client")
+            .rate_limit(config);
+        assert!(matches!(result, Err(QStashError::ConfigError { .. })));
+    }
+}
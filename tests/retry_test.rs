@@ -0,0 +1,327 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use common::mock_qstash::{Failure, MockQStash};
+use qstash_rs::client::{
+    Client, PublishOptions, PublishRequest, PublishRequestUrl, QStashError, ReqwestBackend,
+    RetryPolicy,
+};
+
+fn client(server: &MockQStash) -> Client {
+    Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        })
+        .http_backend(ReqwestBackend::new(
+            reqwest::Client::builder()
+                .timeout(Duration::from_millis(200))
+                .build()
+                .expect("Could not initialize http client"),
+        ))
+}
+
+fn url() -> PublishRequestUrl {
+    PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL"))
+}
+
+fn sent_ids(server: &MockQStash) -> Vec<Option<String>> {
+    server
+        .received()
+        .iter()
+        .map(|r| r.header("Upstash-Deduplication-Id"))
+        .collect()
+}
+
+#[tokio::test]
+async fn publish_should_reuse_the_generated_deduplication_id() {
+    let server = MockQStash::start().await;
+    server.fail_next(1, Failure::ServerError);
+
+    let mut request = PublishRequest::<String>::new(url());
+    request.body = Some("hello".to_string());
+    let responses = client(&server)
+        .publish(request)
+        .await
+        .expect("Could not publish");
+
+    server.assert_attempts(2);
+    let ids = sent_ids(&server);
+    assert!(ids[0].is_some());
+    assert_eq!(ids[0], ids[1]);
+    assert_eq!(responses[0].deduplication_id, ids[0]);
+    assert_eq!(responses[0].message_id.as_deref(), Some("msg_1"));
+}
+
+#[tokio::test]
+async fn publish_json_should_generate_an_id_per_message() {
+    let server = MockQStash::start().await;
+    let qstash_client = client(&server);
+
+    server.fail_next(1, Failure::ServerError);
+    let first = qstash_client
+        .publish_json(url(), "hello", None)
+        .await
+        .expect("Could not publish");
+    server.fail_next(1, Failure::ServerError);
+    let second = qstash_client
+        .publish_json(url(), "hello", None)
+        .await
+        .expect("Could not publish");
+
+    server.assert_attempts(4);
+    let ids = sent_ids(&server);
+    assert_eq!(ids[0], ids[1]);
+    assert_eq!(ids[2], ids[3]);
+    assert_eq!(first[0].deduplication_id, ids[0]);
+    assert_eq!(second[0].deduplication_id, ids[2]);
+    assert_ne!(ids[0], ids[2]);
+}
+
+#[tokio::test]
+async fn publish_should_keep_the_deduplication_of_the_caller() {
+    let server = MockQStash::start().await;
+    let qstash_client = client(&server);
+
+    server.fail_next(1, Failure::ServerError);
+    let explicit = qstash_client
+        .publish_json(
+            url(),
+            "hello",
+            Some(PublishOptions {
+                deduplication_id: Some("order_1".to_string()),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("Could not publish");
+    let content_based = qstash_client
+        .publish_json(
+            url(),
+            "hello",
+            Some(PublishOptions {
+                content_based_deduplication: Some(true),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(
+        sent_ids(&server),
+        vec![
+            Some("order_1".to_string()),
+            Some("order_1".to_string()),
+            None
+        ]
+    );
+    assert_eq!(explicit[0].deduplication_id, None);
+    assert_eq!(content_based[0].deduplication_id, None);
+}
+
+#[tokio::test]
+async fn publish_should_not_retry_without_a_retry_policy() {
+    let server = MockQStash::start().await;
+    server.fail_next(1, Failure::ServerError);
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let _ = qstash_client.publish_json(url(), "hello", None).await;
+
+    assert_eq!(sent_ids(&server), vec![None]);
+}
+
+#[tokio::test]
+async fn publish_should_retry_while_rate_limited() {
+    let server = MockQStash::start().await;
+    server.fail_next(
+        2,
+        Failure::RateLimited {
+            reset: Duration::from_secs(1),
+        },
+    );
+
+    let responses = client(&server)
+        .publish_json(url(), "hello", None)
+        .await
+        .expect("Could not publish");
+
+    server.assert_attempts(3);
+    assert_eq!(responses[0].message_id.as_deref(), Some("msg_1"));
+}
+
+#[tokio::test]
+async fn publish_should_stop_after_the_max_attempts() {
+    let server = MockQStash::start().await;
+    server.fail_next(5, Failure::ServerError);
+
+    let responses = client(&server)
+        .publish_json(url(), "hello", None)
+        .await
+        .expect("Could not publish");
+
+    server.assert_attempts(3);
+    assert_eq!(responses[0].message_id, None);
+    assert_eq!(responses[0].error.as_deref(), Some("internal server error"));
+}
+
+#[tokio::test]
+async fn publish_should_retry_after_a_timeout() {
+    let server = MockQStash::start().await;
+    server.fail_next(1, Failure::Delayed(Duration::from_secs(1)));
+
+    let responses = client(&server)
+        .publish_json(url(), "hello", None)
+        .await
+        .expect("Could not publish");
+
+    server.assert_attempts(2);
+    let ids = sent_ids(&server);
+    assert_eq!(ids[0], ids[1]);
+    assert_eq!(responses[0].message_id.as_deref(), Some("msg_2"));
+}
+
+#[tokio::test]
+async fn publish_should_fail_when_every_attempt_times_out() {
+    let server = MockQStash::start().await;
+    server.latency(Duration::from_secs(1));
+
+    let result = client(&server).publish_json(url(), "hello", None).await;
+
+    assert!(matches!(result, Err(QStashError::PublishError)));
+    server.assert_attempts(3);
+}
+
+#[tokio::test]
+async fn publish_should_not_retry_a_dropped_response() {
+    let server = MockQStash::start().await;
+    server.fail_next(1, Failure::DropConnection);
+
+    let result = client(&server).publish_json(url(), "hello", None).await;
+
+    // the message may have been published, sending it again is left to the caller
+    assert!(matches!(result, Err(QStashError::PublishError)));
+    server.assert_attempts(1);
+}
+
+#[tokio::test]
+async fn get_events_should_not_retry_malformed_json() {
+    let server = MockQStash::start().await;
+    server.fail_next(1, Failure::MalformedJson);
+
+    let result = client(&server).get_events(None).await;
+
+    assert!(matches!(result, Err(QStashError::EventError { .. })));
+    server.assert_attempts(1);
+}
+
+/// gaps returns the time between the requests received by the server.
+fn gaps(server: &MockQStash) -> Vec<Duration> {
+    server
+        .received()
+        .windows(2)
+        .map(|w| w[1].at.duration_since(w[0].at))
+        .collect()
+}
+
+fn backoff_client(server: &MockQStash) -> Client {
+    Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .retry_policy(RetryPolicy {
+            max_attempts: 3,
+            base_backoff: Duration::from_millis(40),
+            max_backoff: Duration::from_secs(1),
+        })
+}
+
+#[tokio::test]
+async fn get_events_should_retry_while_unavailable() {
+    let server = MockQStash::start().await;
+    server.fail_next(2, Failure::Unavailable);
+
+    let result = backoff_client(&server).get_events(None).await;
+
+    assert!(result.expect("Could not get events").events.is_empty());
+    server.assert_attempts(3);
+    // the backoffs are between half and all of 40ms then 80ms
+    let gaps = gaps(&server);
+    assert!(gaps[0] >= Duration::from_millis(20), "{:?}", gaps);
+    assert!(gaps[1] >= Duration::from_millis(40), "{:?}", gaps);
+}
+
+#[tokio::test]
+async fn get_events_should_retry_while_rate_limited() {
+    let server = MockQStash::start().await;
+    server.fail_next(
+        2,
+        Failure::RateLimited {
+            reset: Duration::from_secs(1),
+        },
+    );
+
+    let result = client(&server).get_events(None).await;
+
+    assert!(result.is_ok(), "{:?}", result);
+    server.assert_attempts(3);
+}
+
+#[tokio::test]
+async fn get_events_should_return_the_last_unavailable_response() {
+    let server = MockQStash::start().await;
+    server.fail_next(3, Failure::Unavailable);
+
+    let result = client(&server).get_events(None).await;
+
+    assert!(
+        matches!(
+            result,
+            Err(QStashError::EventError {
+                status: Some(503),
+                ..
+            })
+        ),
+        "{:?}",
+        result
+    );
+    server.assert_attempts(3);
+}
+
+#[tokio::test]
+async fn get_events_should_not_retry_without_a_retry_policy() {
+    let server = MockQStash::start().await;
+    server.fail_next(1, Failure::Unavailable);
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client.get_events(None).await;
+
+    assert!(matches!(
+        result,
+        Err(QStashError::EventError {
+            status: Some(503),
+            ..
+        })
+    ));
+    server.assert_attempts(1);
+}
+
+#[tokio::test]
+async fn events_stream_should_retry_while_unavailable() {
+    let server = MockQStash::start().await;
+    server.fail_next(2, Failure::Unavailable);
+
+    let events: Vec<_> = backoff_client(&server).events_stream(None).collect().await;
+
+    assert!(events.is_empty(), "{:?}", events);
+    server.assert_attempts(3);
+    let gaps = gaps(&server);
+    assert!(gaps[0] >= Duration::from_millis(20), "{:?}", gaps);
+    assert!(gaps[1] >= Duration::from_millis(40), "{:?}", gaps);
+}
@@ -0,0 +1,715 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use http::{
+    header::{HeaderMap, HeaderValue},
+    Method,
+};
+use qstash_rs::client::{
+    schedules::{plan_schedules, CreateScheduleRequest, ListSchedulesRequest, Schedule},
+    Client, PublishOptions, PublishRequest, PublishRequestUrl, QStashError, QstashResponse,
+};
+use serde_json::{json, Value};
+use wiremock::{matchers::any, Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use common::header;
+
+/// FakeSchedules stores the schedules created through it, the way QStash does.
+#[derive(Default)]
+struct FakeSchedules {
+    schedules: Arc<Mutex<BTreeMap<String, Value>>>,
+}
+
+impl Respond for FakeSchedules {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let mut schedules = self.schedules.lock().expect("Could not lock schedules");
+        let path = request.url.path().trim_start_matches("/v2/schedules");
+
+        match (request.method.to_string().as_str(), path) {
+            ("GET", "") => {
+                let schedules: Vec<&Value> = schedules.values().collect();
+                ResponseTemplate::new(200).set_body_json(schedules)
+            }
+            ("GET", id) => match schedules.get(id.trim_start_matches('/')) {
+                Some(schedule) => ResponseTemplate::new(200).set_body_json(schedule),
+                None => ResponseTemplate::new(404),
+            },
+            ("POST", destination) => {
+                let id = header(request, "Upstash-Schedule-Id")
+                    .unwrap_or_else(|| format!("scd_{}", schedules.len() + 1));
+                let schedule = json!({
+                    "scheduleId": id,
+                    "cron": header(request, "Upstash-Cron"),
+                    "destination": destination.trim_start_matches('/'),
+                    "method": header(request, "Upstash-Method"),
+                    "body": String::from_utf8_lossy(&request.body),
+                    "retries": header(request, "Upstash-Retries")
+                        .map(|r| r.parse::<u32>().expect("Invalid retries")),
+                    "delay": header(request, "Upstash-Delay")
+                        .map(|d| d.trim_end_matches('s').parse::<u32>().expect("Invalid delay")),
+                    "callback": header(request, "Upstash-Callback"),
+                    "failureCallback": header(request, "Upstash-Failure-Callback"),
+                    "header": forwarded_headers(request),
+                });
+                schedules.insert(id.clone(), schedule);
+                ResponseTemplate::new(201).set_body_json(json!({ "scheduleId": id }))
+            }
+            ("DELETE", id) => match schedules.remove(id.trim_start_matches('/')) {
+                Some(_) => ResponseTemplate::new(200),
+                None => ResponseTemplate::new(404),
+            },
+            _ => ResponseTemplate::new(404),
+        }
+    }
+}
+
+/// forwarded_headers returns the headers QStash forwards to the destination, without their prefix.
+fn forwarded_headers(request: &Request) -> BTreeMap<String, Vec<String>> {
+    request
+        .headers
+        .iter()
+        .filter_map(|(name, values)| {
+            let name = name.as_str().to_lowercase();
+            let name = name.strip_prefix("upstash-forward-")?;
+            Some((
+                name.to_string(),
+                values.iter().map(|v| v.as_str().to_string()).collect(),
+            ))
+        })
+        .collect()
+}
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(FakeSchedules::default())
+        .mount(&server)
+        .await;
+    server
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::new("token", Some(&server.uri()), None).expect("Could not initialize client")
+}
+
+fn request() -> CreateScheduleRequest {
+    CreateScheduleRequest::new(
+        PublishRequestUrl::Url(
+            "https://example.com/job"
+                .parse()
+                .expect("Could not parse URL"),
+        ),
+        "0 * * * *",
+    )
+}
+
+#[tokio::test]
+async fn create_schedule_should_send_the_delay() {
+    let server = mock_server().await;
+    let mut request = request();
+    request.delay = Some(30);
+
+    let response = client(&server)
+        .create_schedule(request)
+        .await
+        .expect("Could not create schedule");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    assert_eq!(
+        requests[0].url.path(),
+        "/v2/schedules/https://example.com/job"
+    );
+    assert_eq!(
+        header(&requests[0], "Upstash-Cron").as_deref(),
+        Some("0 * * * *")
+    );
+    assert_eq!(
+        header(&requests[0], "Upstash-Delay").as_deref(),
+        Some("30s")
+    );
+    assert_eq!(response.schedule_id, "scd_1");
+}
+
+#[tokio::test]
+async fn get_schedule_should_return_the_delay() {
+    let server = mock_server().await;
+    let qstash_client = client(&server);
+
+    let mut delayed = request();
+    delayed.delay = Some(30);
+    let delayed = qstash_client
+        .create_schedule(delayed)
+        .await
+        .expect("Could not create schedule");
+    let immediate = qstash_client
+        .create_schedule(request())
+        .await
+        .expect("Could not create schedule");
+
+    let schedule = qstash_client
+        .get_schedule(&delayed.schedule_id)
+        .await
+        .expect("Could not get schedule");
+    assert_eq!(schedule.delay, Some(30));
+    assert_eq!(schedule.cron, "0 * * * *");
+
+    let schedule = qstash_client
+        .get_schedule(&immediate.schedule_id)
+        .await
+        .expect("Could not get schedule");
+    assert_eq!(schedule.delay, None);
+}
+
+#[tokio::test]
+async fn create_schedule_should_apply_publish_options() {
+    let server = mock_server().await;
+    let qstash_client = client(&server);
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Upstash-Forward-Team", HeaderValue::from_static("billing"));
+    let request = CreateScheduleRequest::with_options(
+        PublishRequestUrl::Url(
+            "https://example.com/job"
+                .parse()
+                .expect("Could not parse URL"),
+        ),
+        "*/5 * * * *",
+        PublishOptions {
+            headers: Some(headers),
+            retries: Some(2),
+            callback: Some("https://example.com/callback".to_string()),
+            failure_callback: Some("https://example.com/failure".to_string()),
+            method: Some(Method::PUT),
+            ..Default::default()
+        },
+    );
+
+    let response = qstash_client
+        .create_schedule(request)
+        .await
+        .expect("Could not create schedule");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    let sent = |name| header(&requests[0], name);
+    assert_eq!(sent("Upstash-Forward-Team").as_deref(), Some("billing"));
+    assert_eq!(sent("Upstash-Retries").as_deref(), Some("2"));
+    assert_eq!(
+        sent("Upstash-Callback").as_deref(),
+        Some("https://example.com/callback")
+    );
+    assert_eq!(
+        sent("Upstash-Failure-Callback").as_deref(),
+        Some("https://example.com/failure")
+    );
+    assert_eq!(sent("Upstash-Method").as_deref(), Some("PUT"));
+
+    let schedule = qstash_client
+        .get_schedule(&response.schedule_id)
+        .await
+        .expect("Could not get schedule");
+    assert_eq!(schedule.retries, Some(2));
+    assert_eq!(
+        schedule.callback.as_deref(),
+        Some("https://example.com/callback")
+    );
+    assert_eq!(
+        schedule.failure_callback.as_deref(),
+        Some("https://example.com/failure")
+    );
+    assert_eq!(schedule.method.as_deref(), Some("PUT"));
+    assert_eq!(schedule.header["team"], vec!["billing".to_string()]);
+}
+
+#[tokio::test]
+async fn create_schedule_should_send_the_cron_and_options_as_headers() {
+    let server = mock_server().await;
+    let mut request = request();
+    request.body = Some(r#"{"job":"report"}"#.to_string());
+    request.method = Some(Method::PUT);
+    request.retries = Some(2);
+    request.callback = Some("https://example.com/callback".to_string());
+    request.headers = Some(HeaderMap::from_iter([(
+        http::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    )]));
+
+    client(&server)
+        .create_schedule(request)
+        .await
+        .expect("Could not create schedule");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    let sent = &requests[0];
+    assert_eq!(header(sent, "Upstash-Cron").as_deref(), Some("0 * * * *"));
+    assert_eq!(header(sent, "Upstash-Method").as_deref(), Some("PUT"));
+    assert_eq!(header(sent, "Upstash-Retries").as_deref(), Some("2"));
+    assert_eq!(
+        header(sent, "Upstash-Callback").as_deref(),
+        Some("https://example.com/callback")
+    );
+    assert_eq!(
+        header(sent, "Content-Type").as_deref(),
+        Some("application/json")
+    );
+    assert_eq!(sent.body, br#"{"job":"report"}"#);
+}
+
+#[tokio::test]
+async fn create_schedule_should_send_the_failure_callback() {
+    let server = mock_server().await;
+    let mut request = request();
+    request.failure_callback = Some("https://example.com/failure".to_string());
+
+    client(&server)
+        .create_schedule(request)
+        .await
+        .expect("Could not create schedule");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    assert_eq!(
+        header(&requests[0], "Upstash-Failure-Callback").as_deref(),
+        Some("https://example.com/failure")
+    );
+    assert_eq!(header(&requests[0], "Upstash-Callback"), None);
+}
+
+#[test]
+fn schedule_should_deserialize_the_failure_callback() {
+    let schedule: Schedule = serde_json::from_value(json!({
+        "scheduleId": "scd_1",
+        "cron": "0 * * * *",
+        "destination": "https://example.com/job",
+        "failureCallback": "https://example.com/failure",
+    }))
+    .expect("Could not parse schedule");
+    assert_eq!(
+        schedule.failure_callback.as_deref(),
+        Some("https://example.com/failure")
+    );
+
+    let schedule: Schedule = serde_json::from_value(json!({
+        "scheduleId": "scd_2",
+        "cron": "0 * * * *",
+        "destination": "https://example.com/job",
+    }))
+    .expect("Could not parse schedule");
+    assert_eq!(schedule.failure_callback, None);
+}
+
+#[tokio::test]
+async fn get_schedule_should_return_not_found_for_missing_schedules() {
+    let server = mock_server().await;
+
+    let result = client(&server).get_schedule("scd_missing").await;
+
+    assert!(matches!(result, Err(QStashError::ScheduleNotFound(id)) if id == "scd_missing"));
+}
+
+#[tokio::test]
+async fn get_schedule_should_encode_the_id_once() {
+    let server = MockServer::start().await;
+    Mock::given(wiremock::matchers::path("/v2/schedules/scd%201"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "scheduleId": "scd 1",
+            "cron": "0 * * * *",
+            "destination": "https://example.com/job",
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let schedule = client(&server)
+        .get_schedule("scd 1")
+        .await
+        .expect("Could not get schedule");
+
+    assert_eq!(schedule.schedule_id, "scd 1");
+}
+
+#[tokio::test]
+async fn delete_schedule_should_return_the_status_and_body_of_failures() {
+    for (status, body) in [
+        (200, ""),
+        (404, "schedule not found"),
+        (401, "unauthorized"),
+    ] {
+        let server = MockServer::start().await;
+        Mock::given(wiremock::matchers::method("DELETE"))
+            .and(wiremock::matchers::path("/v2/schedules/scd_1"))
+            .respond_with(ResponseTemplate::new(status).set_body_string(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = client(&server).delete_schedule("scd_1").await;
+
+        match status {
+            200 => assert!(result.is_ok()),
+            _ => assert!(matches!(
+                result,
+                Err(QStashError::ScheduleError { status: Some(s), message }) if s == status && message == body
+            )),
+        }
+    }
+}
+
+#[tokio::test]
+async fn pause_and_resume_schedule_should_post_the_action() {
+    let server = MockServer::start().await;
+    for action in ["pause", "resume"] {
+        Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path(format!(
+                "/v2/schedules/scd_1/{}",
+                action
+            )))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+    Mock::given(wiremock::matchers::method("GET"))
+        .and(wiremock::matchers::path("/v2/schedules/scd_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "scheduleId": "scd_1",
+            "cron": "0 * * * *",
+            "destination": "https://example.com/job",
+            "isPaused": true,
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client = client(&server);
+
+    qstash_client
+        .pause_schedule("scd_1")
+        .await
+        .expect("Could not pause schedule");
+    let schedule = qstash_client
+        .get_schedule("scd_1")
+        .await
+        .expect("Could not get schedule");
+    assert!(schedule.is_paused);
+    qstash_client
+        .resume_schedule("scd_1")
+        .await
+        .expect("Could not resume schedule");
+}
+
+#[tokio::test]
+async fn schedules_should_not_be_paused_by_default() {
+    let server = mock_server().await;
+    let qstash_client = client(&server);
+
+    let response = qstash_client
+        .create_schedule(request())
+        .await
+        .expect("Could not create schedule");
+    let schedule = qstash_client
+        .get_schedule(&response.schedule_id)
+        .await
+        .expect("Could not get schedule");
+
+    assert!(!schedule.is_paused);
+}
+
+#[tokio::test]
+async fn create_schedule_should_enqueue_to_the_queue() {
+    let server = mock_server().await;
+    let mut request = request();
+    request.queue = Some("reports".to_string());
+
+    client(&server)
+        .create_schedule(request)
+        .await
+        .expect("Could not create schedule");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    assert_eq!(
+        requests[0].url.path(),
+        "/v2/schedules/https://example.com/job"
+    );
+    assert_eq!(
+        header(&requests[0], "Upstash-Queue-Name").as_deref(),
+        Some("reports")
+    );
+}
+
+fn destination() -> PublishRequestUrl {
+    PublishRequestUrl::Url(
+        "https://example.com/job"
+            .parse()
+            .expect("Could not parse URL"),
+    )
+}
+
+#[tokio::test]
+async fn builder_should_set_the_headers_sent_by_create_schedule() {
+    let server = mock_server().await;
+    let request = CreateScheduleRequest::builder(destination(), "0 * * * *")
+        .schedule_id("scd_report")
+        .retries(3)
+        .delay(10)
+        .callback("https://example.com/callback")
+        .failure_callback("https://example.com/failure")
+        .method(Method::PUT)
+        .header("Content-Type", "application/json")
+        .header("X-Tenant", "acme")
+        .body(r#"{"job":"report"}"#)
+        .queue("reports")
+        .build()
+        .expect("Invalid schedule");
+    assert_eq!(request.retries, Some(3));
+    assert_eq!(request.method, Some(Method::PUT));
+
+    client(&server)
+        .create_schedule(request)
+        .await
+        .expect("Could not create schedule");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    let sent = &requests[0];
+    for (name, value) in [
+        ("Upstash-Cron", "0 * * * *"),
+        ("Upstash-Schedule-Id", "scd_report"),
+        ("Upstash-Retries", "3"),
+        ("Upstash-Delay", "10s"),
+        ("Upstash-Callback", "https://example.com/callback"),
+        ("Upstash-Failure-Callback", "https://example.com/failure"),
+        ("Upstash-Method", "PUT"),
+        ("Upstash-Queue-Name", "reports"),
+        ("Content-Type", "application/json"),
+        ("X-Tenant", "acme"),
+    ] {
+        assert_eq!(header(sent, name).as_deref(), Some(value), "{}", name);
+    }
+    assert_eq!(sent.body, br#"{"job":"report"}"#);
+}
+
+#[test]
+fn builder_should_reject_empty_cron_and_invalid_headers() {
+    assert!(matches!(
+        CreateScheduleRequest::builder(destination(), " ").build(),
+        Err(QStashError::InvalidCron { .. })
+    ));
+    assert!(matches!(
+        CreateScheduleRequest::builder(destination(), "0 * * * *")
+            .header("X Tenant", "acme")
+            .build(),
+        Err(QStashError::ScheduleError { status: None, .. })
+    ));
+}
+
+fn schedule_to(destination: &str) -> Schedule {
+    serde_json::from_value(json!({
+        "scheduleId": format!("scd_{}", destination),
+        "cron": "0 * * * *",
+        "destination": destination,
+    }))
+    .expect("Could not parse schedule")
+}
+
+#[test]
+fn list_schedules_request_should_normalize_urls() {
+    let request = ListSchedulesRequest {
+        destination: Some("https://api.example.com/".to_string()),
+    };
+
+    assert!(request.matches(&schedule_to("https://api.example.com")));
+    assert!(request.matches(&schedule_to("HTTPS://API.example.com/")));
+    assert!(!request.matches(&schedule_to("https://api.example.com/jobs")));
+    assert!(!request.matches(&schedule_to("http://api.example.com")));
+    assert!(!request.matches(&schedule_to("orders")));
+
+    let topic = ListSchedulesRequest {
+        destination: Some("orders".to_string()),
+    };
+    assert!(topic.matches(&schedule_to("orders")));
+    assert!(!topic.matches(&schedule_to("Orders")));
+    assert!(ListSchedulesRequest::default().matches(&schedule_to("orders")));
+}
+
+#[tokio::test]
+async fn find_schedules_for_should_filter_the_listed_schedules() {
+    let server = mock_server().await;
+    let qstash_client = client(&server);
+    for destination in ["https://api.example.com", "https://other.example.com/"] {
+        let request = CreateScheduleRequest::new(
+            PublishRequestUrl::Url(destination.parse().expect("Could not parse URL")),
+            "0 * * * *",
+        );
+        qstash_client
+            .create_schedule(request)
+            .await
+            .expect("Could not create schedule");
+    }
+
+    let schedules = qstash_client
+        .find_schedules_for("https://API.example.com/")
+        .await
+        .expect("Could not list schedules");
+
+    assert_eq!(schedules.len(), 1);
+    assert_eq!(schedules[0].destination, "https://api.example.com/");
+}
+
+#[test]
+fn schedule_should_deserialize_the_run_times() {
+    let schedule: Schedule = serde_json::from_value(json!({
+        "scheduleId": "scd_1",
+        "cron": "0 * * * *",
+        "destination": "https://example.com/job",
+        "lastScheduleTime": 1_700_000_000_000u64,
+        "nextScheduleTime": 32_503_680_000_000u64,
+    }))
+    .expect("Could not parse schedule");
+    assert_eq!(schedule.last_schedule_time, Some(1_700_000_000_000));
+    assert_eq!(schedule.next_schedule_time, Some(32_503_680_000_000));
+    assert!(schedule.next_run_in().expect("Missing next run") > Duration::from_secs(86_400));
+
+    let mut created = schedule_to("https://example.com/job");
+    assert_eq!(created.last_schedule_time, None);
+    assert_eq!(created.next_schedule_time, None);
+    assert_eq!(created.next_run_in(), None);
+
+    created.next_schedule_time = Some(1_700_000_000_000);
+    assert_eq!(created.next_run_in(), Some(Duration::ZERO));
+}
+
+fn scheduled(id: &str, destination: &str, cron: &str) -> Schedule {
+    serde_json::from_value(json!({
+        "scheduleId": id,
+        "cron": cron,
+        "destination": destination,
+    }))
+    .expect("Could not parse schedule")
+}
+
+fn desired(destination: &str, cron: &str) -> CreateScheduleRequest {
+    CreateScheduleRequest::new(
+        PublishRequestUrl::Url(destination.parse().expect("Could not parse URL")),
+        cron,
+    )
+}
+
+#[test]
+fn plan_schedules_should_match_on_destination_and_cron() {
+    let existing = vec![
+        scheduled("scd_hourly", "https://example.com/job", "0 * * * *"),
+        scheduled("scd_daily", "https://example.com/job", "0 3 * * *"),
+        scheduled("scd_report", "https://example.com/report/", "0 * * * *"),
+    ];
+
+    let mut daily = desired("https://example.com/job", "0 3 * * *");
+    daily.retries = Some(5);
+    let plan = plan_schedules(
+        vec![
+            desired("https://example.com/report", "0 * * * *"),
+            daily,
+            desired("https://example.com/job", "*/5 * * * *"),
+            desired("https://example.com/job", "0 3 * * *"),
+        ],
+        &existing,
+    );
+
+    assert_eq!(plan.unchanged, vec!["scd_report", "scd_daily"]);
+    assert_eq!(plan.delete, vec!["scd_hourly"]);
+    let created: Vec<&str> = plan.create.iter().map(|r| r.cron.as_str()).collect();
+    assert_eq!(created, vec!["*/5 * * * *", "0 3 * * *"]);
+}
+
+#[tokio::test]
+async fn reconcile_schedules_should_create_and_delete_the_differences() {
+    let server = mock_server().await;
+    let qstash_client = client(&server);
+    let mut obsolete = desired("https://example.com/obsolete", "0 * * * *");
+    obsolete.schedule_id = Some("scd_obsolete".to_string());
+    let mut kept = desired("https://example.com/job", "0 * * * *");
+    kept.schedule_id = Some("scd_kept".to_string());
+    for request in [obsolete, kept] {
+        qstash_client
+            .create_schedule(request)
+            .await
+            .expect("Could not create schedule");
+    }
+
+    let report = qstash_client
+        .reconcile_schedules(vec![
+            desired("https://example.com/job", "0 * * * *"),
+            desired("https://example.com/new", "0 * * * *"),
+        ])
+        .await
+        .expect("Could not reconcile schedules");
+
+    assert_eq!(report.unchanged, vec!["scd_kept"]);
+    assert_eq!(report.deleted, vec!["scd_obsolete"]);
+    assert_eq!(report.created.len(), 1);
+
+    let mut destinations: Vec<String> = qstash_client
+        .list_schedules()
+        .await
+        .expect("Could not list schedules")
+        .into_iter()
+        .map(|s| s.destination)
+        .collect();
+    destinations.sort();
+    assert_eq!(
+        destinations,
+        vec!["https://example.com/job", "https://example.com/new"]
+    );
+}
+
+#[tokio::test]
+async fn publish_with_a_cron_header_should_return_the_schedule_id() {
+    let server = MockServer::start().await;
+    Mock::given(wiremock::matchers::header("Upstash-Cron", "0 * * * *"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "scheduleId": "scd_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Upstash-Cron", HeaderValue::from_static("0 * * * *"));
+    let mut request = PublishRequest::<String>::new(destination());
+    request.headers = Some(headers);
+    let response = client(&server)
+        .publish(request)
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(response[0].schedule_id.as_deref(), Some("scd_1"));
+    assert_eq!(response[0].message_id, None);
+
+    let response: QstashResponse =
+        serde_json::from_str(r#"{"scheduleId":"scd_2"}"#).expect("Could not parse response");
+    assert_eq!(response.schedule_id.as_deref(), Some("scd_2"));
+    assert!(!serde_json::to_string(&QstashResponse {
+        schedule_id: None,
+        ..response
+    })
+    .expect("Could not serialize response")
+    .contains("scheduleId"));
+}
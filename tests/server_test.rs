@@ -0,0 +1,414 @@
+mod common;
+
+use common::{sign, CURRENT_SIGNING_KEY, NEXT_SIGNING_KEY};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use http::header::{HeaderMap, HeaderValue};
+use qstash_rs::{
+    client::QStashError,
+    server::{DeliveryMetadata, DispatchResult, JobDispatcher, JobHandler, Receiver},
+};
+use serde::Deserialize;
+
+#[test]
+fn receiver_verify_should_work() {
+    let receiver = Receiver::new(CURRENT_SIGNING_KEY, NEXT_SIGNING_KEY);
+    let body = br#"{"hello":"world"}"#;
+
+    let signature = sign(CURRENT_SIGNING_KEY, "https://example.com/jobs", body);
+    receiver
+        .verify(&signature, body, Some("https://example.com/jobs"))
+        .expect("Current key should be accepted");
+
+    let signature = sign(NEXT_SIGNING_KEY, "https://example.com/jobs", body);
+    receiver
+        .verify(&signature, body, None)
+        .expect("Next key should be accepted");
+}
+
+#[test]
+fn receiver_verify_should_fail() {
+    let receiver = Receiver::new(CURRENT_SIGNING_KEY, NEXT_SIGNING_KEY);
+    let body = br#"{"hello":"world"}"#;
+
+    let signature = sign("sig_unknown", "https://example.com/jobs", body);
+    assert!(matches!(
+        receiver.verify(&signature, body, None),
+        Err(QStashError::SignatureError)
+    ));
+
+    let signature = sign(CURRENT_SIGNING_KEY, "https://example.com/jobs", body);
+    assert!(matches!(
+        receiver.verify(&signature, br#"{"hello":"tampered"}"#, None),
+        Err(QStashError::SignatureError)
+    ));
+    assert!(matches!(
+        receiver.verify(&signature, body, Some("https://example.com/other")),
+        Err(QStashError::SignatureError)
+    ));
+    assert!(matches!(
+        receiver.verify("not-a-jwt", body, None),
+        Err(QStashError::SignatureError)
+    ));
+}
+
+#[test]
+fn delivery_metadata_should_work() {
+    let mut headers = HeaderMap::new();
+    headers.insert("Upstash-Message-Id", HeaderValue::from_static("msg_123"));
+    headers.insert("Upstash-Retried", HeaderValue::from_static("2"));
+    headers.insert("Upstash-Schedule-Id", HeaderValue::from_static("scd_123"));
+
+    let metadata = DeliveryMetadata::from_headers(&headers).expect("Should parse metadata");
+    assert_eq!(metadata.message_id, "msg_123");
+    assert_eq!(metadata.retried, 2);
+    assert_eq!(metadata.schedule_id.as_deref(), Some("scd_123"));
+    assert_eq!(metadata.caller_ip, None);
+
+    assert!(matches!(
+        DeliveryMetadata::from_headers(&HeaderMap::new()),
+        Err(QStashError::MetadataError)
+    ));
+}
+
+#[derive(Deserialize)]
+struct Resize {
+    image: String,
+}
+
+struct ResizeHandler {
+    resized: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl JobHandler<Resize> for ResizeHandler {
+    async fn handle(&self, job: Resize, _: DeliveryMetadata) -> Result<(), String> {
+        if job.image.is_empty() {
+            return Err(String::from("Missing image"));
+        }
+        self.resized.lock().expect("Lock poisoned").push(job.image);
+        Ok(())
+    }
+}
+
+/// Build the headers QStash would send along with the given body.
+fn delivery_headers(url: &str, body: &[u8]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("Upstash-Message-Id", HeaderValue::from_static("msg_123"));
+    headers.insert(
+        "Upstash-Signature",
+        HeaderValue::from_str(&sign(CURRENT_SIGNING_KEY, url, body)).expect("Invalid header"),
+    );
+    headers
+}
+
+#[tokio::test]
+async fn dispatcher_should_work() {
+    let resized = Arc::new(Mutex::new(Vec::new()));
+    let dispatcher = JobDispatcher::new(Receiver::new(CURRENT_SIGNING_KEY, NEXT_SIGNING_KEY))
+        .base_url("https://example.com/")
+        .register(
+            "/jobs/resize",
+            ResizeHandler {
+                resized: resized.clone(),
+            },
+        )
+        .register("/jobs/noop", |_: Resize, _: DeliveryMetadata| async {
+            Ok::<(), String>(())
+        });
+
+    let body = br#"{"image":"cat.png"}"#;
+    let headers = delivery_headers("https://example.com/jobs/resize?size=small", body);
+    let result = dispatcher
+        .dispatch("/jobs/resize?size=small", &headers, body)
+        .await;
+    assert_eq!(result, DispatchResult::Ok);
+    assert_eq!(result.status(), 200);
+    assert_eq!(*resized.lock().expect("Lock poisoned"), vec!["cat.png"]);
+
+    let headers = delivery_headers("https://example.com/jobs/noop", body);
+    let result = dispatcher.dispatch("/jobs/noop", &headers, body).await;
+    assert_eq!(result, DispatchResult::Ok);
+}
+
+#[tokio::test]
+async fn dispatcher_should_map_failures() {
+    let dispatcher = JobDispatcher::new(Receiver::new(CURRENT_SIGNING_KEY, NEXT_SIGNING_KEY))
+        .catch_panics(true)
+        .register(
+            "/jobs/resize",
+            ResizeHandler {
+                resized: Arc::new(Mutex::new(Vec::new())),
+            },
+        )
+        .register("/jobs/panic", |_: Resize, _: DeliveryMetadata| async {
+            panic!("Job panicked");
+            #[allow(unreachable_code)]
+            Ok::<(), String>(())
+        });
+
+    let body = br#"{"image":""}"#;
+    let headers = delivery_headers("https://example.com/jobs/resize", body);
+
+    let result = dispatcher.dispatch("/jobs/unknown", &headers, body).await;
+    assert_eq!(result.status(), 404);
+
+    let result = dispatcher
+        .dispatch("/jobs/resize", &HeaderMap::new(), body)
+        .await;
+    assert_eq!(result, DispatchResult::Unauthorized);
+    assert_eq!(result.status(), 401);
+
+    let result = dispatcher
+        .dispatch("/jobs/resize", &headers, br#"{"image":"dog.png"}"#)
+        .await;
+    assert_eq!(result, DispatchResult::Unauthorized);
+
+    let result = dispatcher.dispatch("/jobs/resize", &headers, body).await;
+    assert_eq!(
+        result,
+        DispatchResult::HandlerError(String::from("Missing image"))
+    );
+    assert_eq!(result.status(), 500);
+
+    let result = dispatcher.dispatch("/jobs/panic", &headers, body).await;
+    assert_eq!(result, DispatchResult::Panicked);
+
+    let body = br#"{"file":"cat.png"}"#;
+    let headers = delivery_headers("https://example.com/jobs/resize", body);
+    let result = dispatcher.dispatch("/jobs/resize", &headers, body).await;
+    assert!(matches!(result, DispatchResult::BadRequest(_)));
+    assert_eq!(result.status(), 400);
+}
+
+#[cfg(feature = "axum")]
+mod router {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+        Router,
+    };
+    use qstash_rs::server::{DeliveryMetadata, QstashRouter, Receiver};
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    use super::common::{sign, CURRENT_SIGNING_KEY, NEXT_SIGNING_KEY};
+
+    #[derive(Deserialize)]
+    struct Resize {
+        image: String,
+    }
+
+    /// Simulate a QStash delivery to the given path of the router.
+    async fn deliver(
+        router: Router,
+        path: &str,
+        body: &str,
+        signature: Option<String>,
+    ) -> StatusCode {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri(path)
+            .header("Upstash-Message-Id", "msg_123")
+            .header("Upstash-Retried", "0");
+        if let Some(signature) = signature {
+            request = request.header("Upstash-Signature", signature);
+        }
+
+        let request = request
+            .body(Body::from(body.to_string()))
+            .expect("Could not build request");
+
+        router
+            .oneshot(request)
+            .await
+            .expect("Router should respond")
+            .status()
+    }
+
+    fn router(resized: Arc<Mutex<Vec<(String, String)>>>) -> Router {
+        QstashRouter::new(Receiver::new(CURRENT_SIGNING_KEY, NEXT_SIGNING_KEY))
+            .base_url("https://example.com")
+            .catch_panics(true)
+            .route(
+                "/jobs/resize",
+                move |job: Resize, metadata: DeliveryMetadata| {
+                    let resized = resized.clone();
+                    async move {
+                        resized
+                            .lock()
+                            .expect("Lock poisoned")
+                            .push((job.image, metadata.message_id));
+                        Ok::<(), String>(())
+                    }
+                },
+            )
+            .route("/jobs/fail", |_: Resize, _: DeliveryMetadata| async {
+                Err::<(), String>(String::from("Job failed"))
+            })
+            .route("/jobs/panic", |_: Resize, _: DeliveryMetadata| async {
+                panic!("Job panicked");
+                #[allow(unreachable_code)]
+                Ok::<(), String>(())
+            })
+            .into_router()
+    }
+
+    #[tokio::test]
+    async fn router_should_dispatch_verified_requests() {
+        let resized = Arc::new(Mutex::new(Vec::new()));
+        let body = r#"{"image":"cat.png"}"#;
+        let signature = sign(
+            CURRENT_SIGNING_KEY,
+            "https://example.com/jobs/resize",
+            body.as_bytes(),
+        );
+
+        let status = deliver(
+            router(resized.clone()),
+            "/jobs/resize",
+            body,
+            Some(signature),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            *resized.lock().expect("Lock poisoned"),
+            vec![(String::from("cat.png"), String::from("msg_123"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn router_should_dispatch_when_nested() {
+        let resized = Arc::new(Mutex::new(Vec::new()));
+        let body = r#"{"image":"cat.png"}"#;
+        let signature = sign(
+            CURRENT_SIGNING_KEY,
+            "https://example.com/api/jobs/resize?size=small",
+            body.as_bytes(),
+        );
+
+        let status = deliver(
+            Router::new().nest("/api", router(resized.clone())),
+            "/api/jobs/resize?size=small",
+            body,
+            Some(signature),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            *resized.lock().expect("Lock poisoned"),
+            vec![(String::from("cat.png"), String::from("msg_123"))]
+        );
+
+        // the url QStash signed includes the prefix
+        let signature = sign(
+            CURRENT_SIGNING_KEY,
+            "https://example.com/jobs/resize",
+            body.as_bytes(),
+        );
+        let status = deliver(
+            Router::new().nest("/api", router(resized.clone())),
+            "/api/jobs/resize",
+            body,
+            Some(signature),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn router_should_reject_invalid_requests() {
+        let resized = Arc::new(Mutex::new(Vec::new()));
+        let body = r#"{"image":"cat.png"}"#;
+
+        let status = deliver(router(resized.clone()), "/jobs/resize", body, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let signature = sign(
+            "sig_unknown",
+            "https://example.com/jobs/resize",
+            body.as_bytes(),
+        );
+        let status = deliver(
+            router(resized.clone()),
+            "/jobs/resize",
+            body,
+            Some(signature),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let signature = sign(
+            CURRENT_SIGNING_KEY,
+            "https://example.com/jobs/other",
+            body.as_bytes(),
+        );
+        let status = deliver(
+            router(resized.clone()),
+            "/jobs/resize",
+            body,
+            Some(signature),
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let body = r#"{"file":"cat.png"}"#;
+        let signature = sign(
+            CURRENT_SIGNING_KEY,
+            "https://example.com/jobs/resize",
+            body.as_bytes(),
+        );
+        let status = deliver(
+            router(resized.clone()),
+            "/jobs/resize",
+            body,
+            Some(signature),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        assert!(resized.lock().expect("Lock poisoned").is_empty());
+    }
+
+    #[tokio::test]
+    async fn router_should_map_handler_failures() {
+        let resized = Arc::new(Mutex::new(Vec::new()));
+        let body = r#"{"image":"cat.png"}"#;
+
+        let signature = sign(
+            CURRENT_SIGNING_KEY,
+            "https://example.com/jobs/fail",
+            body.as_bytes(),
+        );
+        let status = deliver(router(resized.clone()), "/jobs/fail", body, Some(signature)).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        let signature = sign(
+            CURRENT_SIGNING_KEY,
+            "https://example.com/jobs/panic",
+            body.as_bytes(),
+        );
+        let status = deliver(
+            router(resized.clone()),
+            "/jobs/panic",
+            body,
+            Some(signature),
+        )
+        .await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        let signature = sign(
+            CURRENT_SIGNING_KEY,
+            "https://example.com/jobs/unknown",
+            body.as_bytes(),
+        );
+        let status = deliver(router(resized), "/jobs/unknown", body, Some(signature)).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,119 @@
+#![cfg(all(feature = "tower", feature = "reqwest"))]
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use qstash_rs::client::{
+    Client, PublishRequest, PublishRequestUrl, QstashPublishService, RateLimit, ReqwestBackend,
+};
+use tokio::time::Instant;
+use tower::{Service, ServiceBuilder, ServiceExt};
+use wiremock::{
+    matchers::{method, path_regex},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn mock_publish(delay: Duration) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/publish/"))
+        .respond_with(
+            ResponseTemplate::new(201)
+                .set_body_json(serde_json::json!({ "messageId": "msg_123" }))
+                .set_delay(delay),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+fn publish_request() -> PublishRequest<Bytes> {
+    let mut request = PublishRequest::new(PublishRequestUrl::Url(
+        "https://example.com".parse().expect("Could not parse URL"),
+    ));
+    request.body = Some(Bytes::from_static(b"hello"));
+    request
+}
+
+#[tokio::test]
+async fn publish_service_should_work() {
+    let server = mock_publish(Duration::ZERO).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let mut service = ServiceBuilder::new()
+        .concurrency_limit(2)
+        .timeout(Duration::from_secs(5))
+        .service(QstashPublishService::new(qstash_client));
+
+    for _ in 0..3 {
+        let response = service
+            .ready()
+            .await
+            .expect("Service should be ready")
+            .call(publish_request())
+            .await
+            .expect("Could not publish");
+        assert_eq!(response.len(), 1);
+        assert_eq!(response[0].message_id.as_deref(), Some("msg_123"));
+    }
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded");
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].body, b"hello");
+}
+
+#[tokio::test]
+async fn publish_service_should_time_out() {
+    let server = mock_publish(Duration::from_secs(2)).await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let service = ServiceBuilder::new()
+        .concurrency_limit(1)
+        .timeout(Duration::from_millis(100))
+        .service(QstashPublishService::new(qstash_client));
+
+    let error = service
+        .oneshot(publish_request())
+        .await
+        .expect_err("Request should time out");
+    assert!(error.is::<tower::timeout::error::Elapsed>());
+}
+
+#[tokio::test(start_paused = true)]
+async fn publish_service_should_wait_for_the_rate_limiter() {
+    let server = mock_publish(Duration::ZERO).await;
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .rate_limit(RateLimit::new(1.0, 1).expect("Invalid rate limit"))
+        .expect("Invalid rate limit")
+        .http_backend(ReqwestBackend::new(
+            reqwest::Client::builder()
+                .pool_idle_timeout(None)
+                .build()
+                .expect("Could not initialize http client"),
+        ));
+    let mut service = QstashPublishService::new(qstash_client);
+
+    let start = Instant::now();
+    service
+        .ready()
+        .await
+        .expect("Service should be ready")
+        .call(publish_request())
+        .await
+        .expect("Could not publish");
+    assert!(start.elapsed() < Duration::from_millis(100));
+
+    let pending = tokio::time::timeout(Duration::from_millis(500), service.ready()).await;
+    assert!(pending.is_err(), "Service should wait for a token");
+
+    service.ready().await.expect("Service should be ready");
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_secs(1), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_millis(1100), "{:?}", elapsed);
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::echo_batch;
+use futures::{stream, SinkExt, StreamExt};
+use qstash_rs::client::{
+    batch::BatchEntry,
+    batching::{BatchingConfig, BatchingPublisher},
+    Client, PublishRequestUrl, QStashError,
+};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn destination() -> PublishRequestUrl {
+    PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL"))
+}
+
+async fn batch_bodies(server: &MockServer) -> Vec<Vec<serde_json::Value>> {
+    server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded")
+        .iter()
+        .map(|r| serde_json::from_slice(&r.body).expect("Batch body should be a JSON array"))
+        .collect()
+}
+
+#[tokio::test]
+async fn publish_sink_should_forward_a_stream() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let publisher = BatchingPublisher::spawn(
+        qstash_client,
+        BatchingConfig {
+            max_entries: 100,
+            max_delay: Duration::from_secs(60),
+            ..BatchingConfig::default()
+        },
+    );
+
+    stream::iter(0..1000)
+        .map(|i| Ok((destination(), i)))
+        .forward(publisher.sink(2))
+        .await
+        .expect("Could not publish the stream");
+
+    let batches = batch_bodies(&server).await;
+    assert_eq!(batches.len(), 10);
+
+    let bodies: Vec<String> = batches
+        .iter()
+        .flatten()
+        .map(|m| {
+            m["body"]
+                .as_str()
+                .expect("Should contain a body")
+                .to_string()
+        })
+        .collect();
+    assert_eq!(bodies, (0..1000).map(|i| i.to_string()).collect::<Vec<_>>());
+    assert_eq!(batches[0][0]["headers"]["content-type"], "application/json");
+}
+
+#[tokio::test]
+async fn publish_sink_should_flush_pending_entries() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let publisher = BatchingPublisher::spawn(
+        qstash_client,
+        BatchingConfig {
+            max_entries: 100,
+            max_delay: Duration::from_secs(3600),
+            ..BatchingConfig::default()
+        },
+    );
+
+    let mut sink = publisher.sink(1);
+    for body in ["a", "b", "c"] {
+        let mut entry = BatchEntry::new(destination());
+        entry.body = Some(body.to_string());
+        sink.feed(entry).await.expect("Could not send the entry");
+    }
+    assert!(batch_bodies(&server).await.is_empty());
+
+    SinkExt::<BatchEntry>::flush(&mut sink)
+        .await
+        .expect("Could not flush the sink");
+    assert_eq!(batch_bodies(&server).await[0].len(), 3);
+}
+
+#[tokio::test]
+async fn publish_sink_should_report_the_failed_item() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .respond_with(
+            ResponseTemplate::new(500).set_body_json(serde_json::json!({ "error": "boom" })),
+        )
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let publisher = BatchingPublisher::spawn(
+        qstash_client,
+        BatchingConfig {
+            max_entries: 1,
+            ..BatchingConfig::default()
+        },
+    );
+
+    let error = stream::iter(0..10)
+        .map(|i| Ok((destination(), i)))
+        .forward(publisher.sink(1))
+        .await
+        .expect_err("Publishing should fail");
+
+    assert_eq!(error.index, 0);
+    assert!(matches!(error.error, QStashError::BatchError));
+}
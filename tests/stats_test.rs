@@ -0,0 +1,328 @@
+#![cfg(feature = "reqwest")]
+
+use std::time::Duration;
+
+use qstash_rs::client::{
+    events::{Event, State},
+    stats::{state_counts, DeliveryFilter, DeliveryStats, TimeWindow},
+    Client, QStashError,
+};
+use wiremock::{
+    matchers::{method, path, query_param, query_param_is_missing},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const URL: &str = "https://example.com/a";
+
+fn event(message_id: &str, state: State, time: u64) -> Event {
+    Event {
+        time,
+        state,
+        message_id: message_id.to_string(),
+        next_delivery_time: None,
+        error: None,
+        url: Some(URL.to_string()),
+        topic_name: None,
+        endpoint_name: None,
+        extra: serde_json::Map::new(),
+    }
+}
+
+fn window() -> TimeWindow {
+    TimeWindow::new(1_000, 10_000)
+}
+
+#[test]
+fn delivery_stats_should_count_outcomes() {
+    let events = vec![
+        event("delivered", State::CREATED, 1_000),
+        event("delivered", State::ACTIVE, 1_010),
+        event("delivered", State::DELIVERED, 1_100),
+        event("failed", State::CREATED, 2_000),
+        event("failed", State::FAILED, 2_500),
+        event("canceled", State::CREATED, 3_000),
+        event("canceled", State::CANCELED, 3_001),
+        event("pending", State::CREATED, 4_000),
+        event("pending", State::ACTIVE, 4_001),
+    ];
+
+    let stats = DeliveryStats::from_events(&events, window());
+    assert_eq!(stats.messages, 4);
+    assert_eq!(stats.delivered, 1);
+    assert_eq!(stats.failed, 1);
+    assert_eq!(stats.canceled, 1);
+    assert_eq!(stats.in_flight, 1);
+    assert_eq!(stats.retries, 0);
+    assert_eq!(stats.p50, Some(Duration::from_millis(100)));
+    assert_eq!(stats.success_rate(), Some(1.0 / 3.0));
+    assert_eq!(stats.retry_rate(), Some(0.0));
+}
+
+#[test]
+fn delivery_stats_should_count_retries() {
+    let events = vec![
+        event("a", State::CREATED, 1_000),
+        event("a", State::RETRY, 1_100),
+        event("a", State::RETRY, 1_300),
+        event("a", State::DELIVERED, 1_700),
+        event("b", State::CREATED, 2_000),
+        event("b", State::DELIVERED, 2_050),
+        event("c", State::CREATED, 3_000),
+        event("c", State::RETRY, 3_100),
+        event("c", State::FAILED, 3_500),
+    ];
+
+    let stats = DeliveryStats::from_events(&events, window());
+    assert_eq!(stats.messages, 3);
+    assert_eq!(stats.delivered, 2);
+    assert_eq!(stats.failed, 1);
+    assert_eq!(stats.retried_messages, 2);
+    assert_eq!(stats.retries, 3);
+    assert_eq!(stats.retry_rate(), Some(2.0 / 3.0));
+    assert_eq!(stats.p50, Some(Duration::from_millis(50)));
+    assert_eq!(stats.p99, Some(Duration::from_millis(700)));
+}
+
+#[test]
+fn delivery_stats_should_handle_out_of_order_events() {
+    // events are returned newest first, with equal timestamps in any order
+    let events = vec![
+        event("a", State::DELIVERED, 1_500),
+        event("a", State::RETRY, 1_200),
+        event("a", State::ACTIVE, 1_000),
+        event("a", State::CREATED, 1_000),
+    ];
+
+    let stats = DeliveryStats::from_events(events.iter().rev(), window());
+    let reversed = DeliveryStats::from_events(&events, window());
+    assert_eq!(stats, reversed);
+    assert_eq!(stats.delivered, 1);
+    assert_eq!(stats.retries, 1);
+    assert_eq!(stats.p50, Some(Duration::from_millis(500)));
+}
+
+#[test]
+fn delivery_stats_should_respect_the_window() {
+    let events = vec![
+        // created before the window
+        event("before", State::CREATED, 500),
+        event("before", State::DELIVERED, 1_500),
+        // delivered after the window, still in flight at its end
+        event("late", State::CREATED, 9_000),
+        event("late", State::RETRY, 9_500),
+        event("late", State::DELIVERED, 12_000),
+        // created after the window
+        event("after", State::CREATED, 10_000),
+        // no CREATED event
+        event("unknown", State::DELIVERED, 2_000),
+    ];
+
+    let stats = DeliveryStats::from_events(&events, window());
+    assert_eq!(stats.messages, 1);
+    assert_eq!(stats.in_flight, 1);
+    assert_eq!(stats.delivered, 0);
+    assert_eq!(stats.retries, 1);
+    assert_eq!(stats.p50, None);
+    assert_eq!(stats.success_rate(), None);
+}
+
+#[test]
+fn delivery_stats_should_compute_percentiles() {
+    let mut events = Vec::new();
+    for i in 0..100u64 {
+        let id = i.to_string();
+        events.push(event(&id, State::CREATED, 1_000 + i));
+        events.push(event(&id, State::DELIVERED, 1_000 + i + (i + 1) * 10));
+    }
+
+    let stats = DeliveryStats::from_events(&events, window());
+    assert_eq!(stats.delivered, 100);
+    assert_eq!(stats.p50, Some(Duration::from_millis(500)));
+    assert_eq!(stats.p95, Some(Duration::from_millis(950)));
+    assert_eq!(stats.p99, Some(Duration::from_millis(990)));
+}
+
+#[test]
+fn delivery_stats_should_be_empty_without_events() {
+    let stats = DeliveryStats::from_events(&[], window());
+    assert_eq!(stats, DeliveryStats::default());
+    assert_eq!(stats.retry_rate(), None);
+}
+
+#[test]
+fn delivery_filter_should_match_destination() {
+    let filter = DeliveryFilter {
+        url: Some(URL.to_string()),
+        ..DeliveryFilter::default()
+    };
+    assert!(filter.matches(&event("a", State::CREATED, 0)));
+
+    let mut other = event("b", State::CREATED, 0);
+    other.url = Some(String::from("https://example.com/b"));
+    assert!(!filter.matches(&other));
+    assert!(DeliveryFilter::default().matches(&other));
+}
+
+#[tokio::test]
+async fn delivery_stats_should_page_events() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "cursor": "1500",
+            "events": [
+                { "time": 2_000, "state": "DELIVERED", "messageId": "a", "url": URL },
+                { "time": 1_900, "state": "DELIVERED", "messageId": "b", "url": "https://example.com/b" },
+                { "time": 1_500, "state": "RETRY", "messageId": "a", "url": URL },
+            ],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("cursor", "1500"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "cursor": "900",
+            "events": [
+                { "time": 1_200, "state": "CREATED", "messageId": "a", "url": URL },
+                { "time": 1_100, "state": "CREATED", "messageId": "b", "url": "https://example.com/b" },
+                { "time": 900, "state": "CREATED", "messageId": "c", "url": URL },
+            ],
+        })))
+        .mount(&server)
+        .await;
+
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let stats = qstash_client
+        .delivery_stats(
+            DeliveryFilter {
+                url: Some(URL.to_string()),
+                ..DeliveryFilter::default()
+            },
+            window(),
+        )
+        .await
+        .expect("Could not compute stats");
+
+    assert_eq!(stats.messages, 1);
+    assert_eq!(stats.delivered, 1);
+    assert_eq!(stats.retries, 1);
+    assert_eq!(stats.p50, Some(Duration::from_millis(800)));
+
+    // the second page reaches the start of the window, the cursor is not followed
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded");
+    assert_eq!(requests.len(), 2);
+}
+
+#[test]
+fn state_counts_should_count_every_state() {
+    let events = vec![
+        event("a", State::CREATED, 1_000),
+        event("b", State::CREATED, 1_001),
+        event("a", State::ACTIVE, 1_010),
+        event("a", State::DELIVERED, 1_100),
+        event("b", State::ERROR, 1_200),
+        event("b", State::RETRY, 1_300),
+        event("b", State::ERROR, 1_400),
+        event("b", State::FAILED, 1_500),
+    ];
+
+    let counts = state_counts(&events);
+
+    assert_eq!(counts.len(), 6);
+    assert_eq!(counts[&State::CREATED], 2);
+    assert_eq!(counts[&State::ACTIVE], 1);
+    assert_eq!(counts[&State::DELIVERED], 1);
+    assert_eq!(counts[&State::ERROR], 2);
+    assert_eq!(counts[&State::RETRY], 1);
+    assert_eq!(counts[&State::FAILED], 1);
+    assert!(!counts.contains_key(&State::CANCELED));
+    assert!(state_counts(&[]).is_empty());
+}
+
+#[tokio::test]
+async fn event_state_counts_should_page_through_the_window() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("fromDate", "1000"))
+        .and(query_param("toDate", "10000"))
+        .and(query_param("cursor", "c2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "events": [
+                event("a", State::ACTIVE, 1_010),
+                event("a", State::CREATED, 1_000),
+            ],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("fromDate", "1000"))
+        .and(query_param("toDate", "10000"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "cursor": "c2",
+            "events": [
+                event("b", State::CREATED, 2_000),
+                event("a", State::DELIVERED, 1_100),
+            ],
+        })))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let counts = qstash_client
+        .event_state_counts(1_000, 10_000)
+        .await
+        .expect("Could not count states");
+
+    assert_eq!(counts.len(), 3);
+    assert_eq!(counts[&State::CREATED], 2);
+    assert_eq!(counts[&State::ACTIVE], 1);
+    assert_eq!(counts[&State::DELIVERED], 1);
+}
+
+#[tokio::test]
+async fn event_state_counts_should_reject_an_inverted_window() {
+    let server = MockServer::start().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client.event_state_counts(10_000, 1_000).await;
+
+    assert!(matches!(result, Err(QStashError::EventError { .. })));
+    assert!(server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded")
+        .is_empty());
+}
+
+#[tokio::test]
+async fn event_state_counts_should_return_the_error_of_a_page() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("not json"))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client.event_state_counts(1_000, 10_000).await;
+
+    assert!(matches!(
+        result,
+        Err(QStashError::EventError {
+            status: Some(200),
+            ..
+        })
+    ));
+}
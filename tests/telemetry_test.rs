@@ -0,0 +1,107 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use common::header;
+use qstash_rs::client::{Client, PublishRequestUrl};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_1" })),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "events": [] })))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn client(server: &MockServer) -> Client {
+    Client::new("token", Some(&server.uri()), None).expect("Could not initialize client")
+}
+
+/// publish_and_read sends a publish and a read only request.
+async fn publish_and_read(qstash_client: &Client) {
+    qstash_client
+        .publish_json(
+            PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL")),
+            "hello",
+            None,
+        )
+        .await
+        .expect("Could not publish");
+    qstash_client
+        .get_events(None)
+        .await
+        .expect("Could not get events");
+}
+
+async fn received(server: &MockServer) -> Vec<wiremock::Request> {
+    server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded")
+}
+
+#[tokio::test]
+async fn requests_should_send_the_telemetry_headers() {
+    let server = mock_server().await;
+
+    publish_and_read(&client(&server)).await;
+
+    let requests = received(&server).await;
+    assert_eq!(requests.len(), 2);
+    for request in &requests {
+        assert_eq!(
+            header(request, "Upstash-Telemetry-Sdk"),
+            Some(format!("qstash-rs/{}", env!("CARGO_PKG_VERSION")))
+        );
+        let runtime = header(request, "Upstash-Telemetry-Runtime").expect("Missing runtime");
+        assert!(runtime.starts_with("rust/1."), "{}", runtime);
+        assert_eq!(header(request, "Upstash-Telemetry-Framework"), None);
+    }
+}
+
+#[tokio::test]
+async fn telemetry_framework_should_send_the_framework() {
+    let server = mock_server().await;
+
+    publish_and_read(&client(&server).telemetry_framework("axum")).await;
+
+    for request in &received(&server).await {
+        assert_eq!(
+            header(request, "Upstash-Telemetry-Framework").as_deref(),
+            Some("axum")
+        );
+    }
+}
+
+#[tokio::test]
+async fn disable_telemetry_should_not_send_the_telemetry_headers() {
+    let server = mock_server().await;
+
+    publish_and_read(
+        &client(&server)
+            .telemetry_framework("axum")
+            .disable_telemetry(),
+    )
+    .await;
+
+    let requests = received(&server).await;
+    assert_eq!(requests.len(), 2);
+    for request in &requests {
+        assert!(request
+            .headers
+            .iter()
+            .all(|(name, _)| !name.as_str().starts_with("upstash-telemetry-")));
+    }
+}
@@ -0,0 +1,243 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use std::time::{Duration, UNIX_EPOCH};
+
+use common::header;
+#[cfg(any(feature = "chrono", feature = "time"))]
+use qstash_rs::client::events::Event;
+use qstash_rs::client::{
+    events::EventRequest, Client, PublishRequest, PublishRequestUrl, QStashError,
+};
+use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_1" })),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+fn url() -> PublishRequestUrl {
+    PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL"))
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn event(time: u64) -> Event {
+    serde_json::from_value(serde_json::json!({
+        "time": time,
+        "state": "DELIVERED",
+        "messageId": "msg_1",
+    }))
+    .expect("Could not parse event")
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn retried_event(next_delivery_time: u64) -> Event {
+    Event {
+        next_delivery_time: Some(next_delivery_time),
+        ..event(1_700_000_000_000)
+    }
+}
+
+async fn sent_not_before(server: &MockServer) -> Option<String> {
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded");
+    header(&requests[0], "Upstash-Not-Before")
+}
+
+#[tokio::test]
+async fn publish_at_should_send_the_not_before_header() {
+    let server = mock_server().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    qstash_client
+        .publish_at(
+            PublishRequest::<String>::new(url()),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        )
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(
+        sent_not_before(&server).await.as_deref(),
+        Some("1700000000")
+    );
+}
+
+#[test]
+fn with_not_before_should_reject_times_before_the_epoch() {
+    let request =
+        PublishRequest::<String>::new(url()).with_not_before(UNIX_EPOCH - Duration::from_secs(1));
+
+    assert!(request.is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn publish_json_at_should_accept_a_chrono_date_time() {
+    use chrono::{FixedOffset, TimeZone};
+
+    let server = mock_server().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let at = FixedOffset::east_opt(2 * 3600)
+        .expect("Invalid offset")
+        .with_ymd_and_hms(2023, 11, 15, 0, 13, 20)
+        .unwrap();
+
+    qstash_client
+        .publish_json_at(url(), "hello", at, None)
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(
+        sent_not_before(&server).await.as_deref(),
+        Some("1700000000")
+    );
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn time_utc_should_convert_the_event_time() {
+    let time = event(1_700_000_000_123).time_utc();
+
+    assert_eq!(time.timestamp_millis(), 1_700_000_000_123);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn time_utc_should_convert_the_edge_values() {
+    use chrono::{DateTime, Utc};
+
+    assert_eq!(event(0).time_utc(), DateTime::<Utc>::UNIX_EPOCH);
+    // beyond the dates chrono can represent, as i64 and as u64
+    assert_eq!(event(i64::MAX as u64).time_utc(), DateTime::<Utc>::MAX_UTC);
+    assert_eq!(event(u64::MAX).time_utc(), DateTime::<Utc>::MAX_UTC);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn next_delivery_utc_should_convert_the_next_delivery_time() {
+    use chrono::{DateTime, Utc};
+
+    assert_eq!(event(0).next_delivery_utc(), None);
+    assert_eq!(
+        retried_event(1_700_000_060_500)
+            .next_delivery_utc()
+            .map(|t| t.timestamp_millis()),
+        Some(1_700_000_060_500)
+    );
+    assert_eq!(
+        retried_event(0).next_delivery_utc(),
+        Some(DateTime::<Utc>::UNIX_EPOCH)
+    );
+    assert_eq!(
+        retried_event(u64::MAX).next_delivery_utc(),
+        Some(DateTime::<Utc>::MAX_UTC)
+    );
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn event_request_should_accept_chrono_date_times() {
+    use chrono::{DateTime, Utc};
+
+    let from = DateTime::<Utc>::from_timestamp_millis(1_699_000_000_123).expect("Invalid date");
+    let request = EventRequest::default()
+        .with_from_date(from)
+        .and_then(|r| r.with_to_date(DateTime::<Utc>::UNIX_EPOCH))
+        .expect("Could not set the dates");
+
+    assert_eq!(request.from_date, Some(1_699_000_000_123));
+    assert_eq!(request.to_date, Some(0));
+}
+
+#[cfg(feature = "time")]
+#[tokio::test]
+async fn publish_json_at_should_accept_an_offset_date_time() {
+    let server = mock_server().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let at = time::OffsetDateTime::from_unix_timestamp(1_700_000_000)
+        .expect("Invalid timestamp")
+        .to_offset(time::UtcOffset::from_hms(-5, 0, 0).expect("Invalid offset"));
+
+    qstash_client
+        .publish_json_at(url(), "hello", at, None)
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(
+        sent_not_before(&server).await.as_deref(),
+        Some("1700000000")
+    );
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn time_odt_should_convert_the_event_time() {
+    let time = event(1_700_000_000_123).time_odt();
+
+    assert_eq!(time.unix_timestamp(), 1_700_000_000);
+    assert_eq!(time.millisecond(), 123);
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn time_odt_should_convert_the_edge_values() {
+    let max = time::PrimitiveDateTime::MAX.assume_utc();
+
+    assert_eq!(event(0).time_odt(), time::OffsetDateTime::UNIX_EPOCH);
+    assert_eq!(event(u64::MAX).time_odt(), max);
+    assert_eq!(event(0).next_delivery_odt(), None);
+    assert_eq!(
+        retried_event(0).next_delivery_odt(),
+        Some(time::OffsetDateTime::UNIX_EPOCH)
+    );
+    assert_eq!(retried_event(u64::MAX).next_delivery_odt(), Some(max));
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn event_request_should_accept_offset_date_times() {
+    let from = time::OffsetDateTime::from_unix_timestamp_nanos(1_699_000_000_123_456_789)
+        .expect("Invalid timestamp");
+
+    let request = EventRequest::default()
+        .with_from_date(from)
+        .expect("Could not set the date");
+
+    assert_eq!(request.from_date, Some(1_699_000_000_123));
+}
+
+#[test]
+fn event_request_should_send_system_times_in_milliseconds() {
+    let request = EventRequest::default()
+        .with_to_date(UNIX_EPOCH + Duration::from_millis(1_699_100_000_250))
+        .expect("Could not set the date");
+
+    assert_eq!(
+        request.query_pairs(),
+        [("toDate", "1699100000250".to_string())]
+    );
+}
+
+#[test]
+fn event_request_should_reject_dates_before_the_epoch() {
+    let result = EventRequest::default().with_from_date(UNIX_EPOCH - Duration::from_millis(1));
+
+    assert!(
+        matches!(result, Err(QStashError::EventError { status: None, .. })),
+        "{:?}",
+        result
+    );
+}
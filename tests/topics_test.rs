@@ -0,0 +1,623 @@
+#![cfg(feature = "reqwest")]
+
+use qstash_rs::client::{
+    events::Event,
+    topics::{
+        validate_topic_name, EndpointHealth, Topic, TopicEndpoint, TopicOverview,
+        MAX_TOPIC_NAME_LENGTH,
+    },
+    Client, PublishRequest, PublishRequestUrl, QStashError,
+};
+use wiremock::{
+    matchers::{body_json, method, path, query_param},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+async fn upsert_topic_endpoints_should_post_the_endpoints() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/topics/orders/endpoints"))
+        .and(body_json(serde_json::json!({
+            "endpoints": [
+                { "name": "billing", "url": "https://billing.example.com" },
+                { "url": "https://shipping.example.com" },
+            ]
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    qstash_client
+        .upsert_topic_endpoints(
+            "orders",
+            vec![
+                TopicEndpoint {
+                    name: Some("billing".to_string()),
+                    url: "https://billing.example.com".to_string(),
+                },
+                TopicEndpoint {
+                    name: None,
+                    url: "https://shipping.example.com".to_string(),
+                },
+            ],
+        )
+        .await
+        .expect("Could not upsert endpoints");
+}
+
+#[tokio::test]
+async fn upsert_topic_endpoints_should_return_the_status_and_message_of_the_api() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(400)
+                .set_body_json(serde_json::json!({ "error": "invalid destination url" })),
+        )
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client
+        .upsert_topic_endpoints(
+            "orders",
+            vec![TopicEndpoint {
+                name: None,
+                url: "not a url".to_string(),
+            }],
+        )
+        .await;
+
+    match result {
+        Err(QStashError::TopicError { status, message }) => {
+            assert_eq!(status, Some(400));
+            assert_eq!(message, "invalid destination url");
+        }
+        other => panic!("Unexpected result {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn list_topics_should_read_topics_without_endpoints() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/topics"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            {
+                "name": "orders",
+                "createdAt": 1_700_000_000_000u64,
+                "updatedAt": 1_700_000_100_000u64,
+                "endpoints": [{ "name": "billing", "url": "https://billing.example.com" }],
+            },
+            {
+                "name": "empty",
+                "createdAt": 1_700_000_000_000u64,
+                "updatedAt": 1_700_000_000_000u64,
+                "endpoints": [],
+            },
+            { "name": "legacy" },
+        ])))
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let topics = qstash_client
+        .list_topics()
+        .await
+        .expect("Could not list topics");
+
+    assert_eq!(topics.len(), 3);
+    assert_eq!(topics[0].name, "orders");
+    assert_eq!(topics[0].updated_at, Some(1_700_000_100_000));
+    assert_eq!(
+        topics[0].endpoints,
+        vec![TopicEndpoint {
+            name: Some("billing".to_string()),
+            url: "https://billing.example.com".to_string(),
+        }]
+    );
+    assert!(topics[1].endpoints.is_empty());
+    assert!(topics[2].endpoints.is_empty());
+    assert_eq!(topics[2].created_at, None);
+}
+
+#[tokio::test]
+async fn get_topic_should_accept_dots_dashes_and_underscores() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/topics/team-a.orders_v2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "name": "team-a.orders_v2",
+            "endpoints": [{ "url": "https://billing.example.com" }],
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let topic = qstash_client
+        .get_topic("team-a.orders_v2")
+        .await
+        .expect("Could not get topic");
+
+    assert_eq!(topic.name, "team-a.orders_v2");
+    assert_eq!(topic.endpoints.len(), 1);
+}
+
+#[tokio::test]
+async fn get_topic_should_return_not_found_for_missing_topics() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(404).set_body_json(serde_json::json!({ "error": "not found" })),
+        )
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let result = qstash_client.get_topic("missing").await;
+
+    assert!(matches!(result, Err(QStashError::TopicNotFound(name)) if name == "missing"));
+}
+
+/// mock_removal answers the removal of endpoints from the orders topic, then the topic
+/// with the remaining endpoints, or a 404 if there are none.
+async fn mock_removal(expected: serde_json::Value, remaining: serde_json::Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/topics/orders/endpoints"))
+        .and(body_json(serde_json::json!({ "endpoints": expected })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let topic = match remaining.as_array().map(|r| r.is_empty()) {
+        Some(false) => ResponseTemplate::new(200)
+            .set_body_json(serde_json::json!({ "name": "orders", "endpoints": remaining })),
+        _ => ResponseTemplate::new(404),
+    };
+    Mock::given(method("GET"))
+        .and(path("/v2/topics/orders"))
+        .respond_with(topic)
+        .mount(&server)
+        .await;
+    server
+}
+
+fn endpoint(name: Option<&str>, url: &str) -> TopicEndpoint {
+    TopicEndpoint {
+        name: name.map(String::from),
+        url: url.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn remove_topic_endpoints_should_remove_by_url() {
+    let server = mock_removal(
+        serde_json::json!([{ "url": "https://billing.example.com" }]),
+        serde_json::json!([{ "url": "https://shipping.example.com" }]),
+    )
+    .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let topic = qstash_client
+        .remove_topic_endpoints(
+            "orders",
+            vec![endpoint(None, "https://billing.example.com")],
+        )
+        .await
+        .expect("Could not remove endpoints")
+        .expect("Topic was deleted");
+
+    assert_eq!(
+        topic.endpoints,
+        vec![endpoint(None, "https://shipping.example.com")]
+    );
+}
+
+#[tokio::test]
+async fn remove_topic_endpoints_should_remove_by_name() {
+    let server = mock_removal(
+        serde_json::json!([{ "name": "billing" }]),
+        serde_json::json!([{ "name": "shipping", "url": "https://shipping.example.com" }]),
+    )
+    .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let topic = qstash_client
+        .remove_topic_endpoints("orders", vec![endpoint(Some("billing"), "")])
+        .await
+        .expect("Could not remove endpoints");
+
+    assert!(topic.is_some());
+}
+
+#[tokio::test]
+async fn remove_topic_endpoints_should_report_the_deleted_topic() {
+    let server = mock_removal(
+        serde_json::json!([
+            { "name": "billing" },
+            { "url": "https://shipping.example.com" },
+        ]),
+        serde_json::json!([]),
+    )
+    .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let topic = qstash_client
+        .remove_topic_endpoints(
+            "orders",
+            vec![
+                endpoint(Some("billing"), ""),
+                endpoint(None, "https://shipping.example.com"),
+            ],
+        )
+        .await
+        .expect("Could not remove endpoints");
+
+    assert!(topic.is_none());
+}
+
+#[tokio::test]
+async fn delete_topic_should_delete_the_topic() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/topics/team-a.orders_v2"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    qstash_client
+        .delete_topic("team-a.orders_v2")
+        .await
+        .expect("Could not delete topic");
+}
+
+#[tokio::test]
+async fn delete_topic_should_return_the_status_and_body_of_failures() {
+    for (status, body) in [(404, "topic not found"), (401, "unauthorized")] {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(status).set_body_string(body))
+            .mount(&server)
+            .await;
+        let qstash_client =
+            Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+        let result = qstash_client.delete_topic("orders").await;
+
+        assert!(matches!(
+            result,
+            Err(QStashError::TopicError { status: Some(s), message }) if s == status && message == body
+        ));
+    }
+}
+
+#[test]
+fn topics_should_round_trip_the_api_json() {
+    let captured: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/topics.json")).expect("Invalid fixture");
+
+    let topics: Vec<Topic> = serde_json::from_value(captured.clone()).expect("Invalid topics");
+
+    assert_eq!(topics[0].name, "billing");
+    assert_eq!(topics[0].created_at, Some(1_699_000_000_000));
+    assert_eq!(topics[0].endpoints[0].name.as_deref(), Some("invoices"));
+    assert_eq!(topics[0].endpoints[1].name, None);
+    assert!(topics[1].endpoints.is_empty());
+    assert_eq!(
+        serde_json::to_value(&topics).expect("Could not serialize"),
+        captured
+    );
+}
+
+#[test]
+fn topic_should_convert_to_a_publish_url() {
+    let topics: Vec<Topic> =
+        serde_json::from_str(include_str!("fixtures/topics.json")).expect("Invalid fixture");
+
+    assert!(matches!(
+        PublishRequestUrl::from(&topics[0]),
+        PublishRequestUrl::Topic(name) if name == "billing"
+    ));
+    assert!(matches!(
+        PublishRequestUrl::from(topics[1].clone()),
+        PublishRequestUrl::Topic(name) if name == "empty"
+    ));
+}
+
+#[tokio::test]
+async fn url_groups_should_select_the_path_of_the_topic_endpoints() {
+    for (url_groups, prefix) in [(false, "/v2/topics"), (true, "/v2/url-groups")] {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(prefix))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("{}/orders", prefix)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "name": "orders" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("{}/orders/endpoints", prefix)))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("{}/orders", prefix)))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let qstash_client = Client::new("token", Some(&server.uri()), None)
+            .expect("Could not initialize client")
+            .url_groups(url_groups);
+
+        qstash_client
+            .list_topics()
+            .await
+            .expect("Could not list topics");
+        qstash_client
+            .get_topic("orders")
+            .await
+            .expect("Could not get topic");
+        qstash_client
+            .upsert_topic_endpoints("orders", vec![endpoint(None, "https://example.com")])
+            .await
+            .expect("Could not upsert endpoints");
+        qstash_client
+            .delete_topic("orders")
+            .await
+            .expect("Could not delete topic");
+    }
+}
+
+#[tokio::test]
+async fn publish_to_a_url_group_should_behave_like_a_topic() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/orders"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!([
+            { "messageId": "msg_1", "url": "https://billing.example.com" },
+        ])))
+        .expect(2)
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    for url in [
+        PublishRequestUrl::Topic("orders".to_string()),
+        PublishRequestUrl::UrlGroup("orders".to_string()),
+    ] {
+        let responses = qstash_client
+            .publish_json(url, serde_json::json!({ "order": 1 }), None)
+            .await
+            .expect("Could not publish");
+        assert_eq!(responses[0].message_id.as_deref(), Some("msg_1"));
+    }
+}
+
+#[tokio::test]
+async fn publish_to_topic_should_return_a_response_per_endpoint() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/topics/orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "name": "orders",
+            "endpoints": [
+                { "name": "billing", "url": "https://billing.example.com" },
+                { "name": "shipping", "url": "https://shipping.example.com" },
+            ],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/orders"))
+        .and(body_json(serde_json::json!({ "order": 1 })))
+        .respond_with(ResponseTemplate::new(201).set_body_json(serde_json::json!([
+            { "messageId": "msg_1", "url": "https://billing.example.com" },
+            { "messageId": "msg_2", "url": "https://shipping.example.com" },
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let topic = qstash_client
+        .get_topic("orders")
+        .await
+        .expect("Could not get topic");
+    let responses = qstash_client
+        .publish_to_topic(&topic, serde_json::json!({ "order": 1 }), None)
+        .await
+        .expect("Could not publish");
+
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].message_id.as_deref(), Some("msg_1"));
+    assert_eq!(
+        responses[1].url.as_deref(),
+        Some("https://shipping.example.com")
+    );
+}
+
+#[tokio::test]
+async fn topic_requests_should_reject_invalid_names_without_sending_them() {
+    let server = MockServer::start().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+    let long = "a".repeat(MAX_TOPIC_NAME_LENGTH + 1);
+
+    for name in ["", "   ", "team a/orders", long.as_str()] {
+        let results = [
+            qstash_client.get_topic(name).await.map(|_| ()),
+            qstash_client.delete_topic(name).await,
+            qstash_client
+                .upsert_topic_endpoints(name, vec![endpoint(None, "https://example.com")])
+                .await,
+            qstash_client
+                .publish_json(PublishRequestUrl::Topic(name.to_string()), "{}", None)
+                .await
+                .map(|_| ()),
+            qstash_client
+                .publish(PublishRequest::<Vec<u8>>::new(PublishRequestUrl::UrlGroup(
+                    name.to_string(),
+                )))
+                .await
+                .map(|_| ()),
+        ];
+        for result in results {
+            assert!(
+                matches!(&result, Err(QStashError::InvalidTopicName(n)) if n == name),
+                "{:?} for {:?}",
+                result,
+                name
+            );
+        }
+    }
+    assert!(server
+        .received_requests()
+        .await
+        .expect("Requests are not recorded")
+        .is_empty());
+}
+
+#[test]
+fn validate_topic_name_should_accept_the_longest_valid_name() {
+    assert!(validate_topic_name(&"a".repeat(MAX_TOPIC_NAME_LENGTH)).is_ok());
+    assert!(validate_topic_name("orders.v2_eu-1").is_ok());
+}
+
+fn canned_events() -> Vec<Event> {
+    let event = |state: &str, topic: &str, endpoint: Option<&str>, url: &str| {
+        serde_json::json!({
+            "time": 1_700_000_000_000u64,
+            "state": state,
+            "messageId": "msg_1",
+            "topicName": topic,
+            "endpointName": endpoint,
+            "url": url,
+        })
+    };
+    serde_json::from_value(serde_json::json!([
+        event(
+            "DELIVERED",
+            "orders",
+            Some("billing"),
+            "https://billing.example.com"
+        ),
+        event(
+            "DELIVERED",
+            "orders",
+            Some("billing"),
+            "https://billing.example.com"
+        ),
+        event(
+            "FAILED",
+            "orders",
+            Some("billing"),
+            "https://billing.example.com"
+        ),
+        event("FAILED", "orders", None, "https://shipping.example.com"),
+        event("RETRY", "orders", None, "https://shipping.example.com"),
+        event(
+            "DELIVERED",
+            "other",
+            Some("billing"),
+            "https://billing.example.com"
+        ),
+        event(
+            "DELIVERED",
+            "orders",
+            Some("removed"),
+            "https://removed.example.com"
+        ),
+    ]))
+    .expect("Invalid events")
+}
+
+fn orders_topic() -> Topic {
+    Topic {
+        name: "orders".to_string(),
+        created_at: None,
+        updated_at: None,
+        endpoints: vec![
+            endpoint(Some("billing"), "https://billing.example.com"),
+            endpoint(None, "https://shipping.example.com"),
+        ],
+    }
+}
+
+#[test]
+fn topic_overview_should_count_the_events_of_every_endpoint() {
+    let overview = TopicOverview::from_events(&orders_topic(), &canned_events());
+
+    assert_eq!(overview.name, "orders");
+    assert_eq!(
+        overview.endpoints,
+        vec![
+            EndpointHealth {
+                endpoint: endpoint(Some("billing"), "https://billing.example.com"),
+                delivered: 2,
+                failed: 1,
+            },
+            EndpointHealth {
+                endpoint: endpoint(None, "https://shipping.example.com"),
+                delivered: 0,
+                failed: 1,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn get_topic_with_stats_should_filter_the_events_by_topic() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/topics/orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(orders_topic()))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("topicName", "orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "events": canned_events(),
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let overview = qstash_client
+        .get_topic_with_stats("orders")
+        .await
+        .expect("Could not get topic overview");
+
+    assert_eq!(
+        overview,
+        TopicOverview::from_events(&orders_topic(), &canned_events())
+    );
+}
@@ -0,0 +1,221 @@
+#![cfg(feature = "opentelemetry")]
+
+mod common;
+
+use std::collections::HashMap;
+
+use common::{echo_batch, header};
+use http::header::{HeaderMap, HeaderValue};
+use opentelemetry::{
+    trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+    Context,
+};
+use qstash_rs::{
+    client::{batch::BatchEntry, Client, PublishRequestUrl},
+    server::DeliveryMetadata,
+};
+use wiremock::{
+    matchers::{method, path_regex},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const SPAN_ID: &str = "00f067aa0ba902b7";
+
+async fn mock_publish() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/publish/"))
+        .respond_with(
+            ResponseTemplate::new(201).set_body_json(serde_json::json!({ "messageId": "msg_123" })),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+fn parent_context() -> Context {
+    Context::new().with_remote_span_context(SpanContext::new(
+        TraceId::from_hex(TRACE_ID).expect("Invalid trace id"),
+        SpanId::from_hex(SPAN_ID).expect("Invalid span id"),
+        TraceFlags::SAMPLED,
+        true,
+        "vendor=value"
+            .parse::<TraceState>()
+            .expect("Invalid trace state"),
+    ))
+}
+
+#[tokio::test]
+async fn publish_should_propagate_trace_context() {
+    let server = mock_publish().await;
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .propagate_trace_context(true);
+
+    let _guard = parent_context().attach();
+    qstash_client
+        .publish_json(
+            PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL")),
+            HashMap::from([("test", "test")]),
+            None,
+        )
+        .await
+        .expect("Could not publish");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded");
+    let request = requests.first().expect("Should have sent a request");
+    assert_eq!(
+        header(request, "Upstash-Forward-traceparent"),
+        Some(format!("00-{}-{}-01", TRACE_ID, SPAN_ID))
+    );
+    assert_eq!(
+        header(request, "Upstash-Forward-tracestate"),
+        Some(String::from("vendor=value"))
+    );
+
+    // QStash strips the forward prefix before delivering the message.
+    let mut headers = HeaderMap::new();
+    headers.insert("Upstash-Message-Id", HeaderValue::from_static("msg_123"));
+    for (name, value) in [
+        ("traceparent", "Upstash-Forward-traceparent"),
+        ("tracestate", "Upstash-Forward-tracestate"),
+    ] {
+        let value = header(request, value).expect("Header should be forwarded");
+        headers.insert(name, HeaderValue::from_str(&value).expect("Invalid header"));
+    }
+
+    let metadata = DeliveryMetadata::from_headers(&headers).expect("Should parse metadata");
+    let context = metadata
+        .trace_context()
+        .expect("Should restore the context");
+    let span = context.span();
+    let span_context = span.span_context();
+    assert_eq!(span_context.trace_id().to_string(), TRACE_ID);
+    assert_eq!(span_context.span_id().to_string(), SPAN_ID);
+    assert!(span_context.is_sampled());
+    assert!(span_context.is_remote());
+    assert_eq!(span_context.trace_state().get("vendor"), Some("value"));
+}
+
+#[tokio::test]
+async fn publish_should_not_propagate_trace_context_by_default() {
+    let server = mock_publish().await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let _guard = parent_context().attach();
+    qstash_client
+        .publish_json(
+            PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL")),
+            HashMap::from([("test", "test")]),
+            None,
+        )
+        .await
+        .expect("Could not publish");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded");
+    let request = requests.first().expect("Should have sent a request");
+    assert!(header(request, "Upstash-Forward-traceparent").is_none());
+}
+
+/// batch_headers returns the headers of every message of the recorded batch
+/// requests, with lowercased names.
+async fn batch_headers(server: &MockServer) -> Vec<HashMap<String, String>> {
+    let requests = server
+        .received_requests()
+        .await
+        .expect("Requests should be recorded");
+    let mut headers = Vec::new();
+    for request in requests {
+        let messages: Vec<serde_json::Value> =
+            serde_json::from_slice(&request.body).expect("Batch body should be a JSON array");
+        for message in messages {
+            let message_headers: HashMap<String, String> =
+                serde_json::from_value(message["headers"].clone()).unwrap_or_default();
+            headers.push(
+                message_headers
+                    .into_iter()
+                    .map(|(name, value)| (name.to_lowercase(), value))
+                    .collect(),
+            );
+        }
+    }
+    headers
+}
+
+#[tokio::test]
+async fn batch_should_propagate_trace_context() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/batch$"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+    let qstash_client = Client::new("token", Some(&server.uri()), None)
+        .expect("Could not initialize client")
+        .propagate_trace_context(true);
+    let url =
+        || PublishRequestUrl::Url("https://example.com".parse().expect("Could not parse URL"));
+
+    let _guard = parent_context().attach();
+    qstash_client
+        .batch(vec![BatchEntry::new(url()), BatchEntry::new(url())])
+        .await
+        .expect("Could not send batch");
+
+    let headers = batch_headers(&server).await;
+    assert_eq!(headers.len(), 2);
+    for message_headers in headers {
+        assert_eq!(
+            message_headers.get("upstash-forward-traceparent"),
+            Some(&format!("00-{}-{}-01", TRACE_ID, SPAN_ID))
+        );
+        assert_eq!(
+            message_headers.get("upstash-forward-tracestate"),
+            Some(&String::from("vendor=value"))
+        );
+    }
+}
+
+#[tokio::test]
+async fn batch_should_not_propagate_trace_context_by_default() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path_regex("^/v2/batch$"))
+        .respond_with(echo_batch)
+        .mount(&server)
+        .await;
+    let qstash_client =
+        Client::new("token", Some(&server.uri()), None).expect("Could not initialize client");
+
+    let _guard = parent_context().attach();
+    qstash_client
+        .batch(vec![BatchEntry::new(PublishRequestUrl::Url(
+            "https://example.com".parse().expect("Could not parse URL"),
+        ))])
+        .await
+        .expect("Could not send batch");
+
+    let headers = batch_headers(&server).await;
+    assert_eq!(headers.len(), 1);
+    assert!(!headers[0].contains_key("upstash-forward-traceparent"));
+}
+
+#[test]
+fn trace_context_should_ignore_invalid_headers() {
+    let mut headers = HeaderMap::new();
+    headers.insert("Upstash-Message-Id", HeaderValue::from_static("msg_123"));
+    let metadata = DeliveryMetadata::from_headers(&headers).expect("Should parse metadata");
+    assert!(metadata.trace_context().is_none());
+
+    headers.insert("traceparent", HeaderValue::from_static("00-invalid-01"));
+    let metadata = DeliveryMetadata::from_headers(&headers).expect("Should parse metadata");
+    assert!(metadata.trace_context().is_none());
+}
@@ -0,0 +1,65 @@
+use qstash_rs::client::{
+    dead_letter_queue::DlqMessage, events::Event, messages::Message, schedules::Schedule,
+    PublishRequestUrl,
+};
+use serde_json::Value;
+
+const SPELLINGS: [(&str, &str); 2] = [
+    ("topicName", include_str!("fixtures/group_topic_name.json")),
+    ("urlGroup", include_str!("fixtures/group_url_group.json")),
+];
+
+fn fixture(document: &str, key: &str) -> Value {
+    let document: Value = serde_json::from_str(document).expect("Invalid fixture");
+    document[key].clone()
+}
+
+#[test]
+fn responses_should_accept_both_group_spellings() {
+    for (spelling, document) in SPELLINGS {
+        let event: Event = serde_json::from_value(fixture(document, "event"))
+            .unwrap_or_else(|e| panic!("{}: invalid event {}", spelling, e));
+        let message: Message = serde_json::from_value(fixture(document, "message"))
+            .unwrap_or_else(|e| panic!("{}: invalid message {}", spelling, e));
+        let dlq_message: DlqMessage = serde_json::from_value(fixture(document, "dlqMessage"))
+            .unwrap_or_else(|e| panic!("{}: invalid dlq message {}", spelling, e));
+        let schedule: Schedule = serde_json::from_value(fixture(document, "schedule"))
+            .unwrap_or_else(|e| panic!("{}: invalid schedule {}", spelling, e));
+
+        assert_eq!(event.group_name(), Some("billing"), "{}", spelling);
+        assert_eq!(event.topic_name.as_deref(), Some("billing"), "{}", spelling);
+        assert_eq!(message.group_name(), Some("billing"), "{}", spelling);
+        assert_eq!(dlq_message.group_name(), Some("billing"), "{}", spelling);
+        assert_eq!(schedule.group_name(), Some("billing"), "{}", spelling);
+    }
+}
+
+#[test]
+fn responses_without_a_group_should_have_no_group_name() {
+    let event: Event = serde_json::from_value(serde_json::json!({
+        "time": 1_699_000_000_000u64,
+        "state": "DELIVERED",
+        "messageId": "msg_1",
+    }))
+    .expect("Invalid event");
+
+    assert_eq!(event.group_name(), None);
+}
+
+#[test]
+fn to_publish_request_should_republish_to_the_endpoint_of_the_url_group() {
+    let (_, document) = SPELLINGS[1];
+    let message: DlqMessage =
+        serde_json::from_value(fixture(document, "dlqMessage")).expect("Invalid dlq message");
+
+    let request = message
+        .to_publish_request()
+        .expect("Could not build request");
+
+    // the other endpoints of the url group already received the message
+    assert!(
+        matches!(&request.url, PublishRequestUrl::Url(url) if url.as_str() == message.url),
+        "{:?}",
+        request.url
+    );
+}